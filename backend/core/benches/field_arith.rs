@@ -0,0 +1,45 @@
+//! `fr_batch_inverse` against one `fr_inverse` per element, for N = 1000
+//!
+//! Run with `cargo bench --bench field_arith`. There is no benchmark harness
+//! dependency, so this times a fixed number of rounds and prints the mean.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use shadow_drop_core::{
+    field::Fr,
+    field_arith::{fr_batch_inverse, fr_inverse},
+};
+
+const N: u64 = 1000;
+const ROUNDS: u32 = 100;
+
+fn mean_time(mut run: impl FnMut()) -> Duration {
+    run();
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        run();
+    }
+    start.elapsed() / ROUNDS
+}
+
+fn main() {
+    let elems: Vec<Fr> = (1..=N).map(|n| Fr::from(n * 0x9e37_79b9)).collect();
+
+    let individual = mean_time(|| {
+        for elem in &elems {
+            black_box(fr_inverse(black_box(*elem)));
+        }
+    });
+    let batch = mean_time(|| {
+        let mut batch = elems.clone();
+        fr_batch_inverse(black_box(&mut batch));
+        black_box(batch);
+    });
+
+    println!("{} individual inversions: {:?}", N, individual);
+    println!("batch inversion of {}:    {:?}", N, batch);
+    println!("speedup: {:.1}x", individual.as_secs_f64() / batch.as_secs_f64());
+}
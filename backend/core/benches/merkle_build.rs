@@ -0,0 +1,45 @@
+//! Building a full tree from recipients in one call, against hashing the
+//! leaves first with `compute_all_leaf_hashes` and building from them
+//!
+//! Run with `cargo bench --bench merkle_build`. Like `field_arith`, this
+//! times a fixed number of rounds and prints the mean.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use shadow_drop_core::merkle::{MAX_LEAVES, MerkleTree, compute_all_leaf_hashes};
+
+const ROUNDS: u32 = 20;
+
+fn mean_time(mut run: impl FnMut()) -> Duration {
+    run();
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        run();
+    }
+    start.elapsed() / ROUNDS
+}
+
+fn main() {
+    let recipients: Vec<(String, u64, [u8; 32])> = (0..MAX_LEAVES)
+        .map(|i| (format!("wallet{}", i), i as u64 + 1, [(i % 251) as u8; 32]))
+        .collect();
+
+    let combined = mean_time(|| {
+        black_box(MerkleTree::from_recipients(black_box(&recipients)).unwrap());
+    });
+    let mut leaf_pass = Duration::ZERO;
+    let two_pass = mean_time(|| {
+        let start = Instant::now();
+        let leaves = compute_all_leaf_hashes(black_box(&recipients));
+        leaf_pass += start.elapsed();
+        let labelled = recipients.iter().map(|(wallet, _, _)| wallet.clone()).zip(leaves).collect();
+        black_box(MerkleTree::from_leaves(labelled).unwrap());
+    });
+
+    println!("from_recipients, {} leaves:           {:?}", MAX_LEAVES, combined);
+    println!("leaf pass + from_leaves, {} leaves:   {:?}", MAX_LEAVES, two_pass);
+    println!("  of which leaf hashing:               {:?}", leaf_pass / (ROUNDS + 1));
+}
@@ -0,0 +1,35 @@
+//! SOL amounts in lamports
+
+/// Amount of SOL in lamports
+pub type Lamports = u64;
+
+/// Lamports in one SOL
+pub const LAMPORTS_PER_SOL: Lamports = 1_000_000_000;
+
+/// Parse a decimal SOL amount such as `"1.5"` into lamports. `None` if it
+/// is not a plain non-negative decimal, has more than 9 fractional digits
+/// or overflows.
+pub fn sol_to_lamports(amount: &str) -> Option<Lamports> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if whole.is_empty() || !is_digits(whole) || !is_digits(fraction) || fraction.len() > 9 {
+        return None;
+    }
+    let fraction: Lamports = format!("{:0<9}", fraction).parse().ok()?;
+    whole.parse::<Lamports>().ok()?.checked_mul(LAMPORTS_PER_SOL)?.checked_add(fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sol_to_lamports() {
+        assert_eq!(sol_to_lamports("1.5"), Some(1_500_000_000));
+        assert_eq!(sol_to_lamports("2"), Some(2 * LAMPORTS_PER_SOL));
+        assert_eq!(sol_to_lamports("0.000000001"), Some(1));
+        for invalid in ["", ".5", "1.0000000001", "-1", "1e9", "1,5", "99999999999"] {
+            assert_eq!(sol_to_lamports(invalid), None, "{invalid:?}");
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! BN254 scalar field helpers
+//!
+//! Hashes, secrets and proof inputs are elements of `Fr`. The `_checked`
+//! constructors reject byte strings that are too long or encode a value at
+//! or above the modulus, instead of reducing them like arkworks'
+//! `from_*_bytes_mod_order`.
+
+use ark_ff::{AdditiveGroup, BigInteger, Field, MontFp, PrimeField};
+
+pub use ark_bn254::Fr;
+
+/// Bytes of a canonical field element
+pub const FR_BYTES: usize = 32;
+
+pub const FR_ZERO: Fr = Fr::ZERO;
+pub const FR_ONE: Fr = Fr::ONE;
+
+/// 2^64, the multiplier of Noir's Poseidon2 sponge IV
+const FR_TWO_POW_64: Fr = MontFp!("18446744073709551616");
+
+/// Why bytes do not encode a field element
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FieldError {
+    #[error("field element must be at most {FR_BYTES} bytes, got {0}")]
+    TooLong(usize),
+
+    #[error("value is not below the BN254 scalar field modulus")]
+    NotCanonical,
+}
+
+/// 2^64 as a field element
+pub fn fr_two_pow_64() -> Fr {
+    FR_TWO_POW_64
+}
+
+/// Field element from at most 32 little-endian bytes
+pub fn fr_from_le_bytes_checked(bytes: &[u8]) -> Result<Fr, FieldError> {
+    if bytes.len() > FR_BYTES {
+        return Err(FieldError::TooLong(bytes.len()));
+    }
+    let mut be = [0u8; FR_BYTES];
+    for (i, byte) in bytes.iter().enumerate() {
+        be[FR_BYTES - 1 - i] = *byte;
+    }
+    fr_from_be_bytes_checked(&be)
+}
+
+/// Field element from at most 32 big-endian bytes
+pub fn fr_from_be_bytes_checked(bytes: &[u8]) -> Result<Fr, FieldError> {
+    if bytes.len() > FR_BYTES {
+        return Err(FieldError::TooLong(bytes.len()));
+    }
+    let mut padded = [0u8; FR_BYTES];
+    padded[FR_BYTES - bytes.len()..].copy_from_slice(bytes);
+
+    let field = Fr::from_be_bytes_mod_order(&padded);
+    if fr_to_be_bytes(field) != padded {
+        return Err(FieldError::NotCanonical);
+    }
+    Ok(field)
+}
+
+/// Canonical 32-byte big-endian encoding
+pub fn fr_to_be_bytes(f: Fr) -> [u8; FR_BYTES] {
+    let mut bytes = [0u8; FR_BYTES];
+    bytes.copy_from_slice(&f.into_bigint().to_bytes_be());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(fr_two_pow_64(), Fr::from(1u128 << 64));
+        assert_eq!(FR_ZERO + FR_ONE, Fr::from(1u64));
+    }
+
+    #[test]
+    fn test_checked_constructors_agree_across_endianness() {
+        let value = Fr::from(0x0102_0304u64);
+        let be = fr_to_be_bytes(value);
+        let mut le = be;
+        le.reverse();
+
+        assert_eq!(fr_from_be_bytes_checked(&be), Ok(value));
+        assert_eq!(fr_from_le_bytes_checked(&le), Ok(value));
+        assert_eq!(fr_from_be_bytes_checked(&[1, 2, 3, 4]), Ok(value));
+        assert_eq!(fr_from_le_bytes_checked(&[4, 3, 2, 1]), Ok(value));
+    }
+
+    #[test]
+    fn test_checked_constructors_reject_out_of_range() {
+        let modulus = Fr::MODULUS.to_bytes_be();
+        assert_eq!(fr_from_be_bytes_checked(&modulus), Err(FieldError::NotCanonical));
+        assert_eq!(fr_from_be_bytes_checked(&[0xff; 32]), Err(FieldError::NotCanonical));
+        assert_eq!(fr_from_be_bytes_checked(&[0; 33]), Err(FieldError::TooLong(33)));
+
+        let max = fr_to_be_bytes(-FR_ONE);
+        assert_eq!(fr_from_be_bytes_checked(&max), Ok(-FR_ONE));
+    }
+}
@@ -0,0 +1,160 @@
+//! BN254 scalar field arithmetic
+//!
+//! Exponentiation, inversion, quadratic residuosity and square roots over
+//! `Fr`, for callers that would otherwise reach for arkworks' `Field` and
+//! `PrimeField` traits and their limb-array exponents.
+
+use ark_ff::{FftField, Field, PrimeField};
+
+use super::field::{FR_ONE, FR_ZERO, Fr};
+
+/// `base^exp` by square-and-multiply
+pub fn fr_pow(base: Fr, exp: u64) -> Fr {
+    let mut result = FR_ONE;
+    let mut square = base;
+    let mut exp = exp;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result *= square;
+        }
+        square.square_in_place();
+        exp >>= 1;
+    }
+    result
+}
+
+/// Multiplicative inverse; None for zero
+pub fn fr_inverse(f: Fr) -> Option<Fr> {
+    f.inverse()
+}
+
+/// Legendre symbol of `f`: 1 for a non-zero square, -1 for a non-square and
+/// 0 for zero, from Euler's criterion `f^((p-1)/2)`
+pub fn fr_legendre(f: Fr) -> i8 {
+    let symbol = f.pow(Fr::MODULUS_MINUS_ONE_DIV_TWO);
+    if symbol == FR_ZERO {
+        0
+    } else if symbol == FR_ONE {
+        1
+    } else {
+        -1
+    }
+}
+
+/// A square root of `f` by Tonelli-Shanks; None if `f` is not a square
+///
+/// With `p - 1 = 2^s * q`, `q` odd, the 2^s-th root of unity `c` generates
+/// the part of the group a plain `f^((q+1)/2)` guess is off by. The other
+/// root is the negation of the returned one.
+pub fn fr_sqrt(f: Fr) -> Option<Fr> {
+    match fr_legendre(f) {
+        0 => return Some(FR_ZERO),
+        -1 => return None,
+        _ => {}
+    }
+
+    let mut m = Fr::TWO_ADICITY;
+    let mut c = Fr::TWO_ADIC_ROOT_OF_UNITY;
+    let mut t = f.pow(Fr::TRACE);
+    let mut r = f.pow(Fr::TRACE_MINUS_ONE_DIV_TWO) * f;
+    while t != FR_ONE {
+        // Least i with t^(2^i) = 1; below m since t^(2^(m-1)) = -1 otherwise
+        let mut i = 0;
+        let mut power = t;
+        while power != FR_ONE {
+            power.square_in_place();
+            i += 1;
+        }
+
+        let mut b = c;
+        for _ in 0..m - i - 1 {
+            b.square_in_place();
+        }
+        m = i;
+        c = b.square();
+        t *= c;
+        r *= b;
+    }
+    Some(r)
+}
+
+/// Invert every element of `elems` in place with a single field inversion
+/// (Montgomery's trick), at the cost of 3 multiplications per element
+///
+/// Zeros have no inverse and are left as zero.
+pub fn fr_batch_inverse(elems: &mut [Fr]) {
+    // prefix[i] is the product of the non-zero elements before i
+    let mut prefix = Vec::with_capacity(elems.len());
+    let mut product = FR_ONE;
+    for elem in elems.iter() {
+        prefix.push(product);
+        if *elem != FR_ZERO {
+            product *= elem;
+        }
+    }
+
+    let Some(mut inverse) = product.inverse() else {
+        return;
+    };
+    for (elem, before) in elems.iter_mut().zip(prefix).rev() {
+        if *elem == FR_ZERO {
+            continue;
+        }
+        // inverse is 1 / (product of the non-zero elements up to elem)
+        let elem_inverse = inverse * before;
+        inverse *= *elem;
+        *elem = elem_inverse;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pow_and_inverse_agree_with_arkworks() {
+        let base = Fr::from(7u64);
+        assert_eq!(fr_pow(base, 0), FR_ONE);
+        assert_eq!(fr_pow(base, 13), base.pow([13u64]));
+        assert_eq!(fr_pow(base, u64::MAX), base.pow([u64::MAX]));
+
+        assert_eq!(fr_inverse(FR_ZERO), None);
+        assert_eq!(fr_inverse(base).map(|inverse| inverse * base), Some(FR_ONE));
+    }
+
+    #[test]
+    fn test_sqrt_finds_roots_of_squares_only() {
+        assert_eq!(fr_legendre(FR_ZERO), 0);
+        assert_eq!(fr_sqrt(FR_ZERO), Some(FR_ZERO));
+
+        for n in 1..200u64 {
+            let square = Fr::from(n).square();
+            assert_eq!(fr_legendre(square), 1);
+            let root = fr_sqrt(square).unwrap();
+            assert!(root == Fr::from(n) || root == -Fr::from(n), "sqrt({}^2)", n);
+
+            let f = Fr::from(n);
+            let expected = f.sqrt();
+            assert_eq!(fr_legendre(f), if expected.is_some() { 1 } else { -1 });
+            assert_eq!(fr_sqrt(f).map(|root| root.square()), expected.map(|root| root.square()));
+        }
+        // A primitive 2^28-th root of unity is a non-square; its square takes
+        // every Tonelli-Shanks step
+        let unity = Fr::TWO_ADIC_ROOT_OF_UNITY;
+        assert_eq!(fr_sqrt(unity), None);
+        assert_eq!(fr_sqrt(unity.square()).map(|root| root.square()), Some(unity.square()));
+    }
+
+    #[test]
+    fn test_batch_inverse_matches_individual_inverses() {
+        let mut elems: Vec<Fr> = (0..20u64).map(Fr::from).collect();
+        let expected: Vec<Fr> = elems.iter().map(|f| fr_inverse(*f).unwrap_or(FR_ZERO)).collect();
+        fr_batch_inverse(&mut elems);
+        assert_eq!(elems, expected);
+
+        let mut zeros = [FR_ZERO; 3];
+        fr_batch_inverse(&mut zeros);
+        assert_eq!(zeros, [FR_ZERO; 3]);
+        fr_batch_inverse(&mut []);
+    }
+}
@@ -0,0 +1,16 @@
+//! Shadow Drop core: the Merkle tree, hashing and field arithmetic shared by
+//! the API server, the CLI and any other tool building or checking trees
+//!
+//! Nothing here touches HTTP, the database or metrics; `shadow-drop-api`
+//! re-exports these modules under `common`.
+
+pub mod amounts;
+pub mod field;
+pub mod field_arith;
+pub mod merkle;
+pub mod nullifier;
+pub mod poseidon;
+pub mod poseidon_trace;
+pub mod proof;
+pub mod wasm;
+pub mod witness;
@@ -0,0 +1,1842 @@
+//! Merkle tree implementation with Poseidon hashing
+//! 
+//! This module provides a proper merkle tree for ZK proofs.
+//! Uses a simplified Poseidon-like hash for demo (replace with light-poseidon for production).
+//! Hashing goes through `LeafHasher`: `PoseidonHasher`, the default, is what
+//! the circuit checks; `Sha256Hasher` builds trees no circuit checks.
+//!
+//! Byte order: every `Hash` (leaves, nodes, roots, nullifiers) is a BN254
+//! field element in big-endian bytes, as ark-bn254's `to_bytes_be`, Noir and
+//! Prover.toml hex use. Circom tooling expects little-endian; convert at the
+//! boundary with `hash_to_le_bytes` / `hash_from_le_bytes`.
+//!
+//! Proofs and their encodings live in `proof` and nullifiers in `nullifier`;
+//! both are re-exported here.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    marker::PhantomData,
+    path::Path,
+    sync::LazyLock,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::amounts::{Lamports, sol_to_lamports};
+use crate::proof::path_from_proof;
+pub use crate::{
+    nullifier::{compute_commitment, compute_nullifier, compute_nullifier_v2, installment_nullifier, round_nullifier},
+    proof::{MerkleProof, recompute_path, try_verify_proof, verify_proof},
+};
+
+/// Tree depth (supports 2^8 = 256 recipients)
+pub const TREE_DEPTH: usize = 8;
+
+/// Maximum number of leaves
+pub const MAX_LEAVES: usize = 1 << TREE_DEPTH;
+
+// The 4-ary layout needs MAX_LEAVES to be a power of 4
+const _: () = assert!(TREE_DEPTH.is_multiple_of(2));
+
+/// A 32-byte hash value
+pub type Hash = [u8; 32];
+
+/// Leaf used to pad trees to `MAX_LEAVES`: `Poseidon2("shadow-drop-empty", "leaf")`,
+/// each string read as a big-endian field element
+pub static EMPTY_LEAF: LazyLock<Hash> =
+    LazyLock::new(|| poseidon_hash_2(&domain_bytes(b"shadow-drop-empty"), &domain_bytes(b"leaf")));
+
+/// Root of an empty binary subtree of height `level`: `ZERO_HASHES[0]` is
+/// `EMPTY_LEAF` and `ZERO_HASHES[TREE_DEPTH]` is the root of an empty tree
+pub static ZERO_HASHES: LazyLock<[Hash; TREE_DEPTH + 1]> = LazyLock::new(|| {
+    let mut hashes = [*EMPTY_LEAF; TREE_DEPTH + 1];
+    for level in 1..=TREE_DEPTH {
+        hashes[level] = hash_pair(&hashes[level - 1], &hashes[level - 1]);
+    }
+    hashes
+});
+
+/// Decode a 32-byte hash from (optionally 0x-prefixed) hex
+pub fn parse_hash(value: &str) -> Option<Hash> {
+    hex::decode(value.strip_prefix("0x").unwrap_or(value)).ok()?.try_into().ok()
+}
+
+/// Reverse a big-endian `Hash` into little-endian bytes, e.g. for Circom
+pub fn hash_to_le_bytes(h: &Hash) -> Hash {
+    let mut bytes = *h;
+    bytes.reverse();
+    bytes
+}
+
+/// Big-endian `Hash` from little-endian bytes
+pub fn hash_from_le_bytes(bytes: &[u8; 32]) -> Hash {
+    hash_to_le_bytes(bytes)
+}
+
+/// Field element of bytes read as little-endian, reduced modulo the field order
+pub fn hash_to_fr_le(h: &Hash) -> Fr {
+    Fr::from_le_bytes_mod_order(h)
+}
+
+/// Field element of bytes read as big-endian (the `Hash` convention), reduced
+/// modulo the field order
+pub fn hash_to_fr_be(h: &Hash) -> Fr {
+    bytes_to_field_element(h)
+}
+
+/// Right-align a short domain tag in 32 bytes
+fn domain_bytes(tag: &[u8]) -> Hash {
+    let mut bytes = [0u8; 32];
+    bytes[32 - tag.len()..].copy_from_slice(tag);
+    bytes
+}
+
+/// Hash functions of a `MerkleTree`'s leaves and internal nodes
+pub trait LeafHasher {
+    /// Leaf committing to a recipient's allocation
+    fn hash_leaf(wallet: &str, amount: Lamports, secret: &[u8; 32]) -> Hash;
+
+    /// Parent of two sibling nodes
+    fn hash_pair(left: &Hash, right: &Hash) -> Hash;
+
+    /// Parent of a group of sibling nodes; only binary trees are supported
+    /// unless overridden
+    fn hash_children(children: &[Hash]) -> Hash {
+        match children {
+            [left, right] => Self::hash_pair(left, right),
+            _ => unreachable!("unsupported merkle arity {}", children.len()),
+        }
+    }
+
+    /// Leaf used to pad trees to `MAX_LEAVES`: the pair hash of the
+    /// `"shadow-drop-empty"` and `"leaf"` domain tags
+    fn empty_leaf() -> Hash {
+        Self::hash_pair(&domain_bytes(b"shadow-drop-empty"), &domain_bytes(b"leaf"))
+    }
+
+    /// Roots of empty binary subtrees by height, as `ZERO_HASHES`
+    fn zero_hashes() -> [Hash; TREE_DEPTH + 1] {
+        let mut hashes = [Self::empty_leaf(); TREE_DEPTH + 1];
+        for level in 1..=TREE_DEPTH {
+            hashes[level] = Self::hash_pair(&hashes[level - 1], &hashes[level - 1]);
+        }
+        hashes
+    }
+}
+
+/// Poseidon2 hashing matching the Noir circuit; the default `LeafHasher`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PoseidonHasher;
+
+impl LeafHasher for PoseidonHasher {
+    fn hash_leaf(wallet: &str, amount: Lamports, secret: &[u8; 32]) -> Hash {
+        compute_leaf_hash(wallet, amount, secret)
+    }
+
+    fn hash_pair(left: &Hash, right: &Hash) -> Hash {
+        hash_pair(left, right)
+    }
+
+    /// Also hashes 4-ary groups, in a single permutation
+    fn hash_children(children: &[Hash]) -> Hash {
+        hash_children(children)
+    }
+
+    fn empty_leaf() -> Hash {
+        *EMPTY_LEAF
+    }
+
+    fn zero_hashes() -> [Hash; TREE_DEPTH + 1] {
+        *ZERO_HASHES
+    }
+}
+
+/// SHA-256 hashing for tests and trees no circuit checks. Leaves and nodes
+/// are domain-separated by a `0x00` / `0x01` prefix byte, so a leaf cannot be
+/// passed off as a node. Hashes are not field elements.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sha256Hasher;
+
+impl LeafHasher for Sha256Hasher {
+    fn hash_leaf(wallet: &str, amount: Lamports, secret: &[u8; 32]) -> Hash {
+        use sha2::{Digest, Sha256};
+
+        Sha256::new()
+            .chain_update([0u8])
+            .chain_update(wallet_to_field_bytes(wallet))
+            .chain_update(amount.to_be_bytes())
+            .chain_update(secret)
+            .finalize()
+            .into()
+    }
+
+    fn hash_pair(left: &Hash, right: &Hash) -> Hash {
+        use sha2::{Digest, Sha256};
+
+        Sha256::new().chain_update([1u8]).chain_update(left).chain_update(right).finalize().into()
+    }
+}
+
+/// Errors in a recipient list passed to the tree builder
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RecipientError {
+    #[error("wallet {wallet} appears at index {first_at} and again at index {duplicate_at}")]
+    DuplicateWallet {
+        wallet: String,
+        first_at: usize,
+        duplicate_at: usize,
+    },
+    #[error("wallets {first} and {duplicate} share leaf index {leaf_index}")]
+    DuplicateLeafIndex {
+        leaf_index: usize,
+        first: String,
+        duplicate: String,
+    },
+}
+
+/// Errors building or decoding merkle structures
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MerkleError {
+    #[error("{count} leaves exceed the tree capacity of {max}")]
+    TooManyLeaves { count: usize, max: usize },
+
+    #[error("invalid recipient list: {}", join_errors(.0))]
+    InvalidRecipients(Vec<RecipientError>),
+
+    #[error("invalid instruction data: {0}")]
+    InvalidInstructionData(String),
+
+    #[error("wallet {0} is not in the tree")]
+    WalletNotFound(String),
+
+    #[error("corrupted tree: {0}")]
+    CorruptedTree(String),
+
+    #[error("invalid commitment data: {0}")]
+    InvalidCommitmentData(String),
+
+    #[error("malformed proof: {0}")]
+    MalformedProof(String),
+}
+
+/// Errors loading or saving a tree as a JSON file
+#[derive(Debug, thiserror::Error)]
+pub enum MerkleFileError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Merkle(#[from] MerkleError),
+
+    #[error("invalid amount_sol {amount:?} for wallet {wallet}")]
+    InvalidAmount { wallet: String, amount: String },
+
+    #[error("line {line}: {reason}")]
+    InvalidCsv { line: usize, reason: String },
+
+    #[error("unsupported tree file version {0:?}, expected {TREE_FILE_VERSION}")]
+    UnsupportedVersion(String),
+
+    #[error("invalid tree file: {0}")]
+    InvalidFile(String),
+
+    #[error("stored root {stored} does not match the rebuilt root {rebuilt}")]
+    RootMismatch { stored: String, rebuilt: String },
+}
+
+/// Format version written to, and the only one accepted from, tree files
+pub const TREE_FILE_VERSION: &str = "1.0";
+
+/// Recipient entry of a file read by `read_recipients_file`
+#[derive(Debug, Deserialize)]
+struct FileRecipient {
+    wallet: String,
+    amount_sol: String,
+}
+
+/// Tree file written by `MerkleTree::to_json_file`; hashes are 0x-prefixed hex
+#[derive(Debug, Serialize, Deserialize)]
+struct TreeFile {
+    version: String,
+    root: String,
+    arity: usize,
+    depth: usize,
+    leaf_count: usize,
+    /// In leaf order
+    recipients: Vec<TreeFileRecipient>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TreeFileRecipient {
+    wallet: String,
+    leaf_index: usize,
+    leaf: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    proof: Option<TreeFileProof>,
+}
+
+/// `MerkleProof` as stored in a tree file
+#[derive(Debug, Serialize, Deserialize)]
+struct TreeFileProof {
+    leaf_index: usize,
+    leaf: String,
+    siblings: Vec<String>,
+}
+
+/// Secrets file written by `MerkleTree::secrets_to_json_file`
+#[derive(Debug, Serialize)]
+struct SecretsFile<'a> {
+    version: &'static str,
+    root: String,
+    secrets: Vec<SecretsFileEntry<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct SecretsFileEntry<'a> {
+    wallet: &'a str,
+    leaf_index: usize,
+    secret: String,
+}
+
+fn hex_hash(hash: &Hash) -> String {
+    format!("0x{}", hex::encode(hash))
+}
+
+fn parse_file_hash(value: &str, wallet: &str) -> Result<Hash, MerkleFileError> {
+    parse_hash(value)
+        .ok_or_else(|| MerkleFileError::InvalidFile(format!("leaf of {} is not a 32-byte hex hash", wallet)))
+}
+
+fn join_errors(errors: &[RecipientError]) -> String {
+    errors.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+}
+
+/// Merkle tree structure, hashed with `H`
+#[derive(Debug, Clone)]
+pub struct MerkleTree<H: LeafHasher = PoseidonHasher> {
+    /// All nodes in the tree (bottom-up, left-to-right per level)
+    nodes: Vec<Hash>,
+    /// Number of leaves
+    leaf_count: usize,
+    /// Leaf index by recipient wallet
+    leaf_indices: BTreeMap<String, usize>,
+    /// Children per internal node (2 for binary, 4 for quaternary)
+    arity: usize,
+    /// Recipient secrets by wallet; empty for trees built from leaf hashes
+    secrets: BTreeMap<String, Hash>,
+    hasher: PhantomData<H>,
+}
+
+/// What the on-chain program stores for a campaign's tree: the root, with the
+/// depth and leaf count a proof against it must agree with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleCommitment {
+    pub root: Hash,
+    pub depth: u8,
+    pub leaf_count: u32,
+    /// Unix timestamp, in seconds
+    pub created_at: i64,
+}
+
+/// Borsh size of a `MerkleCommitment`: root, depth, leaf count and timestamp
+pub const MERKLE_COMMITMENT_LEN: usize = 32 + 1 + 4 + 8;
+
+impl MerkleCommitment {
+    /// Verify a binary proof against the committed root, rejecting proofs
+    /// whose sibling count does not match the committed depth
+    pub fn verify_proof(&self, proof: &MerkleProof) -> bool {
+        proof.siblings.len() == self.depth as usize && verify_proof(&self.root, proof)
+    }
+
+    /// Encode as the program's account state deserializes it.
+    ///
+    /// Layout (Borsh, little endian): `root: [u8; 32]`, `depth: u8`,
+    /// `leaf_count: u32`, `created_at: i64`.
+    pub fn to_borsh_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(MERKLE_COMMITMENT_LEN);
+        data.extend_from_slice(&self.root);
+        data.push(self.depth);
+        data.extend_from_slice(&self.leaf_count.to_le_bytes());
+        data.extend_from_slice(&self.created_at.to_le_bytes());
+        data
+    }
+
+    /// Decode bytes produced by `to_borsh_bytes`
+    pub fn from_borsh_bytes(data: &[u8]) -> Result<Self, MerkleError> {
+        if data.len() != MERKLE_COMMITMENT_LEN {
+            return Err(MerkleError::InvalidCommitmentData(format!(
+                "expected {} bytes, found {}",
+                MERKLE_COMMITMENT_LEN,
+                data.len()
+            )));
+        }
+        let (root, rest) = data.split_at(32);
+        let (depth, rest) = rest.split_at(1);
+        let (leaf_count, created_at) = rest.split_at(4);
+        Ok(Self {
+            root: root.try_into().unwrap(),
+            depth: depth[0],
+            leaf_count: u32::from_le_bytes(leaf_count.try_into().unwrap()),
+            created_at: i64::from_le_bytes(created_at.try_into().unwrap()),
+        })
+    }
+}
+
+impl MerkleTree {
+    /// Build a merkle tree from recipient list
+    ///
+    /// Fails with every duplicated wallet if any wallet appears more than
+    /// once, or if there are more than `MAX_LEAVES` recipients.
+    #[tracing::instrument(skip(recipients), fields(recipients = recipients.len()))]
+    pub fn from_recipients(
+        recipients: &[(String, u64, [u8; 32])],
+    ) -> Result<Self, MerkleError> {
+        MerkleTree::<PoseidonHasher>::with_hasher(recipients)
+    }
+
+    /// Build a merkle tree with each recipient at its own leaf index, such as
+    /// the indices stored for a campaign whose removed recipients left gaps.
+    /// Gaps hold `EMPTY_LEAF`, so the remaining leaves keep their positions.
+    ///
+    /// Fails with every duplicated wallet or leaf index, or if an index is
+    /// beyond the tree capacity.
+    #[tracing::instrument(skip(recipients), fields(recipients = recipients.len()))]
+    pub fn from_indexed_recipients(
+        recipients: &[(usize, String, u64, [u8; 32])],
+    ) -> Result<Self, MerkleError> {
+        check_placement(
+            &recipients
+                .iter()
+                .map(|(leaf_index, wallet, _, _)| (*leaf_index, wallet.as_str()))
+                .collect::<Vec<_>>(),
+        )?;
+
+        let unindexed: Vec<(String, u64, [u8; 32])> = recipients
+            .iter()
+            .map(|(_, wallet, amount, secret)| (wallet.clone(), *amount, *secret))
+            .collect();
+        let placed = recipients
+            .iter()
+            .zip(leaf_hashes::<PoseidonHasher>(&unindexed))
+            .map(|((leaf_index, wallet, _, _), leaf)| (*leaf_index, wallet.clone(), leaf))
+            .collect();
+        Ok(Self::build_at(placed, 2)?.with_secrets(&unindexed))
+    }
+
+    /// Build a 4-ary merkle tree from recipient list
+    ///
+    /// Each internal node hashes 4 children with a single Poseidon2 permutation,
+    /// halving the depth for the same capacity. Proofs carry 3 siblings per level.
+    #[tracing::instrument(skip(recipients), fields(recipients = recipients.len()))]
+    pub fn with_arity_4(
+        recipients: &[(String, u64, [u8; 32])],
+    ) -> Result<Self, MerkleError> {
+        check_duplicates(recipients.iter().map(|(wallet, _, _)| wallet.as_str()))
+            .map_err(MerkleError::InvalidRecipients)?;
+        Ok(Self::build(hash_recipients::<PoseidonHasher>(recipients), 4)?.with_secrets(recipients))
+    }
+
+    /// Build a merkle tree from a JSON file of
+    /// `[{ "wallet": "...", "amount_sol": "1.5" }]`, generating a secret per
+    /// recipient. Leaves commit to the amounts in lamports.
+    pub fn from_file(path: &Path) -> Result<Self, MerkleFileError> {
+        Self::from_amounts(&read_recipients_file(path, RecipientsFormat::Json)?)
+    }
+
+    /// Build a merkle tree from a CSV file of `wallet,amount_sol` lines, like
+    /// `from_file`
+    pub fn from_csv_file(path: &Path) -> Result<Self, MerkleFileError> {
+        Self::from_amounts(&read_recipients_file(path, RecipientsFormat::Csv)?)
+    }
+
+    /// Build a merkle tree from `(wallet, lamports)` pairs, generating a
+    /// secret per recipient
+    pub fn from_amounts(recipients: &[(String, Lamports)]) -> Result<Self, MerkleFileError> {
+        let recipients: Vec<_> = recipients
+            .iter()
+            .map(|(wallet, amount)| (wallet.clone(), *amount, generate_secret()))
+            .collect();
+        Ok(Self::from_recipients(&recipients)?)
+    }
+
+    /// Write the tree to a versioned JSON file: root, shape and every
+    /// recipient's leaf in leaf order, with its proof if `include_proofs` is
+    /// set, e.g. to hand proofs out from an air-gapped machine
+    pub fn to_json_file(&self, path: &Path, include_proofs: bool) -> Result<(), MerkleFileError> {
+        let mut proofs: HashMap<String, MerkleProof> = if include_proofs {
+            self.batch_proofs().into_iter().collect()
+        } else {
+            HashMap::new()
+        };
+        let recipients = self
+            .wallets_in_leaf_order()
+            .into_iter()
+            .map(|(wallet, leaf_index)| TreeFileRecipient {
+                wallet: wallet.to_string(),
+                leaf_index,
+                leaf: hex_hash(&self.nodes[leaf_index]),
+                proof: proofs.remove(wallet).map(|proof| TreeFileProof {
+                    leaf_index: proof.leaf_index,
+                    leaf: hex_hash(&proof.leaf),
+                    siblings: proof.siblings.iter().map(hex_hash).collect(),
+                }),
+            })
+            .collect();
+
+        let file = TreeFile {
+            version: TREE_FILE_VERSION.to_string(),
+            root: hex_hash(&self.root()),
+            arity: self.arity,
+            depth: self.depth(),
+            leaf_count: self.leaf_count,
+            recipients,
+        };
+        std::fs::write(path, serde_json::to_vec_pretty(&file)?)?;
+        Ok(())
+    }
+
+    /// Load a file written by `to_json_file`, rebuilding the tree from its
+    /// leaves. Each leaf goes back to its stored index, so gaps left by
+    /// removed recipients stay empty. Fails unless the rebuilt root is the
+    /// stored one and every stored proof is the rebuilt tree's proof.
+    pub fn from_json_file(path: &Path) -> Result<Self, MerkleFileError> {
+        let file: TreeFile = serde_json::from_slice(&std::fs::read(path)?)?;
+        if file.version != TREE_FILE_VERSION {
+            return Err(MerkleFileError::UnsupportedVersion(file.version));
+        }
+        if file.arity != 2 && file.arity != 4 {
+            return Err(MerkleFileError::InvalidFile(format!("arity {} is not 2 or 4", file.arity)));
+        }
+        if file.recipients.len() != file.leaf_count {
+            return Err(MerkleFileError::InvalidFile(format!(
+                "leaf_count is {} but {} recipients are listed",
+                file.leaf_count,
+                file.recipients.len()
+            )));
+        }
+        if file.leaf_count > MAX_LEAVES {
+            return Err(MerkleError::TooManyLeaves {
+                count: file.leaf_count,
+                max: MAX_LEAVES,
+            }
+            .into());
+        }
+
+        check_placement(
+            &file
+                .recipients
+                .iter()
+                .map(|recipient| (recipient.leaf_index, recipient.wallet.as_str()))
+                .collect::<Vec<_>>(),
+        )?;
+        let mut placed = Vec::with_capacity(file.recipients.len());
+        for recipient in &file.recipients {
+            let leaf = parse_file_hash(&recipient.leaf, &recipient.wallet)?;
+            placed.push((recipient.leaf_index, recipient.wallet.clone(), leaf));
+        }
+
+        let tree = Self::build_at(placed, file.arity)?;
+        if file.depth != tree.depth() {
+            return Err(MerkleFileError::InvalidFile(format!(
+                "depth {} does not match arity {}",
+                file.depth, file.arity
+            )));
+        }
+        if parse_hash(&file.root) != Some(tree.root()) {
+            return Err(MerkleFileError::RootMismatch {
+                stored: file.root,
+                rebuilt: hex_hash(&tree.root()),
+            });
+        }
+
+        for recipient in &file.recipients {
+            let Some(stored) = &recipient.proof else { continue };
+            let matches = tree.get_proof(&recipient.wallet).is_some_and(|proof| {
+                stored.leaf_index == proof.leaf_index
+                    && parse_hash(&stored.leaf) == Some(proof.leaf)
+                    && stored.siblings.len() == proof.siblings.len()
+                    && stored
+                        .siblings
+                        .iter()
+                        .zip(&proof.siblings)
+                        .all(|(stored, sibling)| parse_hash(stored) == Some(*sibling))
+            });
+            if !matches {
+                return Err(MerkleFileError::InvalidFile(format!(
+                    "proof of {} does not match the tree",
+                    recipient.wallet
+                )));
+            }
+        }
+
+        Ok(tree)
+    }
+
+    /// Build a merkle tree from `(wallet_label, leaf_hash)` pairs whose leaves
+    /// were already hashed, e.g. off-chain by a frontend.
+    ///
+    /// Produces the same tree as `from_recipients` for the same leaf hashes.
+    pub fn from_leaves(leaves: Vec<(String, Hash)>) -> Result<Self, MerkleError> {
+        check_duplicates(leaves.iter().map(|(wallet, _)| wallet.as_str()))
+            .map_err(MerkleError::InvalidRecipients)?;
+
+        Self::build(leaves, 2)
+    }
+
+}
+
+impl<H: LeafHasher> MerkleTree<H> {
+    /// Build a binary merkle tree hashed with `H`, e.g.
+    /// `MerkleTree::<Sha256Hasher>::with_hasher` to check tree logic with a
+    /// simpler hash. `from_recipients` builds the Poseidon one.
+    pub fn with_hasher(recipients: &[(String, u64, [u8; 32])]) -> Result<Self, MerkleError> {
+        check_duplicates(recipients.iter().map(|(wallet, _, _)| wallet.as_str()))
+            .map_err(MerkleError::InvalidRecipients)?;
+        Ok(Self::build(hash_recipients::<H>(recipients), 2)?.with_secrets(recipients))
+    }
+
+    /// Write every recipient's secret, in leaf order, to a JSON file for
+    /// safekeeping. Trees built from leaf hashes have no secrets to write.
+    pub fn secrets_to_json_file(&self, path: &Path) -> Result<(), MerkleFileError> {
+        let secrets = self
+            .wallets_in_leaf_order()
+            .into_iter()
+            .filter_map(|(wallet, leaf_index)| {
+                Some(SecretsFileEntry {
+                    wallet,
+                    leaf_index,
+                    secret: hex_hash(self.secrets.get(wallet)?),
+                })
+            })
+            .collect();
+
+        let file = SecretsFile {
+            version: TREE_FILE_VERSION,
+            root: hex_hash(&self.root()),
+            secrets,
+        };
+        std::fs::write(path, serde_json::to_vec_pretty(&file)?)?;
+        Ok(())
+    }
+
+    fn wallets_in_leaf_order(&self) -> Vec<(&str, usize)> {
+        let mut wallets: Vec<(&str, usize)> = self.leaf_indices_sorted().collect();
+        wallets.sort_by_key(|(_, leaf_index)| *leaf_index);
+        wallets
+    }
+
+    fn with_secrets(mut self, recipients: &[(String, u64, [u8; 32])]) -> Self {
+        self.secrets = recipients
+            .iter()
+            .map(|(wallet, _, secret)| (wallet.clone(), *secret))
+            .collect();
+        self
+    }
+
+    fn build(labelled_leaves: Vec<(String, Hash)>, arity: usize) -> Result<Self, MerkleError> {
+        let placed = labelled_leaves
+            .into_iter()
+            .enumerate()
+            .map(|(i, (wallet, leaf))| (i, wallet, leaf))
+            .collect();
+        Self::build_at(placed, arity)
+    }
+
+    /// Build from `(leaf_index, wallet_label, leaf_hash)` triples with unique
+    /// indices below `MAX_LEAVES`; every other leaf is empty
+    fn build_at(placed: Vec<(usize, String, Hash)>, arity: usize) -> Result<Self, MerkleError> {
+        let leaf_count = placed.len();
+        if leaf_count > MAX_LEAVES {
+            return Err(MerkleError::TooManyLeaves {
+                count: leaf_count,
+                max: MAX_LEAVES,
+            });
+        }
+
+        // Pad to power of 2
+        let padded_size = (1 << TREE_DEPTH) as usize;
+        if leaf_count < padded_size {
+            tracing::warn!(
+                leaf_count,
+                padded = padded_size - leaf_count,
+                "padding tree with empty leaves"
+            );
+        }
+        let mut leaves = vec![H::empty_leaf(); padded_size];
+
+        // Build leaf index map
+        let mut leaf_indices = BTreeMap::new();
+        let mut span = 0;
+        for (i, wallet, leaf) in placed {
+            leaf_indices.insert(wallet, i);
+            leaves[i] = leaf;
+            span = span.max(i + 1);
+        }
+        let zero_hashes = H::zero_hashes();
+        
+        // Build tree bottom-up
+        let mut nodes = leaves.clone();
+        let mut current_level = leaves;
+        let mut level = 0;
+        // Nodes left of this index cover at least one real leaf
+        let mut filled = span;
+        
+        while current_level.len() > 1 {
+            level += 1;
+            filled = filled.div_ceil(arity);
+            tracing::debug!(level, nodes = current_level.len() / arity, "hashing level");
+            let mut next_level = Vec::new();
+            for (i, chunk) in current_level.chunks(arity).enumerate() {
+                // Empty binary subtrees are precomputed
+                let parent = if arity == 2 && i >= filled {
+                    zero_hashes[level]
+                } else {
+                    H::hash_children(chunk)
+                };
+                next_level.push(parent);
+                nodes.push(parent);
+            }
+            current_level = next_level;
+        }
+        tracing::debug!(levels = level, "tree built");
+        
+        Ok(Self {
+            nodes,
+            leaf_count,
+            leaf_indices,
+            arity,
+            secrets: BTreeMap::new(),
+            hasher: PhantomData,
+        })
+    }
+
+    /// Children per internal node
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    /// Levels above the leaves: `TREE_DEPTH` for binary trees, `TREE_DEPTH / 2` for 4-ary
+    pub fn depth(&self) -> usize {
+        MAX_LEAVES.ilog(self.arity) as usize
+    }
+
+    /// Number of nodes at `level`, counting from the leaves (level 0) up to the root
+    ///
+    /// ```
+    /// use shadow_drop_core::merkle::MerkleTree;
+    ///
+    /// let tree = MerkleTree::from_recipients(&[("wallet".to_string(), 1, [1u8; 32])]).unwrap();
+    /// assert_eq!(tree.depth(), 8);
+    /// assert_eq!(tree.width_at_level(0), 256);
+    /// assert_eq!(tree.width_at_level(8), 1);
+    /// assert_eq!(tree.total_nodes(), 511);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `level` is greater than `depth()`.
+    pub fn width_at_level(&self, level: usize) -> usize {
+        let depth = self.depth();
+        assert!(level <= depth, "level {} is above the root (depth {})", level, depth);
+        self.arity.pow((depth - level) as u32)
+    }
+
+    /// Number of nodes across all levels, leaves and root included
+    pub fn total_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Number of recipient leaves (excluding padding)
+    pub fn leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    /// Maximum number of leaves the tree can hold
+    pub fn capacity(&self) -> usize {
+        MAX_LEAVES
+    }
+
+    /// Fill ratio from 0.0 (empty) to 1.0 (full)
+    pub fn utilization(&self) -> f64 {
+        self.leaf_count as f64 / self.capacity() as f64
+    }
+
+    /// Whether every leaf slot is taken
+    pub fn is_full(&self) -> bool {
+        self.leaf_count >= self.capacity()
+    }
+    
+    /// Get the merkle root
+    pub fn root(&self) -> Hash {
+        *self.nodes.last().unwrap_or(&[0u8; 32])
+    }
+
+    /// Commitment to this tree as the on-chain program stores it, timestamped now
+    pub fn to_commitment(&self) -> MerkleCommitment {
+        MerkleCommitment {
+            root: self.root(),
+            depth: self.depth() as u8,
+            leaf_count: self.leaf_count as u32,
+            created_at: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    /// Rehash every internal node from the leaf level (padding included) and
+    /// return the recomputed root
+    ///
+    /// Fails on the lowest stored node that does not hash its children, so a
+    /// corrupted node is caught even where it would not change the root, as
+    /// it would still change proofs.
+    pub fn recompute_from_leaves(&self) -> Result<Hash, MerkleError> {
+        let expected_nodes = (MAX_LEAVES * self.arity - 1) / (self.arity - 1);
+        if self.nodes.len() != expected_nodes {
+            return Err(MerkleError::CorruptedTree(format!(
+                "{} nodes, expected {}",
+                self.nodes.len(),
+                expected_nodes
+            )));
+        }
+
+        let mut level_start = 0;
+        let mut current = self.nodes[..MAX_LEAVES].to_vec();
+        let mut level = 0;
+        while current.len() > 1 {
+            level_start += current.len();
+            level += 1;
+            current = current.chunks(self.arity).map(H::hash_children).collect();
+            let stored = &self.nodes[level_start..level_start + current.len()];
+            if let Some(index) = current.iter().zip(stored).position(|(rehashed, stored)| rehashed != stored) {
+                return Err(MerkleError::CorruptedTree(format!(
+                    "node {} at level {} does not match its children",
+                    index, level
+                )));
+            }
+        }
+        Ok(current[0])
+    }
+
+    /// Whether every stored internal node is the hash of its children
+    pub fn is_consistent(&self) -> bool {
+        self.recompute_from_leaves().is_ok_and(|root| root == self.root())
+    }
+    
+    /// Get proof for a wallet
+    #[tracing::instrument(skip(self))]
+    pub fn get_proof(&self, wallet: &str) -> Option<MerkleProof> {
+        let leaf_index = *self.leaf_indices.get(wallet)?;
+        let leaf = self.nodes[leaf_index];
+        
+        let mut siblings = Vec::new();
+        let mut idx = leaf_index;
+        let mut level_start = 0;
+        let mut level_size = 1 << TREE_DEPTH;
+        
+        while level_size > 1 {
+            // Siblings are the other members of this node's group, left to right
+            let group_start = idx - idx % self.arity;
+            for sibling_idx in group_start..group_start + self.arity {
+                if sibling_idx != idx {
+                    siblings.push(self.nodes[level_start + sibling_idx]);
+                }
+            }
+            
+            level_start += level_size;
+            level_size /= self.arity;
+            idx /= self.arity;
+        }
+        
+        tracing::debug!(leaf_index, "proof generated");
+        Some(MerkleProof {
+            leaf_index,
+            siblings,
+            leaf,
+        })
+    }
+    
+    /// Hashes of the nodes on the path from a wallet's leaf up to the root:
+    /// `[leaf, parent, ..., root]`, `depth() + 1` entries. Unlike a proof's
+    /// siblings, these are the nodes the verifier recomputes, which makes it
+    /// easy to see at which level a failing verification diverges.
+    pub fn path_to_root(&self, wallet: &str) -> Result<Vec<Hash>, MerkleError> {
+        let mut idx = self
+            .get_leaf_index(wallet)
+            .ok_or_else(|| MerkleError::WalletNotFound(wallet.to_string()))?;
+
+        let mut path = Vec::with_capacity(self.depth() + 1);
+        let mut level_start = 0;
+        for level in 0..=self.depth() {
+            path.push(self.nodes[level_start + idx]);
+            level_start += self.width_at_level(level);
+            idx /= self.arity;
+        }
+        Ok(path)
+    }
+
+    /// Get leaf index for a wallet
+    pub fn get_leaf_index(&self, wallet: &str) -> Option<usize> {
+        self.leaf_indices.get(wallet).copied()
+    }
+
+    /// Secret of a wallet's leaf; None for trees built from leaf hashes
+    pub fn secret(&self, wallet: &str) -> Option<&Hash> {
+        self.secrets.get(wallet)
+    }
+
+    /// Check whether a wallet has a leaf in the tree, without building a proof
+    pub fn contains(&self, wallet: &str) -> bool {
+        self.leaf_indices.contains_key(wallet)
+    }
+
+    /// Check membership by generating the wallet's proof and verifying it
+    /// against the root. Useful as an integrity check of the tree itself.
+    pub fn contains_with_verify(&self, wallet: &str) -> bool {
+        self.get_proof(wallet)
+            .is_some_and(|proof| path_from_proof::<H>(&proof, self.arity).last() == Some(&self.root()))
+    }
+
+    /// Generate and verify the proof of every recipient, collecting each
+    /// wallet whose proof does not reach the root
+    ///
+    /// Costs a proof per leaf, O(n * depth) hashes; meant as an integrity
+    /// check after building or loading a tree, not on every request.
+    pub fn validate_all_proofs(&self) -> Result<(), Vec<(String, MerkleError)>> {
+        let root = self.root();
+        let failures: Vec<_> = self
+            .leaf_indices
+            .iter()
+            .filter_map(|(wallet, leaf_index)| {
+                let error = match self.get_proof(wallet) {
+                    None => MerkleError::WalletNotFound(wallet.clone()),
+                    Some(proof) if path_from_proof::<H>(&proof, self.arity).last() != Some(&root) => {
+                        MerkleError::CorruptedTree(format!("proof of leaf {} does not reach the root", leaf_index))
+                    }
+                    Some(_) => return None,
+                };
+                Some((wallet.clone(), error))
+            })
+            .collect();
+        if failures.is_empty() { Ok(()) } else { Err(failures) }
+    }
+
+    /// Iterate all registered recipient wallets, sorted
+    pub fn all_wallets(&self) -> impl Iterator<Item = &str> {
+        self.leaf_indices.keys().map(String::as_str)
+    }
+
+    /// Proofs of every recipient as `(wallet, proof)`, in leaf order, e.g. to
+    /// pre-generate all proofs of a campaign at once
+    pub fn batch_proofs(&self) -> Vec<(String, MerkleProof)> {
+        let mut proofs: Vec<(String, MerkleProof)> = self
+            .leaf_indices
+            .keys()
+            .filter_map(|wallet| Some((wallet.clone(), self.get_proof(wallet)?)))
+            .collect();
+        proofs.sort_by_key(|(_, proof)| proof.leaf_index);
+        proofs
+    }
+
+    /// Iterate `(wallet, leaf_index)` pairs sorted by wallet, for audit exports
+    pub fn leaf_indices_sorted(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.leaf_indices.iter().map(|(w, i)| (w.as_str(), *i))
+    }
+}
+
+/// Leaf hashes of `recipients`, in order, as `from_recipients` computes
+/// them before building the tree
+///
+/// Lets callers hash leaves in a pass of their own, e.g. to transform them,
+/// and build the tree from the result with `from_leaves`.
+pub fn compute_all_leaf_hashes(recipients: &[(String, Lamports, [u8; 32])]) -> Vec<Hash> {
+    leaf_hashes::<PoseidonHasher>(recipients)
+}
+
+fn leaf_hashes<H: LeafHasher>(recipients: &[(String, Lamports, [u8; 32])]) -> Vec<Hash> {
+    tracing::debug!(count = recipients.len(), "hashing leaves");
+    let leaves = recipients
+        .iter()
+        .map(|(wallet, amount, secret)| H::hash_leaf(wallet, *amount, secret))
+        .collect();
+    tracing::debug!("leaves hashed");
+    leaves
+}
+
+/// Compute leaves: hash(recipient, amount, secret), labelled by wallet
+fn hash_recipients<H: LeafHasher>(recipients: &[(String, u64, [u8; 32])]) -> Vec<(String, Hash)> {
+    let wallets = recipients.iter().map(|(wallet, _, _)| wallet.clone());
+    wallets.zip(leaf_hashes::<H>(recipients)).collect()
+}
+
+/// Check that `(leaf_index, wallet)` placements fit the tree and that no
+/// wallet or leaf index appears twice, reporting every repeat
+fn check_placement(placed: &[(usize, &str)]) -> Result<(), MerkleError> {
+    if let Some(max_index) = placed.iter().map(|(leaf_index, _)| *leaf_index).max()
+        && max_index >= MAX_LEAVES
+    {
+        return Err(MerkleError::TooManyLeaves {
+            count: max_index + 1,
+            max: MAX_LEAVES,
+        });
+    }
+    let mut errors = check_duplicates(placed.iter().map(|(_, wallet)| *wallet))
+        .err()
+        .unwrap_or_default();
+    let mut taken: HashMap<usize, &str> = HashMap::with_capacity(placed.len());
+    for (leaf_index, wallet) in placed {
+        if let Some(first) = taken.insert(*leaf_index, wallet) {
+            errors.push(RecipientError::DuplicateLeafIndex {
+                leaf_index: *leaf_index,
+                first: first.to_string(),
+                duplicate: wallet.to_string(),
+            });
+        }
+    }
+    if !errors.is_empty() {
+        return Err(MerkleError::InvalidRecipients(errors));
+    }
+    Ok(())
+}
+
+/// Scan wallet labels for repeats, reporting all of them
+fn check_duplicates<'a>(
+    wallets: impl ExactSizeIterator<Item = &'a str>,
+) -> Result<(), Vec<RecipientError>> {
+    let mut first_seen: HashMap<&str, usize> = HashMap::with_capacity(wallets.len());
+    let mut errors = Vec::new();
+
+    for (i, wallet) in wallets.enumerate() {
+        if let Some(&first_at) = first_seen.get(wallet) {
+            errors.push(RecipientError::DuplicateWallet {
+                wallet: wallet.to_string(),
+                first_at,
+                duplicate_at: i,
+            });
+        } else {
+            first_seen.insert(wallet, i);
+        }
+    }
+
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+/// Layout of a recipient list file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecipientsFormat {
+    /// `[{ "wallet": "...", "amount_sol": "1.5" }]`
+    Json,
+    /// `wallet,amount_sol` lines, with or without that header; blank lines
+    /// are skipped
+    Csv,
+}
+
+/// `(wallet, lamports)` of every recipient in a file, whose amounts must be
+/// positive SOL amounts
+pub fn read_recipients_file(path: &Path, format: RecipientsFormat) -> Result<Vec<(String, Lamports)>, MerkleFileError> {
+    let entries = match format {
+        RecipientsFormat::Json => serde_json::from_slice(&std::fs::read(path)?)?,
+        RecipientsFormat::Csv => parse_csv_recipients(&std::fs::read_to_string(path)?)?,
+    };
+    entries
+        .into_iter()
+        .map(|entry| match sol_to_lamports(&entry.amount_sol).filter(|amount| *amount > 0) {
+            Some(amount) => Ok((entry.wallet, amount)),
+            None => Err(MerkleFileError::InvalidAmount {
+                wallet: entry.wallet,
+                amount: entry.amount_sol,
+            }),
+        })
+        .collect()
+}
+
+fn parse_csv_recipients(contents: &str) -> Result<Vec<FileRecipient>, MerkleFileError> {
+    let mut entries = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (entries.is_empty() && line.replace(' ', "") == "wallet,amount_sol") {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [wallet, amount_sol] = fields[..] else {
+            return Err(MerkleFileError::InvalidCsv {
+                line: i + 1,
+                reason: format!("expected wallet,amount_sol but found {} fields", fields.len()),
+            });
+        };
+        if wallet.is_empty() {
+            return Err(MerkleFileError::InvalidCsv {
+                line: i + 1,
+                reason: "wallet is empty".to_string(),
+            });
+        }
+        entries.push(FileRecipient {
+            wallet: wallet.to_string(),
+            amount_sol: amount_sol.to_string(),
+        });
+    }
+    Ok(entries)
+}
+
+use ark_bn254::Fr;
+use ark_ff::{PrimeField, BigInteger};
+use taceo_poseidon2::bn254::t4 as poseidon2;
+
+use super::poseidon::poseidon2_bn254_hash4;
+
+/// Compute leaf hash: hash(recipient, amount, secret)
+/// Matches Noir circuit expectations: poseidon(recipient, amount, secret)
+pub fn compute_leaf_hash(wallet: &str, amount: u64, secret: &[u8; 32]) -> Hash {
+    // 1. Recipient (Wallet) -> Field Element
+    let wallet_bytes = wallet_to_field_bytes(wallet);
+
+    // 2. Amount -> Field Element
+    // Use raw u64 directly (already in correct units)
+    let amount_lamports = amount;
+    // To read as correct integer in BE, put bytes at end.
+    let mut amount_arr = [0u8; 32];
+    amount_arr[24..32].copy_from_slice(&amount_lamports.to_be_bytes());
+
+    // 3. Secret is already [u8; 32], assuming it's random bytes valid for field.
+    // If secret >= Modulus, from_be_bytes will modulo it. That's fine for a secret.
+
+    // Poseidon hash 3 inputs
+    poseidon_hash_3(&wallet_bytes, &amount_arr, secret)
+}
+
+/// Recipient wallet as the circuit's `recipient` field, big-endian.
+/// Must match `wallet_to_field` in zk_proofs.rs:
+/// Decode Base58, take first 31 bytes, pad to 32 bytes (BE)
+pub(crate) fn wallet_to_field_bytes(wallet: &str) -> [u8; 32] {
+    let mut wallet_bytes = [0u8; 32];
+    if let Ok(decoded) = bs58::decode(wallet).into_vec() {
+        let len = decoded.len().min(31);
+        // Place at the end for proper BE integer representation (if we view it as a number)
+        wallet_bytes[32 - len..].copy_from_slice(&decoded[..len]);
+    } else {
+        // Fallback for tests/non-base58 (like "wallet1")
+        // Just use bytes, fit at end
+        let w_bytes = wallet.as_bytes();
+        let len = w_bytes.len().min(31);
+        wallet_bytes[32 - len..].copy_from_slice(&w_bytes[..len]);
+    }
+    wallet_bytes
+}
+
+/// Leaf index as a big-endian field element
+pub(crate) fn index_to_field_bytes(leaf_index: usize) -> [u8; 32] {
+    let mut index_arr = [0u8; 32];
+    index_arr[24..32].copy_from_slice(&(leaf_index as u64).to_be_bytes());
+    index_arr
+}
+
+/// Hash two nodes together
+fn hash_pair(left: &Hash, right: &Hash) -> Hash {
+    poseidon_hash_2(left, right)
+}
+
+/// Hash a group of sibling nodes into their parent
+fn hash_children(children: &[Hash]) -> Hash {
+    match children {
+        [left, right] => hash_pair(left, right),
+        [a, b, c, d] => poseidon_hash_4(a, b, c, d),
+        _ => unreachable!("unsupported merkle arity {}", children.len()),
+    }
+}
+
+/// Poseidon2 hash of 4 nodes in a single permutation
+fn poseidon_hash_4(a: &Hash, b: &Hash, c: &Hash, d: &Hash) -> Hash {
+    let fields = [a, b, c, d].map(bytes_to_field_element);
+    let hash = poseidon2_bn254_hash4(&fields).expect("exactly 4 inputs");
+    field_element_to_bytes(hash)
+}
+
+/// Poseidon2 sponge hash for 2 inputs (BN254)
+/// Matches Noir's Poseidon2::hash([a, b], 2)
+/// 
+/// Noir stdlib sponge construction:
+/// - Initial state: [0, 0, 0, iv] where iv = message_length * 2^64
+/// - Absorb inputs by adding to state[0..n]
+/// - Permute and squeeze state[0]
+pub(crate) fn poseidon_hash_2(a: &[u8; 32], b: &[u8; 32]) -> Hash {
+    let a_field = bytes_to_field_element(a);
+    let b_field = bytes_to_field_element(b);
+    
+    // iv = 2 * 2^64 = 36893488147419103232
+    let two_pow_64 = Fr::from(18446744073709551616u128);
+    let iv = Fr::from(2u64) * two_pow_64;
+    
+    // State after absorbing 2 inputs: [a, b, 0, iv]
+    let mut state = [a_field, b_field, Fr::from(0u64), iv];
+    
+    // Apply permutation
+    poseidon2::permutation_in_place(&mut state);
+    
+    // Output is state[0]
+    field_element_to_bytes(state[0])
+}
+
+/// Poseidon2 sponge hash for 3 inputs (BN254)
+/// Matches Noir's Poseidon2::hash([a, b, c], 3)
+pub(crate) fn poseidon_hash_3(a: &[u8; 32], b: &[u8; 32], c: &[u8; 32]) -> Hash {
+    let a_field = bytes_to_field_element(a);
+    let b_field = bytes_to_field_element(b);
+    let c_field = bytes_to_field_element(c);
+    
+    // iv = 3 * 2^64 = 55340232221128654848
+    let two_pow_64 = Fr::from(18446744073709551616u128);
+    let iv = Fr::from(3u64) * two_pow_64;
+    
+    // State after absorbing 3 inputs: [a, b, c, iv]
+    let mut state = [a_field, b_field, c_field, iv];
+    
+    // Apply permutation
+    poseidon2::permutation_in_place(&mut state);
+    
+    // Output is state[0]
+    field_element_to_bytes(state[0])
+}
+
+// Helpers for light-poseidon conversion
+// Use Big Endian to match Prover.toml hex strings (0x...)
+
+pub(crate) fn bytes_to_field_element(bytes: &[u8; 32]) -> Fr {
+    Fr::from_be_bytes_mod_order(bytes)
+}
+
+pub(crate) fn field_element_to_bytes(field: Fr) -> [u8; 32] {
+    let bigint = field.into_bigint();
+    let mut bytes = [0u8; 32];
+    // Use Big Endian bytes
+    bytes.copy_from_slice(&bigint.to_bytes_be());
+    bytes
+}
+
+/// Generate a random secret for a recipient
+/// Returns bytes that represent a valid BN254 field element (< modulus)
+pub fn generate_secret() -> [u8; 32] {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    
+    // Generate random-ish bytes
+    let mut random_bytes = [0u8; 32];
+    for (i, chunk) in random_bytes.chunks_mut(8).enumerate() {
+        let val = now.wrapping_add(i as u128).to_le_bytes();
+        chunk.copy_from_slice(&val[0..8]);
+    }
+    
+    // Convert to field element (reduces mod field order) then back to bytes
+    // This ensures the value is always < BN254 field modulus
+    let field_element = Fr::from_be_bytes_mod_order(&random_bytes);
+    field_element_to_bytes(field_element)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proof::compute_root_from_proof;
+    
+    #[test]
+    fn test_merkle_tree_basic() {
+        let secret1 = generate_secret();
+        let secret2 = generate_secret();
+        
+        let recipients = vec![
+            ("wallet1".to_string(), 1, secret1),
+            ("wallet2".to_string(), 2, secret2),
+        ];
+        
+        let tree = MerkleTree::from_recipients(&recipients).unwrap();
+        
+        // Root should be non-zero
+        let root = tree.root();
+        assert_ne!(root, [0u8; 32]);
+        
+        // Should get proof for wallet1
+        let proof = tree.get_proof("wallet1");
+        assert!(proof.is_some());
+        
+        let proof = proof.unwrap();
+        assert_eq!(proof.leaf_index, 0);
+        assert_eq!(proof.siblings.len(), TREE_DEPTH);
+    }
+    
+    #[test]
+    fn test_padding_uses_empty_leaf_and_zero_hashes() {
+        let tree = MerkleTree::from_recipients(&[("w".to_string(), 1, [7u8; 32])]).unwrap();
+        let proof = tree.get_proof("w").unwrap();
+
+        // A lone leaf at index 0 has an empty subtree as its sibling on every level
+        assert_eq!(proof.siblings, ZERO_HASHES[..TREE_DEPTH].to_vec());
+        assert_eq!(ZERO_HASHES[0], *EMPTY_LEAF);
+        assert_ne!(*EMPTY_LEAF, [0u8; 32]);
+        assert!(verify_proof(&tree.root(), &proof));
+
+        // The precomputed shortcut gives the same root as hashing every padded pair
+        let mut level = vec![compute_leaf_hash("w", 1, &[7u8; 32])];
+        level.resize(MAX_LEAVES, *EMPTY_LEAF);
+        while level.len() > 1 {
+            level = level.chunks(2).map(hash_children).collect();
+        }
+        assert_eq!(level[0], tree.root());
+    }
+
+    #[test]
+    fn test_recompute_from_leaves_detects_corrupted_nodes() {
+        let recipients: Vec<_> = (0..5)
+            .map(|i| (format!("wallet{}", i), i as u64 + 1, generate_secret()))
+            .collect();
+        let tree = MerkleTree::from_recipients(&recipients).unwrap();
+        assert_eq!(tree.recompute_from_leaves(), Ok(tree.root()));
+        assert!(tree.is_consistent());
+        assert!(MerkleTree::with_arity_4(&recipients).unwrap().is_consistent());
+
+        // An internal node over padding only: the root is unaffected
+        let mut corrupted = tree.clone();
+        corrupted.nodes[MAX_LEAVES + 100] = [9u8; 32];
+        assert_eq!(corrupted.root(), tree.root());
+        assert!(!corrupted.is_consistent());
+        assert_eq!(
+            corrupted.recompute_from_leaves(),
+            Err(MerkleError::CorruptedTree("node 100 at level 1 does not match its children".to_string()))
+        );
+
+        let mut truncated = tree;
+        truncated.nodes.pop();
+        assert!(matches!(truncated.recompute_from_leaves(), Err(MerkleError::CorruptedTree(_))));
+    }
+
+    #[test]
+    fn test_validate_all_proofs_reports_each_failing_wallet() {
+        let recipients: Vec<_> = (0..5)
+            .map(|i| (format!("wallet{}", i), i as u64 + 1, generate_secret()))
+            .collect();
+        let tree = MerkleTree::from_recipients(&recipients).unwrap();
+        assert_eq!(tree.validate_all_proofs(), Ok(()));
+        assert_eq!(MerkleTree::with_arity_4(&recipients).unwrap().validate_all_proofs(), Ok(()));
+
+        // The parent of leaves 0 and 1 is a sibling only in the proofs of 2 and 3
+        let mut corrupted = tree;
+        corrupted.nodes[MAX_LEAVES] = [9u8; 32];
+        let failures = corrupted.validate_all_proofs().unwrap_err();
+        let wallets: Vec<_> = failures.iter().map(|(wallet, _)| wallet.as_str()).collect();
+        assert_eq!(wallets, ["wallet2", "wallet3"]);
+        assert!(matches!(failures[0].1, MerkleError::CorruptedTree(_)));
+    }
+
+    #[test]
+    fn test_sha256_trees_share_the_tree_logic() {
+        let recipients: Vec<_> = (0..5)
+            .map(|i| (format!("wallet{}", i), i as u64 + 1, [i as u8; 32]))
+            .collect();
+        let poseidon = MerkleTree::from_recipients(&recipients).unwrap();
+        let sha256 = MerkleTree::<Sha256Hasher>::with_hasher(&recipients).unwrap();
+        assert_ne!(sha256.root(), poseidon.root());
+        assert_eq!(MerkleTree::<PoseidonHasher>::with_hasher(&recipients).unwrap().root(), poseidon.root());
+
+        // Padding shortcuts agree with rehashing every node
+        assert!(sha256.is_consistent());
+        assert_eq!(Sha256Hasher::zero_hashes()[0], Sha256Hasher::empty_leaf());
+
+        let proof = sha256.get_proof("wallet3").unwrap();
+        assert_eq!(proof.leaf, Sha256Hasher::hash_leaf("wallet3", 4, &[3u8; 32]));
+        assert_eq!(path_from_proof::<Sha256Hasher>(&proof, 2).last(), Some(&sha256.root()));
+        assert!(sha256.contains_with_verify("wallet3"));
+        assert!(!verify_proof(&sha256.root(), &proof), "Poseidon verification rejects SHA-256 proofs");
+    }
+
+    #[test]
+    fn test_arity_4_tree() {
+        let recipients: Vec<_> = (0..5)
+            .map(|i| (format!("wallet{}", i), i as u64 + 1, generate_secret()))
+            .collect();
+
+        let binary = MerkleTree::from_recipients(&recipients).unwrap();
+        let quaternary = MerkleTree::with_arity_4(&recipients).unwrap();
+
+        assert_eq!(quaternary.arity(), 4);
+        assert_ne!(binary.root(), quaternary.root());
+
+        // Half the levels, three siblings per level
+        let proof = quaternary.get_proof("wallet4").unwrap();
+        assert_eq!(proof.leaf_index, 4);
+        assert_eq!(proof.siblings.len(), 3 * TREE_DEPTH / 2);
+        assert_eq!(compute_root_from_proof(&proof, 4), quaternary.root());
+        assert!(quaternary.contains_with_verify("wallet4"));
+
+        assert_eq!(quaternary.depth(), TREE_DEPTH / 2);
+        assert_eq!(quaternary.width_at_level(1), MAX_LEAVES / 4);
+        assert_eq!(quaternary.total_nodes(), (4 * MAX_LEAVES - 1) / 3);
+
+        let proof = binary.get_proof("wallet4").unwrap();
+        assert!(verify_proof(&binary.root(), &proof));
+    }
+
+    #[test]
+    fn test_leaf_order_follows_input_slice() {
+        let secret = [7u8; 32];
+        let forward = vec![
+            ("wallet1".to_string(), 1, secret),
+            ("wallet2".to_string(), 2, secret),
+            ("wallet3".to_string(), 3, secret),
+        ];
+        let mut reversed = forward.clone();
+        reversed.reverse();
+
+        // Rebuilding from the same slice is deterministic
+        let tree = MerkleTree::from_recipients(&forward).unwrap();
+        assert_eq!(tree.root(), MerkleTree::from_recipients(&forward).unwrap().root());
+
+        // Leaf positions come from the slice, so reordering changes the root...
+        let reordered = MerkleTree::from_recipients(&reversed).unwrap();
+        assert_ne!(tree.root(), reordered.root());
+        assert_eq!(reordered.get_leaf_index("wallet3"), Some(0));
+
+        // ...while the audit view is always sorted by wallet
+        let wallets: Vec<&str> = reordered.leaf_indices_sorted().map(|(w, _)| w).collect();
+        assert_eq!(wallets, ["wallet1", "wallet2", "wallet3"]);
+        assert_eq!(
+            reordered.leaf_indices_sorted().collect::<Vec<_>>(),
+            [("wallet1", 2), ("wallet2", 1), ("wallet3", 0)]
+        );
+    }
+
+    #[test]
+    fn test_path_to_root_runs_from_leaf_to_root() {
+        let recipients: Vec<_> = (0..5)
+            .map(|i| (format!("wallet{}", i), i as u64 + 1, generate_secret()))
+            .collect();
+
+        for tree in [
+            MerkleTree::from_recipients(&recipients).unwrap(),
+            MerkleTree::with_arity_4(&recipients).unwrap(),
+        ] {
+            let path = tree.path_to_root("wallet3").unwrap();
+            let leaf_index = tree.get_leaf_index("wallet3").unwrap();
+
+            assert_eq!(path.len(), tree.depth() + 1);
+            assert_eq!(path.first(), Some(&tree.nodes[leaf_index]));
+            assert_eq!(path.last(), Some(&tree.root()));
+        }
+
+        // A verifier recomputes the same nodes from the proof
+        let tree = MerkleTree::from_recipients(&recipients).unwrap();
+        let proof = tree.get_proof("wallet3").unwrap();
+        assert_eq!(recompute_path(&proof), tree.path_to_root("wallet3").unwrap());
+
+        let tree = MerkleTree::from_recipients(&recipients).unwrap();
+        assert_eq!(
+            tree.path_to_root("missing"),
+            Err(MerkleError::WalletNotFound("missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_membership_checks() {
+        let recipients = vec![
+            ("wallet2".to_string(), 2, [2u8; 32]),
+            ("wallet1".to_string(), 1, [1u8; 32]),
+        ];
+        let tree = MerkleTree::from_recipients(&recipients).unwrap();
+
+        assert!(tree.contains("wallet1"));
+        assert!(!tree.contains("wallet3"));
+        assert!(tree.contains_with_verify("wallet2"));
+        assert!(!tree.contains_with_verify("wallet3"));
+        assert_eq!(tree.all_wallets().collect::<Vec<_>>(), ["wallet1", "wallet2"]);
+
+        // A proof does not verify against a different root
+        let proof = tree.get_proof("wallet1").unwrap();
+        assert!(verify_proof(&tree.root(), &proof));
+        assert!(!verify_proof(&[1u8; 32], &proof));
+    }
+
+    #[test]
+    fn test_batch_proofs_cover_every_leaf() {
+        let recipients: Vec<(String, u64, [u8; 32])> = ["c", "a", "b"]
+            .iter()
+            .map(|w| (w.to_string(), 10, [3u8; 32]))
+            .collect();
+        let tree = MerkleTree::from_recipients(&recipients).unwrap();
+
+        let proofs = tree.batch_proofs();
+        let wallets: Vec<&str> = proofs.iter().map(|(w, _)| w.as_str()).collect();
+        assert_eq!(wallets, ["c", "a", "b"]);
+        for (wallet, proof) in &proofs {
+            assert_eq!(proof.siblings, tree.get_proof(wallet).unwrap().siblings);
+            assert!(verify_proof(&tree.root(), proof));
+        }
+    }
+
+    #[test]
+    fn test_capacity_accessors() {
+        let recipients: Vec<_> = (0..64)
+            .map(|i| (format!("wallet{}", i), 1, [0u8; 32]))
+            .collect();
+        let tree = MerkleTree::from_recipients(&recipients).unwrap();
+
+        assert_eq!(tree.leaf_count(), 64);
+        assert_eq!(tree.capacity(), MAX_LEAVES);
+        assert_eq!(tree.utilization(), 0.25);
+        assert!(!tree.is_full());
+
+        let recipients: Vec<_> = (0..MAX_LEAVES)
+            .map(|i| (format!("wallet{}", i), 1, [0u8; 32]))
+            .collect();
+        assert!(MerkleTree::from_recipients(&recipients).unwrap().is_full());
+    }
+
+    #[test]
+    fn test_duplicate_wallets_rejected() {
+        let recipients = vec![
+            ("wallet1".to_string(), 1, [1u8; 32]),
+            ("wallet2".to_string(), 2, [2u8; 32]),
+            ("wallet1".to_string(), 3, [3u8; 32]),
+            ("wallet2".to_string(), 4, [4u8; 32]),
+            ("wallet1".to_string(), 5, [5u8; 32]),
+        ];
+
+        let errors = MerkleTree::from_recipients(&recipients).unwrap_err();
+
+        assert_eq!(
+            errors,
+            MerkleError::InvalidRecipients(vec![
+                RecipientError::DuplicateWallet {
+                    wallet: "wallet1".to_string(),
+                    first_at: 0,
+                    duplicate_at: 2
+                },
+                RecipientError::DuplicateWallet {
+                    wallet: "wallet2".to_string(),
+                    first_at: 1,
+                    duplicate_at: 3
+                },
+                RecipientError::DuplicateWallet {
+                    wallet: "wallet1".to_string(),
+                    first_at: 0,
+                    duplicate_at: 4
+                },
+            ])
+        );
+        assert!(MerkleTree::with_arity_4(&recipients).is_err());
+    }
+
+    #[test]
+    fn test_too_many_recipients_is_an_error() {
+        let recipients: Vec<_> = (0..=MAX_LEAVES)
+            .map(|i| (format!("wallet{i}"), 1, [1u8; 32]))
+            .collect();
+        let too_many = MerkleError::TooManyLeaves {
+            count: MAX_LEAVES + 1,
+            max: MAX_LEAVES,
+        };
+
+        assert_eq!(MerkleTree::from_recipients(&recipients).unwrap_err(), too_many);
+        assert_eq!(MerkleTree::<Sha256Hasher>::with_hasher(&recipients).unwrap_err(), too_many);
+        assert_eq!(MerkleTree::with_arity_4(&recipients).unwrap_err(), too_many);
+        assert!(MerkleTree::from_recipients(&recipients[..MAX_LEAVES]).is_ok());
+    }
+
+    #[test]
+    fn test_from_leaves_matches_from_recipients() {
+        let recipients: Vec<_> = (0..10)
+            .map(|i| (format!("wallet{}", i), i as u64 * 100, generate_secret()))
+            .collect();
+        let leaves: Vec<(String, Hash)> = recipients
+            .iter()
+            .map(|(w, a, s)| (w.clone(), compute_leaf_hash(w, *a, s)))
+            .collect();
+
+        let from_recipients = MerkleTree::from_recipients(&recipients).unwrap();
+        let from_leaves = MerkleTree::from_leaves(leaves).unwrap();
+
+        assert_eq!(from_recipients.root(), from_leaves.root());
+        assert_eq!(from_leaves.leaf_count(), 10);
+        assert_eq!(
+            from_recipients.get_proof("wallet7").unwrap().siblings,
+            from_leaves.get_proof("wallet7").unwrap().siblings
+        );
+    }
+
+    #[test]
+    fn test_precomputed_leaf_hashes_build_the_same_tree() {
+        let recipients: Vec<_> = (0..4)
+            .map(|i| (format!("wallet{}", i), i as u64 + 1, [i as u8 + 1; 32]))
+            .collect();
+        let leaves = compute_all_leaf_hashes(&recipients);
+        assert_eq!(leaves[2], compute_leaf_hash("wallet2", 3, &[3u8; 32]));
+
+        let labelled = recipients.iter().map(|(wallet, _, _)| wallet.clone()).zip(leaves).collect();
+        let tree = MerkleTree::from_leaves(labelled).unwrap();
+        assert_eq!(tree.root(), MerkleTree::from_recipients(&recipients).unwrap().root());
+    }
+
+    #[test]
+    fn test_from_leaves_rejects_overflow_and_duplicates() {
+        let too_many = (0..=MAX_LEAVES).map(|i| (i.to_string(), [0u8; 32])).collect();
+        assert_eq!(
+            MerkleTree::from_leaves(too_many).unwrap_err(),
+            MerkleError::TooManyLeaves {
+                count: MAX_LEAVES + 1,
+                max: MAX_LEAVES
+            }
+        );
+
+        let duplicated = vec![("a".to_string(), [1u8; 32]), ("a".to_string(), [2u8; 32])];
+        assert!(matches!(
+            MerkleTree::from_leaves(duplicated),
+            Err(MerkleError::InvalidRecipients(_))
+        ));
+    }
+
+    #[test]
+    fn test_indexed_recipients_keep_their_leaf_indices() {
+        let recipient = |i: usize, wallet: &str| (i, wallet.to_string(), 100, [i as u8; 32]);
+        let tree = MerkleTree::from_indexed_recipients(&[recipient(0, "first"), recipient(2, "third")]).unwrap();
+
+        assert_eq!(tree.get_leaf_index("third"), Some(2));
+        let proof = tree.get_proof("third").unwrap();
+        assert_eq!(proof.leaf_index, 2);
+        assert!(verify_proof(&tree.root(), &proof));
+        assert_eq!(tree.secret("third"), Some(&[2u8; 32]));
+
+        // The gap is an empty leaf, so packing the same recipients yields the same root
+        let packed = vec![(0, "first".to_string(), 100, [0u8; 32]), (1, "gap".to_string(), 100, [1u8; 32])];
+        let leaves = vec![
+            ("first".to_string(), compute_leaf_hash("first", 100, &[0; 32])),
+            ("gap".to_string(), *EMPTY_LEAF),
+            ("third".to_string(), compute_leaf_hash("third", 100, &[2; 32])),
+        ];
+        assert_eq!(MerkleTree::from_leaves(leaves).unwrap().root(), tree.root());
+        assert_ne!(MerkleTree::from_indexed_recipients(&packed).unwrap().root(), tree.root());
+    }
+
+    #[test]
+    fn test_indexed_recipients_reject_shared_and_out_of_range_indices() {
+        let shared = vec![(1, "a".to_string(), 1, [1u8; 32]), (1, "b".to_string(), 1, [2u8; 32])];
+        assert_eq!(
+            MerkleTree::from_indexed_recipients(&shared).unwrap_err(),
+            MerkleError::InvalidRecipients(vec![RecipientError::DuplicateLeafIndex {
+                leaf_index: 1,
+                first: "a".to_string(),
+                duplicate: "b".to_string(),
+            }])
+        );
+
+        let beyond = vec![(MAX_LEAVES, "a".to_string(), 1, [1u8; 32])];
+        assert_eq!(
+            MerkleTree::from_indexed_recipients(&beyond).unwrap_err(),
+            MerkleError::TooManyLeaves {
+                count: MAX_LEAVES + 1,
+                max: MAX_LEAVES
+            }
+        );
+    }
+
+    #[test]
+    fn test_commitment_round_trips_and_checks_proof_depth() {
+        let recipients = vec![
+            ("wallet1".to_string(), 100, [1u8; 32]),
+            ("wallet2".to_string(), 200, [2u8; 32]),
+        ];
+        let tree = MerkleTree::from_recipients(&recipients).unwrap();
+        let commitment = tree.to_commitment();
+        assert_eq!((commitment.root, commitment.depth, commitment.leaf_count), (tree.root(), 8, 2));
+
+        let data = commitment.to_borsh_bytes();
+        assert_eq!(data.len(), MERKLE_COMMITMENT_LEN);
+        assert_eq!(MerkleCommitment::from_borsh_bytes(&data).unwrap(), commitment);
+        assert!(MerkleCommitment::from_borsh_bytes(&data[1..]).is_err());
+
+        let mut proof = tree.get_proof("wallet2").unwrap();
+        assert!(commitment.verify_proof(&proof));
+        let shallower = MerkleCommitment { depth: 7, ..commitment };
+        assert!(!shallower.verify_proof(&proof));
+        proof.siblings.pop();
+        assert!(!commitment.verify_proof(&proof));
+    }
+
+    #[test]
+    fn test_little_endian_round_trip() {
+        let root = MerkleTree::from_recipients(&[("wallet1".to_string(), 5, [1u8; 32])])
+            .unwrap()
+            .root();
+
+        assert_eq!(hash_to_le_bytes(&hash_from_le_bytes(&root)), root);
+        assert_eq!(hash_from_le_bytes(&hash_to_le_bytes(&root)), root);
+        assert_eq!(hash_to_le_bytes(&root)[0], root[31]);
+        // The same element under either convention
+        assert_eq!(hash_to_fr_le(&hash_to_le_bytes(&root)), hash_to_fr_be(&root));
+        assert_eq!(field_element_to_bytes(hash_to_fr_be(&root)), root);
+    }
+
+    /// Path in the temp directory unique to this process and `name`
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("shadow-drop-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_tree_round_trips_through_json_files() {
+        let input = temp_path("recipients.json");
+        std::fs::write(
+            &input,
+            r#"[{ "wallet": "wallet1", "amount_sol": "1.5" }, { "wallet": "wallet2", "amount_sol": "0.25" }]"#,
+        )
+        .unwrap();
+        let tree = MerkleTree::from_file(&input).unwrap();
+        assert_eq!(tree.leaf_count(), 2);
+        assert_eq!(tree.get_leaf_index("wallet2"), Some(1));
+
+        let output = temp_path("tree.json");
+        tree.to_json_file(&output, true).unwrap();
+        let saved: serde_json::Value = serde_json::from_slice(&std::fs::read(&output).unwrap()).unwrap();
+        assert_eq!(saved["version"], TREE_FILE_VERSION);
+        assert_eq!(saved["root"], hex_hash(&tree.root()));
+        assert_eq!(saved["leaf_count"], 2);
+        assert_eq!(saved["recipients"][1]["proof"]["siblings"].as_array().unwrap().len(), TREE_DEPTH);
+        let loaded = MerkleTree::from_json_file(&output).unwrap();
+        assert_eq!(loaded.root(), tree.root());
+        assert_eq!(loaded.get_leaf_index("wallet2"), Some(1));
+
+        tree.to_json_file(&output, false).unwrap();
+        let saved: serde_json::Value = serde_json::from_slice(&std::fs::read(&output).unwrap()).unwrap();
+        assert!(saved["recipients"][0].get("proof").is_none());
+        assert_eq!(MerkleTree::from_json_file(&output).unwrap().root(), tree.root());
+
+        // The saved secret reproduces the leaf for the amount in lamports
+        let secrets = temp_path("secrets.json");
+        tree.secrets_to_json_file(&secrets).unwrap();
+        let saved: serde_json::Value = serde_json::from_slice(&std::fs::read(&secrets).unwrap()).unwrap();
+        let entry = &saved["secrets"][0];
+        assert_eq!(entry["wallet"], "wallet1");
+        let secret = parse_hash(entry["secret"].as_str().unwrap()).unwrap();
+        assert_eq!(tree.nodes[0], compute_leaf_hash("wallet1", 1_500_000_000, &secret));
+
+        for path in [input, output, secrets] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_from_json_file_rejects_tampered_trees() {
+        let recipients: Vec<(String, u64, [u8; 32])> =
+            (0..3u8).map(|i| (format!("wallet{i}"), 1, [i + 1; 32])).collect();
+        let tree = MerkleTree::with_arity_4(&recipients).unwrap();
+        let path = temp_path("tampered.json");
+        tree.to_json_file(&path, true).unwrap();
+        let saved: serde_json::Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+
+        let tamper = |edit: &dyn Fn(&mut serde_json::Value)| {
+            let mut file = saved.clone();
+            edit(&mut file);
+            std::fs::write(&path, file.to_string()).unwrap();
+            MerkleTree::from_json_file(&path).unwrap_err()
+        };
+        assert!(matches!(
+            tamper(&|file| file["version"] = "2.0".into()),
+            MerkleFileError::UnsupportedVersion(_)
+        ));
+        assert!(matches!(
+            tamper(&|file| file["root"] = hex_hash(&[7u8; 32]).into()),
+            MerkleFileError::RootMismatch { .. }
+        ));
+        assert!(matches!(
+            tamper(&|file| file["recipients"][0]["proof"]["siblings"][0] = hex_hash(&[7u8; 32]).into()),
+            MerkleFileError::InvalidFile(_)
+        ));
+        // A moved leaf is read at its new index, so the root no longer matches
+        assert!(matches!(
+            tamper(&|file| file["recipients"][0]["leaf_index"] = 9.into()),
+            MerkleFileError::RootMismatch { .. }
+        ));
+        assert!(matches!(
+            tamper(&|file| file["recipients"][0]["leaf_index"] = 1.into()),
+            MerkleFileError::Merkle(MerkleError::InvalidRecipients(errors))
+                if matches!(errors[..], [RecipientError::DuplicateLeafIndex { leaf_index: 1, .. }])
+        ));
+        assert!(matches!(
+            tamper(&|file| file["recipients"][0]["leaf_index"] = MAX_LEAVES.into()),
+            MerkleFileError::Merkle(MerkleError::TooManyLeaves { .. })
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_tree_with_a_removed_recipient_round_trips_through_json_files() {
+        // wallet1 was removed, leaving leaf 1 empty
+        let recipients = vec![
+            (0, "wallet0".to_string(), 100, [1u8; 32]),
+            (2, "wallet2".to_string(), 300, [3u8; 32]),
+        ];
+        let tree = MerkleTree::from_indexed_recipients(&recipients).unwrap();
+        let path = temp_path("gapped.json");
+        tree.to_json_file(&path, true).unwrap();
+
+        let loaded = MerkleTree::from_json_file(&path).unwrap();
+        assert_eq!(loaded.root(), tree.root());
+        assert_eq!(loaded.leaf_count(), 2);
+        assert_eq!(loaded.get_leaf_index("wallet2"), Some(2));
+        let proof = loaded.get_proof("wallet2").unwrap();
+        assert_eq!(proof.leaf_index, 2);
+        assert!(verify_proof(&tree.root(), &proof));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_from_file_reports_each_failure_kind() {
+        assert!(matches!(
+            MerkleTree::from_file(&temp_path("missing.json")),
+            Err(MerkleFileError::Io(_))
+        ));
+
+        let path = temp_path("invalid.json");
+        let cases = [
+            (r#"{ "wallet": "wallet1" }"#, "json"),
+            (r#"[{ "wallet": "wallet1", "amount_sol": "1.5 SOL" }]"#, "amount"),
+            (r#"[{ "wallet": "wallet1", "amount_sol": "0" }]"#, "amount"),
+            (r#"[{ "wallet": "wallet1", "amount_sol": "1" }, { "wallet": "wallet1", "amount_sol": "2" }]"#, "merkle"),
+        ];
+        for (contents, kind) in cases {
+            std::fs::write(&path, contents).unwrap();
+            let err = MerkleTree::from_file(&path).unwrap_err();
+            let matched = match kind {
+                "json" => matches!(err, MerkleFileError::Json(_)),
+                "amount" => matches!(err, MerkleFileError::InvalidAmount { .. }),
+                _ => matches!(err, MerkleFileError::Merkle(MerkleError::InvalidRecipients(_))),
+            };
+            assert!(matched, "{kind}: {err}");
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_from_csv_file_matches_json_recipients() {
+        let path = temp_path("recipients.csv");
+        std::fs::write(&path, "wallet,amount_sol\nwallet1, 1.5\n\nwallet2,0.25\n").unwrap();
+        let tree = MerkleTree::from_csv_file(&path).unwrap();
+        assert_eq!(tree.leaf_count(), 2);
+        assert_eq!(tree.get_leaf_index("wallet2"), Some(1));
+        let secret = tree.secrets["wallet1"];
+        assert_eq!(tree.nodes[0], compute_leaf_hash("wallet1", 1_500_000_000, &secret));
+
+        // The header is optional
+        std::fs::write(&path, "wallet1,1\n").unwrap();
+        assert_eq!(MerkleTree::from_csv_file(&path).unwrap().leaf_count(), 1);
+
+        std::fs::write(&path, "wallet,amount_sol\nwallet1,1\nwallet2\n").unwrap();
+        assert!(matches!(
+            MerkleTree::from_csv_file(&path),
+            Err(MerkleFileError::InvalidCsv { line: 3, .. })
+        ));
+        std::fs::write(&path, "wallet1,1\nwallet1,2\n").unwrap();
+        assert!(matches!(
+            MerkleTree::from_csv_file(&path),
+            Err(MerkleFileError::Merkle(MerkleError::InvalidRecipients(_)))
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,109 @@
+//! Nullifiers: the values a claim spends so the same leaf cannot be claimed
+//! twice, and the keys derived from them for rounds and installments
+
+use crate::merkle::{Hash, index_to_field_bytes, poseidon_hash_2, poseidon_hash_3, wallet_to_field_bytes};
+
+/// Compute nullifier: hash(secret, leaf_index)
+///
+/// The on-chain nullifier: what the circuit checks and what claim
+/// instructions carry, since the committed artifacts and the on-chain
+/// verifying key are the v1 build. The circuit binds the leaf to its public
+/// `recipient`, but the nullifier itself is not bound to the wallet, so the
+/// backend spends `compute_nullifier_v2` instead.
+pub fn compute_nullifier(secret: &[u8; 32], leaf_index: usize) -> Hash {
+    poseidon_hash_2(secret, &index_to_field_bytes(leaf_index))
+}
+
+/// Compute nullifier: hash(secret, leaf_index, recipient)
+/// `Poseidon2::hash([secret, leaf_index, recipient], 3)` in Noir, with the
+/// wallet encoded as in `compute_leaf_hash`.
+///
+/// The key backend claims spend in the nullifier store; it never goes on
+/// chain, where the v1 `compute_nullifier` is checked. Moving the circuit to
+/// it is a breaking change: the circuit artifacts, `Prover.toml`, the
+/// on-chain verifying key and the claim instruction must change together.
+pub fn compute_nullifier_v2(secret: &[u8; 32], leaf_index: usize, wallet: &str) -> Hash {
+    poseidon_hash_3(secret, &index_to_field_bytes(leaf_index), &wallet_to_field_bytes(wallet))
+}
+
+/// Compute commitment: hash(root, nullifier)
+/// Lets an on-chain program check a single value binding a claim to a tree.
+/// Matches Noir's `Poseidon2::hash([root, nullifier], 2)`.
+pub fn compute_commitment(root: &Hash, nullifier: &Hash) -> Hash {
+    poseidon_hash_2(root, nullifier)
+}
+
+/// Nullifier key of a claim in one round of a multi-round campaign:
+/// hash(nullifier, round). Spending this instead of the claim's nullifier
+/// lets the same nullifier be spent once per round. `round` is the 16-byte
+/// round id, read as a big-endian field element.
+pub fn round_nullifier(nullifier: &Hash, round: &[u8; 16]) -> Hash {
+    let mut round_bytes = [0u8; 32];
+    round_bytes[16..].copy_from_slice(round);
+    poseidon_hash_2(nullifier, &round_bytes)
+}
+
+/// Nullifier of installment `sequence` of a partial claim:
+/// hash(nullifier, sequence). Each installment spends a fresh value derived
+/// from the one nullifier of the leaf, so the proof stays the same while a
+/// replayed installment is rejected.
+pub fn installment_nullifier(nullifier: &Hash, sequence: u32) -> Hash {
+    poseidon_hash_2(nullifier, &index_to_field_bytes(sequence as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Fr;
+
+    use super::*;
+    use crate::merkle::{MerkleTree, bytes_to_field_element, field_element_to_bytes, generate_secret};
+
+    #[test]
+    fn test_commitment_matches_noir_two_input_hash() {
+        use crate::poseidon::poseidon2_bn254_t4_hash_fields;
+
+        let secret = generate_secret();
+        let tree = MerkleTree::from_recipients(&[("wallet1".to_string(), 1, secret)]).unwrap();
+        let root = tree.root();
+        let nullifier = compute_nullifier_v2(&secret, 0, "wallet1");
+
+        let expected = poseidon2_bn254_t4_hash_fields(&[
+            bytes_to_field_element(&root),
+            bytes_to_field_element(&nullifier),
+        ])
+        .unwrap();
+
+        let commitment = compute_commitment(&root, &nullifier);
+        assert_eq!(commitment, field_element_to_bytes(expected));
+        assert_ne!(commitment, compute_commitment(&nullifier, &root));
+    }
+
+    #[test]
+    fn test_nullifier_uniqueness() {
+        let secret = generate_secret();
+        
+        let null1 = compute_nullifier_v2(&secret, 0, "wallet1");
+        let null2 = compute_nullifier_v2(&secret, 1, "wallet1");
+        let null3 = compute_nullifier_v2(&secret, 0, "wallet2");
+        
+        assert_ne!(null1, null2);
+        assert_ne!(null1, null3);
+    }
+
+    #[test]
+    fn test_nullifier_v2_matches_noir_three_input_hash() {
+        use crate::poseidon::poseidon2_hash_many;
+
+        let secret = generate_secret();
+        let wallet = "So11111111111111111111111111111111111111112";
+        let expected = poseidon2_hash_many(&[
+            bytes_to_field_element(&secret),
+            Fr::from(5u64),
+            bytes_to_field_element(&wallet_to_field_bytes(wallet)),
+        ]);
+
+        assert_eq!(compute_nullifier_v2(&secret, 5, wallet), field_element_to_bytes(expected));
+        let v1 = compute_nullifier(&secret, 5);
+        assert_ne!(compute_nullifier_v2(&secret, 5, wallet), v1);
+    }
+}
@@ -0,0 +1,394 @@
+//! Poseidon2 sponge hashing over BN254
+//!
+//! Mirrors the sponge construction used by Noir's `Poseidon2::hash`:
+//! the capacity element is initialised to `iv = input_count * 2^64`, inputs
+//! are absorbed into the rate portion of the state, and `state[0]` is squeezed
+//! after the permutation.
+
+use ark_ff::MontFp;
+use taceo_poseidon2::bn254::{t3, t4, t8};
+
+use crate::field::{Fr, fr_two_pow_64};
+
+/// Poseidon hashing errors
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PoseidonError {
+    #[error("state size {state_size} accepts {min} to {max} inputs, got {got}")]
+    UnsupportedInputCount {
+        state_size: usize,
+        min: usize,
+        max: usize,
+        got: usize,
+    },
+
+    #[error("cannot hash an empty input")]
+    EmptyInput,
+
+    #[error("Poseidon2 t{0} permutation does not match the reference parameters")]
+    KnownAnswerMismatch(usize),
+}
+
+/// Rate of the t4 sponge (state size 4, capacity 1)
+pub const T4_RATE: usize = 3;
+
+/// State size of the permutation behind the t4 sponge and Noir's `Poseidon2`
+pub const POSEIDON2_T4_STATE_SIZE: usize = 4;
+
+// Stops compiling if `taceo-poseidon2` changes the t4 state size
+const _: fn(&mut [Fr; POSEIDON2_T4_STATE_SIZE]) = t4::permutation_in_place;
+
+/// First external round constant of the BN254 t4 parameters, logged at
+/// startup so operators can check them against Noir's
+pub const POSEIDON2_T4_FIRST_CONSTANT: Fr =
+    MontFp!("11633431549750490989983886834189948010834808234699737327785600195936805266405");
+
+/// Permutation of `[0, 1, 2, 3]` under the reference BN254 t4 parameters, from
+/// the Poseidon2 parameter generation script (HorizenLabs/poseidon2)
+const T4_KNOWN_ANSWER: [Fr; POSEIDON2_T4_STATE_SIZE] = [
+    MontFp!("786823568102245344938517132468097745676732687098822989626730198331658606391"),
+    MontFp!("16105493617470833344375945651585194737369509580406730765188791202038211593826"),
+    MontFp!("2169165722086073256768101917994796590773204847633762971322389403847680713675"),
+    MontFp!("20837792685223053096472825292260687493226094382304778455120670180090619921530"),
+];
+
+/// Check the t4 permutation against its known answer, so a dependency
+/// update that changes the parameters fails at startup rather than silently
+/// changing every hash
+pub fn check_poseidon2_t4() -> Result<(), PoseidonError> {
+    let state = t4::permutation(&[0u64, 1, 2, 3].map(Fr::from));
+    if state != T4_KNOWN_ANSWER {
+        return Err(PoseidonError::KnownAnswerMismatch(POSEIDON2_T4_STATE_SIZE));
+    }
+    Ok(())
+}
+
+/// Sponge IV for a message of `input_count` elements
+fn sponge_iv(input_count: usize) -> Fr {
+    Fr::from(input_count as u64) * fr_two_pow_64()
+}
+
+fn check_input_count(
+    state_size: usize,
+    min: usize,
+    max: usize,
+    got: usize,
+) -> Result<(), PoseidonError> {
+    if got < min || got > max {
+        return Err(PoseidonError::UnsupportedInputCount {
+            state_size,
+            min,
+            max,
+            got,
+        });
+    }
+    Ok(())
+}
+
+/// Poseidon2 t3 sponge (rate 2, capacity 1) for 1 or 2 inputs
+///
+/// State before permutation: `[a, b, iv]`, unused rate slots are zero.
+pub fn poseidon2_bn254_t3_hash_fields(inputs: &[Fr]) -> Result<Fr, PoseidonError> {
+    check_input_count(3, 1, 2, inputs.len())?;
+
+    let mut state = [Fr::from(0u64), Fr::from(0u64), sponge_iv(inputs.len())];
+    state[..inputs.len()].copy_from_slice(inputs);
+
+    t3::permutation_in_place(&mut state);
+    Ok(state[0])
+}
+
+/// Poseidon2 t4 sponge (rate 3, capacity 1) for 1 to 3 inputs
+///
+/// State before permutation: `[a, b, c, iv]`, unused rate slots are zero.
+/// This is what Noir's `Poseidon2::hash` computes for messages of up to 3 elements.
+pub fn poseidon2_bn254_t4_hash_fields(inputs: &[Fr]) -> Result<Fr, PoseidonError> {
+    check_input_count(4, 1, 3, inputs.len())?;
+
+    let mut state = [
+        Fr::from(0u64),
+        Fr::from(0u64),
+        Fr::from(0u64),
+        sponge_iv(inputs.len()),
+    ];
+    state[..inputs.len()].copy_from_slice(inputs);
+
+    t4::permutation_in_place(&mut state);
+    Ok(state[0])
+}
+
+/// Poseidon2 t4 duplex sponge (rate 3, capacity 1), as in Noir's `Poseidon2`
+///
+/// The message length is fixed up front and encoded in the IV. Inputs are
+/// buffered 3 at a time and each full buffer is added into the rate and
+/// permuted; squeezing absorbs the remaining (possibly empty) buffer with one
+/// last permutation.
+#[derive(Debug, Clone)]
+pub struct Poseidon2Sponge {
+    state: [Fr; 4],
+    cache: [Fr; T4_RATE],
+    cache_len: usize,
+}
+
+impl Poseidon2Sponge {
+    /// Start a sponge for a message of `message_len` elements
+    pub fn new(message_len: usize) -> Self {
+        Self {
+            state: [
+                Fr::from(0u64),
+                Fr::from(0u64),
+                Fr::from(0u64),
+                sponge_iv(message_len),
+            ],
+            cache: [Fr::from(0u64); T4_RATE],
+            cache_len: 0,
+        }
+    }
+
+    pub fn absorb(&mut self, input: Fr) {
+        if self.cache_len == T4_RATE {
+            self.duplex();
+        }
+        self.cache[self.cache_len] = input;
+        self.cache_len += 1;
+    }
+
+    pub fn squeeze(mut self) -> Fr {
+        self.duplex();
+        self.state[0]
+    }
+
+    fn duplex(&mut self) {
+        for (slot, input) in self.state.iter_mut().zip(&self.cache[..self.cache_len]) {
+            *slot += input;
+        }
+        t4::permutation_in_place(&mut self.state);
+        self.cache_len = 0;
+    }
+}
+
+/// Poseidon2 t4 duplex sponge over any number of inputs
+///
+/// Matches Noir's `Poseidon2::hash(inputs, inputs.len())`: inputs are absorbed
+/// 3 at a time, permuting between chunks, and the final (possibly partial)
+/// chunk is absorbed before squeezing `state[0]`. The message length is
+/// encoded in the IV, which already separates messages that differ only by
+/// trailing zeros, so no padding element is appended.
+pub fn poseidon_hash_n(inputs: &[Fr]) -> Result<Fr, PoseidonError> {
+    if inputs.is_empty() {
+        return Err(PoseidonError::EmptyInput);
+    }
+    Ok(poseidon2_hash_many(inputs))
+}
+
+/// Stateless Poseidon2 sponge hash of a slice of any length, including empty
+///
+/// Same construction as `poseidon_hash_n`. Noir does not pad: the length in
+/// the IV is the domain separator, so `poseidon2_hash_many(&[a, b])` equals
+/// `poseidon2_bn254_t4_hash_fields(&[a, b])`.
+pub fn poseidon2_hash_many(inputs: &[Fr]) -> Fr {
+    let mut sponge = Poseidon2Sponge::new(inputs.len());
+    for input in inputs {
+        sponge.absorb(*input);
+    }
+    sponge.squeeze()
+}
+
+/// Poseidon2 hash of exactly 4 inputs in a single permutation
+///
+/// Poseidon2 only defines its external matrix for state sizes 2, 3 and
+/// multiples of 4, so there is no standard t5 instance. The smallest standard
+/// width with rate >= 4 is t8, used here with state `[a, b, c, d, 0, 0, 0, iv]`
+/// and `iv = 4 * 2^64`.
+pub fn poseidon2_bn254_hash4(inputs: &[Fr]) -> Result<Fr, PoseidonError> {
+    check_input_count(8, 4, 4, inputs.len())?;
+
+    let mut state = [Fr::from(0u64); 8];
+    state[..4].copy_from_slice(inputs);
+    state[7] = sponge_iv(4);
+
+    t8::permutation_in_place(&mut state);
+    Ok(state[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::{BigInteger, PrimeField};
+    use std::str::FromStr;
+
+    fn fr_from_hex(hex: &str) -> Fr {
+        Fr::from_be_bytes_mod_order(&hex::decode(hex.trim_start_matches("0x")).unwrap())
+    }
+
+    fn fr_to_hex(f: Fr) -> String {
+        format!("0x{}", hex::encode(f.into_bigint().to_bytes_be()))
+    }
+
+    #[test]
+    fn test_t3_permutation_known_answer() {
+        // Reference vector from the Poseidon2 parameter generation script
+        // (HorizenLabs/poseidon2), permutation of [0, 1, 2]
+        let state = t3::permutation(&[Fr::from(0u64), Fr::from(1u64), Fr::from(2u64)]);
+
+        assert_eq!(
+            state[0],
+            Fr::from_str(
+                "5297208644449048816064511434384511824916970985131888684874823260532015509555"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_t4_permutation_matches_reference_parameters() {
+        assert_eq!(check_poseidon2_t4(), Ok(()));
+        // Barretenberg's `poseidon2_params.hpp` lists the same first constant
+        assert_eq!(
+            fr_to_hex(POSEIDON2_T4_FIRST_CONSTANT),
+            "0x19b849f69450b06848da1d39bd5e4a4302bb86744edc26238b0878e269ed23e5"
+        );
+    }
+
+    #[test]
+    fn test_t3_uses_length_dependent_iv() {
+        let x = Fr::from(42u64);
+        let single = poseidon2_bn254_t3_hash_fields(&[x]).unwrap();
+        let padded = poseidon2_bn254_t3_hash_fields(&[x, Fr::from(0u64)]).unwrap();
+
+        // [x] and [x, 0] only differ in the IV and must not collide
+        assert_ne!(single, padded);
+        assert_eq!(single, t3::permutation(&[x, Fr::from(0u64), sponge_iv(1)])[0]);
+    }
+
+    #[test]
+    fn test_t3_rejects_unsupported_input_counts() {
+        assert!(poseidon2_bn254_t3_hash_fields(&[]).is_err());
+        assert_eq!(
+            poseidon2_bn254_t3_hash_fields(&[Fr::from(1u64); 3]),
+            Err(PoseidonError::UnsupportedInputCount {
+                state_size: 3,
+                min: 1,
+                max: 2,
+                got: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_hash4_requires_exactly_four_inputs() {
+        let inputs = [1u64, 2, 3, 4].map(Fr::from);
+
+        // Pinned so a change to the t8 parameters or the IV shows up here
+        assert_eq!(
+            fr_to_hex(poseidon2_bn254_hash4(&inputs).unwrap()),
+            "0x07c49f4dea0425eedda80d2081cbb883ef5dda64a343fd63348ffb7c145d3406"
+        );
+        assert_eq!(
+            poseidon2_bn254_hash4(&inputs[..3]),
+            Err(PoseidonError::UnsupportedInputCount {
+                state_size: 8,
+                min: 4,
+                max: 4,
+                got: 3
+            })
+        );
+
+        let mut reordered = inputs;
+        reordered.swap(0, 3);
+        assert_ne!(
+            poseidon2_bn254_hash4(&inputs).unwrap(),
+            poseidon2_bn254_hash4(&reordered).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_sponge_matches_single_permutation_for_short_inputs() {
+        for len in 1..=T4_RATE {
+            let inputs: Vec<Fr> = (1..=len as u64).map(Fr::from).collect();
+            assert_eq!(
+                poseidon_hash_n(&inputs).unwrap(),
+                poseidon2_bn254_t4_hash_fields(&inputs).unwrap()
+            );
+        }
+    }
+
+    /// `poseidon2_hash_many` of `[1, 2, ..., len]`, pinned for lengths that
+    /// absorb several blocks. These come from this implementation; no Noir
+    /// output for more than 3 inputs is available to check them against, so
+    /// they only catch regressions.
+    const SPONGE_VECTORS: [(u64, &str); 7] = [
+        (0, "0x18dfb8dc9b82229cff974efefc8df78b1ce96d9d844236b496785c698bc6732e"),
+        (1, "0x168758332d5b3e2d13be8048c8011b454590e06c44bce7f702f09103eef5a373"),
+        (3, "0x23864adb160dddf590f1d3303683ebcb914f828e2635f6e85a32f0a1aecd3dd8"),
+        (4, "0x130bf204a32cac1f0ace56c78b731aa3809f06df2731ebcf6b3464a15788b1b9"),
+        (6, "0x07f57fcda925c06dc0a311f3f17fa0218e079b514552744a25ba8a74ee8c9e7a"),
+        (7, "0x16f929bc0d216df4b05bdc44222463edf2b9791bd949ab926eebda06a502d238"),
+        (9, "0x174b592c95a1811beff20ff96e1276cad3d155670a909f90c3658841f0f70fea"),
+    ];
+
+    #[test]
+    fn test_sponge_keeps_pinned_vectors_for_6_and_9_inputs() {
+        for (len, expected) in SPONGE_VECTORS.into_iter().filter(|(len, _)| [6, 9].contains(len)) {
+            let inputs: Vec<Fr> = (1..=len).map(Fr::from).collect();
+            assert_eq!(fr_to_hex(poseidon_hash_n(&inputs).unwrap()), expected, "{len} inputs");
+        }
+    }
+
+    #[test]
+    fn test_sponge_matches_noir_leaf_hash() {
+        // circuits/target/shadow_drop_witness.gz, written by `nargo execute`
+        // for circuits/Prover.toml: witnesses 14 to 18 are the IV and the
+        // permuted state of Poseidon2::hash([recipient, amount, secret], 3)
+        let recipient = fr_from_hex("0x0060a3e9253d35c69b6fbedba12c3e7ca8b85ac5fdc5441008e99b0fc9dce8af");
+        let amount = Fr::from(391_000_000_000u64);
+        let secret = fr_from_hex("0x26837ff7868e0f7207d3a2e9054f2ea4494518a1246ecdd3db8ce3734b549014");
+        let noir_state = [
+            "0x1cf5bd6cb717dab6f83b42feef24cc938b03773504d36dec054475deb37de8db",
+            "0x1ffee1ce2bc7b5751c77ca75ed88e632ec84b0b774efd95c1f6ee7ed95d429a8",
+            "0x185390d10825a71367d370f1139aafc38af99b73bd1c5c3ebd4d5f16fa357f86",
+            "0x2efd8f450c11d6d8650b101d2075e23cf7e0db198a5d4d88a549ff978fb4dd5e",
+        ]
+        .map(fr_from_hex);
+
+        assert_eq!(t4::permutation(&[recipient, amount, secret, sponge_iv(3)]), noir_state);
+        assert_eq!(poseidon_hash_n(&[recipient, amount, secret]).unwrap(), noir_state[0]);
+    }
+
+    #[test]
+    fn test_sponge_separates_lengths() {
+        let six: Vec<Fr> = (1..=6u64).map(Fr::from).collect();
+        let mut seven = six.clone();
+        seven.push(Fr::from(0u64));
+
+        assert_ne!(poseidon_hash_n(&six).unwrap(), poseidon_hash_n(&seven).unwrap());
+        assert_eq!(poseidon_hash_n(&[]), Err(PoseidonError::EmptyInput));
+    }
+
+    #[test]
+    fn test_hash_many_is_backward_compatible() {
+        let (a, b) = (Fr::from(7u64), Fr::from(11u64));
+        assert_eq!(
+            poseidon2_hash_many(&[a, b]),
+            poseidon2_bn254_t4_hash_fields(&[a, b]).unwrap()
+        );
+
+        for (len, expected) in SPONGE_VECTORS {
+            let inputs: Vec<Fr> = (1..=len).map(Fr::from).collect();
+            assert_eq!(fr_to_hex(poseidon2_hash_many(&inputs)), expected, "{len} inputs");
+        }
+    }
+
+    #[test]
+    fn test_t4_matches_noir_nullifier() {
+        // From circuits/Prover.toml, accepted by `nargo execute` against the
+        // committed circuit, which checks the v1 nullifier:
+        // nullifier_hash = Poseidon2::hash([secret, leaf_index], 2)
+        let secret = fr_from_hex("0x26837ff7868e0f7207d3a2e9054f2ea4494518a1246ecdd3db8ce3734b549014");
+        let nullifier = poseidon2_bn254_t4_hash_fields(&[secret, Fr::from(0u64)]).unwrap();
+
+        assert_eq!(
+            fr_to_hex(nullifier),
+            "0x112756337742f7be641770f4a7bf8e5fa3c3f01f8d14abd60cfce6103bba14ab"
+        );
+    }
+}
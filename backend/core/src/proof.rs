@@ -0,0 +1,433 @@
+//! Merkle proofs: verification against a root and the encodings provers
+//! and the on-chain verifier read
+
+use ark_bn254::Fr;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
+
+use crate::merkle::{
+    Hash, LeafHasher, MAX_LEAVES, MerkleError, PoseidonHasher, TREE_DEPTH, bytes_to_field_element,
+    field_element_to_bytes,
+};
+
+/// Merkle proof for a single leaf
+#[derive(Debug, Clone)]
+pub struct MerkleProof {
+    pub leaf_index: usize,
+    pub siblings: Vec<Hash>,
+    pub leaf: Hash,
+}
+
+/// Anchor method name the proof instruction is encoded for
+const VERIFY_PROOF_INSTRUCTION: &str = "verify_proof";
+
+impl MerkleProof {
+    /// Anchor instruction discriminator: `sha256("global:verify_proof")[..8]`
+    pub fn verify_proof_discriminator() -> [u8; 8] {
+        use sha2::{Digest, Sha256};
+
+        let digest = Sha256::digest(format!("global:{}", VERIFY_PROOF_INSTRUCTION));
+        let mut discriminator = [0u8; 8];
+        discriminator.copy_from_slice(&digest[..8]);
+        discriminator
+    }
+
+    /// Encode as instruction data for the on-chain proof verifier.
+    ///
+    /// Layout (Borsh, little endian): discriminator `[u8; 8]`, `leaf_index: u64`,
+    /// `leaf: [u8; 32]`, `siblings: Vec<[u8; 32]>` (u32 length prefix), and
+    /// `path_directions: Vec<u8>` where 1 means the node is the right child at that level.
+    pub fn to_solana_instruction_data(&self) -> Vec<u8> {
+        let directions = self.path_directions();
+        let mut data =
+            Vec::with_capacity(8 + 8 + 32 + 4 + 32 * self.siblings.len() + 4 + directions.len());
+
+        data.extend_from_slice(&Self::verify_proof_discriminator());
+        data.extend_from_slice(&(self.leaf_index as u64).to_le_bytes());
+        data.extend_from_slice(&self.leaf);
+        data.extend_from_slice(&(self.siblings.len() as u32).to_le_bytes());
+        for sibling in &self.siblings {
+            data.extend_from_slice(sibling);
+        }
+        data.extend_from_slice(&(directions.len() as u32).to_le_bytes());
+        data.extend_from_slice(&directions);
+        data
+    }
+
+    /// Decode instruction data produced by `to_solana_instruction_data`,
+    /// returning the encoded leaf index alongside the proof.
+    pub fn from_solana_instruction_data(data: &[u8]) -> Result<(u64, Self), MerkleError> {
+        let mut reader = InstructionReader { data };
+
+        if reader.take(8)? != Self::verify_proof_discriminator() {
+            return Err(MerkleError::InvalidInstructionData(
+                "discriminator mismatch".to_string(),
+            ));
+        }
+        let leaf_index = u64::from_le_bytes(reader.take_array()?);
+        let leaf: Hash = reader.take_array()?;
+
+        let siblings_len = u32::from_le_bytes(reader.take_array()?) as usize;
+        let mut siblings = Vec::with_capacity(siblings_len.min(TREE_DEPTH * 3));
+        for _ in 0..siblings_len {
+            siblings.push(reader.take_array()?);
+        }
+
+        let directions_len = u32::from_le_bytes(reader.take_array()?) as usize;
+        let directions = reader.take(directions_len)?.to_vec();
+        if !reader.data.is_empty() {
+            return Err(MerkleError::InvalidInstructionData(format!(
+                "{} trailing bytes",
+                reader.data.len()
+            )));
+        }
+
+        let proof = Self {
+            leaf_index: leaf_index as usize,
+            siblings,
+            leaf,
+        };
+        if proof.path_directions() != directions {
+            return Err(MerkleError::InvalidInstructionData(
+                "path directions do not match leaf index".to_string(),
+            ));
+        }
+
+        Ok((leaf_index, proof))
+    }
+
+    /// The proof of the same leaf in the subtree of height `new_depth` that
+    /// contains it, for verifiers compiled for a shallower tree
+    ///
+    /// Keeps the first `new_depth` siblings; the proof then verifies against
+    /// that subtree's root, `recompute_path(&proof).last()`, which is the
+    /// root a shallower tree with the same leaves would have. Only leaves
+    /// below `2^new_depth` fit in that tree.
+    pub fn truncate_to_depth(&self, new_depth: usize) -> Result<MerkleProof, MerkleError> {
+        if new_depth > self.siblings.len() {
+            return Err(MerkleError::MalformedProof(format!(
+                "cannot truncate {} siblings to {}",
+                self.siblings.len(),
+                new_depth
+            )));
+        }
+        if self.leaf_index >> new_depth != 0 {
+            return Err(MerkleError::MalformedProof(format!(
+                "leaf index {} does not fit a tree of depth {}",
+                self.leaf_index, new_depth
+            )));
+        }
+        Ok(MerkleProof {
+            leaf_index: self.leaf_index,
+            siblings: self.siblings[..new_depth].to_vec(),
+            leaf: self.leaf,
+        })
+    }
+
+    /// The proof of the same leaf with the tree wrapped as the leftmost
+    /// subtree of one of depth `new_depth`, every added sibling `zero_hash`
+    ///
+    /// The deeper root is the shallow one hashed up with `zero_hash` once per
+    /// added level, so a verifier must take the same `zero_hash` as the root
+    /// of each empty subtree. Proofs already `new_depth` deep or deeper are
+    /// returned unchanged.
+    pub fn extend_to_depth(&self, new_depth: usize, zero_hash: &Hash) -> MerkleProof {
+        let mut siblings = self.siblings.clone();
+        if new_depth > siblings.len() {
+            siblings.resize(new_depth, *zero_hash);
+        }
+        MerkleProof {
+            leaf_index: self.leaf_index,
+            siblings,
+            leaf: self.leaf,
+        }
+    }
+
+    /// Per-level position of the node on the path to the root (binary layout)
+    fn path_directions(&self) -> Vec<u8> {
+        (0..self.siblings.len())
+            .map(|level| ((self.leaf_index >> level) & 1) as u8)
+            .collect()
+    }
+}
+
+/// Cursor over instruction bytes
+struct InstructionReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> InstructionReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], MerkleError> {
+        if self.data.len() < len {
+            return Err(MerkleError::InvalidInstructionData(format!(
+                "expected {} more bytes, found {}",
+                len,
+                self.data.len()
+            )));
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], MerkleError> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+}
+
+/// Hashes are field elements in big-endian bytes; arkworks needs them as `Fr`.
+/// Rejects values at or above the modulus instead of silently reducing them.
+fn canonical_field(hash: &Hash) -> Result<Fr, SerializationError> {
+    let field = bytes_to_field_element(hash);
+    if field_element_to_bytes(field) != *hash {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(field)
+}
+
+/// arkworks encoding: `leaf_index: u64`, `leaf: Fr`, `siblings: Vec<Fr>`
+/// (u64 length prefix), with field elements in their canonical little-endian form.
+impl CanonicalSerialize for MerkleProof {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        (self.leaf_index as u64).serialize_with_mode(&mut writer, compress)?;
+        canonical_field(&self.leaf)?.serialize_with_mode(&mut writer, compress)?;
+        let siblings = self
+            .siblings
+            .iter()
+            .map(canonical_field)
+            .collect::<Result<Vec<_>, _>>()?;
+        siblings.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        let field_size = Fr::default().serialized_size(compress);
+        8 + field_size + 8 + field_size * self.siblings.len()
+    }
+}
+
+impl Valid for MerkleProof {
+    // Field elements are validated while decoding
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for MerkleProof {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let leaf_index = u64::deserialize_with_mode(&mut reader, compress, validate)?;
+        let leaf = Fr::deserialize_with_mode(&mut reader, compress, validate)?;
+        let siblings = Vec::<Fr>::deserialize_with_mode(&mut reader, compress, validate)?;
+
+        Ok(Self {
+            leaf_index: usize::try_from(leaf_index).map_err(|_| SerializationError::InvalidData)?,
+            siblings: siblings.into_iter().map(field_element_to_bytes).collect(),
+            leaf: field_element_to_bytes(leaf),
+        })
+    }
+}
+
+impl MerkleProof {
+    /// Compressed canonical arkworks encoding, for arkworks-based provers.
+    ///
+    /// Fails only if a hash is not a canonical field element, which cannot
+    /// happen for proofs produced by `MerkleTree` but can for decoded input.
+    pub fn to_canonical_bytes(&self) -> Result<Vec<u8>, SerializationError> {
+        let mut bytes = Vec::with_capacity(self.compressed_size());
+        self.serialize_compressed(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Decode bytes produced by `to_canonical_bytes`, rejecting trailing data
+    pub fn from_canonical_bytes(mut bytes: &[u8]) -> Result<Self, SerializationError> {
+        let proof = Self::deserialize_compressed(&mut bytes)?;
+        if !bytes.is_empty() {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(proof)
+    }
+}
+
+/// Verify a binary merkle proof (the layout checked by the Noir circuit)
+pub fn verify_proof(root: &Hash, proof: &MerkleProof) -> bool {
+    try_verify_proof(root, proof).unwrap_or(false)
+}
+
+/// `verify_proof`, failing on proofs no binary tree could have produced
+/// instead of rejecting them like a wrong root
+///
+/// Only the low `TREE_DEPTH` bits of a leaf index pick the path, so an index
+/// past the last leaf would otherwise verify as the leaf it wraps around to.
+pub fn try_verify_proof(root: &Hash, proof: &MerkleProof) -> Result<bool, MerkleError> {
+    if proof.siblings.len() != TREE_DEPTH {
+        return Err(MerkleError::MalformedProof(format!(
+            "{} siblings, expected {}",
+            proof.siblings.len(),
+            TREE_DEPTH
+        )));
+    }
+    if proof.leaf_index >= MAX_LEAVES {
+        return Err(MerkleError::MalformedProof(format!(
+            "leaf index {} is out of bounds for {} leaves",
+            proof.leaf_index, MAX_LEAVES
+        )));
+    }
+    Ok(compute_root_from_proof(proof, 2) == *root)
+}
+
+/// Nodes a verifier recomputes from a binary proof, `[leaf, parent, ..., root]`
+/// like `MerkleTree::path_to_root`, to see where a failing proof diverges
+pub fn recompute_path(proof: &MerkleProof) -> Vec<Hash> {
+    path_from_proof::<PoseidonHasher>(proof, 2)
+}
+
+pub(crate) fn compute_root_from_proof(proof: &MerkleProof, arity: usize) -> Hash {
+    path_from_proof::<PoseidonHasher>(proof, arity).pop().unwrap_or(proof.leaf)
+}
+
+/// Hash the leaf up through its siblings, keeping every node.
+/// At each level the current node is inserted at its position in the group.
+pub(crate) fn path_from_proof<H: LeafHasher>(proof: &MerkleProof, arity: usize) -> Vec<Hash> {
+    let mut path = Vec::with_capacity(proof.siblings.len() / (arity - 1) + 1);
+    path.push(proof.leaf);
+    let mut idx = proof.leaf_index;
+    for level_siblings in proof.siblings.chunks(arity - 1) {
+        let mut children = level_siblings.to_vec();
+        children.insert((idx % arity).min(children.len()), path[path.len() - 1]);
+        path.push(H::hash_children(&children));
+        idx /= arity;
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle::{MerkleTree, ZERO_HASHES, compute_leaf_hash, generate_secret};
+
+    #[test]
+    fn test_verify_proof_rejects_known_bad_inputs() {
+        let empty_wallet_leaf = compute_leaf_hash("", 1, &[1u8; 32]);
+        assert!(canonical_field(&empty_wallet_leaf).is_ok());
+
+        let recipients = vec![
+            ("wallet1".to_string(), 1, [1u8; 32]),
+            ("wallet2".to_string(), 2, [2u8; 32]),
+        ];
+        let tree = MerkleTree::from_recipients(&recipients).unwrap();
+        let proof = tree.get_proof("wallet2").unwrap();
+        assert!(try_verify_proof(&tree.root(), &proof).unwrap());
+
+        let modified_leaf = MerkleProof { leaf: compute_leaf_hash("wallet2", 3, &[2u8; 32]), ..proof.clone() };
+        assert!(!verify_proof(&tree.root(), &modified_leaf));
+
+        let mut zeroed_sibling = proof.clone();
+        zeroed_sibling.siblings[3] = [0u8; 32];
+        assert!(!verify_proof(&tree.root(), &zeroed_sibling));
+
+        let other_tree = MerkleTree::from_recipients(&recipients[..1]).unwrap();
+        assert!(!verify_proof(&other_tree.root(), &proof));
+
+        // Index 257 shares its low 8 bits with wallet2's leaf index 1
+        let out_of_bounds = MerkleProof { leaf_index: proof.leaf_index + MAX_LEAVES, ..proof.clone() };
+        assert!(matches!(
+            try_verify_proof(&tree.root(), &out_of_bounds),
+            Err(MerkleError::MalformedProof(_))
+        ));
+        assert!(!verify_proof(&tree.root(), &out_of_bounds));
+
+        let mut short = proof;
+        short.siblings.pop();
+        assert!(try_verify_proof(&tree.root(), &short).is_err());
+    }
+
+    #[test]
+    fn test_proofs_truncate_to_subtrees_and_extend_with_zero_hashes() {
+        let recipients: Vec<_> = (0..6)
+            .map(|i| (format!("wallet{}", i), i as u64 + 1, [i as u8 + 1; 32]))
+            .collect();
+        let tree = MerkleTree::from_recipients(&recipients).unwrap();
+        let proof = tree.get_proof("wallet5").unwrap();
+        let path = tree.path_to_root("wallet5").unwrap();
+
+        // Leaf 5 is in the first subtree of height 3, not of height 2
+        let truncated = proof.truncate_to_depth(3).unwrap();
+        assert_eq!(truncated.siblings, proof.siblings[..3]);
+        assert_eq!(recompute_path(&truncated).last(), Some(&path[3]));
+        assert!(proof.truncate_to_depth(2).is_err());
+        assert!(proof.truncate_to_depth(TREE_DEPTH + 1).is_err());
+
+        // Back to full depth, zero hashes complete the original proof
+        let extended = truncated.extend_to_depth(TREE_DEPTH, &ZERO_HASHES[3]);
+        assert_eq!(&extended.siblings[3..5], &[ZERO_HASHES[3], ZERO_HASHES[3]]);
+        let mut rebuilt = truncated.clone();
+        for level in 3..TREE_DEPTH {
+            rebuilt = rebuilt.extend_to_depth(level + 1, &ZERO_HASHES[level]);
+        }
+        assert_eq!(rebuilt.siblings, proof.siblings);
+        assert!(verify_proof(&tree.root(), &rebuilt));
+        assert_eq!(proof.extend_to_depth(4, &[0u8; 32]).siblings, proof.siblings);
+    }
+
+    #[test]
+    fn test_solana_instruction_data_round_trip() {
+        let recipients: Vec<_> = (0..6)
+            .map(|i| (format!("wallet{}", i), i as u64, generate_secret()))
+            .collect();
+        let tree = MerkleTree::from_recipients(&recipients).unwrap();
+        let proof = tree.get_proof("wallet5").unwrap();
+
+        let data = proof.to_solana_instruction_data();
+        assert_eq!(&data[..8], &MerkleProof::verify_proof_discriminator());
+        assert_eq!(data.len(), 8 + 8 + 32 + 4 + 32 * TREE_DEPTH + 4 + TREE_DEPTH);
+
+        let (leaf_index, decoded) = MerkleProof::from_solana_instruction_data(&data).unwrap();
+        assert_eq!(leaf_index, 5);
+        assert_eq!(decoded.leaf_index, proof.leaf_index);
+        assert_eq!(decoded.leaf, proof.leaf);
+        assert_eq!(decoded.siblings, proof.siblings);
+
+        // Truncated and tampered data is rejected
+        assert!(MerkleProof::from_solana_instruction_data(&data[..data.len() - 1]).is_err());
+        let mut tampered = data.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(MerkleProof::from_solana_instruction_data(&tampered).is_err());
+    }
+
+    #[test]
+    fn test_canonical_bytes_round_trip() {
+        let recipients: Vec<_> = (0..6)
+            .map(|i| (format!("wallet{}", i), i as u64, generate_secret()))
+            .collect();
+        let tree = MerkleTree::from_recipients(&recipients).unwrap();
+        let proof = tree.get_proof("wallet3").unwrap();
+
+        let bytes = proof.to_canonical_bytes().unwrap();
+        assert_eq!(bytes.len(), 8 + 32 + 8 + 32 * TREE_DEPTH);
+
+        let decoded = MerkleProof::from_canonical_bytes(&bytes).unwrap();
+        assert_eq!(decoded.leaf_index, proof.leaf_index);
+        assert_eq!(decoded.leaf, proof.leaf);
+        assert_eq!(decoded.siblings, proof.siblings);
+        assert!(verify_proof(&tree.root(), &decoded));
+
+        // Trailing bytes and non-canonical field elements are rejected
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert!(MerkleProof::from_canonical_bytes(&extended).is_err());
+        let mut oversized = proof.clone();
+        oversized.leaf = [0xff; 32];
+        assert!(oversized.to_canonical_bytes().is_err());
+    }
+}
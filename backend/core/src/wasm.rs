@@ -0,0 +1,87 @@
+//! Leaf hashing and proof verification for browser builds
+//!
+//! Plain bytes and JSON in, a `String` error out, so each function can be
+//! exported with `#[wasm_bindgen]` as it stands (the error becomes a thrown
+//! JS value). The `shadow-drop-wasm` crate in `backend/wasm` does that;
+//! keeping wasm-bindgen out of here lets the server build without it.
+
+use serde::Deserialize;
+
+use crate::merkle::{Hash, MerkleProof, compute_leaf_hash, parse_hash, verify_proof};
+
+/// A proof as a frontend holds it: the API's `leaf_index` and `merkle_path`,
+/// plus the leaf hash from `wasm_compute_leaf_hash`, all hex
+#[derive(Debug, Deserialize)]
+struct ProofJson {
+    leaf_index: usize,
+    leaf: String,
+    merkle_path: Vec<String>,
+}
+
+/// `compute_leaf_hash` with the secret as a byte slice; errors unless it is
+/// 32 bytes
+pub fn wasm_compute_leaf_hash(wallet: &str, amount_lamports: u64, secret: &[u8]) -> Result<Vec<u8>, String> {
+    let secret: &[u8; 32] = secret
+        .try_into()
+        .map_err(|_| format!("secret must be 32 bytes, got {}", secret.len()))?;
+    Ok(compute_leaf_hash(wallet, amount_lamports, secret).to_vec())
+}
+
+/// `verify_proof` of a JSON proof against a hex root; errors if either does
+/// not parse
+pub fn wasm_verify_proof(proof_json: &str, root_hex: &str) -> Result<bool, String> {
+    let proof: ProofJson = serde_json::from_str(proof_json).map_err(|e| format!("invalid proof: {}", e))?;
+    let root = parse_hex("root", root_hex)?;
+    let proof = MerkleProof {
+        leaf_index: proof.leaf_index,
+        leaf: parse_hex("leaf", &proof.leaf)?,
+        siblings: proof
+            .merkle_path
+            .iter()
+            .map(|sibling| parse_hex("merkle_path entry", sibling))
+            .collect::<Result<_, _>>()?,
+    };
+    Ok(verify_proof(&root, &proof))
+}
+
+fn parse_hex(what: &str, value: &str) -> Result<Hash, String> {
+    parse_hash(value).ok_or_else(|| format!("{} must be 32 hex-encoded bytes", what))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle::MerkleTree;
+
+    #[test]
+    fn test_wasm_entry_points_match_the_native_ones() {
+        let recipients = vec![
+            ("wallet1".to_string(), 100, [1u8; 32]),
+            ("wallet2".to_string(), 200, [2u8; 32]),
+        ];
+        let tree = MerkleTree::from_recipients(&recipients).unwrap();
+        let proof = tree.get_proof("wallet2").unwrap();
+
+        let leaf = wasm_compute_leaf_hash("wallet2", 200, &[2u8; 32]).unwrap();
+        assert_eq!(leaf, proof.leaf);
+        // frontend-test/wasm.test.js checks the browser build against these
+        assert_eq!(hex::encode(&leaf), "199f3d75e27519b284e1933e0502e49b86edc4ca29758678e752bb154cf34319");
+        assert_eq!(
+            hex::encode(tree.root()),
+            "1cf3f347f6076525efdcae54414b5e5a36393f6d866ac4a80a0ae662e1973c1f"
+        );
+        assert!(wasm_compute_leaf_hash("wallet2", 200, &[2u8; 31]).is_err());
+
+        let proof_json = serde_json::json!({
+            "leaf_index": proof.leaf_index,
+            "leaf": hex::encode(&leaf),
+            "merkle_path": proof.siblings.iter().map(hex::encode).collect::<Vec<_>>(),
+        })
+        .to_string();
+        let root = format!("0x{}", hex::encode(tree.root()));
+        assert_eq!(wasm_verify_proof(&proof_json, &root), Ok(true));
+        assert_eq!(wasm_verify_proof(&proof_json, &"00".repeat(32)), Ok(false));
+        assert!(wasm_verify_proof(&proof_json, "0x00").is_err());
+        assert!(wasm_verify_proof("{}", &root).is_err());
+    }
+}
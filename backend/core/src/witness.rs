@@ -0,0 +1,80 @@
+//! Circuit witness: the inputs the Noir claim circuit is proven over, and
+//! the `Prover.toml` nargo reads them from
+
+use serde::{Deserialize, Serialize};
+
+/// Input for ZK proof generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZkProofInput {
+    /// Merkle root (32 bytes hex)
+    pub merkle_root: String,
+    /// Nullifier hash (32 bytes hex)
+    pub nullifier_hash: String,
+    /// Recipient address as field element (32 bytes hex)
+    pub recipient: String,
+    /// Claim amount (as string for large numbers)
+    pub amount: String,
+    /// Secret for nullifier derivation (32 bytes hex)
+    pub secret: String,
+    /// Leaf index in merkle tree
+    pub leaf_index: u64,
+    /// Merkle proof path (8 sibling hashes, each 32 bytes hex)
+    pub merkle_path: Vec<String>,
+}
+
+impl ZkProofInput {
+    /// Generate Prover.toml content for Noir circuit
+    pub fn to_prover_toml(&self) -> String {
+        let mut toml = String::new();
+
+        toml.push_str("# Auto-generated by Shadow Drop backend\n\n");
+
+        // Public inputs
+        toml.push_str(&format!("merkle_root = \"{}\"\n", self.merkle_root));
+        toml.push_str(&format!("nullifier_hash = \"{}\"\n", self.nullifier_hash));
+        toml.push_str(&format!("recipient = \"{}\"\n", self.recipient));
+        toml.push('\n');
+
+        // Private inputs
+        toml.push_str(&format!("amount = \"{}\"\n", self.amount));
+        toml.push_str(&format!("secret = \"{}\"\n", self.secret));
+        toml.push_str(&format!("leaf_index = \"{}\"\n", self.leaf_index));
+        toml.push('\n');
+
+        // Merkle path
+        toml.push_str("merkle_path = [\n");
+        for (i, sibling) in self.merkle_path.iter().enumerate() {
+            if i < self.merkle_path.len() - 1 {
+                toml.push_str(&format!("    \"{}\",\n", sibling));
+            } else {
+                toml.push_str(&format!("    \"{}\"\n", sibling));
+            }
+        }
+        toml.push_str("]\n");
+
+        toml
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prover_toml_lists_public_then_private_inputs() {
+        let input = ZkProofInput {
+            merkle_root: "0x01".to_string(),
+            nullifier_hash: "0x02".to_string(),
+            recipient: "0x03".to_string(),
+            amount: "100".to_string(),
+            secret: "0x04".to_string(),
+            leaf_index: 5,
+            merkle_path: vec!["0x06".to_string(), "0x07".to_string()],
+        };
+        let toml = input.to_prover_toml();
+        assert!(toml.contains("merkle_root = \"0x01\"\nnullifier_hash = \"0x02\"\nrecipient = \"0x03\"\n"));
+        assert!(toml.contains("leaf_index = \"5\"\n"));
+        assert!(toml.ends_with("merkle_path = [\n    \"0x06\",\n    \"0x07\"\n]\n"));
+        assert!(toml.find("recipient").unwrap() < toml.find("secret").unwrap());
+    }
+}
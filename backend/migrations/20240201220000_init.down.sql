@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS recipients;
+DROP TABLE IF EXISTS campaigns;
@@ -0,0 +1,2 @@
+ALTER TABLE recipients ALTER COLUMN amount TYPE DOUBLE PRECISION USING amount::double precision;
+ALTER TABLE campaigns ALTER COLUMN total_amount TYPE DOUBLE PRECISION USING total_amount::double precision;
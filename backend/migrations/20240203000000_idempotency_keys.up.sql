@@ -0,0 +1,11 @@
+-- Stored responses for idempotent claim submissions
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    idempotency_key TEXT NOT NULL,
+    campaign_address TEXT NOT NULL,
+    status_code SMALLINT,
+    response_body JSONB,
+    created_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (idempotency_key, campaign_address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_created_at ON idempotency_keys (created_at);
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS audit_log;
+ALTER TABLE campaigns DROP COLUMN IF EXISTS pause_reason;
+ALTER TABLE campaigns DROP COLUMN IF EXISTS status;
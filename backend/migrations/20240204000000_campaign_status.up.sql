@@ -0,0 +1,13 @@
+-- Campaign lifecycle status and emergency pause support
+ALTER TABLE campaigns ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'active';
+ALTER TABLE campaigns ADD COLUMN IF NOT EXISTS pause_reason TEXT;
+
+CREATE TABLE IF NOT EXISTS audit_log (
+    id SERIAL PRIMARY KEY,
+    campaign_address TEXT NOT NULL,
+    action TEXT NOT NULL,
+    details TEXT,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_campaign ON audit_log (campaign_address, created_at);
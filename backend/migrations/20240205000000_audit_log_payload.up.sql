@@ -0,0 +1,2 @@
+-- Structured context for audit entries, e.g. the request id that caused them
+ALTER TABLE audit_log ADD COLUMN IF NOT EXISTS payload JSONB NOT NULL DEFAULT '{}'::jsonb;
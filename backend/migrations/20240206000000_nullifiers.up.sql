@@ -0,0 +1,8 @@
+-- Nullifiers used to claim, for double-claim protection
+CREATE TABLE IF NOT EXISTS nullifiers (
+    nullifier BYTEA PRIMARY KEY,
+    campaign_address TEXT NOT NULL REFERENCES campaigns(address) ON DELETE CASCADE,
+    spent_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_nullifiers_campaign ON nullifiers (campaign_address);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_nullifiers_expires_at;
+ALTER TABLE nullifiers DROP COLUMN IF EXISTS expires_at;
+ALTER TABLE campaigns DROP COLUMN IF EXISTS close_at;
@@ -0,0 +1,5 @@
+-- Claim window end for seasonal campaigns, and expiry of their spent nullifiers
+ALTER TABLE campaigns ADD COLUMN IF NOT EXISTS close_at TIMESTAMPTZ;
+ALTER TABLE nullifiers ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_nullifiers_expires_at ON nullifiers (expires_at) WHERE expires_at IS NOT NULL;
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS referral_rewards;
+DROP INDEX IF EXISTS idx_recipients_referrer;
+ALTER TABLE campaigns DROP COLUMN IF EXISTS referral_bonus_bps;
+ALTER TABLE recipients DROP COLUMN IF EXISTS referrer_wallet;
@@ -0,0 +1,16 @@
+-- Referral attribution of claims and the bonuses owed to referrers
+ALTER TABLE recipients ADD COLUMN IF NOT EXISTS referrer_wallet TEXT;
+ALTER TABLE campaigns ADD COLUMN IF NOT EXISTS referral_bonus_bps SMALLINT NOT NULL DEFAULT 0
+    CHECK (referral_bonus_bps BETWEEN 0 AND 10000);
+
+CREATE INDEX IF NOT EXISTS idx_recipients_referrer
+    ON recipients (campaign_address, referrer_wallet) WHERE referrer_wallet IS NOT NULL;
+
+-- Pending bonus per referrer, in raw token units like recipient amounts
+CREATE TABLE IF NOT EXISTS referral_rewards (
+    campaign_address TEXT NOT NULL REFERENCES campaigns(address) ON DELETE CASCADE,
+    referrer_wallet TEXT NOT NULL,
+    pending_amount TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (campaign_address, referrer_wallet)
+);
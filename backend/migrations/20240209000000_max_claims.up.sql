@@ -0,0 +1,2 @@
+-- Optional cap on accepted claims for over-subscribed campaigns
+ALTER TABLE campaigns ADD COLUMN IF NOT EXISTS max_claims INTEGER CHECK (max_claims > 0);
@@ -0,0 +1,21 @@
+-- Claim rounds of multi-round campaigns, each with its own recipient tree
+CREATE TABLE IF NOT EXISTS campaign_rounds (
+    id UUID PRIMARY KEY,
+    campaign_address TEXT NOT NULL REFERENCES campaigns(address) ON DELETE CASCADE,
+    round_number INTEGER NOT NULL CHECK (round_number > 0),
+    merkle_root BYTEA NOT NULL,
+    open_at TIMESTAMPTZ NOT NULL,
+    close_at TIMESTAMPTZ,
+    status TEXT NOT NULL DEFAULT 'active',
+    created_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (campaign_address, round_number)
+);
+
+CREATE TABLE IF NOT EXISTS round_recipients (
+    round_id UUID NOT NULL REFERENCES campaign_rounds(id) ON DELETE CASCADE,
+    wallet TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    claimed BOOLEAN NOT NULL DEFAULT FALSE,
+    claimed_at TIMESTAMPTZ,
+    PRIMARY KEY (round_id, wallet)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE recipients DROP COLUMN IF EXISTS claim_count;
+ALTER TABLE recipients DROP COLUMN IF EXISTS claimed_lamports;
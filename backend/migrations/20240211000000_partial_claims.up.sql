@@ -0,0 +1,5 @@
+-- Installment claims: lamports paid out so far and how many claims were made
+ALTER TABLE recipients ADD COLUMN IF NOT EXISTS claimed_lamports BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE recipients ADD COLUMN IF NOT EXISTS claim_count INTEGER NOT NULL DEFAULT 0;
+
+UPDATE recipients SET claimed_lamports = amount::NUMERIC::BIGINT, claim_count = 1 WHERE claimed;
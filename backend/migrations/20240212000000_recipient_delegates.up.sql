@@ -0,0 +1,2 @@
+-- Wallet allowed to submit claims on behalf of a recipient
+ALTER TABLE recipients ADD COLUMN IF NOT EXISTS delegate_wallet TEXT;
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_recipients_leaf_index;
+ALTER TABLE recipients DROP COLUMN IF EXISTS leaf_index;
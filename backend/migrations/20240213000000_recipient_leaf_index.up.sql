@@ -0,0 +1,15 @@
+-- Position of each recipient in its campaign's Merkle tree, in insertion order
+ALTER TABLE recipients ADD COLUMN IF NOT EXISTS leaf_index INTEGER;
+
+UPDATE recipients r SET leaf_index = ordered.leaf_index
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY campaign_address ORDER BY id) - 1 AS leaf_index
+    FROM recipients
+) ordered
+WHERE r.id = ordered.id;
+
+ALTER TABLE recipients ALTER COLUMN leaf_index SET NOT NULL;
+
+-- Cursor pagination walks recipients by leaf index
+CREATE UNIQUE INDEX IF NOT EXISTS idx_recipients_leaf_index
+    ON recipients (campaign_address, leaf_index);
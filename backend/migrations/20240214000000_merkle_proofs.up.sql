@@ -0,0 +1,8 @@
+-- Proofs pre-generated when a campaign activates, served instead of recomputing
+CREATE TABLE IF NOT EXISTS merkle_proofs (
+    campaign_address TEXT NOT NULL REFERENCES campaigns(address) ON DELETE CASCADE,
+    wallet TEXT NOT NULL,
+    proof_json JSONB NOT NULL,
+    proof_generated_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (campaign_address, wallet)
+);
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS idx_recipients_wallet_campaign;
@@ -0,0 +1,2 @@
+-- Campaigns of a wallet are looked up by wallet first
+CREATE INDEX IF NOT EXISTS idx_recipients_wallet_campaign ON recipients (wallet, campaign_address);
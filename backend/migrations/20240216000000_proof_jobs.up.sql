@@ -0,0 +1,11 @@
+-- Proofs generated in the background, polled by job id
+CREATE TABLE IF NOT EXISTS proof_jobs (
+    id UUID PRIMARY KEY,
+    status TEXT NOT NULL DEFAULT 'pending',
+    result JSONB,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_proof_jobs_completed_at ON proof_jobs (completed_at);
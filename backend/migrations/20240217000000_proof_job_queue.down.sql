@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS idx_proof_jobs_pending;
+
+ALTER TABLE proof_jobs
+    DROP COLUMN IF EXISTS campaign_address,
+    DROP COLUMN IF EXISTS wallet,
+    DROP COLUMN IF EXISTS started_at;
@@ -0,0 +1,11 @@
+-- Jobs carry their claim so any worker can pick them up from the table
+ALTER TABLE proof_jobs
+    ADD COLUMN IF NOT EXISTS campaign_address TEXT NOT NULL DEFAULT '',
+    ADD COLUMN IF NOT EXISTS wallet TEXT NOT NULL DEFAULT '',
+    ADD COLUMN IF NOT EXISTS started_at TIMESTAMPTZ;
+
+ALTER TABLE proof_jobs
+    ALTER COLUMN campaign_address DROP DEFAULT,
+    ALTER COLUMN wallet DROP DEFAULT;
+
+CREATE INDEX IF NOT EXISTS idx_proof_jobs_pending ON proof_jobs (created_at) WHERE status = 'pending';
@@ -0,0 +1,3 @@
+-- Borsh-encoded MerkleCommitment, as the on-chain program stores it; NULL
+-- while the campaign has no root yet (drafts)
+ALTER TABLE campaigns ADD COLUMN IF NOT EXISTS merkle_commitment BYTEA;
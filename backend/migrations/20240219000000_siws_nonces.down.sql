@@ -0,0 +1 @@
+DROP TABLE IF EXISTS siws_nonces;
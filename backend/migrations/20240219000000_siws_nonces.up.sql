@@ -0,0 +1,7 @@
+-- Sign-in nonces handed out by GET /auth/nonce; deleted when used or expired
+CREATE TABLE IF NOT EXISTS siws_nonces (
+    nonce TEXT PRIMARY KEY,
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_siws_nonces_expires_at ON siws_nonces (expires_at);
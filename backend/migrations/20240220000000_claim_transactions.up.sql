@@ -0,0 +1,13 @@
+-- Claim transactions sent on-chain by the backend, and whether they confirmed
+CREATE TABLE IF NOT EXISTS claim_transactions (
+    signature TEXT PRIMARY KEY,
+    campaign_address TEXT NOT NULL,
+    wallet TEXT NOT NULL,
+    bundle_id TEXT,
+    status TEXT NOT NULL DEFAULT 'submitted',
+    slot BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_claim_transactions_campaign ON claim_transactions (campaign_address, wallet);
@@ -0,0 +1,7 @@
+-- Leaves of the tree each campaign's computed proofs come from:
+-- [{"wallet", "amount", "secret"}] in leaf order, secrets hex
+CREATE TABLE IF NOT EXISTS merkle_snapshots (
+    campaign_id TEXT PRIMARY KEY REFERENCES campaigns(id) ON DELETE CASCADE,
+    leaves JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
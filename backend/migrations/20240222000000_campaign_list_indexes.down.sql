@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_campaigns_creator_status;
+DROP INDEX IF EXISTS idx_campaigns_status_created_at;
@@ -0,0 +1,3 @@
+-- GET /campaigns filters by status and creator and sorts by creation time
+CREATE INDEX IF NOT EXISTS idx_campaigns_status_created_at ON campaigns (status, created_at);
+CREATE INDEX IF NOT EXISTS idx_campaigns_creator_status ON campaigns (creator_wallet, status);
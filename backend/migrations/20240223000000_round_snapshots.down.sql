@@ -0,0 +1 @@
+ALTER TABLE campaign_rounds DROP COLUMN IF EXISTS leaves;
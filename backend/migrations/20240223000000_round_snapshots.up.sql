@@ -0,0 +1,4 @@
+-- Leaves of each round's tree, built with a fresh secret per recipient when
+-- the round is created: [{"wallet", "amount", "secret", "leaf_index"}],
+-- secrets hex. NULL for rounds created with a root sent by the admin.
+ALTER TABLE campaign_rounds ADD COLUMN IF NOT EXISTS leaves JSONB;
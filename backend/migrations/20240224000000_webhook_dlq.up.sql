@@ -0,0 +1,16 @@
+-- Claim webhooks whose delivery failed, retried with exponential backoff
+-- until 10 retries have failed and the event is marked 'failed'
+CREATE TABLE IF NOT EXISTS webhook_dlq (
+    id UUID PRIMARY KEY,
+    campaign_id TEXT NOT NULL REFERENCES campaigns(id) ON DELETE CASCADE,
+    payload JSONB NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    -- Retries made so far; the first delivery is not counted
+    attempt_count INTEGER NOT NULL DEFAULT 0,
+    last_attempt_at TIMESTAMPTZ NOT NULL,
+    next_attempt_at TIMESTAMPTZ NOT NULL,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_dlq_due ON webhook_dlq (next_attempt_at) WHERE status = 'pending';
@@ -0,0 +1,1346 @@
+//! Command-line tools for operators scripting tree and campaign work
+//!
+//! Usage: `shadow-drop-cli <group> <command> [--flag value ...]`
+//!
+//! - `tree build --input FILE [--format csv|json] --output FILE [--depth 8]
+//!   [--dry-run] [--generate-secrets-file FILE]`: build a tree from
+//!   `wallet,amount_sol` lines or `[{ "wallet", "amount_sol" }]` and write it
+//!   with every proof, and optionally the recipients' secrets
+//! - `tree verify-proof --proof FILE (--root HEX | --tree FILE) [--wallet W]
+//!   [--verbose]`: check a `{ leaf_index, leaf, siblings }` proof, or the proof
+//!   of `W` in a tree file, against a root or a tree file's root, e.g. the root
+//!   stored on chain
+//! - `tree inspect --tree-file FILE [--wallet W]`: summary of a tree file, with
+//!   the proof of one wallet
+//! - `export proofs --tree FILE --output-dir DIR [--format json|borsh]
+//!   [--compress]`: one proof file per recipient, as JSON with the root or as
+//!   Borsh-encoded `verify_proof` instruction data, optionally gzipped
+//! - `campaign create --url URL [--api-key KEY] --recipients-file FILE
+//!   --name NAME --address ADDRESS --creator-wallet WALLET [--open-at TIME]
+//!   [--close-at TIME] [--verify]`: create a campaign through the API and save
+//!   its id to `.shadow-drop-campaign`; `--verify` checks the first
+//!   recipient's proof from the API locally
+//! - `campaign export --url URL --id ID --output FILE`: the campaign's
+//!   settings and allocations, without secrets or claim state, as a portable
+//!   `{ version, campaign, recipients }` file, e.g. to move a campaign from
+//!   devnet to mainnet
+//! - `campaign import --url URL [--api-key KEY] --input FILE [--address
+//!   ADDRESS] [--verify]`: create an exported campaign on another server, as
+//!   `campaign create` does. Its recipients get new secrets, so its Merkle
+//!   root and id differ from the exported campaign's
+//! - `hash poseidon (--inputs HEX ... | --inputs-file FILE) [--format
+//!   hex|decimal] [--verify-against HASH]`: Poseidon2 hash of 1 to 3 field
+//!   elements as Noir computes it, as `POST /hash/poseidon` does;
+//!   `--verify-against` fails unless the hash equals `HASH`
+//! - `nullifier compute --secret HEX --index N [--wallet W]`: claim nullifier
+//! - `nullifier check --url URL --campaign-id ID (--nullifier HEX | --secret
+//!   HEX --index N [--wallet W])`: whether a nullifier is spent, and when,
+//!   from the API
+//! - `migrate [run | rollback [--steps N] | status | list | check]
+//!   [--database-url URL]`: manage database migrations, like the `migrate` tool
+//!
+//! Results are printed to stdout as JSON, except for `migrate`; errors go to
+//! stderr.
+
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    str::FromStr,
+    time::Duration,
+};
+
+use ark_bn254::Fr;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use url::Url;
+
+use shadow_drop_api::{
+    common::{
+        field::{fr_from_be_bytes_checked, fr_to_be_bytes},
+        fees::{LAMPORTS_PER_SOL, Lamports, sol_to_lamports},
+        http,
+        merkle::{
+            Hash, MerkleProof, MerkleTree, RecipientsFormat, TREE_DEPTH, compute_nullifier, compute_nullifier_v2, compute_leaf_hash,
+            parse_hash, read_recipients_file, recompute_path, verify_proof,
+        },
+        poseidon::poseidon2_bn254_t4_hash_fields,
+    },
+    migrations::{self, MigrateCommand},
+};
+
+const USAGE: &str = "Usage: shadow-drop-cli <command>
+
+  tree build --input FILE [--format csv|json] --output FILE [--depth 8]
+             [--dry-run] [--generate-secrets-file FILE]
+  tree verify-proof --proof FILE (--root HEX | --tree FILE) [--wallet WALLET] [--verbose]
+  tree inspect --tree-file FILE [--wallet WALLET]
+  export proofs --tree FILE --output-dir DIR [--format json|borsh] [--compress]
+  campaign create --url URL [--api-key KEY] --recipients-file FILE [--format csv|json]
+                  --name NAME --address ADDRESS --creator-wallet WALLET
+                  [--open-at RFC3339] [--close-at RFC3339] [--verify]
+  campaign export --url URL --id ID --output FILE
+  campaign import --url URL [--api-key KEY] --input FILE [--address ADDRESS] [--verify]
+  hash poseidon (--inputs HEX [HEX ...] | --inputs-file FILE) [--format hex|decimal]
+                [--verify-against HASH]
+  nullifier compute --secret HEX --index N [--wallet WALLET]
+  nullifier check --url URL --campaign-id ID (--nullifier HEX | --secret HEX --index N [--wallet WALLET])
+  migrate [run | rollback [--steps N] | status | list | check] [--database-url URL]";
+
+/// Where `campaign create` saves the id for later commands
+const CAMPAIGN_FILE: &str = ".shadow-drop-campaign";
+
+/// Version written to and expected in `campaign export` files
+const EXPORT_VERSION: &str = "1.0";
+
+/// Recipients fetched per page by `campaign export`, the API's largest page
+const EXPORT_PAGE_SIZE: usize = 100;
+
+/// Connect, read and write timeout of API requests
+const API_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    TreeBuild {
+        input: PathBuf,
+        format: RecipientsFormat,
+        /// `None` on a dry run
+        output: Option<PathBuf>,
+        secrets_file: Option<PathBuf>,
+    },
+    TreeVerifyProof {
+        proof_file: PathBuf,
+        root: ExpectedRoot,
+        /// Picks the proof when `proof_file` is a tree file
+        wallet: Option<String>,
+        verbose: bool,
+    },
+    TreeInspect {
+        tree_file: PathBuf,
+        wallet: Option<String>,
+    },
+    ExportProofs {
+        tree_file: PathBuf,
+        output_dir: PathBuf,
+        format: ProofFormat,
+        compress: bool,
+    },
+    CampaignCreate(CampaignCreate),
+    CampaignExport {
+        url: String,
+        id: String,
+        output: PathBuf,
+    },
+    CampaignImport {
+        url: String,
+        api_key: Option<String>,
+        input: PathBuf,
+        /// Overrides the exported campaign address, e.g. for another cluster
+        address: Option<String>,
+        verify: bool,
+    },
+    HashPoseidon {
+        inputs: HashInputs,
+        format: HashFormat,
+        /// Expected hash, in `format`
+        verify_against: Option<String>,
+    },
+    NullifierCompute {
+        secret: Hash,
+        index: usize,
+        wallet: Option<String>,
+    },
+    NullifierCheck {
+        url: String,
+        campaign_id: String,
+        nullifier: Hash,
+    },
+    Migrate {
+        command: MigrateCommand,
+        database_url: Option<String>,
+    },
+}
+
+/// Encoding of exported proof files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProofFormat {
+    /// `{ wallet, root, leaf_index, leaf, siblings }`, readable by `tree verify-proof`
+    Json,
+    /// `MerkleProof::to_solana_instruction_data`
+    Borsh,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct CampaignCreate {
+    url: String,
+    api_key: Option<String>,
+    recipients_file: PathBuf,
+    format: RecipientsFormat,
+    name: String,
+    address: String,
+    creator_wallet: String,
+    open_at: Option<DateTime<Utc>>,
+    close_at: Option<DateTime<Utc>>,
+    verify: bool,
+}
+
+/// File written by `campaign export` and read by `campaign import`
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportedCampaign {
+    version: String,
+    campaign: CampaignSettings,
+    recipients: Vec<ExportedRecipient>,
+}
+
+/// Fields of `CampaignInfo` that carry over to a campaign created elsewhere
+#[derive(Debug, Serialize, Deserialize)]
+struct CampaignSettings {
+    name: String,
+    address: String,
+    creator_wallet: String,
+    #[serde(default)]
+    token_mint: Option<String>,
+    #[serde(default)]
+    token_symbol: Option<String>,
+    #[serde(default)]
+    token_decimals: Option<u8>,
+    #[serde(default)]
+    close_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    referral_bonus_bps: u16,
+    #[serde(default)]
+    max_claims: Option<usize>,
+}
+
+/// Allocation of an exported recipient, in the `tree build` JSON format
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportedRecipient {
+    wallet: String,
+    amount_sol: String,
+}
+
+/// Field elements `hash poseidon` hashes
+#[derive(Debug, PartialEq, Eq)]
+enum HashInputs {
+    Values(Vec<Hash>),
+    /// One hex field element per line
+    File(PathBuf),
+}
+
+/// How `hash poseidon` prints the hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HashFormat {
+    /// `0x`-prefixed big-endian hex
+    Hex,
+    Decimal,
+}
+
+/// Root a proof is checked against
+#[derive(Debug, PartialEq, Eq)]
+enum ExpectedRoot {
+    Hash(Hash),
+    TreeFile(PathBuf),
+}
+
+/// `--name value` pairs, `--name value value ...` lists and `--name`
+/// switches following the command words
+struct Flags {
+    values: HashMap<String, Vec<String>>,
+    switches: HashSet<String>,
+}
+
+impl Flags {
+    /// Parse `args`, rejecting flags not in `allowed` or `switches` and
+    /// `allowed` flags without a value
+    fn parse(args: &[String], allowed: &[&str], switches: &[&str]) -> Result<Self, String> {
+        Self::parse_lists(args, allowed, switches, &[])
+    }
+
+    /// Like `parse`, with the flags in `lists` also taking every value up to
+    /// the next flag
+    fn parse_lists(args: &[String], allowed: &[&str], switches: &[&str], lists: &[&str]) -> Result<Self, String> {
+        let mut flags = Self {
+            values: HashMap::new(),
+            switches: HashSet::new(),
+        };
+        let mut args = args.iter().peekable();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) if switches.contains(&name) => {
+                    flags.switches.insert(name.to_string());
+                }
+                Some(name) if allowed.contains(&name) || lists.contains(&name) => {
+                    let value = args.next().ok_or_else(|| format!("--{} needs a value", name))?;
+                    let mut values = vec![value.clone()];
+                    if lists.contains(&name) {
+                        while let Some(value) = args.next_if(|arg| !arg.starts_with("--")) {
+                            values.push(value.clone());
+                        }
+                    }
+                    flags.values.insert(name.to_string(), values);
+                }
+                _ => return Err(format!("Unexpected argument '{}'", arg)),
+            }
+        }
+        Ok(flags)
+    }
+
+    fn switch(&self, name: &str) -> bool {
+        self.switches.contains(name)
+    }
+
+    fn optional(&self, name: &str) -> Option<String> {
+        self.values.get(name).and_then(|values| values.first()).cloned()
+    }
+
+    /// Every value of a list flag, empty if it was not given
+    fn list(&self, name: &str) -> &[String] {
+        self.values.get(name).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    fn required(&self, name: &str) -> Result<String, String> {
+        self.optional(name).ok_or_else(|| format!("--{} is required", name))
+    }
+
+    fn time(&self, name: &str) -> Result<Option<DateTime<Utc>>, String> {
+        self.optional(name)
+            .map(|value| {
+                DateTime::parse_from_rfc3339(&value)
+                    .map(|time| time.with_timezone(&Utc))
+                    .map_err(|e| format!("--{} must be an RFC 3339 time, got '{}': {}", name, value, e))
+            })
+            .transpose()
+    }
+
+    /// `--format`, or the format `path`'s extension suggests
+    fn recipients_format(&self, path: &Path) -> Result<RecipientsFormat, String> {
+        match self.optional("format").as_deref() {
+            Some("csv") => Ok(RecipientsFormat::Csv),
+            Some("json") => Ok(RecipientsFormat::Json),
+            Some(other) => Err(format!("--format must be csv or json, got '{}'", other)),
+            None if path.extension().is_some_and(|ext| ext == "csv") => Ok(RecipientsFormat::Csv),
+            None => Ok(RecipientsFormat::Json),
+        }
+    }
+
+    fn leaf_index(&self) -> Result<usize, String> {
+        let index = self.required("index")?;
+        index.parse().map_err(|_| format!("Invalid leaf index '{}'", index))
+    }
+
+    fn hash(&self, name: &str) -> Result<Hash, String> {
+        let value = self.required(name)?;
+        parse_hash(&value).ok_or_else(|| format!("--{} must be 32 bytes of hex, got '{}'", name, value))
+    }
+}
+
+fn parse_command(args: &[String]) -> Result<Command, String> {
+    if args.first().is_some_and(|word| word == "migrate") {
+        let (command, database_url) = migrations::parse_args(&args[1..])?;
+        return Ok(Command::Migrate { command, database_url });
+    }
+    let words: Vec<&str> = args.iter().take(2).map(String::as_str).collect();
+    let rest = args.get(2..).unwrap_or_default();
+    match words.as_slice() {
+        ["tree", "build"] => {
+            let flags = Flags::parse(
+                rest,
+                &["input", "format", "output", "depth", "generate-secrets-file"],
+                &["dry-run"],
+            )?;
+            let input: PathBuf = flags.required("input")?.into();
+            let format = flags.recipients_format(&input)?;
+            // The claim circuit only verifies trees of this depth
+            if let Some(depth) = flags.optional("depth")
+                && depth != TREE_DEPTH.to_string()
+            {
+                return Err(format!("--depth must be {}, got '{}'", TREE_DEPTH, depth));
+            }
+            let secrets_file = flags.optional("generate-secrets-file").map(PathBuf::from);
+            let output = if flags.switch("dry-run") {
+                if secrets_file.is_some() {
+                    return Err("--dry-run writes no files, drop --generate-secrets-file".to_string());
+                }
+                None
+            } else {
+                Some(flags.required("output")?.into())
+            };
+            Ok(Command::TreeBuild {
+                input,
+                format,
+                output,
+                secrets_file,
+            })
+        }
+        ["tree", "verify-proof"] => {
+            let flags = Flags::parse(rest, &["proof", "root", "tree", "wallet"], &["verbose"])?;
+            let root = match (flags.optional("root"), flags.optional("tree")) {
+                (Some(_), None) => ExpectedRoot::Hash(flags.hash("root")?),
+                (None, Some(tree)) => ExpectedRoot::TreeFile(tree.into()),
+                _ => return Err("exactly one of --root and --tree is required".to_string()),
+            };
+            Ok(Command::TreeVerifyProof {
+                proof_file: flags.required("proof")?.into(),
+                root,
+                wallet: flags.optional("wallet"),
+                verbose: flags.switch("verbose"),
+            })
+        }
+        ["tree", "inspect"] => {
+            let flags = Flags::parse(rest, &["tree-file", "wallet"], &[])?;
+            Ok(Command::TreeInspect {
+                tree_file: flags.required("tree-file")?.into(),
+                wallet: flags.optional("wallet"),
+            })
+        }
+        ["export", "proofs"] => {
+            let flags = Flags::parse(rest, &["tree", "output-dir", "format"], &["compress"])?;
+            let format = match flags.optional("format").as_deref() {
+                None | Some("json") => ProofFormat::Json,
+                Some("borsh") => ProofFormat::Borsh,
+                Some(other) => return Err(format!("--format must be json or borsh, got '{}'", other)),
+            };
+            Ok(Command::ExportProofs {
+                tree_file: flags.required("tree")?.into(),
+                output_dir: flags.required("output-dir")?.into(),
+                format,
+                compress: flags.switch("compress"),
+            })
+        }
+        ["campaign", "create"] => {
+            let flags = Flags::parse(
+                rest,
+                &[
+                    "url",
+                    "api-key",
+                    "recipients-file",
+                    "format",
+                    "name",
+                    "address",
+                    "creator-wallet",
+                    "open-at",
+                    "close-at",
+                ],
+                &["verify"],
+            )?;
+            let recipients_file: PathBuf = flags.required("recipients-file")?.into();
+            let (open_at, close_at) = (flags.time("open-at")?, flags.time("close-at")?);
+            if let (Some(open_at), Some(close_at)) = (open_at, close_at)
+                && close_at <= open_at
+            {
+                return Err("--close-at must be after --open-at".to_string());
+            }
+            Ok(Command::CampaignCreate(CampaignCreate {
+                url: flags.required("url")?,
+                api_key: flags.optional("api-key"),
+                format: flags.recipients_format(&recipients_file)?,
+                recipients_file,
+                name: flags.required("name")?,
+                address: flags.required("address")?,
+                creator_wallet: flags.required("creator-wallet")?,
+                open_at,
+                close_at,
+                verify: flags.switch("verify"),
+            }))
+        }
+        ["campaign", "export"] => {
+            let flags = Flags::parse(rest, &["url", "id", "output"], &[])?;
+            Ok(Command::CampaignExport {
+                url: flags.required("url")?,
+                id: flags.required("id")?,
+                output: flags.required("output")?.into(),
+            })
+        }
+        ["campaign", "import"] => {
+            let flags = Flags::parse(rest, &["url", "api-key", "input", "address"], &["verify"])?;
+            Ok(Command::CampaignImport {
+                url: flags.required("url")?,
+                api_key: flags.optional("api-key"),
+                input: flags.required("input")?.into(),
+                address: flags.optional("address"),
+                verify: flags.switch("verify"),
+            })
+        }
+        ["hash", "poseidon"] => {
+            let flags = Flags::parse_lists(rest, &["inputs-file", "format", "verify-against"], &[], &["inputs"])?;
+            // Comma-separated values are still accepted, as before lists
+            let values: Vec<&str> = flags.list("inputs").iter().flat_map(|value| value.split(',')).collect();
+            let inputs = match flags.optional("inputs-file") {
+                Some(_) if !values.is_empty() => return Err("give --inputs or --inputs-file, not both".to_string()),
+                Some(path) => HashInputs::File(path.into()),
+                None if values.is_empty() => return Err("--inputs or --inputs-file is required".to_string()),
+                None => HashInputs::Values(
+                    values
+                        .iter()
+                        .enumerate()
+                        .map(|(i, input)| parse_field(input.trim()).map_err(|e| format!("input at index {}: {}", i, e)))
+                        .collect::<Result<_, _>>()?,
+                ),
+            };
+            let format = match flags.optional("format").as_deref() {
+                None | Some("hex") => HashFormat::Hex,
+                Some("decimal") => HashFormat::Decimal,
+                Some(other) => return Err(format!("--format must be hex or decimal, got '{}'", other)),
+            };
+            Ok(Command::HashPoseidon {
+                inputs,
+                format,
+                verify_against: flags.optional("verify-against"),
+            })
+        }
+        ["nullifier", "compute"] => {
+            let flags = Flags::parse(rest, &["secret", "index", "wallet"], &[])?;
+            Ok(Command::NullifierCompute {
+                secret: flags.hash("secret")?,
+                index: flags.leaf_index()?,
+                wallet: flags.optional("wallet"),
+            })
+        }
+        ["nullifier", "check"] => {
+            let flags = Flags::parse(rest, &["url", "campaign-id", "nullifier", "secret", "index", "wallet"], &[])?;
+            let nullifier = match flags.optional("nullifier") {
+                Some(_) if flags.optional("secret").is_some() => {
+                    return Err("give --nullifier or --secret, not both".to_string());
+                }
+                Some(_) => flags.hash("nullifier")?,
+                None => {
+                    let wallet = flags.optional("wallet");
+                    claim_nullifier(&flags.hash("secret")?, flags.leaf_index()?, wallet.as_deref()).0
+                }
+            };
+            Ok(Command::NullifierCheck {
+                url: flags.required("url")?,
+                campaign_id: flags.required("campaign-id")?,
+                nullifier,
+            })
+        }
+        _ => Err(format!("Unknown command '{}'", words.join(" "))),
+    }
+}
+
+/// A field element given as big-endian hex (0x-prefixed or not), as the
+/// hash API reads string inputs
+fn parse_field(value: &str) -> Result<Hash, String> {
+    let bytes = hex::decode(value.strip_prefix("0x").unwrap_or(value))
+        .map_err(|e| format!("invalid hex '{}': {}", value, e))?;
+    let field = fr_from_be_bytes_checked(&bytes).map_err(|e| e.to_string())?;
+    Ok(fr_to_be_bytes(field))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    dotenv::dotenv().ok();
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let command = match parse_command(&args) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    if let Command::Migrate { command, database_url } = command {
+        return match migrations::execute(command, database_url).await {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("{}", e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let result = match command {
+        Command::TreeBuild {
+            input,
+            format,
+            output,
+            secrets_file,
+        } => tree_build(&input, format, output.as_deref(), secrets_file.as_deref()),
+        Command::TreeVerifyProof {
+            proof_file,
+            root,
+            wallet,
+            verbose,
+        } => tree_verify_proof(&proof_file, &root, wallet.as_deref(), verbose),
+        Command::TreeInspect { tree_file, wallet } => tree_inspect(&tree_file, wallet.as_deref()),
+        Command::ExportProofs {
+            tree_file,
+            output_dir,
+            format,
+            compress,
+        } => export_proofs(&tree_file, &output_dir, format, compress),
+        Command::CampaignCreate(create) => campaign_create(&create),
+        Command::CampaignExport { url, id, output } => campaign_export(&url, &id, &output),
+        Command::CampaignImport {
+            url,
+            api_key,
+            input,
+            address,
+            verify,
+        } => campaign_import(&url, api_key.as_deref(), &input, address.as_deref(), verify),
+        Command::HashPoseidon {
+            inputs,
+            format,
+            verify_against,
+        } => hash_poseidon(&inputs, format, verify_against.as_deref()),
+        Command::NullifierCompute { secret, index, wallet } => Ok(nullifier_compute(&secret, index, wallet.as_deref())),
+        Command::NullifierCheck { url, campaign_id, nullifier } => nullifier_check(&url, &campaign_id, &nullifier),
+        Command::Migrate { .. } => unreachable!(),
+    };
+
+    match result {
+        Ok(output) => {
+            println!("{}", serde_json::to_string_pretty(&output).unwrap_or_default());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn hex_hash(hash: &Hash) -> String {
+    format!("0x{}", hex::encode(hash))
+}
+
+fn proof_json(proof: &MerkleProof) -> Value {
+    json!({
+        "leaf_index": proof.leaf_index,
+        "leaf": hex_hash(&proof.leaf),
+        "siblings": proof.siblings.iter().map(hex_hash).collect::<Vec<_>>(),
+    })
+}
+
+/// Tree of the recipients listed in `input`, with fresh secrets
+fn build_tree(input: &Path, format: RecipientsFormat) -> Result<MerkleTree, String> {
+    let recipients = read_recipients(input, format)?;
+    MerkleTree::from_amounts(&recipients).map_err(|e| format!("{}: {}", input.display(), e))
+}
+
+fn read_recipients(input: &Path, format: RecipientsFormat) -> Result<Vec<(String, Lamports)>, String> {
+    let recipients = read_recipients_file(input, format).map_err(|e| format!("{}: {}", input.display(), e))?;
+    if recipients.is_empty() {
+        return Err(format!("{}: no recipients", input.display()));
+    }
+    Ok(recipients)
+}
+
+/// Build and check the tree, writing it unless `output` is `None`
+fn tree_build(
+    input: &Path,
+    format: RecipientsFormat,
+    output: Option<&Path>,
+    secrets_file: Option<&Path>,
+) -> Result<Value, String> {
+    let tree = build_tree(input, format)?;
+    let root = hex_hash(&tree.root());
+
+    match output {
+        Some(output) => {
+            tree.to_json_file(output, true).map_err(|e| e.to_string())?;
+            if let Some(secrets_file) = secrets_file {
+                tree.secrets_to_json_file(secrets_file).map_err(|e| e.to_string())?;
+            }
+            eprintln!("Built tree with {} leaves, root = {}", tree.leaf_count(), root);
+        }
+        None => eprintln!("Dry run: {} leaves are valid, root = {}", tree.leaf_count(), root),
+    }
+    Ok(json!({
+        "root": root,
+        "leaf_count": tree.leaf_count(),
+        "output": output,
+        "secrets_file": secrets_file,
+    }))
+}
+
+/// The proof of `wallet` in a tree file, or the bare proof object in `path`
+fn read_proof(path: &Path, wallet: Option<&str>) -> Result<MerkleProof, String> {
+    let contents = std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let value: Value = serde_json::from_slice(&contents).map_err(|e| e.to_string())?;
+    if value.get("recipients").is_some() {
+        let wallet = wallet.ok_or("--wallet is required to pick a proof from a tree file")?;
+        let tree = MerkleTree::from_json_file(path).map_err(|e| e.to_string())?;
+        return tree
+            .get_proof(wallet)
+            .ok_or_else(|| format!("wallet {} is not in the tree", wallet));
+    }
+
+    let hash = |value: &Value| value.as_str().and_then(parse_hash);
+    let siblings = value["siblings"].as_array().and_then(|s| s.iter().map(hash).collect::<Option<Vec<_>>>());
+    match (value["leaf_index"].as_u64(), hash(&value["leaf"]), siblings) {
+        (Some(leaf_index), Some(leaf), Some(siblings)) => Ok(MerkleProof {
+            leaf_index: leaf_index as usize,
+            siblings,
+            leaf,
+        }),
+        _ => Err("proof file must be a tree file or { leaf_index, leaf, siblings } with hex hashes".to_string()),
+    }
+}
+
+/// Check the proof, failing with both roots if it does not verify
+fn tree_verify_proof(
+    proof_file: &Path,
+    root: &ExpectedRoot,
+    wallet: Option<&str>,
+    verbose: bool,
+) -> Result<Value, String> {
+    let proof = read_proof(proof_file, wallet)?;
+    let expected = match root {
+        ExpectedRoot::Hash(root) => *root,
+        ExpectedRoot::TreeFile(path) => MerkleTree::from_json_file(path)
+            .map_err(|e| format!("{}: {}", path.display(), e))?
+            .root(),
+    };
+
+    let path = recompute_path(&proof);
+    if verbose {
+        eprintln!("leaf {}: {}", proof.leaf_index, hex_hash(&proof.leaf));
+        for (level, (sibling, node)) in proof.siblings.iter().zip(&path[1..]).enumerate() {
+            let side = if (proof.leaf_index >> level) & 1 == 0 { "right" } else { "left" };
+            eprintln!(
+                "level {}: sibling {} on the {} -> {}",
+                level + 1,
+                hex_hash(sibling),
+                side,
+                hex_hash(node)
+            );
+        }
+    }
+
+    let computed = path[path.len() - 1];
+    if !verify_proof(&expected, &proof) {
+        let mut message = format!(
+            "✗ Proof is INVALID\n  computed root: {}\n  expected root: {}",
+            hex_hash(&computed),
+            hex_hash(&expected)
+        );
+        if proof.siblings.len() != TREE_DEPTH {
+            message.push_str(&format!(
+                "\n  proof has {} siblings, the circuit expects {}",
+                proof.siblings.len(),
+                TREE_DEPTH
+            ));
+        }
+        return Err(message);
+    }
+    eprintln!("✓ Proof is valid");
+    Ok(json!({
+        "valid": true,
+        "leaf_index": proof.leaf_index,
+        "root": hex_hash(&computed),
+    }))
+}
+
+fn tree_inspect(tree_file: &Path, wallet: Option<&str>) -> Result<Value, String> {
+    let tree = MerkleTree::from_json_file(tree_file).map_err(|e| e.to_string())?;
+    let mut output = json!({
+        "root": hex_hash(&tree.root()),
+        "arity": tree.arity(),
+        "depth": tree.depth(),
+        "leaf_count": tree.leaf_count(),
+        "capacity": tree.capacity(),
+    });
+    if let Some(wallet) = wallet {
+        let proof = tree
+            .get_proof(wallet)
+            .ok_or_else(|| format!("wallet {} is not in the tree", wallet))?;
+        output["wallet"] = json!(wallet);
+        output["proof"] = proof_json(&proof);
+    }
+    Ok(output)
+}
+
+/// Write the proof of every recipient to `output_dir/{wallet}.json` (or
+/// `.bin` for Borsh), gzipping them with the system `gzip` if `compress` is set
+fn export_proofs(tree_file: &Path, output_dir: &Path, format: ProofFormat, compress: bool) -> Result<Value, String> {
+    let tree = MerkleTree::from_json_file(tree_file).map_err(|e| format!("{}: {}", tree_file.display(), e))?;
+    if let Err(failures) = tree.validate_all_proofs() {
+        let failures: Vec<String> = failures.iter().map(|(wallet, e)| format!("{}: {}", wallet, e)).collect();
+        return Err(format!("{}: proofs do not verify: {}", tree_file.display(), failures.join("; ")));
+    }
+    let root = hex_hash(&tree.root());
+    let proofs = tree.batch_proofs();
+    // Wallet labels become file names, so they must not reach outside the directory
+    if let Some((wallet, _)) = proofs.iter().find(|(wallet, _)| {
+        wallet.starts_with('.') || !wallet.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+    }) {
+        return Err(format!("wallet {:?} cannot be used as a file name", wallet));
+    }
+    std::fs::create_dir_all(output_dir).map_err(|e| format!("{}: {}", output_dir.display(), e))?;
+
+    let mut paths = Vec::with_capacity(proofs.len());
+    for (wallet, proof) in &proofs {
+        let (extension, contents) = match format {
+            ProofFormat::Json => {
+                let mut file = proof_json(proof);
+                file["wallet"] = json!(wallet);
+                file["root"] = json!(root);
+                ("json", serde_json::to_vec_pretty(&file).map_err(|e| e.to_string())?)
+            }
+            ProofFormat::Borsh => ("bin", proof.to_solana_instruction_data()),
+        };
+        let path = output_dir.join(format!("{}.{}", wallet, extension));
+        std::fs::write(&path, contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+        paths.push(path);
+    }
+
+    if compress && !paths.is_empty() {
+        let status = std::process::Command::new("gzip")
+            .arg("-nf")
+            .arg("--")
+            .args(&paths)
+            .status()
+            .map_err(|e| format!("--compress needs gzip on PATH: {}", e))?;
+        if !status.success() {
+            return Err(format!("gzip failed with {}", status));
+        }
+    }
+    eprintln!("Exported {} proofs to {}", proofs.len(), output_dir.display());
+
+    Ok(json!({
+        "exported": proofs.len(),
+        "output_dir": output_dir,
+        "root": root,
+        "format": match format {
+            ProofFormat::Json => "json",
+            ProofFormat::Borsh => "borsh",
+        },
+        "compressed": compress,
+    }))
+}
+
+/// `data` of a successful API response, or the API's error in words
+fn api_request(
+    method: &str,
+    base_url: &str,
+    path: &str,
+    api_key: Option<&str>,
+    body: Option<&Value>,
+) -> Result<Value, String> {
+    let url = Url::parse(&format!("{}{}", base_url.trim_end_matches('/'), path))
+        .map_err(|e| format!("invalid --url '{}': {}", base_url, e))?;
+    let authorization = api_key.map(|key| format!("Bearer {}", key));
+    let mut headers = vec![("Accept", "application/json")];
+    if let Some(authorization) = &authorization {
+        headers.push(("Authorization", authorization));
+    }
+    if body.is_some() {
+        headers.push(("Content-Type", "application/json"));
+    }
+
+    let body = body.map(Value::to_string);
+    let response = http::send(method, &url, &headers, body.as_deref(), API_TIMEOUT)
+        .map_err(|e| format!("{} {} failed: {}", method, url, e))?;
+    let value: Option<Value> = serde_json::from_slice(&response.body).ok();
+    if !(200..300).contains(&response.status) {
+        return Err(api_error_message(response.status, value.as_ref()));
+    }
+    value
+        .map(|value| value["data"].clone())
+        .ok_or_else(|| format!("{} {} answered with a body that is not JSON", method, url))
+}
+
+/// An `ApiErrorResponse` as a message for operators
+fn api_error_message(status: u16, body: Option<&Value>) -> String {
+    let errors = body.map(|body| &body["errors"]).unwrap_or(&Value::Null);
+    let mut message = format!(
+        "API error (HTTP {}): {}",
+        status,
+        errors["message"].as_str().unwrap_or("no error message")
+    );
+    if let Some(details) = errors["details"].as_str() {
+        message.push_str(&format!(" ({})", details));
+    }
+    for violation in errors["validation"].as_array().into_iter().flatten() {
+        message.push_str(&format!(
+            "\n  {}: {}",
+            violation["path"].as_str().unwrap_or_default(),
+            violation["message"].as_str().unwrap_or_default()
+        ));
+    }
+    if status == 401 || status == 403 {
+        message.push_str("\n  check --api-key");
+    }
+    message
+}
+
+fn campaign_create(create: &CampaignCreate) -> Result<Value, String> {
+    let recipients = read_recipients(&create.recipients_file, create.format)?;
+    // Campaigns have no open time of their own; it starts their schedule
+    let settings = json!({
+        "address": create.address,
+        "name": create.name,
+        "creator_wallet": create.creator_wallet,
+        "vesting_start": create.open_at.map(|open_at| open_at.timestamp()),
+        "close_at": create.close_at,
+    });
+    let mut output = post_campaign(&create.url, create.api_key.as_deref(), &recipients, settings)?;
+    if create.verify {
+        output["verified"] = verify_api_proof(&create.url, &create.address, &recipients[0].0)?;
+    }
+    Ok(output)
+}
+
+/// Build a tree with new secrets over `recipients` and create the campaign
+/// with `settings` through the API, saving its id to `CAMPAIGN_FILE`
+fn post_campaign(
+    url: &str,
+    api_key: Option<&str>,
+    recipients: &[(String, Lamports)],
+    mut settings: Value,
+) -> Result<Value, String> {
+    let tree = MerkleTree::from_amounts(recipients).map_err(|e| e.to_string())?;
+    let total = recipients
+        .iter()
+        .try_fold(0 as Lamports, |total, (_, amount)| total.checked_add(*amount))
+        .ok_or("total amount overflows")?;
+    let root = hex_hash(&tree.root());
+
+    settings["merkle_root"] = json!(root);
+    settings["total_amount"] = json!(total.to_string());
+    settings["recipients"] = recipients
+        .iter()
+        .map(|(wallet, amount)| json!({ "wallet": wallet, "amount": amount.to_string() }))
+        .collect();
+    let campaign = api_request("POST", url, "/api/v1/campaigns", api_key, Some(&settings))?;
+    let id = campaign["id"].as_str().ok_or("API response has no campaign id")?;
+    std::fs::write(CAMPAIGN_FILE, format!("{}\n", id)).map_err(|e| format!("{}: {}", CAMPAIGN_FILE, e))?;
+    eprintln!("Campaign {} has {} recipients, root = {}", id, recipients.len(), root);
+
+    Ok(json!({
+        "campaign_id": id,
+        "address": settings["address"],
+        "merkle_root": root,
+        "total_amount": total.to_string(),
+        "saved_to": CAMPAIGN_FILE,
+    }))
+}
+
+fn campaign_export(url: &str, id: &str, output: &Path) -> Result<Value, String> {
+    let info = api_request("GET", url, &format!("/api/v1/campaigns/id/{}", id), None, None)?;
+    let campaign: CampaignSettings =
+        serde_json::from_value(info).map_err(|e| format!("API campaign {} is malformed: {}", id, e))?;
+
+    let mut recipients = Vec::new();
+    let mut cursor = None;
+    loop {
+        let mut path = format!("/api/v1/campaigns/{}/recipients?limit={}", campaign.address, EXPORT_PAGE_SIZE);
+        if let Some(cursor) = cursor {
+            path.push_str(&format!("&cursor={}", cursor));
+        }
+        let page = api_request("GET", url, &path, None, None)?;
+        for recipient in page["recipients"].as_array().ok_or("API recipient page is malformed")? {
+            let (Some(wallet), Some(amount_sol)) = (recipient["wallet"].as_str(), recipient["amount_sol"].as_f64())
+            else {
+                return Err(format!("API recipient {} is malformed", recipient));
+            };
+            // The API reports allocations as SOL floats; lamports are whole
+            let lamports = (amount_sol * LAMPORTS_PER_SOL as f64).round() as Lamports;
+            recipients.push(ExportedRecipient {
+                wallet: wallet.to_string(),
+                amount_sol: sol_string(lamports),
+            });
+        }
+        match page["next_cursor"].as_u64() {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    let exported = ExportedCampaign {
+        version: EXPORT_VERSION.to_string(),
+        campaign,
+        recipients,
+    };
+    let json = serde_json::to_string_pretty(&exported).map_err(|e| e.to_string())?;
+    std::fs::write(output, json + "\n").map_err(|e| format!("{}: {}", output.display(), e))?;
+    Ok(json!({
+        "campaign_id": id,
+        "address": exported.campaign.address,
+        "recipients": exported.recipients.len(),
+        "output": output.display().to_string(),
+    }))
+}
+
+fn campaign_import(
+    url: &str,
+    api_key: Option<&str>,
+    input: &Path,
+    address: Option<&str>,
+    verify: bool,
+) -> Result<Value, String> {
+    let (mut campaign, recipients) = read_campaign_export(input)?;
+    if let Some(address) = address {
+        campaign.address = address.to_string();
+    }
+    let settings = serde_json::to_value(&campaign).map_err(|e| e.to_string())?;
+    let mut output = post_campaign(url, api_key, &recipients, settings)?;
+    eprintln!("New secrets were generated: the Merkle root and id differ from the exported campaign's");
+    if verify {
+        output["verified"] = verify_api_proof(url, &campaign.address, &recipients[0].0)?;
+    }
+    Ok(output)
+}
+
+/// Settings and `(wallet, lamports)` recipients of a `campaign export` file
+fn read_campaign_export(path: &Path) -> Result<(CampaignSettings, Vec<(String, Lamports)>), String> {
+    let contents = std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let exported: ExportedCampaign =
+        serde_json::from_slice(&contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if exported.version != EXPORT_VERSION {
+        return Err(format!(
+            "{}: unsupported export version '{}', expected {}",
+            path.display(),
+            exported.version,
+            EXPORT_VERSION
+        ));
+    }
+    if exported.recipients.is_empty() {
+        return Err(format!("{} has no recipients", path.display()));
+    }
+
+    let recipients = exported
+        .recipients
+        .into_iter()
+        .map(|recipient| match sol_to_lamports(&recipient.amount_sol).filter(|amount| *amount > 0) {
+            Some(amount) => Ok((recipient.wallet, amount)),
+            None => Err(format!("invalid amount_sol '{}' for {}", recipient.amount_sol, recipient.wallet)),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((exported.campaign, recipients))
+}
+
+/// `lamports` as a decimal SOL amount `sol_to_lamports` reads back exactly
+fn sol_string(lamports: Lamports) -> String {
+    let fraction = format!("{:09}", lamports % LAMPORTS_PER_SOL);
+    let fraction = fraction.trim_end_matches('0');
+    match fraction {
+        "" => (lamports / LAMPORTS_PER_SOL).to_string(),
+        fraction => format!("{}.{}", lamports / LAMPORTS_PER_SOL, fraction),
+    }
+}
+
+/// Fetch the proof of `wallet` from the API and check it against its root
+fn verify_api_proof(url: &str, address: &str, wallet: &str) -> Result<Value, String> {
+    let path = format!("/api/v1/proofs/{}/generate", address);
+    let data = api_request("POST", url, &path, None, Some(&json!({ "wallet": wallet })))?;
+
+    let hash = |value: &Value| value.as_str().and_then(parse_hash);
+    let siblings = data["merkle_path"].as_array().and_then(|s| s.iter().map(hash).collect::<Option<Vec<_>>>());
+    let amount = data["amount"].as_str().and_then(|amount| amount.parse::<u64>().ok());
+    let (Some(root), Some(secret), Some(amount), Some(leaf_index), Some(siblings)) =
+        (hash(&data["merkle_root"]), hash(&data["secret"]), amount, data["leaf_index"].as_u64(), siblings)
+    else {
+        return Err(format!("API proof of {} is malformed", wallet));
+    };
+    let proof = MerkleProof {
+        leaf_index: leaf_index as usize,
+        siblings,
+        leaf: compute_leaf_hash(wallet, amount, &secret),
+    };
+    if !verify_proof(&root, &proof) {
+        return Err(format!("✗ API proof of {} does not verify against its root {}", wallet, hex_hash(&root)));
+    }
+    eprintln!("✓ Proof of {} verified locally", wallet);
+    Ok(json!({ "wallet": wallet, "leaf_index": leaf_index, "root": hex_hash(&root) }))
+}
+
+fn hash_poseidon(inputs: &HashInputs, format: HashFormat, verify_against: Option<&str>) -> Result<Value, String> {
+    let inputs = match inputs {
+        HashInputs::Values(values) => values.clone(),
+        HashInputs::File(path) => read_field_lines(path)?,
+    };
+    let fields: Vec<_> = inputs
+        .iter()
+        .map(|input| fr_from_be_bytes_checked(input).map_err(|e| e.to_string()))
+        .collect::<Result<_, _>>()?;
+    let hash = poseidon2_bn254_t4_hash_fields(&fields).map_err(|e| e.to_string())?;
+    let printed = match format {
+        HashFormat::Hex => hex_hash(&fr_to_be_bytes(hash)),
+        HashFormat::Decimal => hash.to_string(),
+    };
+
+    if let Some(expected) = verify_against {
+        let expected_hash = match format {
+            HashFormat::Hex => {
+                parse_field(expected).and_then(|bytes| fr_from_be_bytes_checked(&bytes).map_err(|e| e.to_string()))
+            }
+            HashFormat::Decimal => {
+                Fr::from_str(expected).map_err(|_| format!("invalid decimal field element '{}'", expected))
+            }
+        }
+        .map_err(|e| format!("--verify-against: {}", e))?;
+        if expected_hash != hash {
+            return Err(format!("✗ Hash {} does not match expected {}", printed, expected));
+        }
+        eprintln!("✓ Hash matches");
+    }
+    Ok(json!({ "hash": printed }))
+}
+
+/// Field elements of an inputs file, one hex value per line; blank lines
+/// are skipped
+fn read_field_lines(path: &Path) -> Result<Vec<Hash>, String> {
+    let contents =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| parse_field(line.trim()).map_err(|e| format!("{} line {}: {}", path.display(), i + 1, e)))
+        .collect()
+}
+
+/// The v2 nullifier the backend spends when the wallet is known, otherwise
+/// the v1 nullifier claim instructions carry, with the version used
+fn claim_nullifier(secret: &Hash, index: usize, wallet: Option<&str>) -> (Hash, &'static str) {
+    match wallet {
+        Some(wallet) => (compute_nullifier_v2(secret, index, wallet), "v2"),
+        None => (compute_nullifier(secret, index), "v1"),
+    }
+}
+
+fn nullifier_compute(secret: &Hash, index: usize, wallet: Option<&str>) -> Value {
+    let (nullifier, version) = claim_nullifier(secret, index, wallet);
+    json!({ "nullifier": hex_hash(&nullifier), "version": version })
+}
+
+fn nullifier_check(url: &str, campaign_id: &str, nullifier: &Hash) -> Result<Value, String> {
+    let campaign_id_param: String = url::form_urlencoded::byte_serialize(campaign_id.as_bytes()).collect();
+    let path = format!("/api/v1/nullifier/{}?campaign_id={}", hex::encode(nullifier), campaign_id_param);
+    let status = api_request("GET", url, &path, None, None)?;
+
+    let spent = status["spent"].as_bool().ok_or("API response has no spent flag")?;
+    match status["spent_at"].as_str() {
+        Some(spent_at) if spent => eprintln!(
+            "Nullifier {} is spent, claimed at {} in campaign {}",
+            hex_hash(nullifier),
+            spent_at,
+            status["campaign_address"].as_str().unwrap_or("unknown")
+        ),
+        _ if spent => eprintln!("Nullifier {} is spent", hex_hash(nullifier)),
+        _ => eprintln!("Nullifier {} is unspent", hex_hash(nullifier)),
+    }
+    Ok(json!({
+        "campaign_id": campaign_id,
+        "nullifier": hex_hash(nullifier),
+        "spent": spent,
+        "spent_at": status["spent_at"],
+        "campaign_address": status["campaign_address"],
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(
+            parse_command(&args(&["tree", "build", "--output", "tree.json", "--input", "r.csv", "--depth", "8"])),
+            Ok(Command::TreeBuild {
+                input: "r.csv".into(),
+                format: RecipientsFormat::Csv,
+                output: Some("tree.json".into()),
+                secrets_file: None,
+            })
+        );
+        assert!(matches!(
+            parse_command(&args(&["tree", "build", "--input", "r.txt", "--format", "csv", "--dry-run"])),
+            Ok(Command::TreeBuild { format: RecipientsFormat::Csv, output: None, .. })
+        ));
+        assert!(parse_command(&args(&["tree", "build", "--input", "r.json"])).is_err());
+        assert!(parse_command(&args(&["tree", "build", "--input", "r.json", "--output", "t", "--depth", "6"])).is_err());
+        assert!(parse_command(&args(&["tree", "build", "--input", "r.json", "--format", "xml", "--dry-run"])).is_err());
+        assert!(
+            parse_command(&args(&["tree", "build", "--input", "r", "--dry-run", "--generate-secrets-file", "s"])).is_err()
+        );
+        assert_eq!(
+            parse_command(&args(&["nullifier", "compute", "--secret", "0x01", "--index", "3"])),
+            Err("--secret must be 32 bytes of hex, got '0x01'".to_string())
+        );
+        assert!(matches!(
+            parse_command(&args(&["hash", "poseidon", "--inputs", "0x01, 02"])),
+            Ok(Command::HashPoseidon { inputs: HashInputs::Values(inputs), .. }) if inputs.len() == 2
+        ));
+        assert_eq!(
+            parse_command(&args(&["hash", "poseidon", "--inputs", "0x01", "02", "03", "--format", "decimal"])),
+            Ok(Command::HashPoseidon {
+                inputs: HashInputs::Values(vec![parse_field("01").unwrap(), parse_field("02").unwrap(), parse_field("03").unwrap()]),
+                format: HashFormat::Decimal,
+                verify_against: None,
+            })
+        );
+        assert!(matches!(
+            parse_command(&args(&["hash", "poseidon", "--inputs-file", "in.txt", "--verify-against", "0x01"])),
+            Ok(Command::HashPoseidon { inputs: HashInputs::File(_), format: HashFormat::Hex, verify_against: Some(_) })
+        ));
+        assert!(parse_command(&args(&["hash", "poseidon", "--inputs", "01", "--inputs-file", "in.txt"])).is_err());
+        assert!(parse_command(&args(&["hash", "poseidon", "--format", "hex"])).is_err());
+        assert_eq!(
+            parse_command(&args(&["tree", "verify-proof", "--proof", "p.json", "--tree", "t.json", "--verbose"])),
+            Ok(Command::TreeVerifyProof {
+                proof_file: "p.json".into(),
+                root: ExpectedRoot::TreeFile("t.json".into()),
+                wallet: None,
+                verbose: true,
+            })
+        );
+        let root = format!("0x{}", "00".repeat(32));
+        for both_or_neither in [&["--root", &root, "--tree", "t.json"][..], &[]] {
+            let mut list = vec!["tree", "verify-proof", "--proof", "p.json"];
+            list.extend(both_or_neither);
+            assert!(parse_command(&args(&list)).is_err());
+        }
+        let secret = format!("0x{}", "01".repeat(32));
+        let check = ["nullifier", "check", "--url", "http://api", "--campaign-id", "c", "--secret", &secret];
+        let expected = compute_nullifier_v2(&[1u8; 32], 4, "w");
+        assert_eq!(
+            parse_command(&args(&[&check[..], &["--index", "4", "--wallet", "w"]].concat())),
+            Ok(Command::NullifierCheck {
+                url: "http://api".to_string(),
+                campaign_id: "c".to_string(),
+                nullifier: expected,
+            })
+        );
+        assert!(parse_command(&args(&check)).is_err(), "--index is needed with --secret");
+        assert!(parse_command(&args(&[&check[..], &["--nullifier", &secret]].concat())).is_err());
+        assert_eq!(
+            parse_command(&args(&["migrate", "rollback", "--steps", "2"])),
+            Ok(Command::Migrate {
+                command: MigrateCommand::Rollback(2),
+                database_url: None,
+            })
+        );
+        assert!(parse_command(&args(&["tree", "inspect"])).is_err());
+        assert!(parse_command(&args(&["tree", "inspect", "--tree-file"])).is_err());
+        assert!(parse_command(&args(&["tree", "inspect", "--tree-file", "t", "--all"])).is_err());
+        assert!(parse_command(&args(&["tree", "plant"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_campaign_create() {
+        let mut list = vec![
+            "campaign", "create", "--url", "http://localhost:3000", "--recipients-file", "r.csv", "--name", "Q1",
+            "--address", "Camp", "--creator-wallet", "Creator", "--open-at", "2025-01-01T00:00:00Z",
+        ];
+        let Ok(Command::CampaignCreate(create)) = parse_command(&args(&list)) else {
+            panic!("campaign create should parse");
+        };
+        assert_eq!(create.format, RecipientsFormat::Csv);
+        assert_eq!(create.open_at.map(|t| t.timestamp()), Some(1_735_689_600));
+        assert!(!create.verify);
+
+        list.extend(["--close-at", "2024-12-31T23:59:59Z"]);
+        assert_eq!(parse_command(&args(&list)), Err("--close-at must be after --open-at".to_string()));
+        list.truncate(list.len() - 4);
+        list.extend(["--open-at", "January 1st"]);
+        assert!(parse_command(&args(&list)).is_err());
+
+        assert!(matches!(
+            parse_command(&args(&["campaign", "import", "--url", "http://x", "--input", "c.json", "--verify"])),
+            Ok(Command::CampaignImport { address: None, verify: true, .. })
+        ));
+        assert!(parse_command(&args(&["campaign", "export", "--url", "http://x", "--output", "c.json"])).is_err());
+    }
+
+    #[test]
+    fn test_campaign_export_files_read_back_exactly() {
+        assert_eq!(sol_string(1_500_000_000), "1.5");
+        assert_eq!(sol_string(2 * LAMPORTS_PER_SOL), "2");
+        assert_eq!(sol_string(1), "0.000000001");
+
+        let path = env::temp_dir().join(format!("shadow-drop-cli-{}-campaign.json", std::process::id()));
+        let mut exported = json!({
+            "version": EXPORT_VERSION,
+            "campaign": { "name": "Q1", "address": "Camp", "creator_wallet": "Creator", "referral_bonus_bps": 50 },
+            "recipients": [
+                { "wallet": "a", "amount_sol": sol_string(123_456_789_012) },
+                { "wallet": "b", "amount_sol": "1" },
+            ],
+        });
+        std::fs::write(&path, exported.to_string()).unwrap();
+        let (campaign, recipients) = read_campaign_export(&path).unwrap();
+        assert_eq!((campaign.address.as_str(), campaign.referral_bonus_bps), ("Camp", 50));
+        assert_eq!(recipients, [("a".to_string(), 123_456_789_012), ("b".to_string(), LAMPORTS_PER_SOL)]);
+
+        exported["version"] = json!("2.0");
+        std::fs::write(&path, exported.to_string()).unwrap();
+        let error = read_campaign_export(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(error.ends_with("unsupported export version '2.0', expected 1.0"), "{}", error);
+    }
+
+    #[test]
+    fn test_api_errors_are_worded_for_operators() {
+        let body = json!({
+            "success": false,
+            "errors": {
+                "code": 400,
+                "message": "Invalid request body",
+                "details": null,
+                "validation": [{ "path": "/recipients/0/amount", "message": "must be a string" }],
+            },
+        });
+        assert_eq!(
+            api_error_message(400, Some(&body)),
+            "API error (HTTP 400): Invalid request body\n  /recipients/0/amount: must be a string"
+        );
+        assert_eq!(
+            api_error_message(401, None),
+            "API error (HTTP 401): no error message\n  check --api-key"
+        );
+    }
+
+    #[test]
+    fn test_export_proofs_writes_verifiable_files() {
+        let dir = env::temp_dir().join(format!("shadow-drop-cli-{}-export", std::process::id()));
+        let recipients: Vec<(String, u64, [u8; 32])> =
+            (0..3u8).map(|i| (format!("wallet{i}"), 1, [i + 1; 32])).collect();
+        let tree = MerkleTree::from_recipients(&recipients).unwrap();
+        std::fs::create_dir_all(&dir).unwrap();
+        let tree_file = dir.join("tree.json");
+        tree.to_json_file(&tree_file, false).unwrap();
+
+        let proofs = dir.join("proofs");
+        let output = export_proofs(&tree_file, &proofs, ProofFormat::Json, false).unwrap();
+        assert_eq!(output["exported"], 3);
+        let proof = read_proof(&proofs.join("wallet2.json"), None).unwrap();
+        assert!(verify_proof(&tree.root(), &proof));
+
+        export_proofs(&tree_file, &proofs, ProofFormat::Borsh, false).unwrap();
+        let data = std::fs::read(proofs.join("wallet1.bin")).unwrap();
+        let (_, decoded) = MerkleProof::from_solana_instruction_data(&data).unwrap();
+        assert_eq!(decoded.siblings, tree.get_proof("wallet1").unwrap().siblings);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_hash_poseidon_matches_library() {
+        let expected = poseidon2_bn254_t4_hash_fields(&[1u64.into(), 2u64.into()]).unwrap();
+        let inputs = HashInputs::Values(vec![parse_field("01").unwrap(), parse_field("02").unwrap()]);
+        let output = hash_poseidon(&inputs, HashFormat::Hex, None).unwrap();
+        assert_eq!(output["hash"], hex_hash(&fr_to_be_bytes(expected)));
+
+        let path = env::temp_dir().join(format!("shadow-drop-cli-{}-inputs.txt", std::process::id()));
+        std::fs::write(&path, "0x01\n\n02\n").unwrap();
+        let decimal = expected.to_string();
+        let output = hash_poseidon(&HashInputs::File(path.clone()), HashFormat::Decimal, Some(&decimal)).unwrap();
+        assert_eq!(output["hash"], decimal);
+        assert!(hash_poseidon(&HashInputs::File(path.clone()), HashFormat::Decimal, Some("1")).is_err());
+        std::fs::write(&path, "0x01\nzz\n").unwrap();
+        let error = hash_poseidon(&HashInputs::File(path.clone()), HashFormat::Hex, None).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(error.ends_with("line 2: invalid hex 'zz': Invalid character 'z' at position 0"), "{}", error);
+    }
+}
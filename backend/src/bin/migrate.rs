@@ -1,16 +1,32 @@
-use sqlx::PgPool;
-use std::env;
+//! Database migration tool
+//!
+//! Usage: `migrate [run | rollback [N | --steps N] | status | list | check] [--database-url URL]`
+//!
+//! See `shadow_drop_api::migrations` for what each command does.
+
+use std::{env, process::ExitCode};
+
+use shadow_drop_api::migrations::{USAGE, execute, parse_args};
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> ExitCode {
     dotenv::dotenv().ok();
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    let pool = PgPool::connect(&database_url).await?;
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let (command, database_url) = match parse_args(&args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Usage: migrate {}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
 
-    println!("Running migrations...");
-    sqlx::migrate!("./migrations")
-        .run(&pool)
-        .await?;
-    println!("Migrations complete!");
-    Ok(())
+    match execute(command, database_url).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,202 @@
+//! Seed the database with test data for local development
+//!
+//! Usage: `seed [--drop-existing]`
+//!
+//! Creates one active and one draft campaign with 10 recipients each, stores
+//! the Merkle root of each recipient tree and marks the first recipient of
+//! every campaign as claimed, registering its nullifier as spent. Re-running is a no-op; `--drop-existing` deletes
+//! the seeded campaigns (and only those) before inserting them again.
+//!
+//! Secrets are derived from the campaign and wallet, so the printed roots and
+//! proof stay valid across runs.
+
+use std::env;
+
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tracing::info;
+use tracing_subscriber::EnvFilter;
+
+use shadow_drop_api::{
+    common::merkle::{MerkleTree, compute_commitment, compute_nullifier, compute_nullifier_v2},
+    models::{CAMPAIGN_STATUS_ACTIVE, CAMPAIGN_STATUS_DRAFT},
+};
+
+const RECIPIENTS_PER_CAMPAIGN: usize = 10;
+const CREATOR_WALLET: &str = "SeedCreator11111111111111111111111111111111";
+
+struct SeedCampaign {
+    address: &'static str,
+    name: &'static str,
+    status: &'static str,
+}
+
+const CAMPAIGNS: [SeedCampaign; 2] = [
+    SeedCampaign {
+        address: "seed-campaign-active",
+        name: "Seed Campaign (active)",
+        status: CAMPAIGN_STATUS_ACTIVE,
+    },
+    SeedCampaign {
+        address: "seed-campaign-draft",
+        name: "Seed Campaign (draft)",
+        status: CAMPAIGN_STATUS_DRAFT,
+    },
+];
+
+/// Deterministic 32 bytes for a seed label
+fn seed_bytes(label: &str) -> [u8; 32] {
+    Sha256::digest(format!("shadow-drop-seed:{}", label)).into()
+}
+
+/// Recipients as `(wallet, amount, secret)`, the tree builder's input
+fn recipients_for(campaign: &SeedCampaign) -> Vec<(String, u64, [u8; 32])> {
+    (0..RECIPIENTS_PER_CAMPAIGN)
+        .map(|i| {
+            // Base58 of 32 bytes, like a real Solana pubkey
+            let wallet = bs58::encode(seed_bytes(&format!("{}/wallet/{}", campaign.address, i)))
+                .into_string();
+            let secret = seed_bytes(&format!("{}/secret/{}", campaign.address, wallet));
+            (wallet, 1_000_000 * (i as u64 + 1), secret)
+        })
+        .collect()
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .init();
+
+    let drop_existing = env::args().skip(1).any(|a| a == "--drop-existing");
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPool::connect(&database_url).await?;
+
+    if drop_existing {
+        let addresses: Vec<String> = CAMPAIGNS.iter().map(|c| c.address.to_string()).collect();
+        let deleted = sqlx::query!("DELETE FROM campaigns WHERE address = ANY($1)", &addresses)
+            .execute(&pool)
+            .await?
+            .rows_affected();
+        info!(deleted, "🧹 Dropped existing seed campaigns");
+    }
+
+    for campaign in &CAMPAIGNS {
+        seed_campaign(&pool, campaign).await?;
+    }
+
+    info!("✅ Seeding complete");
+    Ok(())
+}
+
+async fn seed_campaign(pool: &PgPool, campaign: &SeedCampaign) -> Result<(), Box<dyn std::error::Error>> {
+    let recipients = recipients_for(campaign);
+    let tree = MerkleTree::from_recipients(&recipients)
+        .map_err(|e| format!("Invalid seed recipients: {}", e))?;
+    let merkle_root = hex::encode(tree.root());
+    let total_amount: u64 = recipients.iter().map(|(_, amount, _)| amount).sum();
+    info!(campaign = campaign.address, root = %merkle_root, "🌳 Built merkle tree");
+
+    let mut tx = pool.begin().await?;
+
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO campaigns (
+            id, address, name, merkle_root, total_amount, creator_wallet,
+            created_at, airdrop_type, vesting_start, vesting_cliff_seconds,
+            vesting_duration_seconds, status
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, 'instant', $8, 0, 0, $9)
+        ON CONFLICT (address) DO NOTHING
+        "#,
+        format!("{}-id", campaign.address),
+        campaign.address,
+        campaign.name,
+        merkle_root,
+        total_amount.to_string(),
+        CREATOR_WALLET,
+        Utc::now(),
+        Utc::now().timestamp(),
+        campaign.status
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    info!(campaign = campaign.address, status = campaign.status, inserted, "📦 Campaign");
+
+    for (leaf_index, (wallet, amount, _)) in recipients.iter().enumerate() {
+        sqlx::query!(
+            r#"
+            INSERT INTO recipients (campaign_address, wallet, amount, claimed, leaf_index)
+            VALUES ($1, $2, $3, false, $4)
+            ON CONFLICT (campaign_address, wallet) DO NOTHING
+            "#,
+            campaign.address,
+            wallet,
+            amount.to_string(),
+            leaf_index as i32
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    info!(campaign = campaign.address, count = recipients.len(), "👥 Recipients");
+
+    // One claimed recipient per campaign, so clients can exercise the "already claimed" path
+    let (spent_wallet, _, spent_secret) = &recipients[0];
+    let spent_index = tree.get_leaf_index(spent_wallet).expect("seed wallet is in the tree");
+    sqlx::query!(
+        r#"
+        UPDATE recipients SET claimed = true, claimed_at = COALESCE(claimed_at, $3)
+        WHERE campaign_address = $1 AND wallet = $2
+        "#,
+        campaign.address,
+        spent_wallet,
+        Utc::now()
+    )
+    .execute(&mut *tx)
+    .await?;
+    let spent_nullifier = compute_nullifier_v2(spent_secret, spent_index, spent_wallet);
+    sqlx::query!(
+        r#"
+        INSERT INTO nullifiers (nullifier, campaign_address, spent_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (nullifier) DO NOTHING
+        "#,
+        &spent_nullifier[..],
+        campaign.address,
+        Utc::now()
+    )
+    .execute(&mut *tx)
+    .await?;
+    info!(
+        campaign = campaign.address,
+        wallet = %spent_wallet,
+        nullifier = %hex::encode(spent_nullifier),
+        "🔒 Marked claimed and nullifier spent"
+    );
+
+    tx.commit().await?;
+
+    // A proof for an unclaimed recipient, ready to paste into manual requests
+    let (wallet, amount, secret) = &recipients[1];
+    let proof = tree.get_proof(wallet).expect("seed wallet is in the tree");
+    let nullifier = compute_nullifier(secret, proof.leaf_index);
+    let sample = serde_json::json!({
+        "campaign": campaign.address,
+        "wallet": wallet,
+        "amount": amount.to_string(),
+        "merkle_root": merkle_root,
+        "leaf_index": proof.leaf_index,
+        "leaf": hex::encode(proof.leaf),
+        "merkle_path": proof.siblings.iter().map(hex::encode).collect::<Vec<_>>(),
+        "secret": hex::encode(secret),
+        "nullifier_hash": hex::encode(nullifier),
+        "commitment": hex::encode(compute_commitment(&tree.root(), &nullifier)),
+        "claim_nullifier": hex::encode(compute_nullifier_v2(secret, proof.leaf_index, wallet)),
+    });
+    println!("{}", serde_json::to_string_pretty(&sample)?);
+
+    Ok(())
+}
@@ -0,0 +1,181 @@
+//! Circuit breaker for calls to external services
+//!
+//! After `failure_threshold` consecutive failures the circuit opens and calls
+//! are refused without being attempted until `open_for` has passed. The next
+//! call is then let through as a probe (half-open): its success closes the
+//! circuit, its failure opens it again. Calls arriving while the probe is in
+//! flight are refused too, so a struggling endpoint sees one request at a time.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// State of a circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls go through
+    Closed,
+    /// Calls are refused until the instant
+    Open(Instant),
+    /// One probe call is in flight
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: CircuitState,
+    consecutive_failures: u32,
+    /// When the current probe was let through; a probe that never reports
+    /// back (e.g. its request was dropped) stops blocking after `open_for`
+    probe_started: Instant,
+}
+
+/// Breaker guarding one endpoint
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_for: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    /// Open after `failure_threshold` consecutive failures, for `open_for`
+    pub fn new(failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            open_for,
+            inner: Mutex::new(Inner {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                probe_started: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.inner.lock().unwrap().state
+    }
+
+    /// Ask to make a call. `Err` carries how long to wait before retrying.
+    pub fn acquire(&self) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        match inner.state {
+            CircuitState::Closed => Ok(()),
+            CircuitState::Open(until) if now < until => Err(until - now),
+            CircuitState::HalfOpen if now < inner.probe_started + self.open_for => {
+                Err(inner.probe_started + self.open_for - now)
+            }
+            CircuitState::Open(_) | CircuitState::HalfOpen => {
+                inner.state = CircuitState::HalfOpen;
+                inner.probe_started = now;
+                Ok(())
+            }
+        }
+    }
+
+    /// Record the outcome of a call made after `acquire`, passing it through
+    pub fn record<T, E>(&self, result: Result<T, E>) -> Result<T, E> {
+        let mut inner = self.inner.lock().unwrap();
+        if result.is_ok() {
+            inner.state = CircuitState::Closed;
+            inner.consecutive_failures = 0;
+        } else {
+            inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+            if inner.state == CircuitState::HalfOpen
+                || inner.consecutive_failures >= self.failure_threshold
+            {
+                inner.state = CircuitState::Open(Instant::now() + self.open_for);
+            }
+        }
+        result
+    }
+}
+
+/// One `CircuitBreaker` per endpoint, created on first use with shared settings
+#[derive(Debug)]
+pub struct CircuitBreakers {
+    failure_threshold: u32,
+    open_for: Duration,
+    breakers: Mutex<HashMap<String, Arc<CircuitBreaker>>>,
+}
+
+impl CircuitBreakers {
+    pub fn new(failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            failure_threshold,
+            open_for,
+            breakers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Breaker of `endpoint`
+    pub fn get(&self, endpoint: &str) -> Arc<CircuitBreaker> {
+        self.breakers
+            .lock()
+            .unwrap()
+            .entry(endpoint.to_string())
+            .or_insert_with(|| Arc::new(CircuitBreaker::new(self.failure_threshold, self.open_for)))
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fail(breaker: &CircuitBreaker) {
+        breaker.acquire().unwrap();
+        let _ = breaker.record::<(), _>(Err("rpc down"));
+    }
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let breaker = CircuitBreaker::new(3, Duration::from_secs(30));
+        fail(&breaker);
+        fail(&breaker);
+        // A success resets the count
+        breaker.acquire().unwrap();
+        breaker.record::<_, ()>(Ok(())).unwrap();
+        fail(&breaker);
+        fail(&breaker);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        fail(&breaker);
+        assert!(matches!(breaker.state(), CircuitState::Open(_)));
+        let retry_after = breaker.acquire().unwrap_err();
+        assert!(retry_after > Duration::from_secs(29));
+    }
+
+    #[test]
+    fn test_half_open_lets_one_probe_through() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(50));
+        fail(&breaker);
+        assert!(breaker.acquire().is_err());
+
+        std::thread::sleep(Duration::from_millis(60));
+        breaker.acquire().unwrap();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.acquire().is_err(), "only one probe at a time");
+
+        // A failed probe re-opens the circuit
+        let _ = breaker.record::<(), _>(Err("still down"));
+        assert!(matches!(breaker.state(), CircuitState::Open(_)));
+
+        std::thread::sleep(Duration::from_millis(60));
+        breaker.acquire().unwrap();
+        breaker.record::<_, ()>(Ok(())).unwrap();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.acquire().is_ok());
+    }
+
+    #[test]
+    fn test_breakers_are_kept_per_endpoint() {
+        let breakers = CircuitBreakers::new(1, Duration::from_secs(30));
+        fail(&breakers.get("https://a"));
+        assert!(breakers.get("https://a").acquire().is_err());
+        assert!(breakers.get("https://b").acquire().is_ok());
+    }
+}
@@ -0,0 +1,130 @@
+//! BN254 curve utilities
+//!
+//! `hash_to_g1` maps a 32-byte hash to a G1 point with the Shallue–van de
+//! Woestijne map of RFC 9380 §6.6.1. The simplified SWU map (and ark-ec's
+//! `SWUMap`) requires a curve with `a != 0`; BN254 G1 is `y^2 = x^3 + 3`, so
+//! SvdW with `Z = 1` is the map RFC 9380 specifies for it. G1 has cofactor 1,
+//! so every mapped point is already in the prime-order subgroup.
+
+use std::sync::LazyLock;
+
+use ark_bn254::{Fq, G1Affine};
+use ark_ff::{AdditiveGroup, BigInteger, Field, PrimeField};
+
+use crate::common::merkle::Hash;
+
+/// Curve coefficient `b` of `y^2 = x^3 + b` (`a = 0`)
+const B: u64 = 3;
+
+/// SvdW constants for `Z = 1`, see RFC 9380 §6.6.1
+struct SvdwConstants {
+    /// `g(Z)`
+    c1: Fq,
+    /// `-Z / 2`
+    c2: Fq,
+    /// `sqrt(-g(Z) * 3Z^2)` with `sgn0(c3) == 0`
+    c3: Fq,
+    /// `-4 g(Z) / 3Z^2`
+    c4: Fq,
+}
+
+static SVDW: LazyLock<SvdwConstants> = LazyLock::new(|| {
+    let z = Fq::ONE;
+    let g_z = curve_rhs(z);
+    let three_z2 = Fq::from(3u64) * z.square();
+
+    let mut c3 = (-g_z * three_z2).sqrt().expect("-g(Z) * 3Z^2 must be square");
+    if sgn0(c3) {
+        c3 = -c3;
+    }
+
+    SvdwConstants {
+        c1: g_z,
+        c2: -z / Fq::from(2u64),
+        c3,
+        c4: -Fq::from(4u64) * g_z / three_z2,
+    }
+});
+
+/// `g(x) = x^3 + b`
+fn curve_rhs(x: Fq) -> Fq {
+    x.square() * x + Fq::from(B)
+}
+
+/// RFC 9380 `sgn0` for a prime field: the parity of the canonical integer
+fn sgn0(x: Fq) -> bool {
+    x.into_bigint().is_odd()
+}
+
+/// Map a hash to a BN254 G1 point.
+///
+/// The hash is read as a big-endian integer reduced modulo the base field
+/// and mapped with SvdW. This is the `map_to_curve` step only: suitable for
+/// deriving independent generators, but not a uniform random-oracle hash
+/// (that would need `hash_to_field` with two elements and a point addition).
+pub fn hash_to_g1(hash: &Hash) -> G1Affine {
+    let u = Fq::from_be_bytes_mod_order(hash);
+    let SvdwConstants { c1, c2, c3, c4 } = *SVDW;
+
+    let tv1 = u.square() * c1;
+    let tv2 = Fq::ONE + tv1;
+    let tv1 = Fq::ONE - tv1;
+    // inv0: zero maps to zero
+    let tv3 = (tv1 * tv2).inverse().unwrap_or(Fq::ZERO);
+    let tv4 = u * tv1 * tv3 * c3;
+
+    let x1 = c2 - tv4;
+    let x2 = c2 + tv4;
+    let x3 = (tv2.square() * tv3).square() * c4 + Fq::ONE;
+
+    // The first candidate with a square g(x) wins; one of the three always is
+    let x = [x1, x2]
+        .into_iter()
+        .find(|x| curve_rhs(*x).legendre().is_qr())
+        .unwrap_or(x3);
+    let mut y = curve_rhs(x).sqrt().expect("SvdW candidate must be on the curve");
+    if sgn0(u) != sgn0(y) {
+        y = -y;
+    }
+
+    G1Affine::new_unchecked(x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::AffineRepr;
+
+    fn hash_of(i: u8) -> Hash {
+        let mut hash = [0u8; 32];
+        hash[31] = i;
+        hash[0] = i.wrapping_mul(37);
+        hash
+    }
+
+    #[test]
+    fn test_hash_to_g1_is_on_curve() {
+        for i in 0..=255u8 {
+            let point = hash_to_g1(&hash_of(i));
+            assert!(point.is_on_curve(), "input {} maps off the curve", i);
+            assert!(point.is_in_correct_subgroup_assuming_on_curve());
+            assert!(!point.is_zero());
+        }
+    }
+
+    #[test]
+    fn test_hash_to_g1_distinct_inputs_give_distinct_points() {
+        let points: std::collections::HashSet<_> =
+            (0..=255u8).map(|i| hash_to_g1(&hash_of(i))).collect();
+        assert_eq!(points.len(), 256);
+    }
+
+    #[test]
+    fn test_hash_to_g1_sign_follows_input() {
+        for i in 1..=64u8 {
+            let hash = hash_of(i);
+            let u = Fq::from_be_bytes_mod_order(&hash);
+            assert_eq!(sgn0(hash_to_g1(&hash).y), sgn0(u));
+        }
+    }
+}
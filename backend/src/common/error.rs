@@ -0,0 +1,274 @@
+//! Top-level application error
+//!
+//! Handlers return `Result<_, ShadowDropError>` so that `?` converts domain
+//! errors automatically. The `IntoResponse` impl renders every variant through
+//! `ApiErrorResponse`, keeping the JSON error envelope unchanged.
+
+use axum::{
+    http::{HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    common::{
+        field::FieldError, merkle::MerkleError, poseidon::PoseidonError, response::ApiErrorResponse,
+        signature::SignatureError, siws::SiwsError, solana_tx::JitoError,
+    },
+    funding::{FundingError, retry_after_secs},
+    nullifiers::DBError,
+};
+
+/// Errors from field arithmetic and hashing
+#[derive(Debug, thiserror::Error)]
+pub enum CryptoError {
+    #[error(transparent)]
+    Poseidon(#[from] PoseidonError),
+
+    #[error("Invalid hex: {0}")]
+    InvalidHex(String),
+
+    #[error(transparent)]
+    Field(#[from] FieldError),
+}
+
+/// Every error a handler can return
+#[derive(Debug, thiserror::Error)]
+pub enum ShadowDropError {
+    #[error(transparent)]
+    Merkle(#[from] MerkleError),
+
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error(transparent)]
+    Crypto(#[from] CryptoError),
+
+    #[error(transparent)]
+    Funding(#[from] FundingError),
+
+    #[error(transparent)]
+    Jito(#[from] JitoError),
+
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error("Authentication required")]
+    Unauthorized,
+
+    #[error("{0}")]
+    Forbidden(String),
+
+    #[error("Rate limit exceeded")]
+    RateLimit,
+
+    #[error("{0}")]
+    BadRequest(String),
+
+    /// A malformed request with details such as the accepted limits
+    #[error("{message}")]
+    InvalidRequest {
+        message: String,
+        details: Option<String>,
+    },
+
+    /// The request conflicts with the current state of a resource
+    #[error("{message}")]
+    Conflict {
+        message: String,
+        details: Option<String>,
+    },
+
+    /// Claims are halted by an admin; the reason is returned as details
+    #[error("campaign_paused")]
+    CampaignPaused { reason: Option<String> },
+
+    /// Every claim slot of a capped campaign is taken
+    #[error("campaign_full")]
+    CampaignFull { claims_accepted: i64 },
+
+    /// An unexpected failure whose message is safe to return to the client
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl From<DBError> for ShadowDropError {
+    fn from(error: DBError) -> Self {
+        match error {
+            DBError::AlreadySpent(_) => Self::Conflict {
+                message: "Nullifier already spent".to_string(),
+                details: None,
+            },
+            DBError::Database(e) => Self::Database(e),
+        }
+    }
+}
+
+impl From<SignatureError> for ShadowDropError {
+    fn from(error: SignatureError) -> Self {
+        match error {
+            SignatureError::Mismatch => Self::Forbidden(error.to_string()),
+            SignatureError::InvalidWallet(_) | SignatureError::Malformed => {
+                Self::BadRequest(error.to_string())
+            }
+        }
+    }
+}
+
+impl From<SiwsError> for ShadowDropError {
+    fn from(error: SiwsError) -> Self {
+        Self::BadRequest(error.to_string())
+    }
+}
+
+impl ShadowDropError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Merkle(MerkleError::InvalidInstructionData(_)) => StatusCode::BAD_REQUEST,
+            Self::Merkle(MerkleError::WalletNotFound(_)) => StatusCode::NOT_FOUND,
+            Self::Merkle(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::Database(_) | Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Crypto(_) | Self::BadRequest(_) | Self::InvalidRequest { .. } => StatusCode::BAD_REQUEST,
+            Self::Funding(FundingError::InsufficientFunds { .. }) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::Funding(FundingError::Rpc(_)) => StatusCode::BAD_GATEWAY,
+            Self::Funding(FundingError::CircuitOpen { .. }) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Funding(FundingError::Timeout(_)) => StatusCode::GATEWAY_TIMEOUT,
+            Self::Jito(JitoError::Rpc(_)) => StatusCode::BAD_GATEWAY,
+            Self::Jito(JitoError::Rejected(_)) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::RateLimit => StatusCode::TOO_MANY_REQUESTS,
+            Self::Conflict { .. } => StatusCode::CONFLICT,
+            Self::CampaignPaused { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            Self::CampaignFull { .. } => StatusCode::GONE,
+        }
+    }
+}
+
+impl From<ShadowDropError> for ApiErrorResponse {
+    fn from(error: ShadowDropError) -> Self {
+        let response = ApiErrorResponse::default().with_code(error.status_code());
+
+        match error {
+            ShadowDropError::Merkle(MerkleError::InvalidRecipients(errors)) => {
+                let details = errors.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ");
+                response
+                    .with_message("Campaign recipient list is invalid")
+                    .with_details(details)
+            }
+            ShadowDropError::Database(e) => {
+                // Never leak query or connection details to clients
+                tracing::error!("Database error: {}", e);
+                response
+            }
+            ShadowDropError::Funding(error @ FundingError::InsufficientFunds { .. }) => response
+                .with_message("insufficient_funds")
+                .with_details(error.to_string()),
+            ShadowDropError::Funding(FundingError::Rpc(e)) => {
+                tracing::error!("Solana RPC error: {}", e);
+                response.with_message("Could not verify campaign funding")
+            }
+            ShadowDropError::Jito(JitoError::Rpc(e)) => {
+                tracing::error!("Jito block engine error: {}", e);
+                response.with_message("Could not submit the Jito bundle")
+            }
+            ShadowDropError::Conflict {
+                message,
+                details: Some(details),
+            }
+            | ShadowDropError::InvalidRequest {
+                message,
+                details: Some(details),
+            } => response.with_message(&message).with_details(details),
+            ShadowDropError::CampaignPaused {
+                reason: Some(reason),
+            } => response.with_message("campaign_paused").with_details(reason),
+            ShadowDropError::CampaignFull { claims_accepted } => response
+                .with_message("campaign_full")
+                .with_details(format!("claims_accepted: {}", claims_accepted)),
+            other => response.with_message(&other.to_string()),
+        }
+    }
+}
+
+impl IntoResponse for ShadowDropError {
+    fn into_response(self) -> Response {
+        let retry_after = match &self {
+            Self::Funding(FundingError::CircuitOpen { retry_after }) => Some(retry_after_secs(*retry_after)),
+            _ => None,
+        };
+        let mut response = ApiErrorResponse::from(self).into_response();
+        if let Some(secs) = retry_after {
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::merkle::RecipientError;
+
+    #[test]
+    fn test_variants_map_to_status_codes() {
+        let cases = [
+            (ShadowDropError::NotFound("Campaign not found".into()), StatusCode::NOT_FOUND),
+            (ShadowDropError::Unauthorized, StatusCode::UNAUTHORIZED),
+            (ShadowDropError::RateLimit, StatusCode::TOO_MANY_REQUESTS),
+            (ShadowDropError::BadRequest("bad".into()), StatusCode::BAD_REQUEST),
+            (
+                ShadowDropError::from(sqlx::Error::RowNotFound),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                CryptoError::from(PoseidonError::EmptyInput).into(),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                MerkleError::TooManyLeaves { count: 9, max: 8 }.into(),
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                FundingError::InsufficientFunds { available: 1, required: 2 }.into(),
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (FundingError::Rpc("timeout".into()).into(), StatusCode::BAD_GATEWAY),
+            (
+                FundingError::CircuitOpen { retry_after: std::time::Duration::from_secs(30) }.into(),
+                StatusCode::SERVICE_UNAVAILABLE,
+            ),
+            (JitoError::Rpc("HTTP 503".into()).into(), StatusCode::BAD_GATEWAY),
+            (JitoError::Rejected("bundle has no tip".into()).into(), StatusCode::UNPROCESSABLE_ENTITY),
+        ];
+
+        for (error, status) in cases {
+            assert_eq!(error.into_response().status(), status);
+        }
+    }
+
+    #[test]
+    fn test_error_bodies_keep_the_api_envelope() {
+        let invalid = ApiErrorResponse::from(ShadowDropError::from(
+            MerkleError::InvalidRecipients(vec![RecipientError::DuplicateWallet {
+                wallet: "w".into(),
+                first_at: 0,
+                duplicate_at: 2,
+            }]),
+        ));
+        assert_eq!(invalid.errors.message, "Campaign recipient list is invalid");
+        assert_eq!(
+            invalid.errors.details.as_deref(),
+            Some("wallet w appears at index 0 and again at index 2")
+        );
+
+        let open = ShadowDropError::from(FundingError::CircuitOpen {
+            retry_after: std::time::Duration::from_millis(12_500),
+        })
+        .into_response();
+        assert_eq!(open.headers()[header::RETRY_AFTER], "13");
+
+        let database = ApiErrorResponse::from(ShadowDropError::from(sqlx::Error::PoolTimedOut));
+        assert_eq!(database.errors.message, ApiErrorResponse::default().errors.message);
+    }
+}
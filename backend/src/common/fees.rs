@@ -0,0 +1,81 @@
+//! Protocol fee on claimed amounts
+//!
+//! Fees are taken from each claim: a recipient entitled to `amount` receives
+//! `net_amount(amount)`. Recipient leaves commit to the net amount, so the
+//! amount proven in the claim circuit is the one actually paid out.
+
+pub use shadow_drop_core::amounts::{LAMPORTS_PER_SOL, Lamports, sol_to_lamports};
+
+/// One basis point is 1/10000
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeeConfig {
+    /// Fee as a share of the claimed amount, in basis points (50 = 0.5%)
+    pub protocol_fee_bps: u16,
+    /// Lower bound on the fee of a claim
+    pub min_fee_lamports: Lamports,
+    /// Upper bound on the fee of a claim, if any
+    pub max_fee_lamports: Option<Lamports>,
+}
+
+/// Fee, net amount and the amount they were computed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeBreakdown {
+    pub amount: Lamports,
+    pub fee: Lamports,
+    pub net_amount: Lamports,
+}
+
+/// `amount * protocol_fee_bps / 10000` (rounded down), clamped to the
+/// configured bounds and never more than `amount` itself
+pub fn calculate_fee(amount: Lamports, config: &FeeConfig) -> Lamports {
+    let proportional =
+        (u128::from(amount) * u128::from(config.protocol_fee_bps) / u128::from(BPS_DENOMINATOR))
+            as Lamports;
+    let fee = proportional.max(config.min_fee_lamports);
+    let fee = config.max_fee_lamports.map_or(fee, |max| fee.min(max));
+    fee.min(amount)
+}
+
+/// What the recipient receives after the protocol fee
+pub fn net_amount(amount: Lamports, config: &FeeConfig) -> Lamports {
+    amount - calculate_fee(amount, config)
+}
+
+pub fn fee_breakdown(amount: Lamports, config: &FeeConfig) -> FeeBreakdown {
+    let fee = calculate_fee(amount, config);
+    FeeBreakdown {
+        amount,
+        fee,
+        net_amount: amount - fee,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(bps: u16, min: Lamports, max: Option<Lamports>) -> FeeConfig {
+        FeeConfig {
+            protocol_fee_bps: bps,
+            min_fee_lamports: min,
+            max_fee_lamports: max,
+        }
+    }
+
+    #[test]
+    fn test_fee_is_proportional_then_clamped() {
+        let half_percent = config(50, 0, None);
+        assert_eq!(calculate_fee(1_000_000, &half_percent), 5_000);
+        assert_eq!(net_amount(1_000_000, &half_percent), 995_000);
+
+        assert_eq!(calculate_fee(1_000, &config(50, 100, None)), 100);
+        assert_eq!(calculate_fee(1_000_000, &config(50, 0, Some(1_000))), 1_000);
+        // The minimum never takes more than the claim itself
+        assert_eq!(net_amount(10, &config(50, 100, None)), 0);
+
+        assert_eq!(calculate_fee(Lamports::MAX, &config(10_000, 0, None)), Lamports::MAX);
+        assert_eq!(fee_breakdown(7, &FeeConfig::default()).net_amount, 7);
+    }
+}
@@ -0,0 +1,132 @@
+//! Minimal blocking HTTP/1.1 client
+//!
+//! Each request is sent on its own connection with `Connection: close`, over
+//! `native-tls` for `https`, so no HTTP client crate is needed for the few
+//! calls the backend and CLI make. Responses are read to the end and may use
+//! chunked transfer encoding.
+
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+use url::Url;
+
+/// Status and decoded body of a response
+#[derive(Debug)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+/// Send `method` to `url` with extra `headers` and an optional `body`,
+/// applying `timeout` to connecting, reading and writing
+pub fn send(
+    method: &str,
+    url: &Url,
+    headers: &[(&str, &str)],
+    body: Option<&str>,
+    timeout: Duration,
+) -> Result<HttpResponse, String> {
+    let host = url.host_str().ok_or("URL has no host")?;
+    let port = url.port_or_known_default().ok_or("URL has no port")?;
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+
+    let address = std::net::ToSocketAddrs::to_socket_addrs(&(host, port))
+        .map_err(|e| e.to_string())?
+        .next()
+        .ok_or_else(|| format!("{} did not resolve", host))?;
+    let tcp = TcpStream::connect_timeout(&address, timeout).map_err(|e| e.to_string())?;
+    tcp.set_read_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+    tcp.set_write_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+
+    let body = body.unwrap_or_default();
+    let mut request = format!("{method} {path} HTTP/1.1\r\nHost: {host}\r\n");
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str(&format!("Content-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len()));
+
+    let mut raw = Vec::new();
+    match url.scheme() {
+        "https" => {
+            let connector = native_tls::TlsConnector::new().map_err(|e| e.to_string())?;
+            let mut tls = connector.connect(host, tcp).map_err(|e| e.to_string())?;
+            tls.write_all(request.as_bytes()).map_err(|e| e.to_string())?;
+            tls.read_to_end(&mut raw).map_err(|e| e.to_string())?;
+        }
+        "http" => {
+            let mut tcp = tcp;
+            tcp.write_all(request.as_bytes()).map_err(|e| e.to_string())?;
+            tcp.read_to_end(&mut raw).map_err(|e| e.to_string())?;
+        }
+        other => return Err(format!("unsupported URL scheme '{}'", other)),
+    }
+
+    parse_response(&raw)
+}
+
+/// Status and body of a complete HTTP/1.1 response
+fn parse_response(raw: &[u8]) -> Result<HttpResponse, String> {
+    let mut headers = [httparse::EMPTY_HEADER; 32];
+    let mut response = httparse::Response::new(&mut headers);
+    let header_len = match response.parse(raw).map_err(|e| e.to_string())? {
+        httparse::Status::Complete(len) => len,
+        httparse::Status::Partial => return Err("truncated HTTP response".to_string()),
+    };
+
+    let chunked = response.headers.iter().any(|h| {
+        h.name.eq_ignore_ascii_case("transfer-encoding")
+            && String::from_utf8_lossy(h.value).to_ascii_lowercase().contains("chunked")
+    });
+    let body = &raw[header_len..];
+    Ok(HttpResponse {
+        status: response.code.unwrap_or_default(),
+        body: if chunked { decode_chunked(body)? } else { body.to_vec() },
+    })
+}
+
+fn decode_chunked(mut body: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decoded = Vec::new();
+    loop {
+        let line_end = body
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or("malformed chunked body")?;
+        let size_field = std::str::from_utf8(&body[..line_end]).map_err(|e| e.to_string())?;
+        let size_hex = size_field.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size_hex, 16).map_err(|e| e.to_string())?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Ok(decoded);
+        }
+        if body.len() < size + 2 {
+            return Err("truncated chunked body".to_string());
+        }
+        decoded.extend_from_slice(&body[..size]);
+        body = &body[size + 2..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response_decodes_chunked_bodies() {
+        let response = parse_response(
+            b"HTTP/1.1 404 Not Found\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2;ext\r\nde\r\n0\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(response.status, 404);
+        assert_eq!(response.body, b"abcde");
+
+        let response = parse_response(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok").unwrap();
+        assert_eq!((response.status, response.body.as_slice()), (200, &b"ok"[..]));
+        assert!(parse_response(b"HTTP/1.1 200 OK\r\nContent-").is_err());
+    }
+}
@@ -0,0 +1,107 @@
+//! HS256 JSON Web Tokens for signed-in wallets
+//!
+//! Only what sessions need: a fixed `{"alg":"HS256","typ":"JWT"}` header and
+//! the `sub`, `iat` and `exp` claims. The HMAC comes from OpenSSL.
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use openssl::{hash::MessageDigest, memcmp, pkey::PKey, sign::Signer};
+use serde::{Deserialize, Serialize};
+
+/// `{"alg":"HS256","typ":"JWT"}`, the only header issued or accepted
+const HEADER: &str = r#"{"alg":"HS256","typ":"JWT"}"#;
+
+/// Claims of a session token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claims {
+    /// Signed-in wallet address
+    pub sub: String,
+    /// Issued at, unix seconds
+    pub iat: i64,
+    /// Expires at, unix seconds
+    pub exp: i64,
+}
+
+/// Why a token was rejected
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum TokenError {
+    #[error("malformed token")]
+    Malformed,
+
+    #[error("token signature is invalid")]
+    BadSignature,
+
+    #[error("token has expired")]
+    Expired,
+}
+
+/// Sign `claims` with `secret`
+pub fn issue(secret: &[u8], claims: &Claims) -> String {
+    let claims = serde_json::to_vec(claims).expect("claims serialize");
+    let signing_input = format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(HEADER),
+        URL_SAFE_NO_PAD.encode(claims)
+    );
+    let signature = URL_SAFE_NO_PAD.encode(hmac_sha256(secret, signing_input.as_bytes()));
+    format!("{}.{}", signing_input, signature)
+}
+
+/// The claims of `token` if `secret` signed it and it has not expired at `now`
+pub fn verify(secret: &[u8], token: &str, now: i64) -> Result<Claims, TokenError> {
+    let (signing_input, signature) = token.rsplit_once('.').ok_or(TokenError::Malformed)?;
+    let (header, claims) = signing_input.split_once('.').ok_or(TokenError::Malformed)?;
+    let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| TokenError::Malformed)?;
+
+    let expected = hmac_sha256(secret, signing_input.as_bytes());
+    if signature.len() != expected.len() || !memcmp::eq(&signature, &expected) {
+        return Err(TokenError::BadSignature);
+    }
+    if URL_SAFE_NO_PAD.decode(header).ok().as_deref() != Some(HEADER.as_bytes()) {
+        return Err(TokenError::Malformed);
+    }
+    let claims: Claims = URL_SAFE_NO_PAD
+        .decode(claims)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .ok_or(TokenError::Malformed)?;
+    if claims.exp <= now {
+        return Err(TokenError::Expired);
+    }
+    Ok(claims)
+}
+
+fn hmac_sha256(secret: &[u8], data: &[u8]) -> Vec<u8> {
+    PKey::hmac(secret)
+        .and_then(|key| Signer::new(MessageDigest::sha256(), &key)?.sign_oneshot_to_vec(data))
+        .expect("HMAC-SHA256 is available")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_and_verify() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            hex::encode(hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        let claims = Claims {
+            sub: "wallet".to_string(),
+            iat: 100,
+            exp: 200,
+        };
+        let token = issue(b"secret", &claims);
+        assert!(token.starts_with("eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9."));
+        assert_eq!(verify(b"secret", &token, 150), Ok(claims));
+        assert_eq!(verify(b"secret", &token, 200), Err(TokenError::Expired));
+        assert_eq!(verify(b"other", &token, 150), Err(TokenError::BadSignature));
+        assert_eq!(verify(b"secret", "not-a-token", 150), Err(TokenError::Malformed));
+
+        let (rest, _) = token.rsplit_once('.').unwrap();
+        let forged = format!("{}.{}", rest, URL_SAFE_NO_PAD.encode([0u8; 32]));
+        assert_eq!(verify(b"secret", &forged, 150), Err(TokenError::BadSignature));
+    }
+}
@@ -1,3 +1,18 @@
-pub mod merkle;
+pub mod circuit_breaker;
+pub mod crypto;
+pub mod error;
+pub mod fees;
+pub mod gzip;
+pub mod http;
+pub mod jwt;
 pub mod response;
+pub mod schema;
 pub mod server;
+pub mod signature;
+pub mod siws;
+pub mod solana_tx;
+pub mod stale_cache;
+pub mod subscribers;
+pub mod zip;
+
+pub use shadow_drop_core::{field, field_arith, merkle, poseidon, poseidon_trace};
@@ -6,12 +6,17 @@ use axum::{
 use chrono::Utc;
 use serde::Serialize;
 
+use crate::common::schema::ValidationError;
+
 /// Contains the specific details of an error.
 #[derive(Debug, Serialize)]
 pub struct Errors {
     pub code: u16,
     pub message: String,
     pub details: Option<String>,
+    /// Per-field violations when a request body fails schema validation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation: Option<Vec<ValidationError>>,
 }
 
 /// The standardized application error response structure.
@@ -32,6 +37,7 @@ impl Default for ApiErrorResponse {
                 code: 500,
                 message: "An internal server error occurred.".to_owned(),
                 details: None,
+                validation: None,
             },
             timestamp: Utc::now().timestamp(),
         }
@@ -56,6 +62,11 @@ impl ApiErrorResponse {
         self.errors.details = Some(details);
         self
     }
+    /// Attaches the schema violations that caused a request to be rejected.
+    pub fn with_validation_errors(mut self, errors: Vec<ValidationError>) -> Self {
+        self.errors.validation = Some(errors);
+        self
+    }
 }
 
 /// --- IntoResponse Implementation ---
@@ -1,7 +1,9 @@
 mod error;
 mod success;
 
+use crate::common::error::ShadowDropError;
+
 pub use error::ApiErrorResponse;
 pub use success::ApiSuccessResponse;
 
-pub type ApiResponse<T> = Result<ApiSuccessResponse<T>, ApiErrorResponse>;
+pub type ApiResponse<T> = Result<ApiSuccessResponse<T>, ShadowDropError>;
@@ -0,0 +1,234 @@
+//! Minimal JSON Schema validation for request bodies
+//!
+//! Supports the subset of JSON Schema used by the files in `schemas/`:
+//! `type`, `required`, `properties`, `items`, `minItems`, `maxItems`,
+//! `minLength`, `minimum` and the custom `format: "hex"` (optionally
+//! `0x`-prefixed hex digits).
+
+use std::sync::LazyLock;
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// A single schema violation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationError {
+    /// JSON pointer to the offending value, e.g. `/inputs/0`
+    pub path: String,
+    pub message: String,
+}
+
+/// A parsed JSON schema
+#[derive(Debug)]
+pub struct JsonSchema {
+    root: Value,
+}
+
+/// Schema for `HashRequest` bodies
+pub static HASH_REQUEST_SCHEMA: LazyLock<JsonSchema> =
+    LazyLock::new(|| JsonSchema::parse(include_str!("../../schemas/hash_request.json")));
+
+/// Schema for claim and wallet-only request bodies
+pub static CLAIM_REQUEST_SCHEMA: LazyLock<JsonSchema> =
+    LazyLock::new(|| JsonSchema::parse(include_str!("../../schemas/claim_request.json")));
+
+/// Schema for `MerkleProof` bodies
+pub static MERKLE_PROOF_SCHEMA: LazyLock<JsonSchema> =
+    LazyLock::new(|| JsonSchema::parse(include_str!("../../schemas/merkle_proof.json")));
+
+/// Schema for `ProofJobRequest` bodies
+pub static PROOF_JOB_REQUEST_SCHEMA: LazyLock<JsonSchema> =
+    LazyLock::new(|| JsonSchema::parse(include_str!("../../schemas/proof_job_request.json")));
+
+/// Parse every bundled schema so a broken file fails at startup, not on first request
+pub fn load_schemas() {
+    LazyLock::force(&HASH_REQUEST_SCHEMA);
+    LazyLock::force(&CLAIM_REQUEST_SCHEMA);
+    LazyLock::force(&MERKLE_PROOF_SCHEMA);
+    LazyLock::force(&PROOF_JOB_REQUEST_SCHEMA);
+}
+
+impl JsonSchema {
+    /// Parse a bundled schema. Panics on invalid JSON since schemas ship with the binary.
+    pub fn parse(source: &str) -> Self {
+        Self {
+            root: serde_json::from_str(source).expect("Invalid bundled JSON schema"),
+        }
+    }
+
+    /// Validate a value, collecting every violation
+    pub fn validate(&self, value: &Value) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        validate_node(&self.root, value, String::new(), &mut errors);
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+fn validate_node(schema: &Value, value: &Value, path: String, errors: &mut Vec<ValidationError>) {
+    let mut fail = |message: String| {
+        errors.push(ValidationError {
+            path: if path.is_empty() { "/".to_string() } else { path.clone() },
+            message,
+        })
+    };
+
+    // `type` is one name or a list of alternatives
+    let expected: Vec<&str> = match schema.get("type") {
+        Some(Value::String(name)) => vec![name.as_str()],
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !expected.is_empty() && !expected.iter().any(|name| matches_type(name, value)) {
+        fail(format!("expected {}, found {}", expected.join(" or "), type_name(value)));
+        return;
+    }
+
+    // Emptiness is left to `minLength`, so callers can report it their own way
+    if let Some(format) = schema.get("format").and_then(Value::as_str)
+        && format == "hex"
+        && let Some(s) = value.as_str()
+        && !s.is_empty()
+        && !is_hex(s)
+    {
+        fail("not a hex string".to_string());
+    }
+
+    if let (Some(min), Some(s)) = (schema.get("minLength").and_then(Value::as_u64), value.as_str())
+        && (s.chars().count() as u64) < min
+    {
+        fail(format!("must be at least {} characters", min));
+    }
+
+    if let (Some(min), Some(n)) = (schema.get("minimum").and_then(Value::as_f64), value.as_f64())
+        && n < min
+    {
+        fail(format!("must be at least {}", min));
+    }
+
+    if let Some(items) = value.as_array() {
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+            && (items.len() as u64) < min
+        {
+            fail(format!("must contain at least {} items", min));
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64)
+            && (items.len() as u64) > max
+        {
+            fail(format!("must contain at most {} items", max));
+        }
+        if let Some(item_schema) = schema.get("items") {
+            for (i, item) in items.iter().enumerate() {
+                validate_node(item_schema, item, format!("{}/{}", path, i), errors);
+            }
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for field in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(field) {
+                    errors.push(ValidationError {
+                        path: format!("{}/{}", path, field),
+                        message: "is required".to_string(),
+                    });
+                }
+            }
+        }
+        if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+            for (field, field_schema) in properties {
+                if let Some(field_value) = object.get(field) {
+                    validate_node(field_schema, field_value, format!("{}/{}", path, field), errors);
+                }
+            }
+        }
+    }
+}
+
+fn matches_type(expected: &str, value: &Value) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn is_hex(s: &str) -> bool {
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_bundled_schemas_parse() {
+        load_schemas();
+    }
+
+    #[test]
+    fn test_hash_request_violations_are_reported_with_paths() {
+        let body = json!({ "inputs": ["0x01", "zz", true, 3, -1] });
+
+        let errors = HASH_REQUEST_SCHEMA.validate(&body).unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![
+                ValidationError {
+                    path: "/inputs/1".to_string(),
+                    message: "not a hex string".to_string()
+                },
+                ValidationError {
+                    path: "/inputs/2".to_string(),
+                    message: "expected string or integer, found boolean".to_string()
+                },
+                ValidationError {
+                    path: "/inputs/4".to_string(),
+                    message: "must be at least 0".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_and_mistyped_fields() {
+        assert_eq!(
+            HASH_REQUEST_SCHEMA.validate(&json!({})).unwrap_err()[0].path,
+            "/inputs"
+        );
+        assert_eq!(
+            CLAIM_REQUEST_SCHEMA.validate(&json!([])).unwrap_err()[0].message,
+            "expected object, found array"
+        );
+        assert!(
+            MERKLE_PROOF_SCHEMA
+                .validate(&json!({ "leaf_index": 3, "siblings": ["ab"], "leaf": "0xcd" }))
+                .is_ok()
+        );
+        assert_eq!(
+            MERKLE_PROOF_SCHEMA
+                .validate(&json!({ "leaf_index": 3, "siblings": [""], "leaf": "0xcd" }))
+                .unwrap_err()[0]
+                .message,
+            "must be at least 1 characters"
+        );
+    }
+}
@@ -1,6 +1,25 @@
-use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+use std::{
+    future::IntoFuture,
+    net::{IpAddr, Ipv6Addr, SocketAddr},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
 
-use tokio::net::TcpListener;
+use axum::Router;
+use tokio::{
+    net::TcpListener,
+    signal::unix::{SignalKind, signal},
+    sync::Notify,
+};
+
+/// How long in-flight requests get to finish after a shutdown signal
+const SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often the remaining request count is logged while draining
+const DRAIN_LOG_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Creates a TCP listener that attempts to bind to both IPv6 and IPv4 (dual-stack).
 ///
@@ -72,3 +91,76 @@ pub async fn create_dual_stack_listener(port: u16) -> std::io::Result<TcpListene
         }
     }
 }
+
+/// Resolves on SIGTERM or Ctrl-C, after setting `shutting_down`
+async fn shutdown_signal(shutting_down: Arc<AtomicBool>) {
+    let mut terminate = signal(SignalKind::terminate()).expect("Failed to install SIGTERM handler");
+    tokio::select! {
+        _ = terminate.recv() => {}
+        _ = tokio::signal::ctrl_c() => {}
+    }
+    shutting_down.store(true, Ordering::Release);
+}
+
+/// Serve `app` until SIGTERM, then stop accepting connections and let
+/// in-flight requests finish.
+///
+/// A claim cut off midway may have spent its nullifier without the client
+/// hearing back, so requests get up to `SHUTDOWN_DRAIN_TIMEOUT` to finish.
+/// The count still running (`active_requests`) is logged meanwhile; whatever
+/// is left after the timeout is dropped.
+pub async fn serve_with_graceful_shutdown(
+    listener: TcpListener,
+    app: Router,
+    active_requests: Arc<AtomicUsize>,
+    shutting_down: Arc<AtomicBool>,
+) -> std::io::Result<()> {
+    let signalled = Arc::new(Notify::new());
+    let shutdown = {
+        let signalled = signalled.clone();
+        async move {
+            shutdown_signal(shutting_down).await;
+            signalled.notify_one();
+        }
+    };
+    let server = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown)
+        .into_future();
+    tokio::pin!(server);
+
+    tokio::select! {
+        result = &mut server => return result,
+        _ = signalled.notified() => {}
+    }
+    tracing::info!(
+        active_requests = active_requests.load(Ordering::Acquire),
+        "🛑 Shutdown signal received, draining in-flight requests"
+    );
+
+    let deadline = tokio::time::sleep(SHUTDOWN_DRAIN_TIMEOUT);
+    tokio::pin!(deadline);
+    let mut progress = tokio::time::interval(DRAIN_LOG_INTERVAL);
+    progress.tick().await;
+    loop {
+        tokio::select! {
+            result = &mut server => {
+                tracing::info!("✅ In-flight requests drained, shutting down");
+                return result;
+            }
+            _ = &mut deadline => {
+                tracing::warn!(
+                    active_requests = active_requests.load(Ordering::Acquire),
+                    timeout_secs = SHUTDOWN_DRAIN_TIMEOUT.as_secs(),
+                    "⚠️ Drain timed out, forcing exit"
+                );
+                return Ok(());
+            }
+            _ = progress.tick() => {
+                tracing::info!(
+                    active_requests = active_requests.load(Ordering::Acquire),
+                    "⏳ Draining in-flight requests"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! Wallet signature verification
+//!
+//! Solana wallets sign with Ed25519 and the public key is the wallet address,
+//! so a base58 address and a base58 signature are all that is needed. OpenSSL
+//! (already linked for TLS) does the verification.
+//!
+//! A recipient's own claim is signed over `claim_signing_message`, which
+//! commits to the time in 5-minute buckets so a signature expires on its own.
+
+use openssl::{
+    pkey::{Id, PKey},
+    sign::Verifier,
+};
+use sha2::{Digest, Sha256};
+
+use crate::common::merkle::Hash;
+
+/// Domain separator of `claim_signing_message`
+const CLAIM_DOMAIN: &[u8] = b"shadow-drop-claim";
+
+/// Width of the time buckets a claim signature commits to
+pub const CLAIM_SIGNATURE_WINDOW_SECS: i64 = 5 * 60;
+
+/// Why a wallet signature was rejected
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum SignatureError {
+    #[error("invalid wallet address: {0}")]
+    InvalidWallet(String),
+
+    #[error("signature must be 64 base58-encoded bytes")]
+    Malformed,
+
+    #[error("signature does not match the wallet")]
+    Mismatch,
+}
+
+/// Verify that `wallet` signed `message`, with `signature` base58 encoded
+pub fn verify_wallet_signature(
+    wallet: &str,
+    message: &[u8],
+    signature: &str,
+) -> Result<(), SignatureError> {
+    let signature: [u8; 64] = bs58::decode(signature)
+        .into_vec()
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(SignatureError::Malformed)?;
+    verify_solana_signature(wallet, message, &signature)
+}
+
+/// Verify that `wallet` signed `message` with the raw Ed25519 `signature`
+pub fn verify_solana_signature(
+    wallet: &str,
+    message: &[u8],
+    signature: &[u8; 64],
+) -> Result<(), SignatureError> {
+    let public_key = bs58::decode(wallet)
+        .into_vec()
+        .ok()
+        .filter(|bytes| bytes.len() == 32)
+        .ok_or_else(|| SignatureError::InvalidWallet(wallet.to_string()))?;
+
+    let key = PKey::public_key_from_raw_bytes(&public_key, Id::ED25519)
+        .map_err(|_| SignatureError::InvalidWallet(wallet.to_string()))?;
+    let valid = Verifier::new_without_digest(&key)
+        .and_then(|mut verifier| verifier.verify_oneshot(signature, message))
+        .unwrap_or(false);
+    if valid { Ok(()) } else { Err(SignatureError::Mismatch) }
+}
+
+/// What a recipient signs to claim: `sha256("shadow-drop-claim" || campaign ||
+/// nullifier || bucket)`, with the bucket as 8 little-endian bytes
+pub fn claim_signing_message(campaign: &str, nullifier: &Hash, bucket: i64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(CLAIM_DOMAIN);
+    hasher.update(campaign.as_bytes());
+    hasher.update(nullifier);
+    hasher.update(bucket.to_le_bytes());
+    hasher.finalize().into()
+}
+
+/// The time bucket of the unix timestamp `now`
+pub fn claim_bucket(now: i64) -> i64 {
+    now.div_euclid(CLAIM_SIGNATURE_WINDOW_SECS)
+}
+
+/// Verify a recipient's base58 claim signature, made in the current bucket or
+/// the one before, so a signature made just before a boundary still counts
+pub fn verify_claim_signature(
+    wallet: &str,
+    campaign: &str,
+    nullifier: &Hash,
+    signature: &str,
+    now: i64,
+) -> Result<(), SignatureError> {
+    let bucket = claim_bucket(now);
+    let message = claim_signing_message(campaign, nullifier, bucket);
+    match verify_wallet_signature(wallet, &message, signature) {
+        Err(SignatureError::Mismatch) => {
+            let message = claim_signing_message(campaign, nullifier, bucket - 1);
+            verify_wallet_signature(wallet, &message, signature)
+        }
+        result => result,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use openssl::{pkey::Private, sign::Signer};
+
+    /// A throwaway keypair as `(key, base58 wallet address)`
+    pub(crate) fn test_wallet() -> (PKey<Private>, String) {
+        let key = PKey::generate_ed25519().unwrap();
+        let wallet = bs58::encode(key.raw_public_key().unwrap()).into_string();
+        (key, wallet)
+    }
+
+    /// Base58 signature of `message` by `key`
+    pub(crate) fn sign(key: &PKey<Private>, message: &[u8]) -> String {
+        let signature = Signer::new_without_digest(key)
+            .unwrap()
+            .sign_oneshot_to_vec(message)
+            .unwrap();
+        bs58::encode(signature).into_string()
+    }
+
+    #[test]
+    fn test_verify_wallet_signature() {
+        let (key, wallet) = test_wallet();
+        let (_, other_wallet) = test_wallet();
+        let signature = sign(&key, b"message");
+
+        assert_eq!(verify_wallet_signature(&wallet, b"message", &signature), Ok(()));
+        assert_eq!(
+            verify_wallet_signature(&wallet, b"other message", &signature),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            verify_wallet_signature(&other_wallet, b"message", &signature),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            verify_wallet_signature(&wallet, b"message", "not-base58!"),
+            Err(SignatureError::Malformed)
+        );
+    }
+
+    #[test]
+    fn test_verify_solana_signature() {
+        let key = PKey::private_key_from_raw_bytes(&[0x11; 32], Id::ED25519).unwrap();
+        let wallet = bs58::encode(key.raw_public_key().unwrap()).into_string();
+        let signature: [u8; 64] = bs58::decode(sign(&key, b"message"))
+            .into_vec()
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        assert_eq!(verify_solana_signature(&wallet, b"message", &signature), Ok(()));
+        let mut tampered = signature;
+        tampered[0] ^= 1;
+        assert_eq!(
+            verify_solana_signature(&wallet, b"message", &tampered),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            verify_solana_signature("short", b"message", &signature),
+            Err(SignatureError::InvalidWallet("short".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_claim_signature_expires_after_the_next_bucket() {
+        let (key, wallet) = test_wallet();
+        let nullifier = [7u8; 32];
+        let signed_at = 1_700_000_100;
+        let message = claim_signing_message("campaign", &nullifier, claim_bucket(signed_at));
+        let signature = sign(&key, &message);
+
+        let verify = |campaign: &str, now: i64| {
+            verify_claim_signature(&wallet, campaign, &nullifier, &signature, now)
+        };
+        assert_eq!(verify("campaign", signed_at), Ok(()));
+        assert_eq!(verify("campaign", signed_at + CLAIM_SIGNATURE_WINDOW_SECS), Ok(()));
+        assert_eq!(
+            verify("campaign", signed_at + 2 * CLAIM_SIGNATURE_WINDOW_SECS),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(verify("other", signed_at), Err(SignatureError::Mismatch));
+        assert_ne!(
+            claim_signing_message("campaign", &nullifier, 1),
+            claim_signing_message("campaign", &nullifier, 2)
+        );
+    }
+}
@@ -0,0 +1,243 @@
+//! Sign-In With Solana messages
+//!
+//! The text a wallet signs to log in, in the CAIP-122 layout Solana wallets
+//! produce:
+//!
+//! ```text
+//! example.com wants you to sign in with your Solana account:
+//! 7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU
+//!
+//! Optional statement
+//!
+//! URI: https://example.com
+//! Version: 1
+//! Nonce: 32891756
+//! Issued At: 2024-01-01T00:00:00Z
+//! Expiration Time: 2024-01-01T00:10:00Z
+//! ```
+//!
+//! `URI`, `Version`, `Nonce` and `Issued At` are required here; `Chain ID`,
+//! `Expiration Time`, `Not Before`, `Request ID` and `Resources` are optional.
+
+use chrono::{DateTime, Utc};
+
+/// Ends the first line, after the requesting domain
+const HEADER_SUFFIX: &str = " wants you to sign in with your Solana account:";
+
+/// How far in the future `Issued At` may be, for clock skew
+const MAX_CLOCK_SKEW_SECS: i64 = 60;
+
+/// Why a sign-in message was rejected
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum SiwsError {
+    #[error("malformed sign-in message: {0}")]
+    Malformed(String),
+
+    #[error("sign-in message is for {0}, not this site")]
+    DomainMismatch(String),
+
+    #[error("sign-in message has expired")]
+    Expired,
+
+    #[error("sign-in message is not valid yet")]
+    NotYetValid,
+}
+
+/// A parsed sign-in message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiwsMessage {
+    pub domain: String,
+    /// Wallet address (base58) signing in
+    pub address: String,
+    pub statement: Option<String>,
+    pub uri: String,
+    pub version: String,
+    pub chain_id: Option<String>,
+    pub nonce: String,
+    pub issued_at: DateTime<Utc>,
+    pub expiration_time: Option<DateTime<Utc>>,
+    pub not_before: Option<DateTime<Utc>>,
+    pub request_id: Option<String>,
+    pub resources: Vec<String>,
+}
+
+impl SiwsMessage {
+    pub fn parse(message: &str) -> Result<Self, SiwsError> {
+        let malformed = |what: &str| SiwsError::Malformed(what.to_string());
+        let mut lines = message.split('\n');
+
+        let domain = lines
+            .next()
+            .and_then(|line| line.strip_suffix(HEADER_SUFFIX))
+            .filter(|domain| !domain.is_empty())
+            .ok_or_else(|| malformed("missing header line"))?;
+        let address = lines
+            .next()
+            .filter(|address| !address.is_empty())
+            .ok_or_else(|| malformed("missing address"))?;
+        if lines.next() != Some("") {
+            return Err(malformed("expected a blank line after the address"));
+        }
+
+        let rest: Vec<&str> = lines.collect();
+        let (statement, fields) = match rest.as_slice() {
+            [statement, "", fields @ ..] if field(statement).is_none() => (Some(*statement), fields),
+            fields => (None, fields),
+        };
+
+        let mut message = Self {
+            domain: domain.to_string(),
+            address: address.to_string(),
+            statement: statement.map(str::to_string),
+            uri: String::new(),
+            version: String::new(),
+            chain_id: None,
+            nonce: String::new(),
+            issued_at: DateTime::UNIX_EPOCH,
+            expiration_time: None,
+            not_before: None,
+            request_id: None,
+            resources: Vec::new(),
+        };
+        let mut seen = Vec::new();
+        let mut fields = fields.iter();
+        while let Some(line) = fields.next() {
+            if *line == "Resources:" {
+                message.resources = fields
+                    .by_ref()
+                    .map(|line| line.strip_prefix("- ").map(str::to_string))
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| malformed("resources must be \"- \" lines"))?;
+                break;
+            }
+
+            let (key, value) = field(line).ok_or_else(|| malformed(&format!("unexpected line {:?}", line)))?;
+            if seen.contains(&key) {
+                return Err(malformed(&format!("duplicate {}", key)));
+            }
+            seen.push(key);
+            match key {
+                "URI" => message.uri = value.to_string(),
+                "Version" => message.version = value.to_string(),
+                "Chain ID" => message.chain_id = Some(value.to_string()),
+                "Nonce" => message.nonce = value.to_string(),
+                "Issued At" => message.issued_at = timestamp(key, value)?,
+                "Expiration Time" => message.expiration_time = Some(timestamp(key, value)?),
+                "Not Before" => message.not_before = Some(timestamp(key, value)?),
+                "Request ID" => message.request_id = Some(value.to_string()),
+                _ => unreachable!("field() only returns known keys"),
+            }
+        }
+
+        for required in ["URI", "Version", "Nonce", "Issued At"] {
+            if !seen.contains(&required) {
+                return Err(malformed(&format!("missing {}", required)));
+            }
+        }
+        if message.version != "1" {
+            return Err(malformed(&format!("unsupported version {}", message.version)));
+        }
+        Ok(message)
+    }
+
+    /// Check the message was made for `domain` and is valid at `now`
+    pub fn validate(&self, domain: &str, now: DateTime<Utc>) -> Result<(), SiwsError> {
+        if self.domain != domain {
+            return Err(SiwsError::DomainMismatch(self.domain.clone()));
+        }
+        if self.expiration_time.is_some_and(|expires| expires <= now) {
+            return Err(SiwsError::Expired);
+        }
+        let issued_too_late = (self.issued_at - now).num_seconds() > MAX_CLOCK_SKEW_SECS;
+        if issued_too_late || self.not_before.is_some_and(|not_before| not_before > now) {
+            return Err(SiwsError::NotYetValid);
+        }
+        Ok(())
+    }
+}
+
+/// A `Key: value` line with one of the known keys
+fn field(line: &str) -> Option<(&'static str, &str)> {
+    const KEYS: [&str; 8] = [
+        "URI",
+        "Version",
+        "Chain ID",
+        "Nonce",
+        "Issued At",
+        "Expiration Time",
+        "Not Before",
+        "Request ID",
+    ];
+    let (key, value) = line.split_once(": ")?;
+    KEYS.into_iter().find(|known| *known == key).map(|key| (key, value))
+}
+
+fn timestamp(key: &str, value: &str) -> Result<DateTime<Utc>, SiwsError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| SiwsError::Malformed(format!("{} must be an RFC 3339 time", key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &str = "shadow-drop.app wants you to sign in with your Solana account:
+7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU
+
+Sign in to Shadow Drop
+
+URI: https://shadow-drop.app
+Version: 1
+Chain ID: mainnet
+Nonce: 0123456789abcdef0123456789abcdef
+Issued At: 2024-01-01T00:00:00Z
+Expiration Time: 2024-01-01T00:10:00Z
+Resources:
+- https://shadow-drop.app/terms";
+
+    fn at(time: &str) -> DateTime<Utc> {
+        time.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_validate() {
+        let message = SiwsMessage::parse(MESSAGE).unwrap();
+        assert_eq!(message.domain, "shadow-drop.app");
+        assert_eq!(message.address, "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU");
+        assert_eq!(message.statement.as_deref(), Some("Sign in to Shadow Drop"));
+        assert_eq!(message.nonce, "0123456789abcdef0123456789abcdef");
+        assert_eq!(message.chain_id.as_deref(), Some("mainnet"));
+        assert_eq!(message.expiration_time, Some(at("2024-01-01T00:10:00Z")));
+        assert_eq!(message.resources, ["https://shadow-drop.app/terms"]);
+
+        assert_eq!(message.validate("shadow-drop.app", at("2024-01-01T00:05:00Z")), Ok(()));
+        assert_eq!(
+            message.validate("evil.example", at("2024-01-01T00:05:00Z")),
+            Err(SiwsError::DomainMismatch("shadow-drop.app".to_string()))
+        );
+        assert_eq!(message.validate("shadow-drop.app", at("2024-01-01T00:10:00Z")), Err(SiwsError::Expired));
+        assert_eq!(
+            message.validate("shadow-drop.app", at("2023-12-31T23:50:00Z")),
+            Err(SiwsError::NotYetValid)
+        );
+
+        // The statement is optional
+        let without_statement = MESSAGE.replace("Sign in to Shadow Drop\n\n", "");
+        assert_eq!(SiwsMessage::parse(&without_statement).unwrap().statement, None);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_messages() {
+        for broken in [
+            MESSAGE.replace(" wants you to sign in", " would like you to sign in"),
+            MESSAGE.replace("Nonce: 0123456789abcdef0123456789abcdef\n", ""),
+            MESSAGE.replace("Version: 1", "Version: 2"),
+            MESSAGE.replace("2024-01-01T00:00:00Z", "yesterday"),
+            MESSAGE.replace("URI: https://shadow-drop.app", "URI: a\nURI: b"),
+            MESSAGE.replace("Chain ID", "Chain"),
+        ] {
+            assert!(matches!(SiwsMessage::parse(&broken), Err(SiwsError::Malformed(_))), "{}", broken);
+        }
+    }
+}
@@ -0,0 +1,737 @@
+//! Unsigned Solana transactions for the claim instruction
+//!
+//! Just the parts of the Solana SDK a claim needs, written out here: public
+//! keys, program derived addresses (PDAs) and the legacy transaction wire
+//! format. The frontend decodes the transaction, has the wallet sign it and
+//! sends it. Claims of SPL token campaigns pay out to the recipient's
+//! associated token account (ATA), created first if it does not exist yet.
+//! A claim can instead go to Jito's block engine as a bundle, which lands
+//! whole or not at all and cannot be front-run; the tip paying for it is a
+//! transfer inside the claim transaction, so the wallet signs it too.
+//!
+//! A PDA is `sha256(seeds || [bump] || program_id || "ProgramDerivedAddress")`
+//! for the largest bump whose hash is not an Ed25519 point. No private key
+//! exists for such an address. Checking that needs the curve's base field,
+//! which is defined below with ark-ff's Montgomery backend.
+
+use std::{fmt, str::FromStr, time::Duration};
+
+use ark_ff::{
+    Field, LegendreSymbol, MontFp, PrimeField,
+    fields::{Fp256, MontBackend, MontConfig},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::common::{
+    fees::Lamports,
+    http,
+    merkle::{Hash, MerkleProof},
+};
+
+/// Suffix hashed into every program derived address
+const PDA_MARKER: &[u8] = b"ProgramDerivedAddress";
+
+/// Seed prefix of the account recording a spent nullifier
+pub const NULLIFIER_SEED: &[u8] = b"nullifier";
+
+/// The system program, which creates the nullifier account
+pub const SYSTEM_PROGRAM_ID: Pubkey = Pubkey([0; 32]);
+
+/// SPL Token, `TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA`
+pub const TOKEN_PROGRAM_ID: Pubkey = Pubkey([
+    6, 221, 246, 225, 215, 101, 161, 147, 217, 203, 225, 70, 206, 235, 121, 172, 28, 180, 133,
+    237, 95, 91, 55, 145, 58, 140, 245, 133, 126, 255, 0, 169,
+]);
+
+/// SPL Associated Token Account, `ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL`
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = Pubkey([
+    140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142, 13, 131, 11, 90, 19, 153, 218,
+    255, 16, 132, 4, 142, 123, 216, 219, 233, 248, 89,
+]);
+
+/// Fee of each transaction signature
+pub const LAMPORTS_PER_SIGNATURE: Lamports = 5_000;
+
+/// Rent-exempt minimum of a 165-byte token account, paid when an ATA is created
+pub const TOKEN_ACCOUNT_RENT_LAMPORTS: Lamports = 2_039_280;
+
+/// Attempts at `sendBundle` before a transport failure is returned
+const JITO_SUBMIT_ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry, doubled for each one after
+const JITO_RETRY_BACKOFF: Duration = Duration::from_millis(250);
+
+/// Connect, read and write timeout of block engine requests
+const JITO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Ed25519 base field, `2^255 - 19`
+#[derive(MontConfig)]
+#[modulus = "57896044618658097711785492504343953926634992332820282019728792003956564819949"]
+#[generator = "2"]
+struct Curve25519FqConfig;
+type Curve25519Fq = Fp256<MontBackend<Curve25519FqConfig, 4>>;
+
+/// Edwards curve constant `d = -121665 / 121666`
+const EDWARDS_D: Curve25519Fq =
+    MontFp!("37095705934669439343138083508754565189542113879843219016388785533085940283555");
+
+/// Why a public key could not be parsed
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[error("invalid public key: {0}")]
+pub struct ParsePubkeyError(pub String);
+
+/// A Solana account address; base58 in text
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pubkey(pub [u8; 32]);
+
+impl Pubkey {
+    /// Whether the address decompresses to an Ed25519 point
+    ///
+    /// Matches `CompressedEdwardsY::decompress`: `y` is reduced modulo the
+    /// field and the sign bit of `x` is ignored.
+    pub fn is_on_curve(&self) -> bool {
+        let mut y = self.0;
+        y[31] &= 0x7f;
+        let y2 = Curve25519Fq::from_le_bytes_mod_order(&y).square();
+        // x^2 = (y^2 - 1) / (d y^2 + 1); the denominator is never zero
+        let u = y2 - Curve25519Fq::ONE;
+        let v = EDWARDS_D * y2 + Curve25519Fq::ONE;
+        let x2 = u * v.inverse().expect("d is not a square");
+        x2.legendre() != LegendreSymbol::QuadraticNonResidue
+    }
+
+    /// The program derived address of `seeds` with their bump already
+    /// appended, or `None` if it lands on the curve
+    pub fn create_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> Option<Pubkey> {
+        let mut hasher = Sha256::new();
+        for seed in seeds {
+            hasher.update(seed);
+        }
+        hasher.update(program_id.0);
+        hasher.update(PDA_MARKER);
+        let address = Pubkey(hasher.finalize().into());
+        (!address.is_on_curve()).then_some(address)
+    }
+
+    /// The canonical program derived address of `seeds` and its bump
+    pub fn find_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> (Pubkey, u8) {
+        (0..=u8::MAX)
+            .rev()
+            .find_map(|bump| {
+                let bump_seed = [bump];
+                let mut seeds = seeds.to_vec();
+                seeds.push(&bump_seed);
+                Self::create_program_address(&seeds, program_id).map(|address| (address, bump))
+            })
+            // Each bump fails with probability about 1/2
+            .expect("no bump gives an off-curve address")
+    }
+}
+
+impl FromStr for Pubkey {
+    type Err = ParsePubkeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        bs58::decode(s)
+            .into_vec()
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(Pubkey)
+            .ok_or_else(|| ParsePubkeyError(s.to_string()))
+    }
+}
+
+impl fmt::Display for Pubkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&bs58::encode(self.0).into_string())
+    }
+}
+
+impl fmt::Debug for Pubkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Pubkey({})", self)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum JitoError {
+    /// The block engine was unreachable, overloaded or answered garbage
+    #[error("Jito block engine request failed: {0}")]
+    Rpc(String),
+
+    /// The block engine refused the bundle itself
+    #[error("Jito rejected the bundle: {0}")]
+    Rejected(String),
+}
+
+/// An account an instruction reads or writes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// A call into a program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<AccountMeta>,
+    pub data: Vec<u8>,
+}
+
+/// A legacy transaction whose signatures are still zeroed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    /// Which of the leading `account_keys` sign, and which of the signing and
+    /// non-signing keys are read-only
+    pub num_required_signatures: u8,
+    pub num_readonly_signed: u8,
+    pub num_readonly_unsigned: u8,
+    /// Signers first, writable before read-only within each group
+    pub account_keys: Vec<Pubkey>,
+    pub recent_blockhash: Hash,
+    pub instructions: Vec<Instruction>,
+}
+
+impl Transaction {
+    /// Order the accounts of `instructions` as a message requires, with
+    /// `payer` first as the fee payer
+    pub fn new_unsigned(instructions: Vec<Instruction>, payer: &Pubkey, recent_blockhash: Hash) -> Self {
+        // (key, is_signer, is_writable) in order of first use, flags merged
+        let mut keys: Vec<(Pubkey, bool, bool)> = vec![(*payer, true, true)];
+        let uses = instructions.iter().flat_map(|instruction| {
+            let accounts = instruction.accounts.iter();
+            accounts
+                .map(|meta| (meta.pubkey, meta.is_signer, meta.is_writable))
+                .chain([(instruction.program_id, false, false)])
+        });
+        for (pubkey, is_signer, is_writable) in uses {
+            match keys.iter_mut().find(|(key, _, _)| *key == pubkey) {
+                Some((_, signer, writable)) => {
+                    *signer |= is_signer;
+                    *writable |= is_writable;
+                }
+                None => keys.push((pubkey, is_signer, is_writable)),
+            }
+        }
+        // Stable, so the payer stays first
+        keys.sort_by_key(|(_, is_signer, is_writable)| (!is_signer, !is_writable));
+
+        let count = |signer: bool, writable: bool| {
+            keys.iter().filter(|(_, s, w)| *s == signer && *w == writable).count() as u8
+        };
+        Self {
+            num_required_signatures: count(true, true) + count(true, false),
+            num_readonly_signed: count(true, false),
+            num_readonly_unsigned: count(false, false),
+            account_keys: keys.into_iter().map(|(key, _, _)| key).collect(),
+            recent_blockhash,
+            instructions,
+        }
+    }
+
+    /// The same transaction with `instruction` appended, accounts reordered
+    pub fn with_instruction(self, instruction: Instruction) -> Self {
+        let payer = self.account_keys[0];
+        let mut instructions = self.instructions;
+        instructions.push(instruction);
+        Self::new_unsigned(instructions, &payer, self.recent_blockhash)
+    }
+
+    /// Wire format: the signature slots, then the message
+    ///
+    /// Instructions refer to accounts by their index in `account_keys`.
+    pub fn serialize(&self) -> Vec<u8> {
+        let index = |key: &Pubkey| {
+            self.account_keys
+                .iter()
+                .position(|k| k == key)
+                .expect("new_unsigned lists every account") as u8
+        };
+
+        let mut out = Vec::new();
+        write_compact_u16(&mut out, self.num_required_signatures.into());
+        out.resize(out.len() + 64 * usize::from(self.num_required_signatures), 0);
+
+        out.extend_from_slice(&[
+            self.num_required_signatures,
+            self.num_readonly_signed,
+            self.num_readonly_unsigned,
+        ]);
+        write_compact_u16(&mut out, self.account_keys.len());
+        for key in &self.account_keys {
+            out.extend_from_slice(&key.0);
+        }
+        out.extend_from_slice(&self.recent_blockhash);
+        write_compact_u16(&mut out, self.instructions.len());
+        for instruction in &self.instructions {
+            out.push(index(&instruction.program_id));
+            write_compact_u16(&mut out, instruction.accounts.len());
+            out.extend(instruction.accounts.iter().map(|meta| index(&meta.pubkey)));
+            write_compact_u16(&mut out, instruction.data.len());
+            out.extend_from_slice(&instruction.data);
+        }
+        out
+    }
+}
+
+/// Solana's `ShortU16` length prefix: 7 bits per byte, low bits first
+fn write_compact_u16(out: &mut Vec<u8>, value: usize) {
+    let mut value = u16::try_from(value).expect("length over u16::MAX");
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// The associated token account of `wallet` for `mint`
+pub fn derive_ata(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let seeds: [&[u8]; 3] = [&wallet.0, &TOKEN_PROGRAM_ID.0, &mint.0];
+    Pubkey::find_program_address(&seeds, &ASSOCIATED_TOKEN_PROGRAM_ID).0
+}
+
+/// Create `wallet`'s associated token account for `mint`, paid by `payer`
+///
+/// The ATA program's `Create` instruction, which fails if the account exists.
+pub fn create_associated_token_account(payer: &Pubkey, wallet: &Pubkey, mint: &Pubkey) -> Instruction {
+    let account = |pubkey: Pubkey, is_signer: bool, is_writable: bool| AccountMeta {
+        pubkey,
+        is_signer,
+        is_writable,
+    };
+
+    Instruction {
+        program_id: ASSOCIATED_TOKEN_PROGRAM_ID,
+        accounts: vec![
+            account(*payer, true, true),
+            account(derive_ata(wallet, mint), false, true),
+            account(*wallet, false, false),
+            account(*mint, false, false),
+            account(SYSTEM_PROGRAM_ID, false, false),
+            account(TOKEN_PROGRAM_ID, false, false),
+        ],
+        data: vec![0],
+    }
+}
+
+/// Where a token campaign pays a claim: the payer's ATA for `mint`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenPayout {
+    pub mint: Pubkey,
+    /// Whether the ATA has to be created first
+    pub create_account: bool,
+}
+
+/// Account recording that `nullifier` is spent
+pub fn nullifier_address(nullifier: &Hash, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[NULLIFIER_SEED, nullifier], program_id)
+}
+
+/// The claim instruction: `proof` as instruction data, with the payer, the
+/// campaign and the nullifier account it creates
+pub fn build_claim_instruction(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    campaign_pda: &Pubkey,
+    proof: &MerkleProof,
+    nullifier: &Hash,
+) -> Instruction {
+    let (nullifier_pda, _) = nullifier_address(nullifier, program_id);
+    let writable = |pubkey: Pubkey, is_signer: bool| AccountMeta {
+        pubkey,
+        is_signer,
+        is_writable: true,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            writable(*payer, true),
+            writable(*campaign_pda, false),
+            writable(nullifier_pda, false),
+            AccountMeta {
+                pubkey: SYSTEM_PROGRAM_ID,
+                is_signer: false,
+                is_writable: false,
+            },
+        ],
+        data: proof.to_solana_instruction_data(),
+    }
+}
+
+/// An unsigned claim transaction paid for by `payer`
+///
+/// A token payout adds the payer's ATA, the mint and the token program to
+/// the claim's accounts, preceded by the ATA's creation if it is missing.
+pub fn build_claim_transaction(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    campaign_pda: &Pubkey,
+    proof: &MerkleProof,
+    nullifier: &Hash,
+    token: Option<&TokenPayout>,
+    recent_blockhash: Hash,
+) -> Transaction {
+    let mut claim = build_claim_instruction(program_id, payer, campaign_pda, proof, nullifier);
+    let mut instructions = Vec::new();
+    if let Some(token) = token {
+        if token.create_account {
+            instructions.push(create_associated_token_account(payer, payer, &token.mint));
+        }
+        claim.accounts.extend([
+            AccountMeta {
+                pubkey: derive_ata(payer, &token.mint),
+                is_signer: false,
+                is_writable: true,
+            },
+            AccountMeta {
+                pubkey: token.mint,
+                is_signer: false,
+                is_writable: false,
+            },
+            AccountMeta {
+                pubkey: TOKEN_PROGRAM_ID,
+                is_signer: false,
+                is_writable: false,
+            },
+        ]);
+    }
+    instructions.push(claim);
+    Transaction::new_unsigned(instructions, payer, recent_blockhash)
+}
+
+/// Lamports the payer spends on a claim: signature fees, plus the rent of a
+/// token account it creates
+pub fn estimated_claim_cost(transaction: &Transaction, token: Option<&TokenPayout>) -> Lamports {
+    let signatures = Lamports::from(transaction.num_required_signatures) * LAMPORTS_PER_SIGNATURE;
+    let rent = match token {
+        Some(token) if token.create_account => TOKEN_ACCOUNT_RENT_LAMPORTS,
+        _ => 0,
+    };
+    signatures + rent
+}
+
+/// System program `Transfer` of `lamports` from `from` to `to`
+pub fn system_transfer(from: &Pubkey, to: &Pubkey, lamports: Lamports) -> Instruction {
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&lamports.to_le_bytes());
+    Instruction {
+        program_id: SYSTEM_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta {
+                pubkey: *from,
+                is_signer: true,
+                is_writable: true,
+            },
+            AccountMeta {
+                pubkey: *to,
+                is_signer: false,
+                is_writable: true,
+            },
+        ],
+        data,
+    }
+}
+
+/// The first signature of a wire-format transaction, base58, which is its
+/// ID; None if the transaction is unsigned
+pub fn transaction_signature(transaction: &[u8]) -> Option<String> {
+    // A single-byte signature count, since a claim has far fewer than 128 signers
+    match transaction {
+        [count @ 1..0x80, rest @ ..] if rest.len() >= 64 * usize::from(*count) => {
+            let signature = &rest[..64];
+            signature.iter().any(|byte| *byte != 0).then(|| bs58::encode(signature).into_string())
+        }
+        _ => None,
+    }
+}
+
+/// Submit signed `transactions` (wire format) to the Jito block engine at
+/// `jito_url` as one bundle, returning the bundle ID
+///
+/// The tip is not added here: it has to be in a transaction before the
+/// wallet signs it, see `system_transfer`. Transport failures are retried
+/// with backoff; a bundle the block engine rejects is not.
+pub async fn submit_jito_bundle(jito_url: &str, transactions: &[Vec<u8>]) -> Result<String, JitoError> {
+    let url = Url::parse(jito_url).map_err(|e| JitoError::Rpc(format!("invalid Jito URL: {}", e)))?;
+    let encoded: Vec<String> = transactions.iter().map(|tx| STANDARD.encode(tx)).collect();
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "sendBundle",
+        "params": [encoded, { "encoding": "base64" }],
+    })
+    .to_string();
+
+    let mut attempt = 1;
+    loop {
+        let (url, request) = (url.clone(), request.clone());
+        let result = tokio::task::spawn_blocking(move || send_bundle(&url, &request))
+            .await
+            .map_err(|e| JitoError::Rpc(e.to_string()))?;
+        match result {
+            Err(JitoError::Rpc(e)) if attempt < JITO_SUBMIT_ATTEMPTS => {
+                tracing::warn!(attempt, error = %e, "Jito bundle submission failed, retrying");
+                tokio::time::sleep(JITO_RETRY_BACKOFF * 2u32.pow(attempt - 1)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// One blocking `sendBundle` call
+///
+/// Rate limiting (HTTP 429) and server errors are transport failures, to be
+/// retried; a JSON-RPC error answer is the block engine's verdict on the bundle.
+fn send_bundle(url: &Url, request: &str) -> Result<String, JitoError> {
+    let headers = [("Content-Type", "application/json")];
+    let response = http::send("POST", url, &headers, Some(request), JITO_TIMEOUT).map_err(JitoError::Rpc)?;
+    if response.status == 429 || response.status >= 500 {
+        return Err(JitoError::Rpc(format!("block engine returned HTTP {}", response.status)));
+    }
+    let body: Value = serde_json::from_slice(&response.body)
+        .map_err(|e| JitoError::Rpc(format!("invalid JSON response: {}", e)))?;
+    if let Some(error) = body.get("error") {
+        return Err(JitoError::Rejected(error.to_string()));
+    }
+    if !(200..300).contains(&response.status) {
+        return Err(JitoError::Rejected(format!("block engine returned HTTP {}", response.status)));
+    }
+    body["result"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| JitoError::Rpc("response has no bundle ID".to_string()))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::common::signature::tests::test_wallet;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    fn pubkey(s: &str) -> Pubkey {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_program_addresses_match_the_sdk() {
+        // Vectors from solana-program's `test_create_program_address`
+        let program_id = pubkey("BPFLoaderUpgradeab1e11111111111111111111111");
+        let seed_key = pubkey("SeedPubey1111111111111111111111111111111111");
+        let cases: [(&[&[u8]], &str); 4] = [
+            (&[b"", &[1]], "BwqrghZA2htAcqq8dzP1WDAhTXYTYWj7CHxF5j7TDBAe"),
+            (&["☉".as_bytes(), &[0]], "13yWmRpaTR4r5nAktwLqMpRNr28tnVUZw26rTvPSSB19"),
+            (&[b"Talking", b"Squirrels"], "2fnQrngrQT4SeLcdToJAD96phoEjNL2man2kfRLCASVk"),
+            (&[&seed_key.0, &[1]], "976ymqVnfE32QFe6NfGDctSvVa36LWnvYxhU6G2232YL"),
+        ];
+        for (seeds, expected) in cases {
+            assert_eq!(Pubkey::create_program_address(seeds, &program_id), Some(pubkey(expected)));
+        }
+
+        let (address, bump) = nullifier_address(&[7u8; 32], &program_id);
+        assert!(!address.is_on_curve());
+        assert_eq!(
+            Pubkey::create_program_address(&[NULLIFIER_SEED, &[7u8; 32], &[bump]], &program_id),
+            Some(address)
+        );
+    }
+
+    #[test]
+    fn test_wallet_keys_are_on_the_curve() {
+        for _ in 0..8 {
+            let (_, wallet) = test_wallet();
+            assert!(pubkey(&wallet).is_on_curve());
+        }
+        assert!("not base58!".parse::<Pubkey>().is_err());
+        assert!(bs58::encode([1u8; 31]).into_string().parse::<Pubkey>().is_err());
+    }
+
+    #[test]
+    fn test_claim_transaction_layout() {
+        let program_id = pubkey("7wjDqUQUpnudD25MELXBiayNiMrStXaKAdrLMwzccu7v");
+        let payer = Pubkey([1; 32]);
+        let campaign = Pubkey([2; 32]);
+        let nullifier = [3u8; 32];
+        let proof = MerkleProof {
+            leaf_index: 5,
+            siblings: vec![[4u8; 32]; 3],
+            leaf: [5u8; 32],
+        };
+
+        let tx = build_claim_transaction(&program_id, &payer, &campaign, &proof, &nullifier, None, [9; 32]);
+        let (nullifier_pda, _) = nullifier_address(&nullifier, &program_id);
+        assert_eq!(
+            tx.account_keys,
+            [payer, campaign, nullifier_pda, SYSTEM_PROGRAM_ID, program_id]
+        );
+        assert_eq!(
+            (tx.num_required_signatures, tx.num_readonly_signed, tx.num_readonly_unsigned),
+            (1, 0, 2)
+        );
+
+        let bytes = tx.serialize();
+        let data = proof.to_solana_instruction_data();
+        // One zeroed signature, the header, five keys and the blockhash
+        let message = 1 + 64;
+        let instruction = message + 3 + 1 + 5 * 32 + 32;
+        assert_eq!(bytes[0], 1);
+        assert_eq!(&bytes[1..message], [0u8; 64]);
+        assert_eq!(&bytes[message..message + 4], [1, 0, 2, 5]);
+        assert_eq!(&bytes[instruction - 32..instruction], [9u8; 32]);
+        // One instruction: program index 4, accounts 0..4, then the data
+        assert_eq!(&bytes[instruction..instruction + 7], [1, 4, 4, 0, 1, 2, 3]);
+        let mut length = Vec::new();
+        write_compact_u16(&mut length, data.len());
+        assert_eq!(length, [(data.len() & 0x7f) as u8 | 0x80, (data.len() >> 7) as u8]);
+        assert_eq!(&bytes[instruction + 7..instruction + 9], length);
+        assert_eq!(&bytes[instruction + 9..], data);
+        assert_eq!(estimated_claim_cost(&tx, None), LAMPORTS_PER_SIGNATURE);
+    }
+
+    #[test]
+    fn test_token_claim_creates_a_missing_ata_first() {
+        assert_eq!(TOKEN_PROGRAM_ID, pubkey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"));
+        assert_eq!(ASSOCIATED_TOKEN_PROGRAM_ID, pubkey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"));
+
+        let program_id = Pubkey([8; 32]);
+        let payer = Pubkey([1; 32]);
+        let mint = Pubkey([6; 32]);
+        let ata = derive_ata(&payer, &mint);
+        assert!(!ata.is_on_curve());
+        assert_ne!(ata, derive_ata(&Pubkey([2; 32]), &mint));
+        let proof = MerkleProof {
+            leaf_index: 0,
+            siblings: vec![[4u8; 32]],
+            leaf: [5u8; 32],
+        };
+        let build = |create_account| {
+            let token = TokenPayout { mint, create_account };
+            let campaign = Pubkey([2; 32]);
+            let tx =
+                build_claim_transaction(&program_id, &payer, &campaign, &proof, &[3; 32], Some(&token), [9; 32]);
+            let cost = estimated_claim_cost(&tx, Some(&token));
+            (tx, cost)
+        };
+
+        let (tx, cost) = build(true);
+        assert_eq!(tx.instructions.len(), 2);
+        assert_eq!(tx.instructions[0], create_associated_token_account(&payer, &payer, &mint));
+        assert_eq!(tx.instructions[0].accounts[1].pubkey, ata);
+        let claim = &tx.instructions[1];
+        assert_eq!(claim.program_id, program_id);
+        let token_accounts: Vec<Pubkey> = claim.accounts[4..].iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(token_accounts, [ata, mint, TOKEN_PROGRAM_ID]);
+        assert_eq!(cost, LAMPORTS_PER_SIGNATURE + TOKEN_ACCOUNT_RENT_LAMPORTS);
+        // Writable keys in order of first use: the ATA creation comes first
+        let nullifier_pda = nullifier_address(&[3; 32], &program_id).0;
+        assert_eq!(tx.account_keys[..4], [payer, ata, Pubkey([2; 32]), nullifier_pda]);
+
+        let (tx, cost) = build(false);
+        assert_eq!(tx.instructions.len(), 1);
+        assert_eq!(cost, LAMPORTS_PER_SIGNATURE);
+    }
+
+    #[test]
+    fn test_tip_is_a_system_transfer_appended_last() {
+        let payer = Pubkey([1; 32]);
+        let tip_account = Pubkey([5; 32]);
+        let tip = system_transfer(&payer, &tip_account, 10_000);
+        // Transfer is instruction 2 of the system program
+        assert_eq!(tip.data, [2, 0, 0, 0, 0x10, 0x27, 0, 0, 0, 0, 0, 0]);
+
+        let claim = build_claim_instruction(
+            &Pubkey([8; 32]),
+            &payer,
+            &Pubkey([2; 32]),
+            &MerkleProof {
+                leaf_index: 0,
+                siblings: vec![],
+                leaf: [5u8; 32],
+            },
+            &[3; 32],
+        );
+        let tx = Transaction::new_unsigned(vec![claim], &payer, [9; 32]).with_instruction(tip.clone());
+        assert_eq!(tx.instructions.last(), Some(&tip));
+        assert_eq!(tx.account_keys[0], payer);
+        assert_eq!(tx.num_required_signatures, 1);
+        // The tip account is writable, so it sorts ahead of the read-only programs
+        assert_eq!(tx.account_keys[3], tip_account);
+    }
+
+    /// HTTP server answering successive connections with `responses`
+    /// (status, body), returning the requests it read
+    pub async fn serve_block_engine(
+        responses: Vec<(u16, &'static str)>,
+    ) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for (status, body) in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = vec![0u8; 8192];
+                let read = socket.read(&mut request).await.unwrap();
+                requests.push(String::from_utf8_lossy(&request[..read]).into_owned());
+
+                let response = format!(
+                    "HTTP/1.1 {status} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                    body.len()
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+        (format!("http://{}", address), server)
+    }
+
+    #[test]
+    fn test_transaction_signature_is_the_first_signature() {
+        let unsigned = Transaction::new_unsigned(vec![], &Pubkey([1; 32]), [9; 32]).serialize();
+        assert_eq!(transaction_signature(&unsigned), None);
+        let mut signed = unsigned;
+        signed[1..65].copy_from_slice(&[3; 64]);
+        assert_eq!(transaction_signature(&signed), Some(bs58::encode([3u8; 64]).into_string()));
+        assert_eq!(transaction_signature(&signed[..40]), None);
+        assert_eq!(transaction_signature(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn test_submit_jito_bundle_retries_transport_failures() {
+        let (jito_url, server) = serve_block_engine(vec![
+            (503, "unavailable"),
+            (200, r#"{"jsonrpc":"2.0","result":"2id3YC2jK9G5Wo2phDx4gJVAew8DcY5NAojnVuao8rkxwPYPe8cSwE5GzhEgJA2y8fVjDEo6iR6ykBvDxrTQrtpb","id":1}"#),
+        ])
+        .await;
+        let bundle_id = submit_jito_bundle(&jito_url, &[vec![1, 2, 3]]).await.unwrap();
+        assert!(bundle_id.starts_with("2id3YC"));
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].contains(r#""method":"sendBundle""#));
+        assert!(requests[1].contains(r#""params":[["AQID"],{"encoding":"base64"}]"#));
+
+        // A rejected bundle is not sent again
+        let (jito_url, server) = serve_block_engine(vec![(
+            400,
+            r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"bundle must tip"},"id":1}"#,
+        )])
+        .await;
+        assert!(matches!(
+            submit_jito_bundle(&jito_url, &[vec![1]]).await,
+            Err(JitoError::Rejected(error)) if error.contains("bundle must tip")
+        ));
+        assert_eq!(server.await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,78 @@
+//! Last-known-good values for reads that tolerate stale data
+//!
+//! Handlers store every value they read successfully. When the database is
+//! unreachable (`is_unavailable`), they can answer from the cache instead,
+//! as long as the entry is younger than the cache's maximum age, and flag
+//! the response with `X-Cache-Warning: stale`.
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Response header marking an answer served from a `StaleCache`
+pub const CACHE_WARNING_HEADER: &str = "x-cache-warning";
+
+/// Oldest entry served while the database is unreachable
+pub const STALE_MAX_AGE: Duration = Duration::from_secs(60);
+
+/// Last successfully fetched value per key, with when it was fetched
+#[derive(Debug)]
+pub struct StaleCache<T> {
+    max_age: Duration,
+    entries: Mutex<HashMap<String, (T, Instant)>>,
+}
+
+impl<T: Clone> StaleCache<T> {
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            max_age,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Remember `value` as the latest fetched for `key`
+    pub fn store(&self, key: &str, value: T) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), (value, Instant::now()));
+    }
+
+    /// The value last stored for `key`, unless it is older than the maximum age
+    pub fn get(&self, key: &str) -> Option<T> {
+        let entries = self.entries.lock().unwrap();
+        let (value, fetched_at) = entries.get(key)?;
+        (fetched_at.elapsed() < self.max_age).then(|| value.clone())
+    }
+}
+
+/// Whether a query failed because the database could not be reached, as
+/// opposed to failing on its own
+pub fn is_unavailable(error: &sqlx::Error) -> bool {
+    matches!(error, sqlx::Error::PoolTimedOut | sqlx::Error::Io(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_expire_after_max_age() {
+        let cache = StaleCache::new(Duration::from_millis(50));
+        cache.store("campaign", 1);
+        assert_eq!(cache.get("campaign"), Some(1));
+        assert_eq!(cache.get("other"), None);
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get("campaign"), None);
+    }
+
+    #[test]
+    fn test_only_connection_failures_count_as_unavailable() {
+        assert!(is_unavailable(&sqlx::Error::PoolTimedOut));
+        assert!(is_unavailable(&sqlx::Error::Io(std::io::ErrorKind::ConnectionRefused.into())));
+        assert!(!is_unavailable(&sqlx::Error::RowNotFound));
+    }
+}
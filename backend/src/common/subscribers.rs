@@ -0,0 +1,70 @@
+//! One-shot subscriptions to the outcome of keyed work
+//!
+//! A subscriber gets a `oneshot::Receiver` for a key; `notify` sends the value
+//! to every receiver registered for it and forgets them. Subscriptions whose
+//! receiver was dropped are pruned as new ones are made, so keys that are
+//! never notified (e.g. finished by another instance) do not accumulate.
+
+use std::{collections::HashMap, hash::Hash, sync::Mutex};
+
+use tokio::sync::oneshot;
+
+#[derive(Debug)]
+pub struct Subscribers<K, V> {
+    waiting: Mutex<HashMap<K, Vec<oneshot::Sender<V>>>>,
+}
+
+impl<K: Eq + Hash, V: Clone> Subscribers<K, V> {
+    pub fn new() -> Self {
+        Self {
+            waiting: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Receiver of the next value notified for `key`
+    pub fn subscribe(&self, key: K) -> oneshot::Receiver<V> {
+        let (sender, receiver) = oneshot::channel();
+        let mut waiting = self.waiting.lock().unwrap();
+        waiting.retain(|_, senders| {
+            senders.retain(|sender| !sender.is_closed());
+            !senders.is_empty()
+        });
+        waiting.entry(key).or_default().push(sender);
+        receiver
+    }
+
+    /// Send `value` to everyone subscribed to `key`
+    pub fn notify(&self, key: &K, value: V) {
+        let senders = self.waiting.lock().unwrap().remove(key).unwrap_or_default();
+        for sender in senders {
+            let _ = sender.send(value.clone());
+        }
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> Default for Subscribers<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_notify_reaches_every_subscriber_of_a_key() {
+        let subscribers = Subscribers::new();
+        let first = subscribers.subscribe("job");
+        let second = subscribers.subscribe("job");
+        let other = subscribers.subscribe("other");
+
+        subscribers.notify(&"job", 7);
+        assert_eq!(first.await.unwrap(), 7);
+        assert_eq!(second.await.unwrap(), 7);
+
+        drop(other);
+        subscribers.subscribe("next");
+        assert!(!subscribers.waiting.lock().unwrap().contains_key("other"));
+    }
+}
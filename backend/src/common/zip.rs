@@ -0,0 +1,158 @@
+//! Uncompressed ZIP archives, written one entry at a time
+//!
+//! Just enough of the format (APPNOTE 6.3) for downloads of generated files:
+//! entries are stored without compression and there is no ZIP64, so an
+//! archive holds at most 65535 entries and 4 GiB. Each entry's bytes can be
+//! sent as soon as it is written; `finish` produces the trailing directory.
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const END_OF_DIRECTORY_SIGNATURE: u32 = 0x0605_4b50;
+/// 1.0: stored entries only
+const VERSION_NEEDED: u16 = 10;
+/// Bit 11: names are UTF-8
+const FLAG_UTF8: u16 = 1 << 11;
+/// MS-DOS date of 1980-01-01, the earliest a ZIP can record
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+/// CRC-32 (IEEE) lookup table for the reflected polynomial 0xEDB88320
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// CRC-32 of `data`, as ZIP headers record it
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, byte| {
+        CRC32_TABLE[((crc ^ u32::from(*byte)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+/// What the central directory repeats about an entry
+#[derive(Debug)]
+struct Entry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Writes the entries of one archive, tracking their offsets
+#[derive(Debug, Default)]
+pub struct ZipWriter {
+    entries: Vec<Entry>,
+    offset: u32,
+}
+
+impl ZipWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Local header and contents of a file named `name`
+    ///
+    /// # Panics
+    ///
+    /// If the archive would exceed the 65535-entry or 4 GiB limits.
+    pub fn entry(&mut self, name: &str, contents: &[u8]) -> Vec<u8> {
+        assert!(self.entries.len() < usize::from(u16::MAX), "too many ZIP entries");
+        let size = u32::try_from(contents.len()).expect("ZIP entry over 4 GiB");
+        let crc = crc32(contents);
+
+        let mut out = Vec::with_capacity(30 + name.len() + contents.len());
+        out.extend_from_slice(&LOCAL_HEADER_SIGNATURE.to_le_bytes());
+        out.extend_from_slice(&VERSION_NEEDED.to_le_bytes());
+        write_common_fields(&mut out, name, crc, size);
+        out.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(contents);
+
+        self.entries.push(Entry {
+            name: name.to_string(),
+            crc,
+            size,
+            offset: self.offset,
+        });
+        self.offset = u32::try_from(out.len())
+            .ok()
+            .and_then(|len| self.offset.checked_add(len))
+            .expect("ZIP archive over 4 GiB");
+        out
+    }
+
+    /// Central directory and end record, to send after the last entry
+    pub fn finish(self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for entry in &self.entries {
+            out.extend_from_slice(&CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+            out.extend_from_slice(&20u16.to_le_bytes()); // version made by: 2.0, MS-DOS
+            out.extend_from_slice(&VERSION_NEEDED.to_le_bytes());
+            write_common_fields(&mut out, &entry.name, entry.crc, entry.size);
+            out.extend_from_slice(&[0u8; 12]); // extra, comment, disk, internal and external attributes
+            out.extend_from_slice(&entry.offset.to_le_bytes());
+            out.extend_from_slice(entry.name.as_bytes());
+        }
+
+        let directory_size = out.len() as u32;
+        let count = self.entries.len() as u16;
+        out.extend_from_slice(&END_OF_DIRECTORY_SIGNATURE.to_le_bytes());
+        out.extend_from_slice(&[0u8; 4]); // this disk, directory disk
+        out.extend_from_slice(&count.to_le_bytes());
+        out.extend_from_slice(&count.to_le_bytes());
+        out.extend_from_slice(&directory_size.to_le_bytes());
+        out.extend_from_slice(&self.offset.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        out
+    }
+}
+
+/// Flags through name length, shared by local and central headers
+fn write_common_fields(out: &mut Vec<u8>, name: &str, crc: u32, size: u32) {
+    out.extend_from_slice(&FLAG_UTF8.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // method: stored
+    out.extend_from_slice(&0u16.to_le_bytes()); // time: midnight
+    out.extend_from_slice(&DOS_DATE.to_le_bytes());
+    out.extend_from_slice(&crc.to_le_bytes());
+    out.extend_from_slice(&size.to_le_bytes()); // compressed size
+    out.extend_from_slice(&size.to_le_bytes());
+    out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_layout() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let mut zip = ZipWriter::new();
+        let first = zip.entry("a.json", b"{}");
+        let second = zip.entry("b.json", b"[1]");
+        assert_eq!(&first[..4], b"PK\x03\x04");
+        assert_eq!(first.len(), 30 + 6 + 2);
+        let directory = zip.finish();
+
+        // The end record counts both entries and points at the directory
+        let end = &directory[directory.len() - 22..];
+        assert_eq!(&end[..4], b"PK\x05\x06");
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 2);
+        let directory_offset = u32::from_le_bytes(end[16..20].try_into().unwrap()) as usize;
+        assert_eq!(directory_offset, first.len() + second.len());
+        assert_eq!(&directory[..4], b"PK\x01\x02");
+        // The second central entry records where its local header starts
+        let second_central = &directory[46 + 6..];
+        assert_eq!(u32::from_le_bytes(second_central[42..46].try_into().unwrap()), first.len() as u32);
+    }
+}
@@ -2,15 +2,131 @@ use std::env;
 
 use dotenv::dotenv;
 
+use crate::common::{
+    fees::{BPS_DENOMINATOR, FeeConfig, Lamports},
+    solana_tx::Pubkey,
+};
+
+/// The deployed shadow-drop program (`declare_id!` in contracts/)
+const DEFAULT_PROGRAM_ID: &str = "7wjDqUQUpnudD25MELXBiayNiMrStXaKAdrLMwzccu7v";
+
+/// One of the block engine's published tip accounts
+const DEFAULT_JITO_TIP_ACCOUNT: &str = "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5";
+
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
     pub port: u16,
     pub cors_allowed_origins: Vec<String>,
 }
 
+/// Output format of the log subscriber
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Compact, colored console output for development
+    Pretty,
+    /// One Bunyan-formatted JSON object per line for log aggregators
+    Json,
+}
+
+impl LogFormat {
+    fn parse(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "json" | "bunyan" => Self::Json,
+            _ => Self::Pretty,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LoggingConfig {
     pub level: String,
+    pub format: LogFormat,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthConfig {
+    /// Shared secret for admin-only endpoints. Admin routes are disabled when unset.
+    pub admin_api_key: Option<String>,
+    /// HS256 key of session tokens. Sign-in is disabled when unset.
+    pub jwt_secret: Option<String>,
+    /// Domain sign-in messages must be made for
+    pub siws_domain: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct HashConfig {
+    /// Maximum number of field elements accepted by the sponge hash endpoint
+    pub max_sponge_inputs: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct NullifierConfig {
+    /// Days after a campaign's `close_at` that its spent nullifiers are kept
+    pub retention_days: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CampaignConfig {
+    /// Activate campaigns without checking the creator's on-chain balance
+    pub skip_fund_check: bool,
+    /// Solana JSON-RPC endpoint used for balance checks
+    pub solana_rpc_url: String,
+    /// Consecutive RPC failures that open an endpoint's circuit
+    pub rpc_failure_threshold: u32,
+    /// How long an open circuit refuses RPC calls
+    pub rpc_circuit_open_secs: u64,
+    /// On-chain program that claim transactions are built for
+    pub program_id: Pubkey,
+    /// Reject a recipient's own claim unless the wallet signed it; on unless
+    /// `REQUIRE_CLAIM_SIGNATURE` is `0` or `false`
+    pub require_claim_signature: bool,
+    /// How long a sent claim transaction may take to confirm before it counts as failed
+    pub tx_confirm_timeout_secs: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct JitoConfig {
+    /// Block engine JSON-RPC endpoint; `submit_method=jito` is refused when unset
+    pub rpc_url: Option<String>,
+    /// Tip added to claim transactions built for a bundle
+    pub tip_lamports: Lamports,
+    /// Account the tip is paid to
+    pub tip_account: Pubkey,
+}
+
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    /// Endpoint every recorded claim is POSTed to; no webhooks are sent when unset
+    pub claim_url: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// Redis holding generated proofs; an in-memory map is used when unset
+    pub redis_url: Option<String>,
+    /// Expiry of cached proofs of campaigns without a `close_at`
+    pub default_ttl_secs: u64,
+    /// Campaign Merkle trees kept in memory
+    pub tree_cache_size: usize,
+}
+
+/// How long each route group may take before answering 504
+#[derive(Debug, Clone)]
+pub struct TimeoutConfig {
+    /// `/hash` endpoints
+    pub hash_secs: u64,
+    /// Proof generation
+    pub proof_secs: u64,
+    /// `POST /campaigns/{address}/claim`
+    pub claim_secs: u64,
+    /// Read-only endpoints
+    pub read_secs: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProofConfig {
+    /// Worker tasks computing queued proof jobs
+    pub workers: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -20,6 +136,16 @@ pub struct Config {
 
     pub server: ServerConfig,
     pub logging: LoggingConfig,
+    pub auth: AuthConfig,
+    pub hash: HashConfig,
+    pub nullifier: NullifierConfig,
+    pub fees: FeeConfig,
+    pub campaign: CampaignConfig,
+    pub jito: JitoConfig,
+    pub webhook: WebhookConfig,
+    pub cache: CacheConfig,
+    pub proof: ProofConfig,
+    pub timeouts: TimeoutConfig,
 }
 
 impl Config {
@@ -46,7 +172,117 @@ impl Config {
                     .collect(),
             },
             logging: LoggingConfig {
-                level: env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string()),
+                level: env::var("RUST_LOG")
+                    .or_else(|_| env::var("LOG_LEVEL"))
+                    .unwrap_or_else(|_| "info".to_string()),
+                format: env::var("LOG_FORMAT")
+                    .map(|f| LogFormat::parse(&f))
+                    .unwrap_or(if is_production { LogFormat::Json } else { LogFormat::Pretty }),
+            },
+            auth: AuthConfig {
+                admin_api_key: env::var("ADMIN_API_KEY").ok().filter(|k| !k.is_empty()),
+                jwt_secret: env::var("JWT_SECRET").ok().filter(|k| !k.is_empty()),
+                siws_domain: env::var("SIWS_DOMAIN").unwrap_or_else(|_| "localhost:5173".to_string()),
+            },
+            hash: HashConfig {
+                max_sponge_inputs: env::var("POSEIDON_MAX_INPUTS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(16),
+            },
+            nullifier: NullifierConfig {
+                retention_days: env::var("NULLIFIER_RETENTION_DAYS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(7),
+            },
+            fees: FeeConfig {
+                protocol_fee_bps: env::var("PROTOCOL_FEE_BPS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .filter(|bps: &u16| u64::from(*bps) <= BPS_DENOMINATOR)
+                    .or_else(|| env::var("PROTOCOL_FEE_BPS").is_err().then_some(0))
+                    .expect("PROTOCOL_FEE_BPS must be between 0 and 10000"),
+                min_fee_lamports: env::var("MIN_FEE_LAMPORTS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0),
+                max_fee_lamports: env::var("MAX_FEE_LAMPORTS").ok().and_then(|v| v.parse().ok()),
+            },
+            campaign: CampaignConfig {
+                skip_fund_check: env::var("SKIP_FUND_CHECK")
+                    .map(|v| matches!(v.trim(), "1" | "true"))
+                    .unwrap_or(false),
+                solana_rpc_url: env::var("SOLANA_RPC_URL")
+                    .unwrap_or_else(|_| "https://api.devnet.solana.com".to_string()),
+                rpc_failure_threshold: env::var("RPC_CIRCUIT_FAILURES")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(5),
+                rpc_circuit_open_secs: env::var("RPC_CIRCUIT_OPEN_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(30),
+                program_id: env::var("SHADOW_DROP_PROGRAM_ID")
+                    .unwrap_or_else(|_| DEFAULT_PROGRAM_ID.to_string())
+                    .parse()
+                    .expect("SHADOW_DROP_PROGRAM_ID must be a base58 public key"),
+                require_claim_signature: env::var("REQUIRE_CLAIM_SIGNATURE")
+                    .map(|v| !matches!(v.trim(), "0" | "false"))
+                    .unwrap_or(true),
+                tx_confirm_timeout_secs: env::var("TX_CONFIRM_TIMEOUT_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(60),
+            },
+            jito: JitoConfig {
+                rpc_url: env::var("JITO_RPC_URL").ok().filter(|u| !u.is_empty()),
+                tip_lamports: env::var("JITO_TIP_LAMPORTS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(10_000),
+                tip_account: env::var("JITO_TIP_ACCOUNT")
+                    .unwrap_or_else(|_| DEFAULT_JITO_TIP_ACCOUNT.to_string())
+                    .parse()
+                    .expect("JITO_TIP_ACCOUNT must be a base58 public key"),
+            },
+            webhook: WebhookConfig {
+                claim_url: env::var("CLAIM_WEBHOOK_URL").ok().filter(|u| !u.is_empty()),
+            },
+            cache: CacheConfig {
+                redis_url: env::var("REDIS_URL").ok().filter(|u| !u.is_empty()),
+                default_ttl_secs: env::var("PROOF_CACHE_TTL_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(24 * 60 * 60),
+                tree_cache_size: env::var("TREE_CACHE_SIZE")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(10),
+            },
+            proof: ProofConfig {
+                workers: env::var("PROOF_WORKERS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(4),
+            },
+            timeouts: TimeoutConfig {
+                hash_secs: env::var("HASH_TIMEOUT_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(5),
+                proof_secs: env::var("PROOF_TIMEOUT_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(30),
+                claim_secs: env::var("CLAIM_TIMEOUT_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(10),
+                read_secs: env::var("READ_TIMEOUT_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(5),
             },
         }
     }
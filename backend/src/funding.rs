@@ -0,0 +1,334 @@
+//! Creator balance checks before a campaign goes live, account lookups for
+//! claim transactions, and waiting for those transactions to confirm
+//!
+//! All come from Solana JSON-RPC (`getBalance`, `getAccountInfo`,
+//! `getSignatureStatuses`), one POST sent with `common::http` per call.
+//! Every call goes through the endpoint's `CircuitBreaker`, so an unreachable
+//! node is not retried by each activation while it is down.
+
+use std::time::Duration;
+
+use serde_json::{Value, json};
+use url::Url;
+
+use crate::common::{circuit_breaker::CircuitBreaker, fees::Lamports, http};
+
+/// Allowance on top of the distributed total for vault rent and transaction fees
+pub const ESTIMATED_FEE_LAMPORTS: Lamports = 10_000_000;
+
+/// Connect, read and write timeout of RPC requests
+const RPC_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Wait between `getSignatureStatuses` polls
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, thiserror::Error)]
+pub enum FundingError {
+    #[error("insufficient funds: {available} lamports available, {required} required")]
+    InsufficientFunds { available: Lamports, required: Lamports },
+
+    #[error("Solana RPC request failed: {0}")]
+    Rpc(String),
+
+    /// Recent RPC calls failed and the endpoint's circuit is open
+    #[error("Solana RPC is unavailable, retry in {}s", retry_after_secs(*retry_after))]
+    CircuitOpen { retry_after: Duration },
+
+    /// A transaction did not reach the awaited commitment in time
+    #[error("transaction was not confirmed within {}s", .0.as_secs())]
+    Timeout(Duration),
+}
+
+/// How settled a transaction is, least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Commitment {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+impl Commitment {
+    fn parse(status: &str) -> Option<Self> {
+        match status {
+            "processed" => Some(Self::Processed),
+            "confirmed" => Some(Self::Confirmed),
+            "finalized" => Some(Self::Finalized),
+            _ => None,
+        }
+    }
+}
+
+/// Where a transaction landed and whether it succeeded
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionStatus {
+    pub slot: u64,
+    pub commitment: Commitment,
+    /// The transaction's error; None when it succeeded
+    pub err: Option<Value>,
+}
+
+/// Whole seconds to wait, rounded up, for a `Retry-After` header
+pub fn retry_after_secs(retry_after: Duration) -> u64 {
+    retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0)
+}
+
+/// Check the creator holds `total_lamports` plus `ESTIMATED_FEE_LAMPORTS`
+pub async fn validate_campaign_funds(
+    total_lamports: Lamports,
+    creator_pubkey: &str,
+    rpc_url: &str,
+    breaker: &CircuitBreaker,
+) -> Result<(), FundingError> {
+    let available = get_balance(rpc_url, creator_pubkey, breaker).await?;
+    check_funds(available, total_lamports)
+}
+
+fn check_funds(available: Lamports, total_lamports: Lamports) -> Result<(), FundingError> {
+    let required = total_lamports.saturating_add(ESTIMATED_FEE_LAMPORTS);
+    if available < required {
+        return Err(FundingError::InsufficientFunds { available, required });
+    }
+    Ok(())
+}
+
+/// Lamport balance of an account
+pub async fn get_balance(
+    rpc_url: &str,
+    pubkey: &str,
+    breaker: &CircuitBreaker,
+) -> Result<Lamports, FundingError> {
+    let params = json!([pubkey, { "commitment": "confirmed" }]);
+    rpc_call(rpc_url, "getBalance", params, breaker).await?["value"]
+        .as_u64()
+        .ok_or_else(|| FundingError::Rpc("response has no balance".to_string()))
+}
+
+/// Whether an account exists; `getAccountInfo` answers null for one that does not
+pub async fn account_exists(
+    rpc_url: &str,
+    pubkey: &str,
+    breaker: &CircuitBreaker,
+) -> Result<bool, FundingError> {
+    let params = json!([pubkey, { "commitment": "confirmed", "encoding": "base64" }]);
+    let result = rpc_call(rpc_url, "getAccountInfo", params, breaker).await?;
+    match result.get("value") {
+        Some(Value::Null) => Ok(false),
+        Some(_) => Ok(true),
+        None => Err(FundingError::Rpc("response has no account value".to_string())),
+    }
+}
+
+/// Poll `getSignatureStatuses` every 500ms until `signature` reaches
+/// `commitment`, or `FundingError::Timeout` after `timeout`
+///
+/// A transaction that failed on-chain is returned too, with `err` set. Failed
+/// polls are logged and retried until the timeout.
+pub async fn poll_transaction(
+    rpc_url: &str,
+    signature: &str,
+    commitment: Commitment,
+    timeout: Duration,
+    breaker: &CircuitBreaker,
+) -> Result<TransactionStatus, FundingError> {
+    let params = json!([[signature], { "searchTransactionHistory": false }]);
+    let poll = async {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        let mut attempt = 0u32;
+        loop {
+            interval.tick().await;
+            attempt += 1;
+            let result = match rpc_call(rpc_url, "getSignatureStatuses", params.clone(), breaker).await {
+                Ok(result) => result,
+                Err(e) => {
+                    tracing::warn!(signature, attempt, "Signature status poll failed: {}", e);
+                    continue;
+                }
+            };
+            let slot = result["context"]["slot"].as_u64();
+            let status = &result["value"][0];
+            tracing::debug!(signature, attempt, slot, status = %status, "Polled signature status");
+
+            let reached = status["confirmationStatus"].as_str().and_then(Commitment::parse);
+            if let (Some(reached), Some(landed)) = (reached, status["slot"].as_u64()) {
+                let err = Some(status["err"].clone()).filter(|err| !err.is_null());
+                if reached >= commitment || err.is_some() {
+                    return TransactionStatus {
+                        slot: landed,
+                        commitment: reached,
+                        err,
+                    };
+                }
+            }
+        }
+    };
+    tokio::time::timeout(timeout, poll)
+        .await
+        .map_err(|_| FundingError::Timeout(timeout))
+}
+
+/// The `result` of a JSON-RPC call.
+///
+/// Transport failures and unreadable responses count against the breaker;
+/// a JSON-RPC error answer means the node is up and does not.
+async fn rpc_call(
+    rpc_url: &str,
+    method: &str,
+    params: Value,
+    breaker: &CircuitBreaker,
+) -> Result<Value, FundingError> {
+    let url = Url::parse(rpc_url).map_err(|e| FundingError::Rpc(format!("invalid RPC URL: {}", e)))?;
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    });
+
+    breaker
+        .acquire()
+        .map_err(|retry_after| FundingError::CircuitOpen { retry_after })?;
+    let response = breaker.record(
+        tokio::task::spawn_blocking(move || post_json(&url, &request.to_string()))
+            .await
+            .map_err(|e| FundingError::Rpc(e.to_string()))?
+            .map_err(FundingError::Rpc)
+            .and_then(|body| {
+                serde_json::from_slice::<Value>(&body)
+                    .map_err(|e| FundingError::Rpc(format!("invalid JSON response: {}", e)))
+            }),
+    )?;
+    if let Some(error) = response.get("error") {
+        return Err(FundingError::Rpc(error.to_string()));
+    }
+    Ok(response["result"].clone())
+}
+
+/// Blocking POST returning the response body of a 2xx answer
+fn post_json(url: &Url, body: &str) -> Result<Vec<u8>, String> {
+    let response = http::send("POST", url, &[("Content-Type", "application/json")], Some(body), RPC_TIMEOUT)?;
+    if !(200..300).contains(&response.status) {
+        return Err(format!("RPC returned HTTP {}", response.status));
+    }
+    Ok(response.body)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    #[test]
+    fn test_funds_must_cover_total_and_fees() {
+        assert!(check_funds(1_000 + ESTIMATED_FEE_LAMPORTS, 1_000).is_ok());
+        assert!(matches!(
+            check_funds(1_000, 1_000),
+            Err(FundingError::InsufficientFunds { available: 1_000, required })
+                if required == 1_000 + ESTIMATED_FEE_LAMPORTS
+        ));
+    }
+
+    /// One-shot HTTP server answering with a chunked `getBalance` result
+    async fn serve_balance(balance: u64) -> String {
+        serve_rpc("getBalance", balance.to_string()).await
+    }
+
+    /// One-shot HTTP server answering a `method` call with `value` (JSON)
+    pub async fn serve_rpc(method: &'static str, value: String) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0u8; 4096];
+            let read = socket.read(&mut request).await.unwrap();
+            assert!(String::from_utf8_lossy(&request[..read]).contains(method));
+
+            let body = format!(r#"{{"jsonrpc":"2.0","result":{{"context":{{"slot":1}},"value":{value}}},"id":1}}"#);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{}\r\n0\r\n\r\n",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn test_validate_campaign_funds_reads_balance_over_rpc() {
+        let breaker = CircuitBreaker::new(5, Duration::from_secs(30));
+        let rpc_url = serve_balance(5 * ESTIMATED_FEE_LAMPORTS).await;
+        validate_campaign_funds(ESTIMATED_FEE_LAMPORTS, "creator", &rpc_url, &breaker)
+            .await
+            .unwrap();
+
+        let rpc_url = serve_balance(ESTIMATED_FEE_LAMPORTS).await;
+        assert!(matches!(
+            validate_campaign_funds(1, "creator", &rpc_url, &breaker).await,
+            Err(FundingError::InsufficientFunds { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_account_exists_reads_account_info() {
+        let breaker = CircuitBreaker::new(5, Duration::from_secs(30));
+        let rpc_url = serve_rpc("getAccountInfo", "null".to_string()).await;
+        assert!(!account_exists(&rpc_url, "ata", &breaker).await.unwrap());
+
+        let account = r#"{"data":["","base64"],"executable":false,"lamports":2039280,"owner":"TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA","rentEpoch":0}"#;
+        let rpc_url = serve_rpc("getAccountInfo", account.to_string()).await;
+        assert!(account_exists(&rpc_url, "ata", &breaker).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_poll_transaction_waits_for_the_commitment() {
+        let breaker = CircuitBreaker::new(100, Duration::from_secs(30));
+        let timeout = Duration::from_secs(5);
+        let status = |commitment: &str, err: &str| {
+            format!(r#"[{{"slot":42,"confirmations":null,"err":{err},"confirmationStatus":"{commitment}"}}]"#)
+        };
+
+        let rpc_url = serve_rpc("getSignatureStatuses", status("finalized", "null")).await;
+        let landed = poll_transaction(&rpc_url, "sig", Commitment::Confirmed, timeout, &breaker)
+            .await
+            .unwrap();
+        assert_eq!((landed.slot, landed.commitment, landed.err), (42, Commitment::Finalized, None));
+
+        // A failed transaction is final whatever its commitment
+        let failed = status("processed", r#"{"InstructionError":[0,{"Custom":1}]}"#);
+        let rpc_url = serve_rpc("getSignatureStatuses", failed).await;
+        let landed = poll_transaction(&rpc_url, "sig", Commitment::Finalized, timeout, &breaker)
+            .await
+            .unwrap();
+        assert!(landed.err.is_some());
+
+        // Unknown at first, then the server is gone: polls fail until the timeout
+        let rpc_url = serve_rpc("getSignatureStatuses", "[null]".to_string()).await;
+        let timeout = Duration::from_millis(1200);
+        assert!(matches!(
+            poll_transaction(&rpc_url, "sig", Commitment::Confirmed, timeout, &breaker).await,
+            Err(FundingError::Timeout(t)) if t == timeout
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_rpc_opens_the_circuit() {
+        // Bound and dropped, so nothing listens on the port
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let rpc_url = format!("http://{}", address);
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(30));
+
+        for _ in 0..2 {
+            assert!(matches!(
+                get_balance(&rpc_url, "creator", &breaker).await,
+                Err(FundingError::Rpc(_))
+            ));
+        }
+        assert!(matches!(
+            get_balance(&rpc_url, "creator", &breaker).await,
+            Err(FundingError::CircuitOpen { retry_after }) if retry_after_secs(retry_after) == 30
+        ));
+    }
+}
@@ -1,8 +1,15 @@
 pub mod common;
 pub mod config;
+pub mod funding;
 pub mod logging;
+pub mod metrics;
+pub mod migrations;
 pub mod middleware;
 pub mod models;
+pub mod nullifiers;
+pub mod proof_cache;
 pub mod routes;
 pub mod state;
+pub mod tree_cache;
+pub mod webhooks;
 pub mod zk;
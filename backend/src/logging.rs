@@ -1,11 +1,22 @@
-use tracing::Subscriber;
+use std::io::Write;
+
+use chrono::{SecondsFormat, Utc};
+use serde_json::{Map, Value};
+use tracing::{
+    Event, Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+};
+use tracing_log::NormalizeEvent;
 use tracing_subscriber::{
-    EnvFilter, Registry, fmt,
-    layer::SubscriberExt,
+    EnvFilter, Layer, Registry, fmt,
+    fmt::MakeWriter,
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
     reload::{self, Handle},
 };
 
-use crate::config::Config;
+use crate::config::{Config, LogFormat};
 
 pub type ReloadFilterHandle = Handle<EnvFilter, Registry>;
 
@@ -18,27 +29,211 @@ pub fn setup_subscriber(
     // Create a reloadable layer from the main filter. This allows changing the log level at runtime.
     let (filter_layer, log_reload_handle) = reload::Layer::new(main_filter);
 
-    // ===================================================================
-    // Appender Layers Configuration
-    // ===================================================================
-    // Terminal Appender: For logging to the console (stdout).
-    let terminal_appender_layer = fmt::layer()
-        .with_writer(std::io::stdout) // Direct output to the standard output.
-        .with_ansi(true) // Enable ANSI color codes for pretty, colored logs in the terminal.
-        .with_target(true) // Include the log's target (e.g., module path).
-        .with_file(false) // Don't show file names in terminal for cleaner output
-        .with_line_number(false) // Don't show line numbers in terminal
-        .with_level(true)
-        .compact(); // Compact formatting for cleaner output.
-
     // ===================================================================
     // Subscriber Assembly
     // ===================================================================
     // Combine all layers into a single subscriber registry.
-    // The order matters: filter layer comes first, then the formatters.
-    let registry = tracing_subscriber::registry()
-        .with(filter_layer) // Global filter layer.
-        .with(terminal_appender_layer); // Logs that pass the filter go to the terminal.
+    // The order matters: filter layer comes first, then the formatter.
+    let registry = tracing_subscriber::registry().with(filter_layer); // Global filter layer.
+
+    match config.logging.format {
+        LogFormat::Pretty => {
+            // Terminal Appender: For logging to the console (stdout).
+            let terminal_appender_layer = fmt::layer()
+                .with_writer(std::io::stdout) // Direct output to the standard output.
+                .with_ansi(true) // Enable ANSI color codes for pretty, colored logs in the terminal.
+                .with_target(true) // Include the log's target (e.g., module path).
+                .with_file(false) // Don't show file names in terminal for cleaner output
+                .with_line_number(false) // Don't show line numbers in terminal
+                .with_level(true)
+                .compact(); // Compact formatting for cleaner output.
+
+            (Box::new(registry.with(terminal_appender_layer)), log_reload_handle)
+        }
+        LogFormat::Json => {
+            // JSON Appender: one Bunyan record per line, for log aggregators.
+            let json_appender_layer =
+                BunyanLayer::new(env!("CARGO_PKG_NAME"), std::io::stdout);
+
+            (Box::new(registry.with(json_appender_layer)), log_reload_handle)
+        }
+    }
+}
+
+/// Bunyan numeric level for a tracing level
+fn bunyan_level(level: &tracing::Level) -> u8 {
+    match *level {
+        tracing::Level::TRACE => 10,
+        tracing::Level::DEBUG => 20,
+        tracing::Level::INFO => 30,
+        tracing::Level::WARN => 40,
+        tracing::Level::ERROR => 50,
+    }
+}
+
+/// Formats events as Bunyan JSON records.
+///
+/// Every record carries the Bunyan core fields (`v`, `name`, `msg`, `level`,
+/// `hostname`, `pid`, `time`), the event's own fields and the fields of all
+/// enclosing spans, so values recorded on the request span (e.g. `request_id`,
+/// `campaign_id`) appear on every line logged while handling that request.
+pub struct BunyanLayer<W> {
+    name: String,
+    hostname: String,
+    pid: u32,
+    make_writer: W,
+}
+
+impl<W> BunyanLayer<W>
+where
+    W: for<'a> MakeWriter<'a> + 'static,
+{
+    pub fn new(name: &str, make_writer: W) -> Self {
+        Self {
+            name: name.to_string(),
+            hostname: std::env::var("HOSTNAME").unwrap_or_else(|_| "unknown".to_string()),
+            pid: std::process::id(),
+            make_writer,
+        }
+    }
+}
+
+/// Span fields collected so far, stored in the span's extensions
+struct SpanFields(Map<String, Value>);
+
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), Value::from(format!("{:?}", value)));
+    }
+}
+
+impl<S, W> Layer<S> for BunyanLayer<W>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'a> MakeWriter<'a> + 'static,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut fields = Map::new();
+        attrs.record(&mut JsonVisitor(&mut fields));
+        span.extensions_mut().insert(SpanFields(fields));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        if let Some(SpanFields(fields)) = span.extensions_mut().get_mut::<SpanFields>() {
+            values.record(&mut JsonVisitor(fields));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        // Events bridged from the `log` crate carry their real metadata in fields
+        let normalized = event.normalized_metadata();
+        let metadata = normalized.as_ref().unwrap_or_else(|| event.metadata());
+
+        let mut record = Map::new();
+        record.insert("v".to_string(), Value::from(0));
+        record.insert("name".to_string(), Value::from(self.name.as_str()));
+        record.insert("hostname".to_string(), Value::from(self.hostname.as_str()));
+        record.insert("pid".to_string(), Value::from(self.pid));
+        record.insert("level".to_string(), Value::from(bunyan_level(metadata.level())));
+        record.insert(
+            "time".to_string(),
+            Value::from(Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)),
+        );
+        record.insert("target".to_string(), Value::from(metadata.target()));
+
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                if let Some(SpanFields(fields)) = span.extensions().get::<SpanFields>() {
+                    record.extend(fields.clone());
+                }
+                record.insert("span".to_string(), Value::from(span.name()));
+            }
+        }
+
+        let mut fields = Map::new();
+        event.record(&mut JsonVisitor(&mut fields));
+        let msg = fields.remove("message").unwrap_or_else(|| Value::from(""));
+        record.extend(fields.into_iter().filter(|(k, _)| !k.starts_with("log.")));
+        record.insert("msg".to_string(), msg);
+
+        let mut line = Value::Object(record).to_string();
+        line.push('\n');
+        // Logging must never take the server down; drop the line if stdout is gone
+        let _ = self.make_writer.make_writer().write_all(line.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_bunyan_records_include_span_fields() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::registry()
+            .with(BunyanLayer::new("test-app", move || writer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!(
+                "http_request",
+                request_id = "req-1",
+                campaign_id = tracing::field::Empty
+            );
+            let _guard = span.enter();
+            span.record("campaign_id", "campaign-1");
+            tracing::warn!(leaf_count = 3, "padding tree");
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let record: Value = serde_json::from_str(output.trim()).unwrap();
 
-    (Box::new(registry), log_reload_handle)
+        assert_eq!(record["v"], 0);
+        assert_eq!(record["name"], "test-app");
+        assert_eq!(record["level"], 40);
+        assert_eq!(record["msg"], "padding tree");
+        assert_eq!(record["leaf_count"], 3);
+        assert_eq!(record["request_id"], "req-1");
+        assert_eq!(record["campaign_id"], "campaign-1");
+        assert_eq!(record["span"], "http_request");
+    }
 }
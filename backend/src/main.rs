@@ -1,17 +1,35 @@
-use std::{net::SocketAddr, sync::Arc};
+use std::{sync::Arc, time::Duration};
 
 use axum::{
-    http::{HeaderValue, Method, header},
+    http::{HeaderName, HeaderValue, Method, header},
     middleware,
 };
 
 use shadow_drop_api::{
-    common::server::create_dual_stack_listener, config::Config, logging,
-    middleware::http_trace_middleware::http_trace_middleware, routes::app_routes, state::AppState,
+    common::{
+        field::fr_to_be_bytes,
+        poseidon::{POSEIDON2_T4_FIRST_CONSTANT, check_poseidon2_t4},
+        schema::load_schemas,
+        server::{create_dual_stack_listener, serve_with_graceful_shutdown},
+    },
+    config::Config,
+    logging,
+    models::{ProofJobStore, WebhookDlqStore},
+    middleware::{
+        http_trace_middleware::http_trace_middleware, request_id::REQUEST_ID_HEADER,
+        timeout::RESPONSE_TIME_HEADER,
+    },
+    nullifiers::PgNullifierDB,
+    routes::{TOTAL_COUNT_HEADER, app_routes, spawn_proof_workers},
+    state::AppState,
+    webhooks,
 };
 use sqlx::PgPool;
-use tower_http::cors::CorsLayer;
-use tracing::info;
+use tower_http::{
+    cors::CorsLayer,
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+};
+use tracing::{error, info};
 use tracing_subscriber::util::SubscriberInitExt;
 
 #[tokio::main]
@@ -41,9 +59,41 @@ async fn main() -> std::io::Result<()> {
         .await
         .expect("Failed to run migrations");
 
-    let app_state = AppState::new(config.clone(), pool);
+    load_schemas();
+
+    check_poseidon2_t4().expect("Poseidon2 self-check failed");
+    info!(
+        "✅ Poseidon2 BN254 t4 initialized, first constant = 0x{}",
+        hex::encode(fr_to_be_bytes(POSEIDON2_T4_FIRST_CONSTANT))
+    );
+
+    // Hourly cleanup of nullifiers past their campaign's retention window
+    PgNullifierDB::new(pool.clone(), config.nullifier.retention_days)
+        .spawn_expiry_task(Duration::from_secs(60 * 60));
+
+    // Finished proof jobs are kept for an hour; sweep every five minutes
+    ProofJobStore::new(pool.clone()).spawn_cleanup_task(Duration::from_secs(5 * 60));
+
+    // Dead-lettered claim webhooks are retried once due, polled every minute
+    if let Some(url) = &config.webhook.claim_url {
+        webhooks::spawn_retry_task(WebhookDlqStore::new(pool.clone()), url.clone(), Duration::from_secs(60));
+    }
+
+    let app_state = AppState::new(config.clone(), pool.clone());
     info!("✅ Application state initialized");
 
+    // Until loaded, claims check every nullifier against the database
+    let nullifier_bloom = app_state.nullifier_bloom.clone();
+    tokio::spawn(async move {
+        match nullifier_bloom.load(&pool).await {
+            Ok(count) => info!(count, "✅ Nullifier Bloom filter loaded"),
+            Err(e) => error!(error = %e, "Failed to load nullifier Bloom filter"),
+        }
+    });
+
+    spawn_proof_workers(app_state.clone(), config.proof.workers);
+    info!(workers = config.proof.workers, "✅ Proof workers started");
+
     let allowed_origins: Vec<_> = app_state
         .config
         .server
@@ -58,15 +108,32 @@ async fn main() -> std::io::Result<()> {
 
     let cors = CorsLayer::new()
         .allow_origin(allowed_origins)
-        .allow_methods([Method::GET, Method::POST])
-        .allow_headers([header::ACCEPT, header::CONTENT_TYPE]);
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE])
+        .allow_headers([
+            header::ACCEPT,
+            header::CONTENT_TYPE,
+            header::AUTHORIZATION,
+            HeaderName::from_static("x-idempotency-key"),
+            REQUEST_ID_HEADER,
+        ])
+        .expose_headers([REQUEST_ID_HEADER, RESPONSE_TIME_HEADER, TOTAL_COUNT_HEADER]);
 
     let app = app_routes(app_state.clone())
         .layer(middleware::from_fn(http_trace_middleware))
-        .layer(cors)
-        .into_make_service_with_connect_info::<SocketAddr>();
+        // Echo X-Request-ID on every response, including errors
+        .layer(PropagateRequestIdLayer::x_request_id())
+        // Runs before the trace middleware so the request span can carry the id.
+        // A client-supplied X-Request-ID is kept as is.
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+        .layer(cors);
 
     let listener = create_dual_stack_listener(app_state.config.server.port).await?;
 
-    axum::serve(listener, app).await
+    serve_with_graceful_shutdown(
+        listener,
+        app,
+        app_state.active_requests.clone(),
+        app_state.shutting_down.clone(),
+    )
+    .await
 }
@@ -0,0 +1,308 @@
+//! Prometheus metrics
+//!
+//! A small in-process registry rendered in the Prometheus text exposition
+//! format (version 0.0.4) by `GET /metrics`. Metrics are process globals so
+//! code without access to `AppState`, such as Merkle hashing, can record them.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::Mutex,
+    time::Instant,
+};
+
+/// `Content-Type` of the text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Upper bounds in seconds of the default latency buckets
+pub const DEFAULT_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Buckets for in-memory work that finishes well under a millisecond
+pub const FAST_BUCKETS: &[f64] = &[0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1];
+
+/// Buckets of `PROOF_GEN_LATENCY`, around the 50ms latency target
+pub const PROOF_GEN_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0];
+
+/// Claims handled, by campaign address and `success`, `rejected` or `error`
+pub static CLAIMS_TOTAL: CounterVec = CounterVec::new(
+    "shadow_drop_claims_total",
+    "Claims handled by campaign and outcome",
+    &["campaign_id", "status"],
+);
+
+/// Time to build a Merkle proof from a tree
+pub static PROOF_GENERATION_DURATION: HistogramVec = HistogramVec::new(
+    "shadow_drop_proof_generation_duration_seconds",
+    "Time to generate a Merkle proof",
+    &[],
+    FAST_BUCKETS,
+);
+
+/// Latency of every proof lookup, by tree depth and whether the proof came
+/// from a cache (`true`) or was built from the tree (`false`)
+pub static PROOF_GEN_LATENCY: HistogramVec = HistogramVec::new(
+    "shadow_drop_proof_gen_latency_seconds",
+    "Proof generation latency by tree depth and cache use",
+    &["depth", "cached"],
+    PROOF_GEN_BUCKETS,
+);
+
+/// Poseidon hashes computed, by number of inputs
+pub static HASH_REQUESTS_TOTAL: CounterVec = CounterVec::new(
+    "shadow_drop_hash_requests_total",
+    "Poseidon hashes computed by number of inputs",
+    &["inputs"],
+);
+
+/// Database query latency, by store operation
+pub static DB_QUERY_DURATION: HistogramVec = HistogramVec::new(
+    "shadow_drop_db_query_duration_seconds",
+    "Database query latency by operation",
+    &["operation"],
+    DEFAULT_BUCKETS,
+);
+
+/// Early nullifier checks on claims, by `spent`, `unspent` or `filtered`
+/// (skipped because the Bloom filter has not seen the nullifier)
+pub static NULLIFIER_CHECKS_TOTAL: CounterVec = CounterVec::new(
+    "shadow_drop_nullifier_checks_total",
+    "Nullifier checks on incoming claims by result",
+    &["result"],
+);
+
+/// Counters sharing a name, one per label value combination
+#[derive(Debug)]
+pub struct CounterVec {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    values: Mutex<BTreeMap<Vec<String>, u64>>,
+}
+
+impl CounterVec {
+    pub const fn new(name: &'static str, help: &'static str, labels: &'static [&'static str]) -> Self {
+        Self {
+            name,
+            help,
+            labels,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Add one to the counter of `values`, given in the order of the label names
+    pub fn inc(&self, values: &[&str]) {
+        self.inc_by(values, 1);
+    }
+
+    /// Add `count` to the counter of `values`
+    pub fn inc_by(&self, values: &[&str], count: u64) {
+        debug_assert_eq!(values.len(), self.labels.len(), "{} label count", self.name);
+        let key = values.iter().map(ToString::to_string).collect();
+        *self.values.lock().unwrap().entry(key).or_default() += count;
+    }
+
+    /// Current value of the counter of `values`
+    pub fn get(&self, values: &[&str]) -> u64 {
+        let key: Vec<String> = values.iter().map(ToString::to_string).collect();
+        self.values.lock().unwrap().get(&key).copied().unwrap_or(0)
+    }
+
+    fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "counter");
+        for (values, count) in self.values.lock().unwrap().iter() {
+            let _ = writeln!(out, "{}{} {}", self.name, label_set(self.labels, values, None), count);
+        }
+    }
+}
+
+/// Histograms sharing a name and buckets, one per label value combination
+#[derive(Debug)]
+pub struct HistogramVec {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    buckets: &'static [f64],
+    series: Mutex<BTreeMap<Vec<String>, Series>>,
+}
+
+/// Observations of one histogram, with non-cumulative bucket counts
+#[derive(Debug, Default)]
+struct Series {
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl HistogramVec {
+    pub const fn new(
+        name: &'static str,
+        help: &'static str,
+        labels: &'static [&'static str],
+        buckets: &'static [f64],
+    ) -> Self {
+        Self {
+            name,
+            help,
+            labels,
+            buckets,
+            series: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Record a duration of `seconds` for `values`
+    pub fn observe(&self, values: &[&str], seconds: f64) {
+        debug_assert_eq!(values.len(), self.labels.len(), "{} label count", self.name);
+        let key = values.iter().map(ToString::to_string).collect();
+        let mut series = self.series.lock().unwrap();
+        let series = series.entry(key).or_insert_with(|| Series {
+            buckets: vec![0; self.buckets.len()],
+            ..Series::default()
+        });
+        if let Some(bucket) = self.buckets.iter().position(|le| seconds <= *le) {
+            series.buckets[bucket] += 1;
+        }
+        series.sum += seconds;
+        series.count += 1;
+    }
+
+    /// Record the time elapsed since `start` for `values`
+    pub fn observe_since(&self, values: &[&str], start: Instant) {
+        self.observe(values, start.elapsed().as_secs_f64());
+    }
+
+    /// Number of observations recorded for `values`
+    pub fn count(&self, values: &[&str]) -> u64 {
+        let key: Vec<String> = values.iter().map(ToString::to_string).collect();
+        self.series.lock().unwrap().get(&key).map_or(0, |series| series.count)
+    }
+
+    fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "histogram");
+        for (values, series) in self.series.lock().unwrap().iter() {
+            let mut cumulative = 0;
+            for (le, count) in self.buckets.iter().zip(&series.buckets) {
+                cumulative += count;
+                let labels = label_set(self.labels, values, Some(&le.to_string()));
+                let _ = writeln!(out, "{}_bucket{} {}", self.name, labels, cumulative);
+            }
+            let labels = label_set(self.labels, values, Some("+Inf"));
+            let _ = writeln!(out, "{}_bucket{} {}", self.name, labels, series.count);
+            let labels = label_set(self.labels, values, None);
+            let _ = writeln!(out, "{}_sum{} {}", self.name, labels, series.sum);
+            let _ = writeln!(out, "{}_count{} {}", self.name, labels, series.count);
+        }
+    }
+}
+
+/// Record a proof served `start`-to-now from a tree of `depth`
+pub fn observe_proof_gen(depth: usize, cached: bool, start: Instant) {
+    PROOF_GEN_LATENCY.observe_since(&[&depth.to_string(), if cached { "true" } else { "false" }], start);
+}
+
+/// Record `count` uncached proofs from a tree of `depth`, generated together
+/// `start`-to-now, each taking an equal share of the time
+pub fn observe_merkle_proofs(depth: usize, count: usize, start: Instant) {
+    let seconds = start.elapsed().as_secs_f64() / count.max(1) as f64;
+    for _ in 0..count {
+        PROOF_GENERATION_DURATION.observe(&[], seconds);
+        PROOF_GEN_LATENCY.observe(&[&depth.to_string(), "false"], seconds);
+    }
+}
+
+/// Count the Poseidon leaf hashes (3 inputs each) of a tree built from
+/// `leaves` recipients
+pub fn observe_leaf_hashes(leaves: usize) {
+    HASH_REQUESTS_TOTAL.inc_by(&["3"], leaves as u64);
+}
+
+/// Await `query`, recording its latency under `operation`
+pub async fn time_db<T>(operation: &str, query: impl Future<Output = T>) -> T {
+    let start = Instant::now();
+    let result = query.await;
+    DB_QUERY_DURATION.observe_since(&[operation], start);
+    result
+}
+
+/// Every metric in the text exposition format
+pub fn render() -> String {
+    let mut out = String::new();
+    CLAIMS_TOTAL.render(&mut out);
+    PROOF_GENERATION_DURATION.render(&mut out);
+    PROOF_GEN_LATENCY.render(&mut out);
+    HASH_REQUESTS_TOTAL.render(&mut out);
+    DB_QUERY_DURATION.render(&mut out);
+    NULLIFIER_CHECKS_TOTAL.render(&mut out);
+    out
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// `{name="value",...}`, with an optional trailing `le` bucket label
+fn label_set(names: &[&str], values: &[String], le: Option<&str>) -> String {
+    let pairs: Vec<String> = names
+        .iter()
+        .zip(values)
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape(value)))
+        .chain(le.map(|le| format!("le=\"{}\"", le)))
+        .collect();
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+/// Escape a label value as the exposition format requires
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters_render_per_label_set() {
+        let counter = CounterVec::new("test_total", "Test counter", &["result"]);
+        counter.inc(&["spent"]);
+        counter.inc(&["spent"]);
+        counter.inc(&["un\"spent"]);
+
+        let mut out = String::new();
+        counter.render(&mut out);
+        assert_eq!(
+            out,
+            "# HELP test_total Test counter\n\
+             # TYPE test_total counter\n\
+             test_total{result=\"spent\"} 2\n\
+             test_total{result=\"un\\\"spent\"} 1\n"
+        );
+    }
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let histogram = HistogramVec::new("test_seconds", "Test histogram", &[], &[0.1, 1.0]);
+        histogram.observe(&[], 0.05);
+        histogram.observe(&[], 0.5);
+        histogram.observe(&[], 5.0);
+
+        let mut out = String::new();
+        histogram.render(&mut out);
+        assert!(out.contains("test_seconds_bucket{le=\"0.1\"} 1\n"));
+        assert!(out.contains("test_seconds_bucket{le=\"1\"} 2\n"));
+        assert!(out.contains("test_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(out.contains("test_seconds_sum 5.55\n"));
+        assert!(out.contains("test_seconds_count 3\n"));
+    }
+
+    #[test]
+    fn test_proof_lookups_are_labelled_by_depth_and_cache() {
+        let before = PROOF_GEN_LATENCY.count(&["8", "true"]);
+        observe_proof_gen(8, true, Instant::now());
+        assert!(PROOF_GEN_LATENCY.count(&["8", "true"]) > before);
+        assert!(render().contains("shadow_drop_proof_gen_latency_seconds_bucket{depth=\"8\",cached=\"true\",le=\"0.05\"}"));
+    }
+}
@@ -0,0 +1,38 @@
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts},
+};
+
+use crate::{common::error::ShadowDropError, state::AppState};
+
+/// Extractor guarding admin-only handlers.
+///
+/// Requires an `Authorization: Bearer <ADMIN_API_KEY>` header. When no admin
+/// key is configured, every admin request is rejected.
+pub struct AdminAuth;
+
+impl FromRequestParts<AppState> for AdminAuth {
+    type Rejection = ShadowDropError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let Some(expected) = state.config.auth.admin_api_key.as_deref() else {
+            return Err(ShadowDropError::Forbidden(
+                "Admin endpoints are disabled".to_string(),
+            ));
+        };
+
+        let provided = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+
+        match provided {
+            Some(token) if token == expected => Ok(AdminAuth),
+            _ => Err(ShadowDropError::Unauthorized),
+        }
+    }
+}
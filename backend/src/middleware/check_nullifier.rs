@@ -0,0 +1,93 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{Body, to_bytes},
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    common::{
+        error::ShadowDropError,
+        merkle::{parse_hash, round_nullifier},
+    },
+    metrics,
+    middleware::json_schema::MAX_BODY_BYTES,
+    nullifiers::{DBError, NullifierBloomFilter, NullifierDB},
+};
+
+/// The only fields of a claim body this layer looks at
+#[derive(Deserialize)]
+struct NullifierField {
+    nullifier_hash: Option<String>,
+    round_id: Option<String>,
+}
+
+/// Route middleware rejecting claims whose `nullifier_hash` is already spent,
+/// with 409 and without running the handler. Round claims are checked
+/// against their round-scoped nullifier (`round_nullifier`).
+///
+/// This is an early reject only: the handler still spends the nullifier
+/// atomically, which is what closes the race between concurrent claims.
+/// Nullifiers the Bloom filter has not seen skip the lookup, and successful
+/// claims add theirs to it. Requests carrying an idempotency key
+/// (`idempotency_header`) pass through so a retry of a successful claim can
+/// replay its stored response.
+///
+/// Attach per route with
+/// `middleware::from_fn_with_state((nullifiers, bloom, IDEMPOTENCY_KEY_HEADER), check_nullifier)`.
+pub async fn check_nullifier(
+    State((nullifiers, bloom, idempotency_header)): State<(
+        Arc<dyn NullifierDB>,
+        NullifierBloomFilter,
+        &'static str,
+    )>,
+    req: Request,
+    next: Next,
+) -> Result<Response, ShadowDropError> {
+    if req.headers().contains_key(idempotency_header) {
+        return Ok(next.run(req).await);
+    }
+
+    let (parts, body) = req.into_parts();
+    let bytes = to_bytes(body, MAX_BODY_BYTES)
+        .await
+        .map_err(|e| ShadowDropError::BadRequest(format!("Request body could not be read: {}", e)))?;
+
+    // Malformed bodies and hashes are left for the handler to report
+    let nullifier = serde_json::from_slice::<NullifierField>(&bytes)
+        .ok()
+        .and_then(|field| {
+            let nullifier = parse_hash(field.nullifier_hash.as_deref()?)?;
+            match field.round_id {
+                Some(round_id) => {
+                    let round_id = Uuid::parse_str(&round_id).ok()?;
+                    Some(round_nullifier(&nullifier, round_id.as_bytes()))
+                }
+                None => Some(nullifier),
+            }
+        });
+
+    if let Some(nullifier) = nullifier {
+        if !bloom.probably_spent(&nullifier) {
+            metrics::NULLIFIER_CHECKS_TOTAL.inc(&["filtered"]);
+        } else {
+            let spent = nullifiers.is_spent(&nullifier).await?;
+            metrics::NULLIFIER_CHECKS_TOTAL.inc(&[if spent { "spent" } else { "unspent" }]);
+            if spent {
+                return Err(DBError::AlreadySpent(nullifier).into());
+            }
+        }
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(bytes))).await;
+    if let Some(nullifier) = nullifier
+        && response.status().is_success()
+    {
+        bloom.mark_spent(&nullifier);
+    }
+    Ok(response)
+}
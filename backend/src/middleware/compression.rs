@@ -0,0 +1,171 @@
+use axum::{
+    body::{Body, HttpBody, to_bytes},
+    extract::Request,
+    http::{HeaderMap, HeaderValue, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::common::{gzip::gzip, response::ApiErrorResponse};
+
+/// Bodies smaller than this are sent as they are
+const MIN_COMPRESS_BYTES: u64 = 1024;
+
+/// Router middleware gzipping response bodies of at least 1 KiB for clients
+/// that accept it (`Accept-Encoding: gzip`).
+///
+/// Only bodies of known length are compressed: streamed responses such as
+/// event streams and ZIP downloads pass through, as do responses that already
+/// have a `Content-Encoding`.
+pub async fn compress_response(req: Request, next: Next) -> Response {
+    let accepts_gzip = accepts_gzip(req.headers());
+    let mut response = next.run(req).await;
+    let compressible = response
+        .body()
+        .size_hint()
+        .exact()
+        .is_some_and(|len| len >= MIN_COMPRESS_BYTES)
+        && !response.headers().contains_key(header::CONTENT_ENCODING);
+    if !compressible {
+        return response;
+    }
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept-encoding"));
+    if !accepts_gzip {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = to_bytes(body, usize::MAX).await else {
+        return ApiErrorResponse::default()
+            .with_message("Response body could not be read")
+            .into_response();
+    };
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+    Response::from_parts(parts, Body::from(gzip(&bytes)))
+}
+
+/// Whether `Accept-Encoding` allows gzip, directly or through `*`
+fn accepts_gzip(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|coding| {
+            let mut params = coding.split(';');
+            let name = params.next().unwrap_or_default().trim();
+            let refused = params.any(|param| {
+                param.trim().strip_prefix("q=").and_then(|q| q.trim().parse::<f32>().ok()) == Some(0.0)
+            });
+            (name.eq_ignore_ascii_case("gzip") || name == "*") && !refused
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Json, Router, http::StatusCode, middleware, routing::get};
+    use serde_json::{Value, json};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{
+        common::merkle::MerkleTree,
+        routes::{app_routes, test_helpers::test_state},
+    };
+
+    /// Every proof of a 256-leaf tree, as a bulk proof response lists them
+    fn proofs_json() -> Value {
+        let recipients: Vec<_> = (0..256u32)
+            .map(|i| (format!("wallet-{i}"), u64::from(i) + 1, [(i % 251) as u8; 32]))
+            .collect();
+        let tree = MerkleTree::from_recipients(&recipients).unwrap();
+        let proofs: Vec<_> = tree
+            .batch_proofs()
+            .into_iter()
+            .map(|(wallet, proof)| {
+                json!({
+                    "wallet": wallet,
+                    "leaf_index": proof.leaf_index,
+                    "leaf": hex::encode(proof.leaf),
+                    "siblings": proof.siblings.iter().map(hex::encode).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        json!({ "root": hex::encode(tree.root()), "proofs": proofs })
+    }
+
+    #[test]
+    fn test_accept_encoding() {
+        let accepts = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::ACCEPT_ENCODING, HeaderValue::from_str(value).unwrap());
+            accepts_gzip(&headers)
+        };
+        assert!(accepts("gzip"));
+        assert!(accepts("br;q=1.0, GZIP;q=0.5"));
+        assert!(accepts("*"));
+        assert!(!accepts("gzip;q=0"));
+        assert!(!accepts("br, zstd"));
+        assert!(!accepts_gzip(&HeaderMap::new()));
+    }
+
+    #[tokio::test]
+    async fn test_large_bodies_are_gzipped() {
+        let proofs = proofs_json();
+        let app = Router::new()
+            .route("/proofs", get(move || async move { Json(proofs) }))
+            .route("/small", get(|| async { Json(json!({ "ok": true })) }))
+            .layer(middleware::from_fn(compress_response));
+        let request = |path: &str, encoding: Option<&str>| {
+            let mut request = Request::get(path);
+            if let Some(encoding) = encoding {
+                request = request.header(header::ACCEPT_ENCODING, encoding);
+            }
+            request.body(Body::empty()).unwrap()
+        };
+
+        let plain = app.clone().oneshot(request("/proofs", None)).await.unwrap();
+        assert!(!plain.headers().contains_key(header::CONTENT_ENCODING));
+        assert_eq!(plain.headers()[header::VARY], "accept-encoding");
+        let plain = to_bytes(plain.into_body(), usize::MAX).await.unwrap();
+
+        let gzipped = app.clone().oneshot(request("/proofs", Some("gzip, br"))).await.unwrap();
+        assert_eq!(gzipped.status(), StatusCode::OK);
+        assert_eq!(gzipped.headers()[header::CONTENT_ENCODING], "gzip");
+        let gzipped = to_bytes(gzipped.into_body(), usize::MAX).await.unwrap();
+        // Repeated keys and upper-level siblings make up most of the body
+        assert!(
+            gzipped.len() * 2 < plain.len(),
+            "{} bytes gzipped from {}",
+            gzipped.len(),
+            plain.len()
+        );
+
+        let small = app.oneshot(request("/small", Some("gzip"))).await.unwrap();
+        assert!(!small.headers().contains_key(header::CONTENT_ENCODING));
+    }
+
+    #[sqlx::test]
+    async fn test_metrics_are_not_compressed(pool: PgPool) {
+        let app = app_routes(test_state(pool));
+        let request = |path: &str| {
+            Request::get(path)
+                .header(header::ACCEPT_ENCODING, "gzip")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let docs = app.clone().oneshot(request("/openapi.json")).await.unwrap();
+        assert_eq!(docs.headers()[header::CONTENT_ENCODING], "gzip");
+        let metrics = app.oneshot(request("/metrics")).await.unwrap();
+        assert_eq!(metrics.status(), StatusCode::OK);
+        assert!(!metrics.headers().contains_key(header::CONTENT_ENCODING));
+        assert!(!metrics.headers().contains_key(header::VARY));
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use axum::{
+    extract::{Path, Request, State},
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::state::AppState;
+
+/// `ETag` and `Cache-Control` of responses that never change, by campaign address
+#[derive(Debug, Clone, Default)]
+pub struct EtagCache {
+    inner: Arc<RwLock<HashMap<String, (HeaderValue, HeaderValue)>>>,
+}
+
+impl EtagCache {
+    pub fn get(&self, address: &str) -> Option<(HeaderValue, HeaderValue)> {
+        self.inner.read().unwrap().get(address).cloned()
+    }
+
+    pub fn insert(&self, address: &str, etag: HeaderValue, cache_control: HeaderValue) {
+        self.inner
+            .write()
+            .unwrap()
+            .insert(address.to_string(), (etag, cache_control));
+    }
+
+    /// Forget a campaign's validators, e.g. after a round replaces its root
+    pub fn remove(&self, address: &str) {
+        self.inner.write().unwrap().remove(address);
+    }
+}
+
+/// Route middleware answering `If-None-Match` requests for a campaign's root
+/// with 304 and no body when the client's copy is current.
+///
+/// The handler sets the `ETag`. Responses marked `immutable` are remembered
+/// in `AppState::root_etags`, so revalidating them does not reach the handler
+/// or the database.
+pub async fn conditional_root(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let if_none_match = req
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned);
+    if let Some(if_none_match) = &if_none_match
+        && let Some((etag, cache_control)) = state.root_etags.get(&address)
+        && etag_matches(if_none_match, &etag)
+    {
+        return not_modified(etag, Some(cache_control));
+    }
+
+    let response = next.run(req).await;
+    let Some(etag) = response.headers().get(header::ETAG).cloned() else {
+        return response;
+    };
+    let cache_control = response.headers().get(header::CACHE_CONTROL).cloned();
+    if let Some(cache_control) = &cache_control
+        && response.status().is_success()
+        && cache_control.to_str().is_ok_and(|value| value.contains("immutable"))
+    {
+        state.root_etags.insert(&address, etag.clone(), cache_control.clone());
+    }
+    match if_none_match {
+        Some(if_none_match) if etag_matches(&if_none_match, &etag) => not_modified(etag, cache_control),
+        _ => response,
+    }
+}
+
+/// Weak comparison (RFC 9110 §13.1.2) of `etag` with an `If-None-Match` list
+fn etag_matches(if_none_match: &str, etag: &HeaderValue) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_owned();
+    let Ok(etag) = etag.to_str() else {
+        return false;
+    };
+    if_none_match.trim() == "*" || if_none_match.split(',').any(|tag| opaque(tag) == opaque(etag))
+}
+
+fn not_modified(etag: HeaderValue, cache_control: Option<HeaderValue>) -> Response {
+    let mut response = StatusCode::NOT_MODIFIED.into_response();
+    response.headers_mut().insert(header::ETAG, etag);
+    if let Some(cache_control) = cache_control {
+        response.headers_mut().insert(header::CACHE_CONTROL, cache_control);
+    }
+    response
+}
@@ -0,0 +1,28 @@
+use axum::{
+    extract::Request,
+    http::{StatusCode, header},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::common::response::ApiErrorResponse;
+
+/// Route middleware answering 415 unless the request declares a JSON body
+/// (`application/json`, parameters such as `charset` allowed).
+///
+/// Attach per route with `middleware::from_fn(require_json)`, outside any
+/// body validation so other payloads are never parsed.
+pub async fn require_json(req: Request, next: Next) -> Result<Response, ApiErrorResponse> {
+    let is_json = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("application/json"));
+    if !is_json {
+        return Err(ApiErrorResponse::default()
+            .with_code(StatusCode::UNSUPPORTED_MEDIA_TYPE)
+            .with_message("Content-Type must be application/json"));
+    }
+    Ok(next.run(req).await)
+}
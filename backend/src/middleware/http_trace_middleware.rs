@@ -9,8 +9,9 @@ use axum::{
     middleware::Next,
     response::Response,
 };
+use tower_http::request_id::RequestId;
 use tracing::{
-    Instrument, debug, info_span,
+    Instrument, debug, field, info_span,
     log::{Level, log},
 };
 
@@ -83,6 +84,12 @@ pub async fn http_trace_middleware(
     let uri = req.uri().clone();
     let version = req.version();
     let client_info = ClientInfo::extract(&req);
+    let request_id = req
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .unwrap_or_default()
+        .to_string();
 
     // Create a span that will wrap the entire request-response lifecycle.
     let span = info_span!(
@@ -90,7 +97,10 @@ pub async fn http_trace_middleware(
         method = %method,
         uri = %uri.path(),
         version = ?version,
-        cliet_ip = %client_ip
+        cliet_ip = %client_ip,
+        request_id = %request_id,
+        // Recorded by campaign handlers once the path is extracted
+        campaign_id = field::Empty
     );
 
     // The `.instrument()` call is crucial. It ensures that any log created
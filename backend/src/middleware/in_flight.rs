@@ -0,0 +1,66 @@
+use std::sync::atomic::Ordering;
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{common::response::ApiErrorResponse, state::AppState};
+
+/// Decrements the active request count when the request ends, even if its
+/// future is dropped
+struct ActiveRequest<'a>(&'a AppState);
+
+impl Drop for ActiveRequest<'_> {
+    fn drop(&mut self) {
+        self.0.active_requests.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Router middleware counting requests in `AppState::active_requests`, so
+/// shutdown can wait for them. Once `shutting_down` is set, requests still
+/// arriving on open connections get 503 instead of starting work.
+pub async fn track_in_flight(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, ApiErrorResponse> {
+    if state.shutting_down.load(Ordering::Acquire) {
+        return Err(ApiErrorResponse::default()
+            .with_code(StatusCode::SERVICE_UNAVAILABLE)
+            .with_message("Server is shutting down"));
+    }
+    state.active_requests.fetch_add(1, Ordering::AcqRel);
+    let _active = ActiveRequest(&state);
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::routes::{
+        app_routes,
+        test_helpers::{send, test_state},
+    };
+
+    #[sqlx::test]
+    async fn test_requests_are_refused_once_shutting_down(pool: PgPool) {
+        let state = test_state(pool);
+        let app = app_routes(state.clone());
+        let request = || Request::get("/api/v1/does-not-exist").body(Body::empty()).unwrap();
+
+        let (status, _) = send(&app, request()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(state.active_requests.load(Ordering::Acquire), 0);
+
+        state.shutting_down.store(true, Ordering::Release);
+        let (status, body) = send(&app, request()).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["errors"]["message"], "Server is shutting down");
+    }
+}
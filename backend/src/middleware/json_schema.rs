@@ -0,0 +1,47 @@
+use axum::{
+    body::{Body, to_bytes},
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use serde_json::Value;
+
+use crate::common::{response::ApiErrorResponse, schema::JsonSchema};
+
+/// Largest request body buffered for validation (2 MiB)
+pub(crate) const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Route middleware validating the JSON body against a schema before the handler runs.
+///
+/// Attach per route with
+/// `middleware::from_fn_with_state(&*SOME_SCHEMA, validate_json_body)`.
+pub async fn validate_json_body(
+    State(schema): State<&'static JsonSchema>,
+    req: Request,
+    next: Next,
+) -> Result<Response, ApiErrorResponse> {
+    let (parts, body) = req.into_parts();
+    let bytes = to_bytes(body, MAX_BODY_BYTES).await.map_err(|e| {
+        ApiErrorResponse::default()
+            .with_code(StatusCode::PAYLOAD_TOO_LARGE)
+            .with_message("Request body could not be read")
+            .with_details(e.to_string())
+    })?;
+
+    let value: Value = serde_json::from_slice(&bytes).map_err(|e| {
+        ApiErrorResponse::default()
+            .with_code(StatusCode::BAD_REQUEST)
+            .with_message("Request body is not valid JSON")
+            .with_details(e.to_string())
+    })?;
+
+    schema.validate(&value).map_err(|errors| {
+        ApiErrorResponse::default()
+            .with_code(StatusCode::BAD_REQUEST)
+            .with_message("Request body failed validation")
+            .with_validation_errors(errors)
+    })?;
+
+    Ok(next.run(Request::from_parts(parts, Body::from(bytes))).await)
+}
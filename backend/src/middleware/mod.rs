@@ -1 +1,11 @@
+pub mod admin_auth;
+pub mod check_nullifier;
+pub mod compression;
+pub mod conditional_get;
+pub mod content_type;
 pub mod http_trace_middleware;
+pub mod in_flight;
+pub mod json_schema;
+pub mod rate_limit;
+pub mod request_id;
+pub mod timeout;
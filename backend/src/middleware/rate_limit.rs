@@ -0,0 +1,93 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::common::error::ShadowDropError;
+
+/// Tracked clients above which expired windows are swept
+const SWEEP_THRESHOLD: usize = 10_000;
+
+/// Fixed-window request counter per client IP
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: u32,
+    window: Duration,
+    windows: Mutex<HashMap<IpAddr, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    /// Allow `limit` requests per client in every `window`
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count a request from `client`; false once it is over the limit
+    pub fn check(&self, client: IpAddr) -> bool {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap();
+        if windows.len() > SWEEP_THRESHOLD {
+            windows.retain(|_, (started, _)| now.duration_since(*started) < self.window);
+        }
+
+        let (started, count) = windows.entry(client).or_insert((now, 0));
+        if now.duration_since(*started) >= self.window {
+            *started = now;
+            *count = 0;
+        }
+        *count += 1;
+        *count <= self.limit
+    }
+}
+
+/// Route middleware answering 429 once a client IP exceeds the limiter.
+///
+/// Clients are told apart by `ConnectInfo`; requests without it (e.g. in
+/// tests) share one bucket.
+///
+/// Attach per route with `middleware::from_fn_with_state(Arc::new(RateLimiter::new(..)), rate_limit)`.
+pub async fn rate_limit(
+    State(limiter): State<Arc<RateLimiter>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, ShadowDropError> {
+    let client = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED), |info| info.0.ip());
+    if !limiter.check(client) {
+        return Err(ShadowDropError::RateLimit);
+    }
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_applies_per_client_and_window() {
+        let limiter = RateLimiter::new(2, Duration::from_millis(50));
+        let (a, b) = (IpAddr::from([10, 0, 0, 1]), IpAddr::from([10, 0, 0, 2]));
+
+        assert!(limiter.check(a));
+        assert!(limiter.check(a));
+        assert!(!limiter.check(a));
+        assert!(limiter.check(b));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(limiter.check(a));
+    }
+}
@@ -0,0 +1,9 @@
+use axum::http::{HeaderMap, HeaderName};
+
+/// Correlation id header, set by `SetRequestIdLayer` (or the client) and echoed on responses
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Read the correlation id of the current request
+pub fn request_id(headers: &HeaderMap) -> Option<&str> {
+    headers.get(REQUEST_ID_HEADER).and_then(|v| v.to_str().ok())
+}
@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use tokio::time::Instant;
+
+use crate::common::response::ApiErrorResponse;
+
+/// Milliseconds the router spent on the request
+pub const RESPONSE_TIME_HEADER: HeaderName = HeaderName::from_static("x-response-time");
+
+/// Route middleware answering 504 once the rest of the stack has run for
+/// `timeout`; the handler is dropped where it stands.
+///
+/// Attach per route group with
+/// `middleware::from_fn_with_state(Duration::from_secs(secs), request_timeout)`.
+pub async fn request_timeout(State(timeout): State<Duration>, req: Request, next: Next) -> Response {
+    let (method, path) = (req.method().clone(), req.uri().path().to_owned());
+    match tokio::time::timeout(timeout, next.run(req)).await {
+        Ok(response) => response,
+        Err(_) => {
+            tracing::warn!(%method, path, timeout_secs = timeout.as_secs(), "⏱️ Request timed out");
+            ApiErrorResponse::default()
+                .with_code(StatusCode::GATEWAY_TIMEOUT)
+                .with_message("request_timeout")
+                .with_details(format!("timeout_secs: {}", timeout.as_secs()))
+                .into_response()
+        }
+    }
+}
+
+/// `request_timeout` for GET and HEAD requests; other methods are not limited
+pub async fn read_timeout(State(timeout): State<Duration>, req: Request, next: Next) -> Response {
+    if matches!(*req.method(), Method::GET | Method::HEAD) {
+        request_timeout(State(timeout), req, next).await
+    } else {
+        next.run(req).await
+    }
+}
+
+/// Router middleware setting `X-Response-Time` on every response
+pub async fn response_time(req: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let mut response = next.run(req).await;
+    let millis = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+    response
+        .headers_mut()
+        .insert(RESPONSE_TIME_HEADER, HeaderValue::from(millis));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, body::Body, middleware, routing::get};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_slow_handler_times_out() {
+        tokio::time::pause();
+        let app = Router::new()
+            .route("/slow", get(|| tokio::time::sleep(Duration::from_secs(60))))
+            .route("/fast", get(|| async {}))
+            .layer(middleware::from_fn_with_state(Duration::from_secs(5), request_timeout))
+            .layer(middleware::from_fn(response_time));
+        let request = |path: &str| Request::get(path).body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(request("/slow")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        let elapsed: u64 = response.headers()[RESPONSE_TIME_HEADER].to_str().unwrap().parse().unwrap();
+        // The paused clock rounds timers up to the next millisecond
+        assert!((5000..5010).contains(&elapsed), "{elapsed} ms");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["errors"]["message"], "request_timeout");
+        assert_eq!(body["errors"]["details"], "timeout_secs: 5");
+
+        let response = app.oneshot(request("/fast")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[RESPONSE_TIME_HEADER], "0");
+    }
+}
@@ -0,0 +1,263 @@
+//! Database migration commands
+//!
+//! Shared by the `migrate` tool and `shadow-drop-cli migrate`:
+//!
+//! - `run` (default): apply all pending migrations
+//! - `rollback [N]` / `rollback --steps N`: revert the N most recent migrations (default 1)
+//! - `status`: show which migrations are applied
+//! - `list`: show all migrations bundled from `./migrations`
+//! - `check`: show what `run` would apply, without applying it
+//!
+//! `--database-url URL` overrides `DATABASE_URL`. Results are printed to stdout.
+
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+};
+
+use sqlx::{PgPool, migrate::Migrator};
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Arguments accepted after the tool name
+pub const USAGE: &str = "[run | rollback [N | --steps N] | status | list | check] [--database-url URL]";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MigrateCommand {
+    Run,
+    Rollback(usize),
+    Status,
+    List,
+    Check,
+}
+
+/// Parse the command and the `--database-url` override, which may come anywhere
+pub fn parse_args(args: &[String]) -> Result<(MigrateCommand, Option<String>), String> {
+    let mut database_url = None;
+    let mut words = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--database-url" => {
+                let url = args.next().ok_or("--database-url needs a value")?;
+                database_url = Some(url.clone());
+            }
+            "--steps" => {
+                let steps = args.next().ok_or("--steps needs a value")?;
+                words.push(steps.as_str());
+            }
+            word => words.push(word),
+        }
+    }
+
+    let command = match words.as_slice() {
+        [] | ["run"] => MigrateCommand::Run,
+        ["rollback" | "--rollback"] => MigrateCommand::Rollback(1),
+        ["rollback" | "--rollback", n] => MigrateCommand::Rollback(
+            n.parse::<usize>()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| format!("Invalid rollback count '{}'", n))?,
+        ),
+        ["status"] => MigrateCommand::Status,
+        ["list"] => MigrateCommand::List,
+        ["check"] => MigrateCommand::Check,
+        other => return Err(format!("Unknown command '{}'", other.join(" "))),
+    };
+    Ok((command, database_url))
+}
+
+/// Run `command` against `database_url`, or `DATABASE_URL` if not given
+pub async fn execute(command: MigrateCommand, database_url: Option<String>) -> Result<(), String> {
+    // Listing bundled migrations does not need a database
+    if command == MigrateCommand::List {
+        list();
+        return Ok(());
+    }
+
+    let database_url = database_url
+        .or_else(|| env::var("DATABASE_URL").ok())
+        .ok_or("DATABASE_URL must be set or --database-url given")?;
+    let pool = PgPool::connect(&database_url)
+        .await
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    match command {
+        MigrateCommand::Run => run(&pool).await,
+        MigrateCommand::Rollback(count) => rollback(&pool, count).await,
+        MigrateCommand::Status => status(&pool).await,
+        MigrateCommand::Check => check(&pool).await,
+        MigrateCommand::List => unreachable!(),
+    }
+}
+
+async fn run(pool: &PgPool) -> Result<(), String> {
+    println!("Running migrations...");
+    MIGRATOR
+        .run(pool)
+        .await
+        .map_err(|e| format!("Migration failed: {}", e))?;
+    println!("Migrations complete!");
+    Ok(())
+}
+
+async fn rollback(pool: &PgPool, count: usize) -> Result<(), String> {
+    let applied = applied_versions(pool).await?;
+    if applied.is_empty() {
+        return Err("No migration to undo: the database has no applied migrations".to_string());
+    }
+    if count > applied.len() {
+        return Err(format!(
+            "Cannot roll back {} migrations: only {} applied",
+            count,
+            applied.len()
+        ));
+    }
+
+    // `applied` is newest first; everything above `target` gets reverted
+    for version in &applied[..count] {
+        if !is_reversible(*version) {
+            return Err(format!("Migration {} has no down script", version));
+        }
+    }
+    let target = applied.get(count).copied().unwrap_or(0);
+
+    println!("Rolling back {} migration(s)...", count);
+    MIGRATOR
+        .undo(pool, target)
+        .await
+        .map_err(|e| format!("Rollback failed: {}", e))?;
+    println!("Rolled back to version {}", target);
+    Ok(())
+}
+
+async fn status(pool: &PgPool) -> Result<(), String> {
+    let applied: HashSet<i64> = applied_versions(pool).await?.into_iter().collect();
+
+    for migration in MIGRATOR.iter().filter(|m| m.migration_type.is_up_migration()) {
+        let mark = if applied.contains(&migration.version) { "x" } else { " " };
+        println!("[{}] {} {}", mark, migration.version, migration.description);
+    }
+
+    let known: HashSet<i64> = MIGRATOR.iter().map(|m| m.version).collect();
+    for version in applied.difference(&known) {
+        println!("[?] {} applied but missing from ./migrations", version);
+    }
+    Ok(())
+}
+
+/// Print the migrations `run` would apply, and the applied ones whose file
+/// changed since, which make `run` fail
+async fn check(pool: &PgPool) -> Result<(), String> {
+    let applied = applied_checksums(pool).await?;
+    let mut pending = 0;
+    let mut changed = 0;
+
+    for migration in MIGRATOR.iter().filter(|m| m.migration_type.is_up_migration()) {
+        match applied.get(&migration.version) {
+            None => {
+                pending += 1;
+                println!("would apply {} {}", migration.version, migration.description);
+            }
+            Some(checksum) if checksum[..] != migration.checksum[..] => {
+                changed += 1;
+                println!("changed since applied: {} {}", migration.version, migration.description);
+            }
+            Some(_) => {}
+        }
+    }
+
+    if changed > 0 {
+        return Err(format!("{} applied migration(s) changed; run would fail", changed));
+    }
+    if pending == 0 {
+        println!("Database is up to date");
+    }
+    Ok(())
+}
+
+fn list() {
+    for migration in MIGRATOR.iter().filter(|m| m.migration_type.is_up_migration()) {
+        println!(
+            "{} {}{}",
+            migration.version,
+            migration.description,
+            if is_reversible(migration.version) { "" } else { " (irreversible)" }
+        );
+    }
+}
+
+fn is_reversible(version: i64) -> bool {
+    MIGRATOR
+        .iter()
+        .any(|m| m.version == version && m.migration_type.is_down_migration())
+}
+
+/// Successfully applied migration versions, newest first
+async fn applied_versions(pool: &PgPool) -> Result<Vec<i64>, String> {
+    let mut versions: Vec<i64> = applied_checksums(pool).await?.into_keys().collect();
+    versions.sort_unstable_by(|a, b| b.cmp(a));
+    Ok(versions)
+}
+
+/// Checksums of the successfully applied migrations, by version
+///
+/// Uses unchecked queries: `_sqlx_migrations` is created by the migrator itself
+/// and is not part of the schema the `query!` macros are checked against.
+async fn applied_checksums(pool: &PgPool) -> Result<HashMap<i64, Vec<u8>>, String> {
+    let table_exists: bool =
+        sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+            .fetch_one(pool)
+            .await
+            .map_err(|e| format!("Failed to query migration state: {}", e))?;
+    if !table_exists {
+        return Ok(HashMap::new());
+    }
+
+    let rows: Vec<(i64, Vec<u8>)> =
+        sqlx::query_as("SELECT version, checksum FROM _sqlx_migrations WHERE success")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to query migration state: {}", e))?;
+    Ok(rows.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse_args(&args(&[])), Ok((MigrateCommand::Run, None)));
+        assert_eq!(parse_args(&args(&["rollback"])), Ok((MigrateCommand::Rollback(1), None)));
+        assert_eq!(parse_args(&args(&["--rollback", "3"])), Ok((MigrateCommand::Rollback(3), None)));
+        assert_eq!(
+            parse_args(&args(&["rollback", "--steps", "2", "--database-url", "postgres://db"])),
+            Ok((MigrateCommand::Rollback(2), Some("postgres://db".to_string())))
+        );
+        assert_eq!(parse_args(&args(&["status"])), Ok((MigrateCommand::Status, None)));
+        assert_eq!(parse_args(&args(&["check"])), Ok((MigrateCommand::Check, None)));
+        assert!(parse_args(&args(&["rollback", "0"])).is_err());
+        assert!(parse_args(&args(&["status", "--database-url"])).is_err());
+        assert!(parse_args(&args(&["drop"])).is_err());
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_check_reports_pending_then_up_to_date(pool: PgPool) {
+        assert!(applied_versions(&pool).await.unwrap().is_empty());
+        check(&pool).await.unwrap();
+
+        MIGRATOR.run(&pool).await.unwrap();
+        let applied = applied_versions(&pool).await.unwrap();
+        assert_eq!(applied.len(), MIGRATOR.iter().filter(|m| m.migration_type.is_up_migration()).count());
+        assert!(applied.windows(2).all(|pair| pair[0] > pair[1]), "newest first");
+
+        rollback(&pool, 1).await.unwrap();
+        assert_eq!(applied_versions(&pool).await.unwrap(), applied[1..]);
+        check(&pool).await.unwrap();
+    }
+}
@@ -1,6 +1,114 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool};
+use sqlx::{FromRow, PgConnection, PgPool, Postgres, QueryBuilder, postgres::PgListener};
+use uuid::Uuid;
+
+use crate::{
+    common::{
+        fees::Lamports,
+        merkle::{Hash, MAX_LEAVES, MerkleCommitment, TREE_DEPTH, parse_hash},
+    },
+    metrics,
+    nullifiers::{self, DBError},
+};
+
+/// Campaign accepts claims
+pub const CAMPAIGN_STATUS_ACTIVE: &str = "active";
+/// Campaign claims are temporarily halted by an operator
+pub const CAMPAIGN_STATUS_PAUSED: &str = "paused";
+/// Campaign is registered but not yet funded or opened for claims
+pub const CAMPAIGN_STATUS_DRAFT: &str = "draft";
+
+/// Round accepts claims within its window
+pub const ROUND_STATUS_ACTIVE: &str = "active";
+
+/// Content-addressed campaign id: the first 16 bytes of the Merkle root as a
+/// version 5 (name-based) UUID, so identical recipient lists share an id.
+/// The root is already a hash, so it stands in for the SHA-1 of a v5 name.
+pub fn campaign_id_from_root(root: &Hash) -> Uuid {
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&root[..16]);
+    uuid::Builder::from_sha1_bytes(bytes).into_uuid()
+}
+
+/// Borsh-encoded commitment to a binary tree with `root`, as the campaigns
+/// table stores it
+/// Claim slots of a locked campaign: how many recipients other than `wallet`
+/// hold one, and whether `wallet` does. A recipient takes a slot with its
+/// first claim, full or installment, and keeps it until it is paid out.
+async fn claim_slots(conn: &mut PgConnection, address: &str, wallet: &str) -> Result<(i64, bool), sqlx::Error> {
+    let rec = sqlx::query!(
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE (claimed OR claim_count > 0) AND wallet <> $2) AS "taken!",
+            COALESCE(BOOL_OR((claimed OR claim_count > 0) AND wallet = $2), false) AS "holds!"
+        FROM recipients WHERE campaign_address = $1
+        "#,
+        address,
+        wallet
+    )
+    .fetch_one(conn)
+    .await?;
+    Ok((rec.taken, rec.holds))
+}
+
+fn commitment_bytes(root: &Hash, leaf_count: usize, created_at: DateTime<Utc>) -> Vec<u8> {
+    MerkleCommitment {
+        root: *root,
+        depth: TREE_DEPTH as u8,
+        leaf_count: leaf_count as u32,
+        created_at: created_at.timestamp(),
+    }
+    .to_borsh_bytes()
+}
+
+/// Move a campaign from `from_status` to `to_status` and audit it within `tx`.
+/// Returns false if the campaign is not in `from_status`.
+async fn transition_in(
+    tx: &mut PgConnection,
+    address: &str,
+    from_status: &str,
+    to_status: &str,
+    pause_reason: Option<&str>,
+    request_id: Option<&str>,
+) -> Result<bool, sqlx::Error> {
+    let updated = sqlx::query!(
+        r#"
+        UPDATE campaigns
+        SET status = $1, pause_reason = $2
+        WHERE address = $3 AND status = $4
+        "#,
+        to_status,
+        pause_reason,
+        address,
+        from_status
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+
+    if !updated {
+        return Ok(false);
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO audit_log (campaign_address, action, details, payload, created_at)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        address,
+        format!("campaign_{}", to_status),
+        pause_reason,
+        serde_json::json!({ "request_id": request_id }),
+        Utc::now()
+    )
+    .execute(&mut *tx)
+    .await?;
+    Ok(true)
+}
 
 /// A single recipient in a campaign
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -11,6 +119,10 @@ pub struct Recipient {
     pub amount: String, // Stored as Raw Integer String
     pub claimed: bool,
     pub claimed_at: Option<DateTime<Utc>>,
+    /// Position of the recipient's leaf in the campaign tree; set when loaded
+    /// from the database, where removed recipients leave gaps
+    #[serde(skip)]
+    pub leaf_index: Option<i32>,
 }
 
 /// Campaign data stored in the backend
@@ -36,10 +148,19 @@ pub struct Campaign {
     pub token_mint: Option<String>,
     pub token_symbol: Option<String>,
     pub token_decimals: Option<i16>, // SQLx maps SMALLINT to i16.
+    // Lifecycle fields
+    pub status: String,
+    pub pause_reason: Option<String>,
+    /// End of the claim window; spent nullifiers expire a retention period after it
+    pub close_at: Option<DateTime<Utc>>,
+    /// Share of a referred claim credited to the referrer, in basis points
+    pub referral_bonus_bps: i16,
+    /// Claims accepted first come, first served; None = every recipient can claim
+    pub max_claims: Option<i32>,
 }
 
 /// Response for campaign info (without recipient list for privacy)
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CampaignInfo {
     pub id: String,
     pub address: String,
@@ -55,6 +176,11 @@ pub struct CampaignInfo {
     pub token_mint: Option<String>,
     pub token_symbol: Option<String>,
     pub token_decimals: Option<u8>,
+    pub status: String,
+    pub pause_reason: Option<String>,
+    pub close_at: Option<DateTime<Utc>>,
+    pub referral_bonus_bps: u16,
+    pub max_claims: Option<usize>,
 }
 
 impl From<&Campaign> for CampaignInfo {
@@ -73,6 +199,134 @@ impl From<&Campaign> for CampaignInfo {
             token_mint: campaign.token_mint.clone(),
             token_symbol: campaign.token_symbol.clone(),
             token_decimals: campaign.token_decimals.map(|d| d as u8),
+            status: campaign.status.clone(),
+            pause_reason: campaign.pause_reason.clone(),
+            close_at: campaign.close_at,
+            referral_bonus_bps: campaign.referral_bonus_bps as u16,
+            max_claims: campaign.max_claims.map(|m| m as usize),
+        }
+    }
+}
+
+/// Column campaign listings are sorted by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CampaignSort {
+    #[default]
+    CreatedAt,
+    Name,
+}
+
+impl CampaignSort {
+    fn column(self) -> &'static str {
+        match self {
+            Self::CreatedAt => "created_at",
+            Self::Name => "name",
+        }
+    }
+}
+
+/// Which campaigns `CampaignStore::list` returns, and in what order
+#[derive(Debug, Default)]
+pub struct CampaignFilter {
+    pub status: Option<String>,
+    pub creator_wallet: Option<String>,
+    /// Created at or after
+    pub created_after: Option<DateTime<Utc>>,
+    /// Created before
+    pub created_before: Option<DateTime<Utc>>,
+    pub sort: CampaignSort,
+    pub descending: bool,
+}
+
+impl CampaignFilter {
+    /// `WHERE` clause with a condition per set field
+    fn push_where<'a>(&'a self, query: &mut QueryBuilder<'a, Postgres>) {
+        query.push(" WHERE TRUE");
+        if let Some(status) = &self.status {
+            query.push(" AND c.status = ").push_bind(status);
+        }
+        if let Some(creator_wallet) = &self.creator_wallet {
+            query.push(" AND c.creator_wallet = ").push_bind(creator_wallet);
+        }
+        if let Some(created_after) = self.created_after {
+            query.push(" AND c.created_at >= ").push_bind(created_after);
+        }
+        if let Some(created_before) = self.created_before {
+            query.push(" AND c.created_at < ").push_bind(created_before);
+        }
+    }
+}
+
+/// A listed campaign with its recipient counts
+#[derive(FromRow)]
+struct CampaignListRow {
+    #[sqlx(flatten)]
+    campaign: Campaign,
+    total_recipients: i64,
+    claimed_count: i64,
+}
+
+/// One claim round of a multi-round campaign, with its own recipient tree
+#[derive(Debug, Clone)]
+pub struct CampaignRound {
+    pub id: Uuid,
+    pub campaign_address: String,
+    /// 1-based, in creation order
+    pub round_number: i32,
+    pub merkle_root: Vec<u8>,
+    pub open_at: DateTime<Utc>,
+    pub close_at: Option<DateTime<Utc>>,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub recipients: Vec<Recipient>,
+}
+
+impl CampaignRound {
+    /// Whether the round takes claims at `now`
+    pub fn is_open(&self, now: DateTime<Utc>) -> bool {
+        self.status == ROUND_STATUS_ACTIVE
+            && self.open_at <= now
+            && self.close_at.is_none_or(|close_at| close_at > now)
+    }
+}
+
+/// A round to add with `CampaignStore::create_round`
+pub struct NewRound<'a> {
+    /// Root of the tree over `leaves`
+    pub merkle_root: Hash,
+    /// Leaves `(leaf_index, wallet, amount, secret)` of the round's tree
+    pub leaves: &'a [(usize, String, u64, Hash)],
+    pub open_at: DateTime<Utc>,
+    pub close_at: Option<DateTime<Utc>>,
+    /// Recipients `(wallet, amount)` as listed
+    pub recipients: &'a [(String, String)],
+}
+
+/// Response for round info (without recipient list for privacy)
+#[derive(Debug, Serialize)]
+pub struct RoundInfo {
+    pub id: String,
+    pub round_number: i32,
+    pub merkle_root: String,
+    pub open_at: DateTime<Utc>,
+    pub close_at: Option<DateTime<Utc>>,
+    pub status: String,
+    pub total_recipients: usize,
+    pub claimed_count: usize,
+}
+
+impl From<&CampaignRound> for RoundInfo {
+    fn from(round: &CampaignRound) -> Self {
+        RoundInfo {
+            id: round.id.to_string(),
+            round_number: round.round_number,
+            merkle_root: hex::encode(&round.merkle_root),
+            open_at: round.open_at,
+            close_at: round.close_at,
+            status: round.status.clone(),
+            total_recipients: round.recipients.len(),
+            claimed_count: round.recipients.iter().filter(|r| r.claimed).count(),
         }
     }
 }
@@ -85,6 +339,170 @@ pub struct EligibilityResponse {
     pub already_claimed: bool,
 }
 
+/// Result of recording a claim
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimOutcome {
+    /// `amount` is what this claim paid out: the allocation minus earlier
+    /// installments. `last_slot` is set when this claim reached `max_claims`.
+    Claimed { amount: Lamports, last_slot: bool },
+    /// The wallet is not a recipient or has already claimed
+    NotClaimable,
+    /// The claim's nullifier was spent by an earlier claim
+    NullifierSpent,
+    /// `max_claims` was reached before this claim
+    CampaignFull { claims_accepted: i64 },
+}
+
+/// Result of paying out an installment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallmentOutcome {
+    /// The installment was paid; the recipient's balance after it
+    Paid(RecipientBalance),
+    /// The installment does not fit the remaining allocation, another claim
+    /// took its sequence number first or the campaign is not active
+    NotClaimable,
+    /// `max_claims` recipients had started claiming before this one
+    CampaignFull { claims_accepted: i64 },
+}
+
+/// Result of appending recipients to a campaign
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppendOutcome {
+    /// `added` recipients were appended; `already_listed` were skipped as
+    /// recipients of the campaign already
+    Appended { added: usize, already_listed: Vec<String> },
+    /// Recipients can only be added to drafts; carries the current status
+    NotDraft(String),
+    /// The campaign's leaves would span `count` indices, more than a tree holds
+    TooManyLeaves { count: usize },
+}
+
+/// Result of removing a recipient from a campaign
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoveOutcome {
+    /// The recipient was removed; `amount` was its allocation
+    Removed { amount: String },
+    /// Recipients can only be removed from drafts; carries the current status
+    NotDraft(String),
+    /// The wallet is not a recipient of the campaign
+    NotARecipient,
+}
+
+/// Allocation of a recipient before and after an update (raw integer strings)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AmountChange {
+    pub wallet: String,
+    pub old_amount: String,
+    pub new_amount: String,
+}
+
+/// Result of updating recipient allocations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateOutcome {
+    /// Every allocation was changed, in input order
+    Updated(Vec<AmountChange>),
+    /// Allocations can only change in drafts; carries the current status
+    NotDraft(String),
+    /// The wallet is not a recipient of the campaign; nothing was changed
+    NotARecipient(String),
+}
+
+/// How much of a recipient's allocation has been claimed, in lamports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecipientBalance {
+    pub allocation: Lamports,
+    pub claimed: Lamports,
+    /// Claims made so far; the sequence number of the next installment
+    pub claim_count: i32,
+}
+
+impl RecipientBalance {
+    pub fn remaining(&self) -> Lamports {
+        self.allocation.saturating_sub(self.claimed)
+    }
+}
+
+/// A campaign's current Merkle root
+#[derive(Debug, Serialize)]
+pub struct CampaignRoot {
+    pub merkle_root: String,
+    pub status: String,
+    /// Whether a round has replaced the root the campaign was created with
+    pub has_rounds: bool,
+    /// Whether the root is a real one with a stored commitment, rather than
+    /// the all-zero placeholder
+    #[serde(skip)]
+    pub committed: bool,
+}
+
+/// Claim slots left in a campaign
+#[derive(Debug, Serialize)]
+pub struct Availability {
+    /// Untaken slots under `max_claims`, or recipients yet to claim when
+    /// uncapped; a recipient takes a slot with its first installment
+    pub remaining_slots: i64,
+    pub is_open: bool,
+    /// Recipients that have claimed all or part of their allocation
+    pub claims_accepted: i64,
+    pub max_claims: Option<usize>,
+}
+
+/// A recipient as listed to the campaign's frontend
+#[derive(Debug, Serialize)]
+pub struct RecipientSummary {
+    pub wallet: String,
+    /// Allocation converted from lamports
+    pub amount_sol: f64,
+    pub leaf_index: usize,
+    /// `unclaimed`, `partial` (some installments claimed) or `claimed`
+    pub claim_status: String,
+}
+
+/// One page of a campaign's recipients, in leaf order
+#[derive(Debug, Serialize)]
+pub struct RecipientPage {
+    pub recipients: Vec<RecipientSummary>,
+    /// Leaf index to pass as `cursor` for the next page; None on the last page
+    pub next_cursor: Option<usize>,
+    /// Recipients in the campaign
+    pub total: usize,
+}
+
+/// Claims attributed to a referrer within a campaign
+#[derive(Debug, Serialize)]
+pub struct ReferralStats {
+    pub referred_count: i64,
+    /// Sum of referred claim amounts, converted from lamports
+    pub referred_volume_sol: f64,
+    /// Bonus owed to the referrer and not yet paid out (raw integer string)
+    pub pending_reward: String,
+}
+
+/// A campaign listing a wallet as recipient
+#[derive(Debug, Serialize)]
+pub struct RecipientCampaign {
+    pub campaign_id: String,
+    pub address: String,
+    pub name: String,
+    pub status: String,
+    /// The wallet's allocation converted from lamports
+    pub amount_sol: f64,
+    /// `unclaimed`, `partial` (some installments claimed) or `claimed`
+    pub claim_status: String,
+    pub close_at: Option<DateTime<Utc>>,
+}
+
+/// Whether a wallet is a recipient of one campaign
+#[derive(Debug, Serialize)]
+pub struct EligibilityCheck {
+    pub campaign_id: String,
+    pub eligible: bool,
+    /// The wallet's allocation converted from lamports, if eligible
+    pub amount_sol: Option<f64>,
+    /// `unclaimed`, `partial` or `claimed`, if eligible
+    pub claim_status: Option<String>,
+}
+
 /// Eligible campaign info for a wallet
 #[derive(Debug, Serialize)]
 pub struct EligibleCampaign {
@@ -104,11 +522,13 @@ pub struct EligibleCampaign {
 #[derive(Debug, Clone)]
 pub struct CampaignStore {
     db: PgPool,
+    /// Days nullifiers spent by claims outlive their campaign's `close_at`
+    nullifier_retention_days: i64,
 }
 
 impl CampaignStore {
-    pub fn new(db: PgPool) -> Self {
-        Self { db }
+    pub fn new(db: PgPool, nullifier_retention_days: i64) -> Self {
+        Self { db, nullifier_retention_days }
     }
 
     /// Create a new campaign
@@ -122,9 +542,10 @@ impl CampaignStore {
                 id, address, name, merkle_root, total_amount, creator_wallet, 
                 tx_signature, vault_address, created_at, airdrop_type, 
                 vesting_start, vesting_cliff_seconds, vesting_duration_seconds, 
-                token_mint, token_symbol, token_decimals
+                token_mint, token_symbol, token_decimals, status, pause_reason, close_at,
+                referral_bonus_bps, max_claims, merkle_commitment
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22)
             "#,
             campaign.id,
             campaign.address,
@@ -141,24 +562,32 @@ impl CampaignStore {
             campaign.vesting_duration_seconds,
             campaign.token_mint,
             campaign.token_symbol,
-            campaign.token_decimals
+            campaign.token_decimals,
+            campaign.status,
+            campaign.pause_reason,
+            campaign.close_at,
+            campaign.referral_bonus_bps,
+            campaign.max_claims,
+            parse_hash(&campaign.merkle_root)
+                .map(|root| commitment_bytes(&root, campaign.recipients.len(), campaign.created_at))
         )
         .execute(&mut *tx)
         .await
         .expect("Failed to insert campaign");
 
-        // Insert recipients
-        for r in &campaign.recipients {
+        // Insert recipients; list order is leaf order
+        for (leaf_index, r) in campaign.recipients.iter().enumerate() {
             sqlx::query!(
                 r#"
-                INSERT INTO recipients (campaign_address, wallet, amount, claimed, claimed_at)
-                VALUES ($1, $2, $3, $4, $5)
+                INSERT INTO recipients (campaign_address, wallet, amount, claimed, claimed_at, leaf_index)
+                VALUES ($1, $2, $3, $4, $5, $6)
                 "#,
                 campaign.address,
                 r.wallet,
                 r.amount, // String
                 r.claimed,
-                r.claimed_at
+                r.claimed_at,
+                leaf_index as i32
             )
             .execute(&mut *tx)
             .await
@@ -172,29 +601,39 @@ impl CampaignStore {
 
     /// Get a campaign by address
     pub async fn get(&self, address: &str) -> Option<Campaign> {
-        let rec = sqlx::query!(
-            "SELECT * FROM campaigns WHERE address = $1",
-            address
+        self.try_get(address).await.ok()?
+    }
+
+    /// Get a campaign by address, keeping database errors apart from a
+    /// missing campaign
+    pub async fn try_get(&self, address: &str) -> Result<Option<Campaign>, sqlx::Error> {
+        let Some(rec) = metrics::time_db(
+            "campaign_get",
+            sqlx::query!("SELECT * FROM campaigns WHERE address = $1", address).fetch_optional(&self.db),
         )
-        .fetch_optional(&self.db)
-        .await
-        .ok()??;
+        .await?
+        else {
+            return Ok(None);
+        };
 
         // Fetch recipients
-        let recipients = sqlx::query_as!(
-            Recipient,
-            r#"
-            SELECT id, wallet, amount, claimed, claimed_at 
-            FROM recipients 
-            WHERE campaign_address = $1
-            "#,
-            address
+        let recipients = metrics::time_db(
+            "campaign_recipients",
+            sqlx::query_as!(
+                Recipient,
+                r#"
+                SELECT id, wallet, amount, claimed, claimed_at, leaf_index AS "leaf_index?"
+                FROM recipients 
+                WHERE campaign_address = $1
+                ORDER BY leaf_index
+                "#,
+                address
+            )
+            .fetch_all(&self.db),
         )
-        .fetch_all(&self.db)
-        .await
-        .unwrap_or_default();
+        .await?;
 
-        Some(Campaign {
+        Ok(Some(Campaign {
             id: rec.id,
             address: rec.address,
             name: rec.name,
@@ -212,7 +651,76 @@ impl CampaignStore {
             token_mint: rec.token_mint,
             token_symbol: rec.token_symbol,
             token_decimals: rec.token_decimals, // Now i16 in query, structure expects i16
+            status: rec.status,
+            pause_reason: rec.pause_reason,
+            close_at: rec.close_at,
+            referral_bonus_bps: rec.referral_bonus_bps,
+            max_claims: rec.max_claims,
+        }))
+    }
+
+    /// Get a campaign by its id
+    pub async fn get_by_id(&self, id: &str) -> Option<Campaign> {
+        let address = sqlx::query_scalar!("SELECT address FROM campaigns WHERE id = $1", id)
+            .fetch_optional(&self.db)
+            .await
+            .ok()??;
+        self.get(&address).await
+    }
+
+    /// The commitment stored for a campaign's current root; None if the
+    /// campaign does not exist or has no root yet
+    pub async fn get_commitment(&self, address: &str) -> Result<Option<MerkleCommitment>, sqlx::Error> {
+        let data = sqlx::query_scalar!("SELECT merkle_commitment FROM campaigns WHERE address = $1", address)
+            .fetch_optional(&self.db)
+            .await?
+            .flatten();
+        data.map(|data| {
+            MerkleCommitment::from_borsh_bytes(&data).map_err(|e| sqlx::Error::Decode(Box::new(e)))
         })
+        .transpose()
+    }
+
+    /// One page of the campaigns matching `filter`, and how many match in all
+    pub async fn list(
+        &self,
+        filter: &CampaignFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<CampaignInfo>, i64), sqlx::Error> {
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM campaigns c");
+        filter.push_where(&mut count);
+        let total = count.build_query_scalar().fetch_one(&self.db).await?;
+
+        let mut query = QueryBuilder::new(
+            r#"
+            SELECT
+                c.*,
+                (SELECT COUNT(*) FROM recipients WHERE campaign_address = c.address) AS total_recipients,
+                (SELECT COUNT(*) FROM recipients
+                    WHERE campaign_address = c.address AND claimed) AS claimed_count
+            FROM campaigns c"#,
+        );
+        filter.push_where(&mut query);
+        // Address breaks ties so pages do not overlap
+        let direction = if filter.descending { "DESC" } else { "ASC" };
+        query.push(format!(
+            " ORDER BY c.{} {direction}, c.address {direction}",
+            filter.sort.column()
+        ));
+        query.push(" LIMIT ").push_bind(limit).push(" OFFSET ").push_bind(offset);
+        let rows: Vec<CampaignListRow> =
+            metrics::time_db("campaign_list", query.build_query_as().fetch_all(&self.db)).await?;
+
+        let campaigns = rows
+            .iter()
+            .map(|row| CampaignInfo {
+                total_recipients: row.total_recipients as usize,
+                claimed_count: row.claimed_count as usize,
+                ..CampaignInfo::from(&row.campaign)
+            })
+            .collect();
+        Ok((campaigns, total))
     }
 
     /// Get all campaigns for a wallet
@@ -259,56 +767,1791 @@ impl CampaignStore {
         })
     }
 
-    /// Mark a recipient as claimed
-    pub async fn mark_claimed(&self, address: &str, wallet: &str) -> bool {
-        let result = sqlx::query!(
+    /// Mark a recipient as claimed, spending `nullifier`, optionally
+    /// attributing the claim to a referrer. Only recipients of an active
+    /// campaign can claim.
+    ///
+    /// The nullifier is spent and a referrer is credited `referral_bonus_bps`
+    /// of the claimed amount in `referral_rewards`, both in the same
+    /// transaction as the claim: a rejected claim spends nothing. The
+    /// campaign row is locked while claim slots are counted against
+    /// `max_claims`, so concurrent claims cannot overfill the last slot.
+    pub async fn mark_claimed(
+        &self,
+        address: &str,
+        wallet: &str,
+        referrer: Option<&str>,
+        nullifier: &Hash,
+    ) -> ClaimOutcome {
+        let result: Result<ClaimOutcome, sqlx::Error> = async {
+            let mut tx = self.db.begin().await?;
+            let max_claims = sqlx::query_scalar!(
+                "SELECT max_claims FROM campaigns WHERE address = $1 FOR UPDATE",
+                address
+            )
+            .fetch_optional(&mut *tx)
+            .await?
+            .flatten();
+
+            let mut last_slot = false;
+            if let Some(max_claims) = max_claims {
+                let (claims_accepted, holds_slot) = claim_slots(&mut tx, address, wallet).await?;
+                if !holds_slot {
+                    if claims_accepted >= i64::from(max_claims) {
+                        return Ok(ClaimOutcome::CampaignFull { claims_accepted });
+                    }
+                    last_slot = claims_accepted + 1 == i64::from(max_claims);
+                }
+            }
+
+            // Spending first makes a replayed nullifier fail before the recipient is touched
+            match nullifiers::spend(&mut tx, nullifier, address, self.nullifier_retention_days).await {
+                Err(DBError::AlreadySpent(_)) => return Ok(ClaimOutcome::NullifierSpent),
+                Err(DBError::Database(e)) => return Err(e),
+                Ok(()) => {}
+            }
+
+            // Pays out whatever earlier installments left of the allocation
+            let claimed = sqlx::query_scalar!(
+                r#"
+                WITH prior AS (
+                    SELECT id, claimed_lamports FROM recipients
+                    WHERE campaign_address = $2 AND wallet = $3 AND claimed = false
+                        AND EXISTS (SELECT 1 FROM campaigns WHERE address = $2 AND status = $5)
+                    FOR UPDATE
+                )
+                UPDATE recipients r
+                SET claimed = true, claimed_at = $1, referrer_wallet = $4,
+                    claimed_lamports = r.amount::NUMERIC::BIGINT, claim_count = r.claim_count + 1
+                FROM prior WHERE r.id = prior.id
+                RETURNING r.claimed_lamports - prior.claimed_lamports AS "paid!"
+                "#,
+                Utc::now(),
+                address,
+                wallet,
+                referrer,
+                CAMPAIGN_STATUS_ACTIVE
+            )
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            let Some(paid) = claimed else {
+                return Ok(ClaimOutcome::NotClaimable);
+            };
+            let amount = paid.max(0) as Lamports;
+            if let Some(referrer) = referrer {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO referral_rewards (campaign_address, referrer_wallet, pending_amount, updated_at)
+                    SELECT address, $2, FLOOR($3::TEXT::NUMERIC * referral_bonus_bps / 10000)::TEXT, $4
+                    FROM campaigns WHERE address = $1 AND referral_bonus_bps > 0
+                    ON CONFLICT (campaign_address, referrer_wallet) DO UPDATE
+                    SET pending_amount = (referral_rewards.pending_amount::NUMERIC
+                            + EXCLUDED.pending_amount::NUMERIC)::TEXT,
+                        updated_at = EXCLUDED.updated_at
+                    "#,
+                    address,
+                    referrer,
+                    amount.to_string(),
+                    Utc::now()
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await?;
+            Ok(ClaimOutcome::Claimed { amount, last_slot })
+        }
+        .await;
+
+        result.unwrap_or_else(|e| {
+            tracing::error!("Failed to mark claim: {}", e);
+            ClaimOutcome::NotClaimable
+        })
+    }
+
+    /// Claimed and remaining allocation of a recipient, None if the wallet is not one
+    pub async fn recipient_balance(&self, address: &str, wallet: &str) -> Option<RecipientBalance> {
+        let rec = sqlx::query!(
+            r#"
+            SELECT amount::NUMERIC::BIGINT AS "allocation!", claimed_lamports, claim_count
+            FROM recipients WHERE campaign_address = $1 AND wallet = $2
+            "#,
+            address,
+            wallet
+        )
+        .fetch_optional(&self.db)
+        .await
+        .ok()??;
+
+        Some(RecipientBalance {
+            allocation: rec.allocation.max(0) as Lamports,
+            claimed: rec.claimed_lamports.max(0) as Lamports,
+            claim_count: rec.claim_count,
+        })
+    }
+
+    /// Pay out `amount` of a recipient's allocation as claim number `sequence`,
+    /// spending `nullifier`, and return the balance after it.
+    ///
+    /// The nullifier is left unspent unless the installment is paid. The
+    /// campaign row is locked as in `mark_claimed`: a recipient's first
+    /// installment takes a claim slot, refused once `max_claims` are held.
+    /// The recipient counts as claimed once the allocation is exhausted.
+    pub async fn claim_installment(
+        &self,
+        address: &str,
+        wallet: &str,
+        amount: Lamports,
+        sequence: i32,
+        nullifier: &Hash,
+    ) -> Result<InstallmentOutcome, DBError> {
+        let Ok(amount) = i64::try_from(amount) else {
+            return Ok(InstallmentOutcome::NotClaimable);
+        };
+        let mut tx = self.db.begin().await?;
+        let max_claims = sqlx::query_scalar!(
+            "SELECT max_claims FROM campaigns WHERE address = $1 FOR UPDATE",
+            address
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .flatten();
+        if let Some(max_claims) = max_claims {
+            let (claims_accepted, holds_slot) = claim_slots(&mut tx, address, wallet).await?;
+            if !holds_slot && claims_accepted >= i64::from(max_claims) {
+                return Ok(InstallmentOutcome::CampaignFull { claims_accepted });
+            }
+        }
+
+        nullifiers::spend(&mut tx, nullifier, address, self.nullifier_retention_days).await?;
+        let rec = sqlx::query!(
             r#"
-            UPDATE recipients 
-            SET claimed = true, claimed_at = $1 
+            UPDATE recipients
+            SET claimed_lamports = claimed_lamports + $4,
+                claim_count = claim_count + 1,
+                claimed = claimed_lamports + $4 >= amount::NUMERIC,
+                claimed_at = $1
             WHERE campaign_address = $2 AND wallet = $3 AND claimed = false
+                AND claim_count = $5 AND claimed_lamports + $4 <= amount::NUMERIC
+                AND EXISTS (SELECT 1 FROM campaigns WHERE address = $2 AND status = $6)
+            RETURNING amount::NUMERIC::BIGINT AS "allocation!", claimed_lamports, claim_count
             "#,
             Utc::now(),
             address,
+            wallet,
+            amount,
+            sequence,
+            CAMPAIGN_STATUS_ACTIVE
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(rec) = rec else {
+            return Ok(InstallmentOutcome::NotClaimable);
+        };
+        tx.commit().await?;
+        Ok(InstallmentOutcome::Paid(RecipientBalance {
+            allocation: rec.allocation.max(0) as Lamports,
+            claimed: rec.claimed_lamports.max(0) as Lamports,
+            claim_count: rec.claim_count,
+        }))
+    }
+
+    /// Delegate a recipient registered, None if the wallet is not a recipient
+    pub async fn recipient_delegate(&self, address: &str, wallet: &str) -> Option<Option<String>> {
+        sqlx::query_scalar!(
+            "SELECT delegate_wallet FROM recipients WHERE campaign_address = $1 AND wallet = $2",
+            address,
             wallet
         )
-        .execute(&self.db)
-        .await;
+        .fetch_optional(&self.db)
+        .await
+        .ok()?
+    }
 
-        match result {
-            Ok(res) => res.rows_affected() > 0,
-            Err(_) => false,
+    /// Register (or with None, remove) the wallet allowed to claim for a
+    /// recipient. Returns false if the wallet is not a recipient.
+    pub async fn set_delegate(
+        &self,
+        address: &str,
+        wallet: &str,
+        delegate: Option<&str>,
+        request_id: Option<&str>,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+        let updated = sqlx::query!(
+            "UPDATE recipients SET delegate_wallet = $3 WHERE campaign_address = $1 AND wallet = $2",
+            address,
+            wallet,
+            delegate
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            return Ok(false);
         }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO audit_log (campaign_address, action, details, payload, created_at)
+            VALUES ($1, 'delegate_set', $2, $3, $4)
+            "#,
+            address,
+            format!("{} delegated to {}", wallet, delegate.unwrap_or("nobody")),
+            serde_json::json!({ "wallet": wallet, "delegate_wallet": delegate, "request_id": request_id }),
+            Utc::now()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
     }
 
-    /// Get all campaigns where the wallet is eligible to claim
-    pub async fn get_eligible_for_wallet(&self, wallet: &str) -> Vec<EligibleCampaign> {
-        let rows = sqlx::query!(
+    /// Audit a claim a delegate submitted for a recipient
+    pub async fn record_delegate_claim(
+        &self,
+        address: &str,
+        wallet: &str,
+        delegate: &str,
+        request_id: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
             r#"
-            SELECT c.address, c.name, c.total_amount, c.vault_address, c.created_at, 
-                   c.token_mint, c.token_symbol, c.token_decimals,
-                   r.amount,
-                   (SELECT COUNT(*) FROM recipients WHERE campaign_address = c.address) as total_recipients
-            FROM campaigns c
-            JOIN recipients r ON c.address = r.campaign_address
-            WHERE r.wallet = $1 AND r.claimed = false
+            INSERT INTO audit_log (campaign_address, action, details, payload, created_at)
+            VALUES ($1, 'delegate_claim', $2, $3, $4)
             "#,
-            wallet
+            address,
+            format!("{} claimed for {}", delegate, wallet),
+            serde_json::json!({ "wallet": wallet, "delegate_wallet": delegate, "request_id": request_id }),
+            Utc::now()
         )
-        .fetch_all(&self.db)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    /// A campaign's current root, None if the campaign does not exist
+    pub async fn root(&self, address: &str) -> Result<Option<CampaignRoot>, sqlx::Error> {
+        sqlx::query_as!(
+            CampaignRoot,
+            r#"
+            SELECT
+                c.merkle_root,
+                c.status,
+                EXISTS (SELECT 1 FROM campaign_rounds WHERE campaign_address = c.address) AS "has_rounds!",
+                (c.merkle_commitment IS NOT NULL AND c.merkle_root <> $2) AS "committed!"
+            FROM campaigns c WHERE c.address = $1
+            "#,
+            address,
+            hex::encode([0u8; 32])
+        )
+        .fetch_optional(&self.db)
         .await
-        .unwrap_or_default();
+    }
 
-        rows.into_iter().map(|row| EligibleCampaign {
-            address: row.address,
-            name: row.name,
-            amount: row.amount,
-            total_amount: row.total_amount,
-            total_recipients: row.total_recipients.unwrap_or(0) as usize,
-            vault_address: row.vault_address,
-            created_at: row.created_at,
-            token_mint: row.token_mint,
-            token_symbol: row.token_symbol,
-            token_decimals: row.token_decimals.map(|d| d as u8),
-        }).collect()
+    /// Remaining claim slots, None if the campaign does not exist
+    pub async fn availability(&self, address: &str) -> Option<Availability> {
+        let rec = sqlx::query!(
+            r#"
+            SELECT
+                c.status,
+                c.close_at,
+                c.max_claims,
+                (SELECT COUNT(*) FROM recipients
+                    WHERE campaign_address = c.address AND (claimed OR claim_count > 0)) AS "claimed!",
+                (SELECT COUNT(*) FROM recipients WHERE campaign_address = c.address) AS "recipients!"
+            FROM campaigns c WHERE c.address = $1
+            "#,
+            address
+        )
+        .fetch_optional(&self.db)
+        .await
+        .ok()??;
+
+        let capacity = rec.max_claims.map_or(rec.recipients, |m| i64::from(m).min(rec.recipients));
+        let remaining_slots = (capacity - rec.claimed).max(0);
+        let window_open = rec.close_at.is_none_or(|close_at| close_at > Utc::now());
+        Some(Availability {
+            remaining_slots,
+            is_open: rec.status == CAMPAIGN_STATUS_ACTIVE && window_open && remaining_slots > 0,
+            claims_accepted: rec.claimed,
+            max_claims: rec.max_claims.map(|m| m as usize),
+        })
+    }
+
+    /// Append recipients `(wallet, amount)` to a draft campaign, None if it does not exist.
+    ///
+    /// New recipients take the next leaf indices in list order and their
+    /// amounts are added to `total_amount`. The Merkle root is left as is;
+    /// the tree is built from the full list when the campaign activates.
+    pub async fn append_recipients(
+        &self,
+        address: &str,
+        recipients: &[(String, Lamports)],
+        request_id: Option<&str>,
+    ) -> Result<Option<AppendOutcome>, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+        let Some(status) =
+            sqlx::query_scalar!("SELECT status FROM campaigns WHERE address = $1 FOR UPDATE", address)
+                .fetch_optional(&mut *tx)
+                .await?
+        else {
+            return Ok(None);
+        };
+        if status != CAMPAIGN_STATUS_DRAFT {
+            return Ok(Some(AppendOutcome::NotDraft(status)));
+        }
+
+        let wallets: Vec<String> = recipients.iter().map(|(wallet, _)| wallet.clone()).collect();
+        let already_listed: Vec<String> = sqlx::query_scalar!(
+            "SELECT wallet FROM recipients WHERE campaign_address = $1 AND wallet = ANY($2)",
+            address,
+            &wallets
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let (wallets, amounts): (Vec<String>, Vec<String>) = recipients
+            .iter()
+            .filter(|(wallet, _)| !already_listed.contains(wallet))
+            .map(|(wallet, amount)| (wallet.clone(), amount.to_string()))
+            .unzip();
+
+        // Removed recipients leave gaps, so the next index follows the highest
+        // one, and the tree must reach past it
+        let next_leaf_index = sqlx::query_scalar!(
+            r#"SELECT COALESCE(MAX(leaf_index) + 1, 0) AS "next_leaf_index!" FROM recipients WHERE campaign_address = $1"#,
+            address
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        let count = next_leaf_index as usize + wallets.len();
+        if count > MAX_LEAVES {
+            return Ok(Some(AppendOutcome::TooManyLeaves { count }));
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO recipients (campaign_address, wallet, amount, claimed, leaf_index)
+            SELECT $1, r.wallet, r.amount, false, $4 + r.position::INTEGER - 1
+            FROM UNNEST($2::text[], $3::text[]) WITH ORDINALITY AS r (wallet, amount, position)
+            "#,
+            address,
+            &wallets,
+            &amounts,
+            next_leaf_index
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let added_amount: u128 = recipients
+            .iter()
+            .filter(|(wallet, _)| !already_listed.contains(wallet))
+            .map(|(_, amount)| u128::from(*amount))
+            .sum();
+        sqlx::query!(
+            r#"
+            UPDATE campaigns SET total_amount = (total_amount::NUMERIC + $2::TEXT::NUMERIC)::TEXT
+            WHERE address = $1
+            "#,
+            address,
+            added_amount.to_string()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO audit_log (campaign_address, action, details, payload, created_at)
+            VALUES ($1, 'recipients_added', $2, $3, $4)
+            "#,
+            address,
+            format!("{} recipients", wallets.len()),
+            serde_json::json!({ "wallets": wallets, "request_id": request_id }),
+            Utc::now()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(AppendOutcome::Appended {
+            added: wallets.len(),
+            already_listed,
+        }))
+    }
+
+    /// Remove a recipient from a draft campaign, None if the campaign does not exist.
+    ///
+    /// Other recipients keep their leaf indices. The allocation is taken off
+    /// `total_amount` and the removal is audited with `reason`.
+    pub async fn remove_recipient(
+        &self,
+        address: &str,
+        wallet: &str,
+        reason: &str,
+        request_id: Option<&str>,
+    ) -> Result<Option<RemoveOutcome>, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+        let Some(status) =
+            sqlx::query_scalar!("SELECT status FROM campaigns WHERE address = $1 FOR UPDATE", address)
+                .fetch_optional(&mut *tx)
+                .await?
+        else {
+            return Ok(None);
+        };
+        if status != CAMPAIGN_STATUS_DRAFT {
+            return Ok(Some(RemoveOutcome::NotDraft(status)));
+        }
+
+        let Some(removed) = sqlx::query!(
+            r#"
+            DELETE FROM recipients WHERE campaign_address = $1 AND wallet = $2
+            RETURNING amount, leaf_index
+            "#,
+            address,
+            wallet
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(Some(RemoveOutcome::NotARecipient));
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE campaigns SET total_amount = (total_amount::NUMERIC - $2::TEXT::NUMERIC)::TEXT
+            WHERE address = $1
+            "#,
+            address,
+            removed.amount
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO audit_log (campaign_address, action, details, payload, created_at)
+            VALUES ($1, 'recipient_removed', $2, $3, $4)
+            "#,
+            address,
+            reason,
+            serde_json::json!({
+                "wallet": wallet,
+                "amount": removed.amount,
+                "leaf_index": removed.leaf_index,
+                "request_id": request_id,
+            }),
+            Utc::now()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(RemoveOutcome::Removed { amount: removed.amount }))
+    }
+
+    /// Set the allocation of recipients `(wallet, amount)` of a draft
+    /// campaign, None if the campaign does not exist.
+    ///
+    /// All updates apply in one transaction: if any wallet is not a
+    /// recipient, none is changed. `total_amount` moves by the difference.
+    pub async fn update_amounts(
+        &self,
+        address: &str,
+        updates: &[(String, Lamports)],
+        request_id: Option<&str>,
+    ) -> Result<Option<UpdateOutcome>, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+        let Some(status) =
+            sqlx::query_scalar!("SELECT status FROM campaigns WHERE address = $1 FOR UPDATE", address)
+                .fetch_optional(&mut *tx)
+                .await?
+        else {
+            return Ok(None);
+        };
+        if status != CAMPAIGN_STATUS_DRAFT {
+            return Ok(Some(UpdateOutcome::NotDraft(status)));
+        }
+
+        let mut changes = Vec::with_capacity(updates.len());
+        for (wallet, amount) in updates {
+            // The old amount is read in the same statement, before the update applies
+            let Some(old_amount) = sqlx::query_scalar!(
+                r#"
+                UPDATE recipients r SET amount = $3
+                FROM recipients old
+                WHERE old.id = r.id AND r.campaign_address = $1 AND r.wallet = $2
+                RETURNING old.amount
+                "#,
+                address,
+                wallet,
+                amount.to_string()
+            )
+            .fetch_optional(&mut *tx)
+            .await?
+            else {
+                return Ok(Some(UpdateOutcome::NotARecipient(wallet.clone())));
+            };
+            changes.push(AmountChange {
+                wallet: wallet.clone(),
+                old_amount,
+                new_amount: amount.to_string(),
+            });
+        }
+
+        let delta: i128 = changes
+            .iter()
+            .map(|c| c.new_amount.parse::<i128>().unwrap_or(0) - c.old_amount.parse::<i128>().unwrap_or(0))
+            .sum();
+        sqlx::query!(
+            r#"
+            UPDATE campaigns SET total_amount = (total_amount::NUMERIC + $2::TEXT::NUMERIC)::TEXT
+            WHERE address = $1
+            "#,
+            address,
+            delta.to_string()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for change in &changes {
+            sqlx::query!(
+                r#"
+                INSERT INTO audit_log (campaign_address, action, details, payload, created_at)
+                VALUES ($1, 'recipient_amount_updated', $2, $3, $4)
+                "#,
+                address,
+                format!("{}: {} -> {}", change.wallet, change.old_amount, change.new_amount),
+                serde_json::json!({
+                    "wallet": change.wallet,
+                    "old_amount": change.old_amount,
+                    "new_amount": change.new_amount,
+                    "request_id": request_id,
+                }),
+                Utc::now()
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(Some(UpdateOutcome::Updated(changes)))
+    }
+
+    /// Replace the cached proofs of a campaign with `proofs` as `(wallet, proof_json)`.
+    ///
+    /// Proofs of the previous tree are deleted in the same transaction, so a
+    /// rebuilt tree never serves stale proofs.
+    pub async fn replace_proofs(
+        &self,
+        address: &str,
+        proofs: &[(String, serde_json::Value)],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+        sqlx::query!("DELETE FROM merkle_proofs WHERE campaign_address = $1", address)
+            .execute(&mut *tx)
+            .await?;
+
+        let (wallets, proof_json): (Vec<String>, Vec<serde_json::Value>) = proofs.iter().cloned().unzip();
+        sqlx::query!(
+            r#"
+            INSERT INTO merkle_proofs (campaign_address, wallet, proof_json, proof_generated_at)
+            SELECT $1, p.wallet, p.proof_json, $4
+            FROM UNNEST($2::text[], $3::jsonb[]) AS p (wallet, proof_json)
+            "#,
+            address,
+            &wallets,
+            &proof_json,
+            Utc::now()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await
+    }
+
+    /// Leaves `(leaf_index, wallet, amount, secret)` of a campaign's tree snapshot
+    pub async fn merkle_snapshot(
+        &self,
+        campaign_id: &str,
+    ) -> Result<Option<Vec<(usize, String, u64, Hash)>>, sqlx::Error> {
+        let leaves = sqlx::query_scalar!("SELECT leaves FROM merkle_snapshots WHERE campaign_id = $1", campaign_id)
+            .fetch_optional(&self.db)
+            .await?;
+        Ok(leaves.and_then(SnapshotLeaf::parse))
+    }
+
+    /// Store a campaign's tree snapshot unless it already has one; false if it did
+    pub async fn save_merkle_snapshot(
+        &self,
+        campaign_id: &str,
+        leaves: &[(usize, String, u64, Hash)],
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO merkle_snapshots (campaign_id, leaves) VALUES ($1, $2)
+            ON CONFLICT (campaign_id) DO NOTHING
+            "#,
+            campaign_id,
+            SnapshotLeaf::to_json(leaves)
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Drop the tree snapshot of a campaign; returns the campaign id if it had one
+    pub async fn delete_merkle_snapshot(&self, address: &str) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            DELETE FROM merkle_snapshots
+            WHERE campaign_id = (SELECT id FROM campaigns WHERE address = $1)
+            RETURNING campaign_id
+            "#,
+            address
+        )
+        .fetch_optional(&self.db)
+        .await
+    }
+
+    /// Pre-generated proof of a recipient and when it was generated
+    pub async fn cached_proof(
+        &self,
+        address: &str,
+        wallet: &str,
+    ) -> Option<(serde_json::Value, DateTime<Utc>)> {
+        sqlx::query!(
+            r#"
+            SELECT proof_json, proof_generated_at FROM merkle_proofs
+            WHERE campaign_address = $1 AND wallet = $2
+            "#,
+            address,
+            wallet
+        )
+        .fetch_optional(&self.db)
+        .await
+        .ok()?
+        .map(|r| (r.proof_json, r.proof_generated_at))
+    }
+
+    /// Up to `limit` recipients with a leaf index above `cursor` (from the
+    /// first when None), None if the campaign does not exist
+    pub async fn recipients_page(
+        &self,
+        address: &str,
+        cursor: Option<usize>,
+        limit: usize,
+    ) -> Option<RecipientPage> {
+        let total = sqlx::query_scalar!(
+            r#"
+            SELECT (SELECT COUNT(*) FROM recipients WHERE campaign_address = c.address) AS "total!"
+            FROM campaigns c WHERE c.address = $1
+            "#,
+            address
+        )
+        .fetch_optional(&self.db)
+        .await
+        .ok()??;
+
+        // One extra row tells whether another page follows
+        let mut rows = sqlx::query!(
+            r#"
+            SELECT
+                wallet,
+                amount::NUMERIC::FLOAT8 / 1e9 AS "amount_sol!",
+                leaf_index,
+                CASE
+                    WHEN claimed THEN 'claimed'
+                    WHEN claim_count > 0 THEN 'partial'
+                    ELSE 'unclaimed'
+                END AS "claim_status!"
+            FROM recipients
+            WHERE campaign_address = $1 AND leaf_index > $2
+            ORDER BY leaf_index
+            LIMIT $3
+            "#,
+            address,
+            cursor.map_or(-1, |c| c.min(i32::MAX as usize) as i32),
+            limit as i64 + 1
+        )
+        .fetch_all(&self.db)
+        .await
+        .ok()?;
+
+        let has_more = rows.len() > limit;
+        rows.truncate(limit);
+        let recipients: Vec<RecipientSummary> = rows
+            .into_iter()
+            .map(|r| RecipientSummary {
+                wallet: r.wallet,
+                amount_sol: r.amount_sol,
+                leaf_index: r.leaf_index as usize,
+                claim_status: r.claim_status,
+            })
+            .collect();
+
+        Some(RecipientPage {
+            next_cursor: recipients.last().filter(|_| has_more).map(|r| r.leaf_index),
+            recipients,
+            total: total as usize,
+        })
+    }
+
+    /// Referred claims and pending bonus of a referrer, None if the campaign does not exist
+    pub async fn referral_stats(&self, address: &str, referrer: &str) -> Option<ReferralStats> {
+        sqlx::query_as!(
+            ReferralStats,
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM recipients
+                    WHERE campaign_address = c.address AND referrer_wallet = $2) AS "referred_count!",
+                (SELECT COALESCE(SUM(amount::NUMERIC), 0)::FLOAT8 / 1e9 FROM recipients
+                    WHERE campaign_address = c.address AND referrer_wallet = $2) AS "referred_volume_sol!",
+                COALESCE(
+                    (SELECT pending_amount FROM referral_rewards
+                        WHERE campaign_address = c.address AND referrer_wallet = $2),
+                    '0'
+                ) AS "pending_reward!"
+            FROM campaigns c WHERE c.address = $1
+            "#,
+            address,
+            referrer
+        )
+        .fetch_optional(&self.db)
+        .await
+        .ok()?
+    }
+
+    /// Get the status and pause reason of a campaign
+    pub async fn get_status(&self, address: &str) -> Option<(String, Option<String>)> {
+        let rec = metrics::time_db(
+            "campaign_get_status",
+            sqlx::query!("SELECT status, pause_reason FROM campaigns WHERE address = $1", address)
+                .fetch_optional(&self.db),
+        )
+        .await
+        .ok()??;
+
+        Some((rec.status, rec.pause_reason))
+    }
+
+    /// Change a campaign's status and record the change in the audit log.
+    /// Returns false if the campaign does not exist or is not in `from_status`.
+    ///
+    /// `request_id` is stored in the audit entry's payload for log correlation.
+    pub async fn transition_status(
+        &self,
+        address: &str,
+        from_status: &str,
+        to_status: &str,
+        pause_reason: Option<&str>,
+        request_id: Option<&str>,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+        if !transition_in(&mut tx, address, from_status, to_status, pause_reason, request_id).await? {
+            return Ok(false);
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Move a draft to active and commit it to `merkle_root`, the root of its
+    /// `leaf_count` recipients, in the same transaction. Returns false if the
+    /// campaign is not a draft.
+    pub async fn activate(
+        &self,
+        address: &str,
+        merkle_root: &Hash,
+        leaf_count: usize,
+        request_id: Option<&str>,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+        if !transition_in(&mut tx, address, CAMPAIGN_STATUS_DRAFT, CAMPAIGN_STATUS_ACTIVE, None, request_id).await? {
+            return Ok(false);
+        }
+
+        sqlx::query!(
+            "UPDATE campaigns SET merkle_root = $2, merkle_commitment = $3 WHERE address = $1",
+            address,
+            hex::encode(merkle_root),
+            commitment_bytes(merkle_root, leaf_count, Utc::now())
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Copy a campaign into a new draft, returning the new address (None if
+    /// the source does not exist).
+    ///
+    /// Settings, the claim window length and the recipient list carry over.
+    /// Claims, nullifiers and the Merkle root do not: the root is rebuilt
+    /// when the draft is activated. Drafts have no on-chain account yet, so
+    /// the new id doubles as the address until one is registered.
+    pub async fn clone_campaign(
+        &self,
+        source_address: &str,
+        request_id: Option<&str>,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+        let new_address = Uuid::new_v4().to_string();
+        let now = Utc::now();
+
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO campaigns (
+                id, address, name, merkle_root, total_amount, creator_wallet, created_at,
+                airdrop_type, vesting_start, vesting_cliff_seconds, vesting_duration_seconds,
+                token_mint, token_symbol, token_decimals, status, close_at, referral_bonus_bps,
+                max_claims
+            )
+            SELECT
+                $2, $2, name, '', total_amount, creator_wallet, $3::TIMESTAMPTZ,
+                airdrop_type, $4, vesting_cliff_seconds, vesting_duration_seconds,
+                token_mint, token_symbol, token_decimals, $5, $3::TIMESTAMPTZ + (close_at - created_at),
+                referral_bonus_bps, max_claims
+            FROM campaigns WHERE address = $1
+            "#,
+            source_address,
+            new_address,
+            now,
+            now.timestamp(),
+            CAMPAIGN_STATUS_DRAFT
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if inserted == 0 {
+            return Ok(None);
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO recipients (campaign_address, wallet, amount, claimed, leaf_index)
+            SELECT $2, wallet, amount, false, leaf_index FROM recipients WHERE campaign_address = $1
+            "#,
+            source_address,
+            new_address
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO audit_log (campaign_address, action, details, payload, created_at)
+            VALUES ($1, 'campaign_cloned', $2, $3, $4)
+            "#,
+            new_address,
+            format!("cloned from {}", source_address),
+            serde_json::json!({ "source_campaign": source_address, "request_id": request_id }),
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(new_address))
+    }
+
+    /// Add the next round to a campaign, returning it (None if the campaign
+    /// does not exist).
+    ///
+    /// The campaign row is locked while the round number is picked, and the
+    /// campaign's `merkle_root` and commitment are set to the new round's.
+    /// The round's leaves are stored with it, so its proofs come from the
+    /// tree its `merkle_root` was built from.
+    pub async fn create_round(
+        &self,
+        address: &str,
+        round: NewRound<'_>,
+        request_id: Option<&str>,
+    ) -> Result<Option<CampaignRound>, sqlx::Error> {
+        let NewRound { merkle_root, leaves, open_at, close_at, recipients } = round;
+        let mut tx = self.db.begin().await?;
+        let exists = sqlx::query_scalar!("SELECT address FROM campaigns WHERE address = $1 FOR UPDATE", address)
+            .fetch_optional(&mut *tx)
+            .await?
+            .is_some();
+        if !exists {
+            return Ok(None);
+        }
+
+        let round_id = Uuid::new_v4();
+        let round_number = sqlx::query_scalar!(
+            r#"
+            INSERT INTO campaign_rounds (
+                id, campaign_address, round_number, merkle_root, open_at, close_at, status, created_at, leaves
+            )
+            SELECT $1, $2, COALESCE(MAX(round_number), 0) + 1, $3, $4, $5, $6, $7, $8
+            FROM campaign_rounds WHERE campaign_address = $2
+            RETURNING round_number
+            "#,
+            round_id,
+            address,
+            &merkle_root[..],
+            open_at,
+            close_at,
+            ROUND_STATUS_ACTIVE,
+            Utc::now(),
+            SnapshotLeaf::to_json(leaves)
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let (wallets, amounts): (Vec<String>, Vec<String>) = recipients.iter().cloned().unzip();
+        sqlx::query!(
+            r#"
+            INSERT INTO round_recipients (round_id, wallet, amount)
+            SELECT $1, wallet, amount FROM UNNEST($2::text[], $3::text[]) AS r (wallet, amount)
+            "#,
+            round_id,
+            &wallets,
+            &amounts
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE campaigns SET merkle_root = $2, merkle_commitment = $3 WHERE address = $1",
+            address,
+            hex::encode(merkle_root),
+            commitment_bytes(&merkle_root, recipients.len(), Utc::now())
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO audit_log (campaign_address, action, details, payload, created_at)
+            VALUES ($1, 'round_created', $2, $3, $4)
+            "#,
+            address,
+            format!("round {}", round_number),
+            serde_json::json!({
+                "round_id": round_id.to_string(),
+                "round_number": round_number,
+                "request_id": request_id,
+            }),
+            Utc::now()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(self.get_round(address, round_id).await)
+    }
+
+    /// Leaves `(leaf_index, wallet, amount, secret)` of a round's tree, None
+    /// for a round created without one
+    pub async fn round_snapshot(&self, round_id: Uuid) -> Result<Option<Vec<(usize, String, u64, Hash)>>, sqlx::Error> {
+        let leaves = sqlx::query_scalar!("SELECT leaves FROM campaign_rounds WHERE id = $1", round_id)
+            .fetch_optional(&self.db)
+            .await?
+            .flatten();
+        Ok(leaves.and_then(SnapshotLeaf::parse))
+    }
+
+    /// Get a round of a campaign with its recipients
+    pub async fn get_round(&self, address: &str, round_id: Uuid) -> Option<CampaignRound> {
+        let rec = sqlx::query!(
+            "SELECT * FROM campaign_rounds WHERE id = $1 AND campaign_address = $2",
+            round_id,
+            address
+        )
+        .fetch_optional(&self.db)
+        .await
+        .ok()??;
+
+        let recipients = sqlx::query!(
+            r#"
+            SELECT wallet, amount, claimed, claimed_at
+            FROM round_recipients
+            WHERE round_id = $1
+            ORDER BY wallet
+            "#,
+            round_id
+        )
+        .fetch_all(&self.db)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|r| Recipient {
+            id: None,
+            wallet: r.wallet,
+            amount: r.amount,
+            claimed: r.claimed,
+            claimed_at: r.claimed_at,
+            leaf_index: None,
+        })
+        .collect();
+
+        Some(CampaignRound {
+            id: rec.id,
+            campaign_address: rec.campaign_address,
+            round_number: rec.round_number,
+            merkle_root: rec.merkle_root,
+            open_at: rec.open_at,
+            close_at: rec.close_at,
+            status: rec.status,
+            created_at: rec.created_at,
+            recipients,
+        })
+    }
+
+    /// Mark a recipient of a round as claimed, spending the round-scoped
+    /// `nullifier` in the same transaction, and return the claimed amount.
+    /// None, with the nullifier unspent, if the wallet cannot claim.
+    pub async fn mark_round_claimed(
+        &self,
+        address: &str,
+        round_id: Uuid,
+        wallet: &str,
+        nullifier: &Hash,
+    ) -> Result<Option<String>, DBError> {
+        let mut tx = self.db.begin().await?;
+        nullifiers::spend(&mut tx, nullifier, address, self.nullifier_retention_days).await?;
+        let amount = sqlx::query_scalar!(
+            r#"
+            UPDATE round_recipients
+            SET claimed = true, claimed_at = $1
+            WHERE round_id = $2 AND wallet = $3 AND claimed = false
+            RETURNING amount
+            "#,
+            Utc::now(),
+            round_id,
+            wallet
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if amount.is_some() {
+            tx.commit().await?;
+        }
+        Ok(amount)
+    }
+
+    /// Campaigns listing `wallet` as recipient, newest first, optionally
+    /// filtered by campaign status and whether the wallet has claimed
+    pub async fn campaigns_for_recipient(
+        &self,
+        wallet: &str,
+        status: Option<&str>,
+        claimed: Option<bool>,
+    ) -> Vec<RecipientCampaign> {
+        sqlx::query_as!(
+            RecipientCampaign,
+            r#"
+            SELECT
+                c.id AS campaign_id,
+                c.address,
+                c.name,
+                c.status,
+                r.amount::NUMERIC::FLOAT8 / 1e9 AS "amount_sol!",
+                CASE
+                    WHEN r.claimed THEN 'claimed'
+                    WHEN r.claim_count > 0 THEN 'partial'
+                    ELSE 'unclaimed'
+                END AS "claim_status!",
+                c.close_at
+            FROM recipients r
+            JOIN campaigns c ON c.address = r.campaign_address
+            WHERE r.wallet = $1
+                AND ($2::TEXT IS NULL OR c.status = $2)
+                AND ($3::BOOLEAN IS NULL OR r.claimed = $3)
+            ORDER BY c.created_at DESC
+            "#,
+            wallet,
+            status,
+            claimed
+        )
+        .fetch_all(&self.db)
+        .await
+        .unwrap_or_default()
+    }
+
+    /// Eligibility of `wallet` for each campaign id, in input order, from a
+    /// single query. Unknown campaign ids are reported as not eligible.
+    pub async fn check_eligibility_many(
+        &self,
+        wallet: &str,
+        campaign_ids: &[String],
+    ) -> Result<Vec<EligibilityCheck>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                c.id AS campaign_id,
+                r.amount::NUMERIC::FLOAT8 / 1e9 AS "amount_sol!",
+                CASE
+                    WHEN r.claimed THEN 'claimed'
+                    WHEN r.claim_count > 0 THEN 'partial'
+                    ELSE 'unclaimed'
+                END AS "claim_status!"
+            FROM recipients r
+            JOIN campaigns c ON c.address = r.campaign_address
+            WHERE r.wallet = $1 AND c.id = ANY($2)
+            "#,
+            wallet,
+            campaign_ids
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let found: HashMap<String, (f64, String)> = rows
+            .into_iter()
+            .map(|row| (row.campaign_id, (row.amount_sol, row.claim_status)))
+            .collect();
+        Ok(campaign_ids
+            .iter()
+            .map(|id| {
+                let (amount_sol, claim_status) = found.get(id).cloned().unzip();
+                EligibilityCheck {
+                    campaign_id: id.clone(),
+                    eligible: amount_sol.is_some(),
+                    amount_sol,
+                    claim_status,
+                }
+            })
+            .collect())
+    }
+
+    /// Get all campaigns where the wallet is eligible to claim
+    pub async fn get_eligible_for_wallet(&self, wallet: &str) -> Vec<EligibleCampaign> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT c.address, c.name, c.total_amount, c.vault_address, c.created_at, 
+                   c.token_mint, c.token_symbol, c.token_decimals,
+                   r.amount,
+                   (SELECT COUNT(*) FROM recipients WHERE campaign_address = c.address) as total_recipients
+            FROM campaigns c
+            JOIN recipients r ON c.address = r.campaign_address
+            WHERE r.wallet = $1 AND r.claimed = false
+            "#,
+            wallet
+        )
+        .fetch_all(&self.db)
+        .await
+        .unwrap_or_default();
+
+        rows.into_iter().map(|row| EligibleCampaign {
+            address: row.address,
+            name: row.name,
+            amount: row.amount,
+            total_amount: row.total_amount,
+            total_recipients: row.total_recipients.unwrap_or(0) as usize,
+            vault_address: row.vault_address,
+            created_at: row.created_at,
+            token_mint: row.token_mint,
+            token_symbol: row.token_symbol,
+            token_decimals: row.token_decimals.map(|d| d as u8),
+        }).collect()
+    }
+}
+
+/// How long a stored idempotent response stays valid
+pub const IDEMPOTENCY_KEY_TTL_HOURS: i64 = 24;
+
+/// State of an idempotency key when a request tries to claim it
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdempotencyState {
+    /// The key was free and is now reserved for this request
+    Acquired,
+    /// Another request holding the key has not finished yet
+    Processing,
+    /// A previous request finished; its response is replayed
+    Completed {
+        status_code: u16,
+        response_body: serde_json::Value,
+    },
+}
+
+/// Persistent idempotency key store using PostgreSQL
+#[derive(Debug, Clone)]
+pub struct IdempotencyStore {
+    db: PgPool,
+}
+
+impl IdempotencyStore {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Reserve a key for a campaign, or report what an earlier request did with it
+    pub async fn begin(
+        &self,
+        key: &str,
+        campaign_address: &str,
+    ) -> Result<IdempotencyState, sqlx::Error> {
+        self.purge_expired().await?;
+
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO idempotency_keys (idempotency_key, campaign_address, created_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (idempotency_key, campaign_address) DO NOTHING
+            "#,
+            key,
+            campaign_address,
+            Utc::now()
+        )
+        .execute(&self.db)
+        .await?
+        .rows_affected()
+            > 0;
+
+        if inserted {
+            return Ok(IdempotencyState::Acquired);
+        }
+
+        let existing = sqlx::query!(
+            r#"
+            SELECT status_code, response_body
+            FROM idempotency_keys
+            WHERE idempotency_key = $1 AND campaign_address = $2
+            "#,
+            key,
+            campaign_address
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(match existing {
+            Some(rec) => match (rec.status_code, rec.response_body) {
+                (Some(status_code), Some(response_body)) => IdempotencyState::Completed {
+                    status_code: status_code as u16,
+                    response_body,
+                },
+                _ => IdempotencyState::Processing,
+            },
+            // Expired and purged between the insert and the lookup
+            None => IdempotencyState::Processing,
+        })
+    }
+
+    /// Store the final response for a reserved key
+    pub async fn complete(
+        &self,
+        key: &str,
+        campaign_address: &str,
+        status_code: u16,
+        response_body: &serde_json::Value,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE idempotency_keys
+            SET status_code = $1, response_body = $2
+            WHERE idempotency_key = $3 AND campaign_address = $4
+            "#,
+            status_code as i16,
+            response_body,
+            key,
+            campaign_address
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Delete keys older than the TTL
+    pub async fn purge_expired(&self) -> Result<u64, sqlx::Error> {
+        let cutoff = Utc::now() - chrono::Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS);
+        let result = sqlx::query!(
+            "DELETE FROM idempotency_keys WHERE created_at < $1",
+            cutoff
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Hours a finished proof job is kept before cleanup
+pub const PROOF_JOB_TTL_HOURS: i64 = 1;
+
+/// Channel notified with the id of each new proof job
+pub const PROOF_JOB_CHANNEL: &str = "proof_jobs";
+
+/// Job waiting for a worker
+pub const PROOF_JOB_PENDING: &str = "pending";
+/// Job picked up by a worker and being proved
+pub const PROOF_JOB_RUNNING: &str = "running";
+/// Job whose proof is stored in `result`
+pub const PROOF_JOB_COMPLETED: &str = "completed";
+/// Job whose proof could not be computed; the reason is in `error`
+pub const PROOF_JOB_FAILED: &str = "failed";
+/// Job cancelled before a worker picked it up
+pub const PROOF_JOB_CANCELLED: &str = "cancelled";
+
+/// Background proof generation job
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ProofJob {
+    #[serde(rename = "job_id")]
+    pub id: Uuid,
+    pub campaign_address: String,
+    pub wallet: String,
+    pub status: String,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl ProofJob {
+    /// Whether the job completed, failed or was cancelled
+    pub fn is_finished(&self) -> bool {
+        self.completed_at.is_some()
+    }
+}
+
+/// Result of cancelling a proof job
+#[derive(Debug, Clone)]
+pub enum CancelJobOutcome {
+    Cancelled(ProofJob),
+    NotFound,
+    /// The job already finished; carries its status
+    NotPending(String),
+}
+
+/// Proof jobs stored in PostgreSQL
+#[derive(Debug, Clone)]
+pub struct ProofJobStore {
+    db: PgPool,
+}
+
+impl ProofJobStore {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Queue a proof of `wallet`'s claim on a campaign, notifying
+    /// `PROOF_JOB_CHANNEL` with the new job's id when the insert commits
+    pub async fn create(&self, campaign_address: &str, wallet: &str) -> Result<Uuid, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let mut tx = self.db.begin().await?;
+        sqlx::query!(
+            "INSERT INTO proof_jobs (id, campaign_address, wallet, status) VALUES ($1, $2, $3, $4)",
+            id,
+            campaign_address,
+            wallet,
+            PROOF_JOB_PENDING
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!("SELECT pg_notify($1, $2::text)", PROOF_JOB_CHANNEL, id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(id)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<ProofJob>, sqlx::Error> {
+        sqlx::query_as!(
+            ProofJob,
+            r#"
+            SELECT id, campaign_address, wallet, status, result, error, created_at, started_at, completed_at
+            FROM proof_jobs WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.db)
+        .await
+    }
+
+    /// Take the oldest pending job and mark it running, or `None` if the
+    /// queue is empty. `SKIP LOCKED` lets concurrent workers each take a
+    /// different job instead of waiting on one another.
+    pub async fn claim_next(&self) -> Result<Option<ProofJob>, sqlx::Error> {
+        sqlx::query_as!(
+            ProofJob,
+            r#"
+            UPDATE proof_jobs
+            SET status = $1, started_at = NOW()
+            WHERE id = (
+                SELECT id FROM proof_jobs
+                WHERE status = $2
+                ORDER BY created_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, campaign_address, wallet, status, result, error, created_at, started_at, completed_at
+            "#,
+            PROOF_JOB_RUNNING,
+            PROOF_JOB_PENDING
+        )
+        .fetch_optional(&self.db)
+        .await
+    }
+
+    /// Store the proof of a running job, returning the finished job. `None`
+    /// if the job is not running (e.g. purged meanwhile); the proof is dropped.
+    pub async fn complete(&self, id: Uuid, result: &serde_json::Value) -> Result<Option<ProofJob>, sqlx::Error> {
+        self.finish(id, PROOF_JOB_RUNNING, PROOF_JOB_COMPLETED, Some(result), None).await
+    }
+
+    /// Mark a running job as failed with `error`
+    pub async fn fail(&self, id: Uuid, error: &str) -> Result<Option<ProofJob>, sqlx::Error> {
+        self.finish(id, PROOF_JOB_RUNNING, PROOF_JOB_FAILED, None, Some(error)).await
+    }
+
+    /// Cancel a job no worker has picked up yet
+    pub async fn cancel(&self, id: Uuid) -> Result<CancelJobOutcome, sqlx::Error> {
+        if let Some(job) = self.finish(id, PROOF_JOB_PENDING, PROOF_JOB_CANCELLED, None, None).await? {
+            return Ok(CancelJobOutcome::Cancelled(job));
+        }
+        Ok(match self.get(id).await? {
+            Some(job) => CancelJobOutcome::NotPending(job.status),
+            None => CancelJobOutcome::NotFound,
+        })
+    }
+
+    /// Move a job from status `from` to the final `status`
+    async fn finish(
+        &self,
+        id: Uuid,
+        from: &str,
+        status: &str,
+        result: Option<&serde_json::Value>,
+        error: Option<&str>,
+    ) -> Result<Option<ProofJob>, sqlx::Error> {
+        sqlx::query_as!(
+            ProofJob,
+            r#"
+            UPDATE proof_jobs
+            SET status = $2, result = $3, error = $4, completed_at = NOW()
+            WHERE id = $1 AND status = $5
+            RETURNING id, campaign_address, wallet, status, result, error, created_at, started_at, completed_at
+            "#,
+            id,
+            status,
+            result,
+            error,
+            from
+        )
+        .fetch_optional(&self.db)
+        .await
+    }
+
+    /// Connection listening on `PROOF_JOB_CHANNEL`
+    pub async fn listen(&self) -> Result<PgListener, sqlx::Error> {
+        let mut listener = PgListener::connect_with(&self.db).await?;
+        listener.listen(PROOF_JOB_CHANNEL).await?;
+        Ok(listener)
+    }
+
+    /// Delete jobs that finished more than `PROOF_JOB_TTL_HOURS` ago
+    pub async fn purge_expired(&self) -> Result<u64, sqlx::Error> {
+        let cutoff = Utc::now() - chrono::Duration::hours(PROOF_JOB_TTL_HOURS);
+        let result = sqlx::query!("DELETE FROM proof_jobs WHERE completed_at < $1", cutoff)
+            .execute(&self.db)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Run `purge_expired` every `period` until the runtime shuts down
+    pub fn spawn_cleanup_task(self, period: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(period);
+            loop {
+                ticker.tick().await;
+                match self.purge_expired().await {
+                    Ok(0) => {}
+                    Ok(purged) => tracing::info!(purged, "🧹 Purged finished proof jobs"),
+                    Err(e) => tracing::error!("Failed to purge proof jobs: {}", e),
+                }
+            }
+        })
+    }
+}
+
+/// Minutes a sign-in nonce can be used for
+pub const SIWS_NONCE_TTL_MINUTES: i64 = 10;
+
+/// Sign-in nonces stored in PostgreSQL, each usable once
+#[derive(Debug, Clone)]
+pub struct SiwsNonceStore {
+    db: PgPool,
+}
+
+impl SiwsNonceStore {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Record an issued nonce; returns when it expires
+    pub async fn issue(&self, nonce: &str) -> Result<DateTime<Utc>, sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query!("DELETE FROM siws_nonces WHERE expires_at <= $1", now)
+            .execute(&self.db)
+            .await?;
+
+        let expires_at = now + chrono::Duration::minutes(SIWS_NONCE_TTL_MINUTES);
+        sqlx::query!(
+            "INSERT INTO siws_nonces (nonce, expires_at) VALUES ($1, $2)",
+            nonce,
+            expires_at
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(expires_at)
+    }
+
+    /// Use up a nonce; false if it was never issued, has expired or was used
+    pub async fn consume(&self, nonce: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM siws_nonces WHERE nonce = $1 AND expires_at > $2",
+            nonce,
+            Utc::now()
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// One leaf of a `merkle_snapshots` row or of a round's `leaves`
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotLeaf {
+    wallet: String,
+    amount: u64,
+    /// 32 bytes hex
+    secret: String,
+    /// Absent from snapshots taken before leaves kept their stored index,
+    /// whose leaves sit in list order
+    #[serde(default)]
+    leaf_index: Option<usize>,
+}
+
+impl SnapshotLeaf {
+    /// Stored snapshot of leaves `(leaf_index, wallet, amount, secret)`
+    fn to_json(leaves: &[(usize, String, u64, Hash)]) -> serde_json::Value {
+        let leaves: Vec<SnapshotLeaf> = leaves
+            .iter()
+            .map(|(leaf_index, wallet, amount, secret)| SnapshotLeaf {
+                wallet: wallet.clone(),
+                amount: *amount,
+                secret: hex::encode(secret),
+                leaf_index: Some(*leaf_index),
+            })
+            .collect();
+        serde_json::to_value(leaves).expect("leaves serialize")
+    }
+
+    /// Leaves of a stored snapshot, None if it is malformed
+    fn parse(leaves: serde_json::Value) -> Option<Vec<(usize, String, u64, Hash)>> {
+        let leaves: Vec<SnapshotLeaf> = serde_json::from_value(leaves).ok()?;
+        leaves
+            .into_iter()
+            .enumerate()
+            .map(|(i, leaf)| {
+                let secret = parse_hash(&leaf.secret)?;
+                Some((leaf.leaf_index.unwrap_or(i), leaf.wallet, leaf.amount, secret))
+            })
+            .collect()
+    }
+}
+
+/// Claim transaction sent, not confirmed yet
+pub const CLAIM_TX_SUBMITTED: &str = "submitted";
+/// Claim transaction landed at the awaited commitment
+pub const CLAIM_TX_CONFIRMED: &str = "confirmed";
+/// Claim transaction failed on-chain or was not confirmed in time
+pub const CLAIM_TX_FAILED: &str = "failed";
+
+/// Claim transactions the backend sent on-chain, stored in `claim_transactions`
+#[derive(Debug, Clone)]
+pub struct ClaimTransactionStore {
+    db: PgPool,
+}
+
+impl ClaimTransactionStore {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Record a sent claim transaction as `CLAIM_TX_SUBMITTED`
+    pub async fn record(
+        &self,
+        signature: &str,
+        campaign_address: &str,
+        wallet: &str,
+        bundle_id: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO claim_transactions (signature, campaign_address, wallet, bundle_id, status)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (signature) DO NOTHING
+            "#,
+            signature,
+            campaign_address,
+            wallet,
+            bundle_id,
+            CLAIM_TX_SUBMITTED
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    /// Set the outcome of a sent transaction, with the slot it landed in
+    pub async fn set_status(&self, signature: &str, status: &str, slot: Option<u64>) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE claim_transactions SET status = $2, slot = $3, updated_at = NOW() WHERE signature = $1",
+            signature,
+            status,
+            slot.map(|slot| slot as i64)
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    /// Status of a sent transaction, if it is known
+    pub async fn status(&self, signature: &str) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!("SELECT status FROM claim_transactions WHERE signature = $1", signature)
+            .fetch_optional(&self.db)
+            .await
+    }
+}
+
+/// Dead-lettered webhook waiting for its next retry
+pub const WEBHOOK_PENDING: &str = "pending";
+/// Dead-lettered webhook whose retries all failed; only replayed by hand
+pub const WEBHOOK_FAILED: &str = "failed";
+/// Retries of a dead-lettered webhook before it is marked failed
+pub const WEBHOOK_MAX_RETRIES: i32 = 10;
+
+/// A webhook event whose delivery failed
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct DeadLetter {
+    pub id: Uuid,
+    pub campaign_id: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    /// Retries made so far; the first delivery is not counted
+    pub attempt_count: i32,
+    pub last_attempt_at: DateTime<Utc>,
+    pub next_attempt_at: DateTime<Utc>,
+    /// Why the last delivery failed
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Dead-lettered webhooks stored in PostgreSQL
+#[derive(Debug, Clone)]
+pub struct WebhookDlqStore {
+    db: PgPool,
+}
+
+impl WebhookDlqStore {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Dead-letter a webhook of the campaign at `address` whose first
+    /// delivery failed with `error`; it is retried a second later. None if
+    /// the campaign does not exist.
+    pub async fn insert(
+        &self,
+        address: &str,
+        payload: &serde_json::Value,
+        error: &str,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO webhook_dlq (id, campaign_id, payload, status, last_attempt_at, next_attempt_at, error)
+            SELECT $1, id, $3, $4, NOW(), NOW() + INTERVAL '1 second', $5
+            FROM campaigns WHERE address = $2
+            RETURNING id
+            "#,
+            Uuid::new_v4(),
+            address,
+            payload,
+            WEBHOOK_PENDING,
+            error
+        )
+        .fetch_optional(&self.db)
+        .await
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<DeadLetter>, sqlx::Error> {
+        sqlx::query_as!(DeadLetter, "SELECT * FROM webhook_dlq WHERE id = $1", id)
+            .fetch_optional(&self.db)
+            .await
+    }
+
+    /// Dead letters, newest first, optionally only those with `status`
+    pub async fn list(&self, status: Option<&str>, limit: i64) -> Result<Vec<DeadLetter>, sqlx::Error> {
+        sqlx::query_as!(
+            DeadLetter,
+            r#"
+            SELECT * FROM webhook_dlq
+            WHERE $1::TEXT IS NULL OR status = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+            status,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await
+    }
+
+    /// Up to `limit` pending dead letters due for a retry, the longest overdue first
+    pub async fn due(&self, limit: i64) -> Result<Vec<DeadLetter>, sqlx::Error> {
+        sqlx::query_as!(
+            DeadLetter,
+            r#"
+            SELECT * FROM webhook_dlq
+            WHERE status = $1 AND next_attempt_at <= NOW()
+            ORDER BY next_attempt_at
+            LIMIT $2
+            "#,
+            WEBHOOK_PENDING,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await
+    }
+
+    /// Drop a dead letter once it is delivered; false if it was gone already
+    pub async fn delete(&self, id: Uuid) -> Result<bool, sqlx::Error> {
+        let deleted = sqlx::query!("DELETE FROM webhook_dlq WHERE id = $1", id)
+            .execute(&self.db)
+            .await?
+            .rows_affected();
+        Ok(deleted > 0)
+    }
+
+    /// Record a failed retry. Retry `n` (1-based) is followed by the next
+    /// after `2^n` seconds, so retries wait 1s, 2s, 4s, ... 512s; the event is
+    /// marked failed once `WEBHOOK_MAX_RETRIES` retries have failed.
+    pub async fn record_failure(&self, id: Uuid, error: &str) -> Result<Option<DeadLetter>, sqlx::Error> {
+        sqlx::query_as!(
+            DeadLetter,
+            r#"
+            UPDATE webhook_dlq
+            SET attempt_count = attempt_count + 1,
+                last_attempt_at = NOW(),
+                next_attempt_at = NOW() + make_interval(secs => power(2, attempt_count + 1)),
+                status = CASE WHEN attempt_count + 1 >= $3 THEN $4 ELSE status END,
+                error = $2
+            WHERE id = $1
+            RETURNING *
+            "#,
+            id,
+            error,
+            WEBHOOK_MAX_RETRIES,
+            WEBHOOK_FAILED
+        )
+        .fetch_optional(&self.db)
+        .await
     }
 }
@@ -0,0 +1,494 @@
+//! Spent-nullifier storage
+//!
+//! Handlers depend on `Arc<dyn NullifierDB>` so they can be exercised against
+//! `InMemoryNullifierDB` without a database. `PgNullifierDB` is the production backend.
+//! `NullifierBloomFilter` sits in front of it: a nullifier the filter has never
+//! seen is certainly unspent by this process and needs no query. Claims spend
+//! their nullifier with `spend`, in the transaction recording the claim, so a
+//! rejected or interrupted claim leaves it unspent.
+
+use std::{
+    collections::{HashMap, HashSet, hash_map::Entry},
+    future::Future,
+    hash::{BuildHasher, RandomState},
+    pin::Pin,
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
+use sqlx::{PgConnection, PgPool};
+use tokio::task::JoinHandle;
+
+use crate::{common::merkle::Hash, metrics};
+
+/// Nullifier storage errors
+#[derive(Debug, thiserror::Error)]
+pub enum DBError {
+    #[error("nullifier {} is already spent", hex::encode(.0))]
+    AlreadySpent(Hash),
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// Boxed future returned by `NullifierDB` methods, keeping the trait object safe
+pub type DBFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, DBError>> + Send + 'a>>;
+
+/// Where and when a nullifier was spent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpentNullifier {
+    pub campaign_address: String,
+    pub spent_at: DateTime<Utc>,
+}
+
+/// Record of which nullifiers have been used to claim
+pub trait NullifierDB: Send + Sync {
+    /// Whether the nullifier has been spent
+    fn is_spent<'a>(&'a self, nullifier: &'a Hash) -> DBFuture<'a, bool>;
+
+    /// The spend of a nullifier, `None` if it is unspent
+    fn find_spent<'a>(&'a self, nullifier: &'a Hash) -> DBFuture<'a, Option<SpentNullifier>>;
+
+    /// Atomically mark a nullifier as spent for a campaign.
+    /// Fails with `DBError::AlreadySpent` if it was spent before.
+    fn mark_spent<'a>(&'a self, nullifier: Hash, campaign_address: &'a str) -> DBFuture<'a, ()>;
+
+    /// Spent flags for several nullifiers, in input order
+    fn batch_is_spent<'a>(&'a self, nullifiers: &'a [Hash]) -> DBFuture<'a, Vec<bool>>;
+}
+
+/// Mark a nullifier as spent for a campaign on `conn`, typically inside the
+/// transaction of the claim it pays for. An expired entry is replaced as if
+/// it had never been spent. Fails with `DBError::AlreadySpent` otherwise.
+pub async fn spend(
+    conn: &mut PgConnection,
+    nullifier: &Hash,
+    campaign_address: &str,
+    retention_days: i64,
+) -> Result<(), DBError> {
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO nullifiers (nullifier, campaign_address, spent_at, expires_at)
+        VALUES (
+            $1, $2, $3,
+            (SELECT close_at + make_interval(days => $4) FROM campaigns WHERE address = $2)
+        )
+        ON CONFLICT (nullifier) DO UPDATE
+        SET campaign_address = EXCLUDED.campaign_address,
+            spent_at = EXCLUDED.spent_at,
+            expires_at = EXCLUDED.expires_at
+        WHERE nullifiers.expires_at <= NOW()
+        "#,
+        &nullifier[..],
+        campaign_address,
+        Utc::now(),
+        retention_days as i32
+    )
+    .execute(conn)
+    .await?
+    .rows_affected();
+
+    if inserted == 0 {
+        return Err(DBError::AlreadySpent(*nullifier));
+    }
+    Ok(())
+}
+
+/// Rows per statement in `PgNullifierDB::batch_insert`
+const BATCH_INSERT_CHUNK: usize = 1000;
+
+/// Nullifiers the Bloom filter is sized for at least
+const BLOOM_MIN_CAPACITY: usize = 1_000_000;
+
+/// False positive rate of the Bloom filter at its capacity
+const BLOOM_FALSE_POSITIVE_RATE: f64 = 0.01;
+
+/// Postgres-backed nullifier store
+///
+/// Nullifiers of campaigns with a `close_at` expire `retention_days` after it.
+/// Expired nullifiers count as unspent, so the same claim can be submitted
+/// again once the window has closed (used to reclaim unclaimed tokens).
+#[derive(Clone)]
+pub struct PgNullifierDB {
+    db: PgPool,
+    retention_days: i64,
+}
+
+impl PgNullifierDB {
+    pub fn new(db: PgPool, retention_days: i64) -> Self {
+        Self { db, retention_days }
+    }
+
+    /// Delete expired nullifiers, returning how many were removed
+    pub async fn purge_expired(&self) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM nullifiers WHERE expires_at < NOW()")
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Register many spent nullifiers at once, e.g. when sealing a closed campaign.
+    ///
+    /// Entries are `(nullifier, campaign_address)`. Inserts run in one
+    /// transaction, `BATCH_INSERT_CHUNK` rows per statement. Returns how many
+    /// rows were inserted; nullifiers that are already spent are skipped.
+    pub async fn batch_insert(&self, entries: &[(Hash, String)]) -> Result<usize, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+        let spent_at = Utc::now();
+        let mut inserted = 0;
+
+        for chunk in entries.chunks(BATCH_INSERT_CHUNK) {
+            let (nullifiers, campaigns): (Vec<Vec<u8>>, Vec<String>) = chunk
+                .iter()
+                .map(|(nullifier, campaign)| (nullifier.to_vec(), campaign.clone()))
+                .unzip();
+
+            // LEFT JOIN keeps rows of unknown campaigns so the foreign key rejects them
+            inserted += sqlx::query!(
+                r#"
+                INSERT INTO nullifiers (nullifier, campaign_address, spent_at, expires_at)
+                SELECT n.nullifier, n.campaign_address, $3, c.close_at + make_interval(days => $4)
+                FROM UNNEST($1::bytea[], $2::text[]) AS n (nullifier, campaign_address)
+                LEFT JOIN campaigns c ON c.address = n.campaign_address
+                ON CONFLICT (nullifier) DO NOTHING
+                "#,
+                &nullifiers,
+                &campaigns,
+                spent_at,
+                self.retention_days as i32
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected() as usize;
+        }
+
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    /// Run `purge_expired` every `period` until the runtime shuts down
+    pub fn spawn_expiry_task(self, period: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(period);
+            loop {
+                ticker.tick().await;
+                match self.purge_expired().await {
+                    Ok(0) => {}
+                    Ok(purged) => tracing::info!(purged, "🧹 Purged expired nullifiers"),
+                    Err(e) => tracing::error!("Failed to purge expired nullifiers: {}", e),
+                }
+            }
+        })
+    }
+}
+
+impl NullifierDB for PgNullifierDB {
+    fn is_spent<'a>(&'a self, nullifier: &'a Hash) -> DBFuture<'a, bool> {
+        Box::pin(metrics::time_db("nullifier_is_spent", async move {
+            let spent = sqlx::query_scalar!(
+                r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM nullifiers
+                    WHERE nullifier = $1 AND (expires_at IS NULL OR expires_at > NOW())
+                ) AS "spent!"
+                "#,
+                &nullifier[..]
+            )
+            .fetch_one(&self.db)
+            .await?;
+            Ok(spent)
+        }))
+    }
+
+    fn find_spent<'a>(&'a self, nullifier: &'a Hash) -> DBFuture<'a, Option<SpentNullifier>> {
+        Box::pin(metrics::time_db("nullifier_find_spent", async move {
+            let spent = sqlx::query_as!(
+                SpentNullifier,
+                r#"
+                SELECT campaign_address, spent_at FROM nullifiers
+                WHERE nullifier = $1 AND (expires_at IS NULL OR expires_at > NOW())
+                "#,
+                &nullifier[..]
+            )
+            .fetch_optional(&self.db)
+            .await?;
+            Ok(spent)
+        }))
+    }
+
+    fn mark_spent<'a>(&'a self, nullifier: Hash, campaign_address: &'a str) -> DBFuture<'a, ()> {
+        Box::pin(metrics::time_db("nullifier_mark_spent", async move {
+            let mut conn = self.db.acquire().await?;
+            spend(&mut conn, &nullifier, campaign_address, self.retention_days).await
+        }))
+    }
+
+    fn batch_is_spent<'a>(&'a self, nullifiers: &'a [Hash]) -> DBFuture<'a, Vec<bool>> {
+        Box::pin(metrics::time_db("nullifier_batch_is_spent", async move {
+            let values: Vec<Vec<u8>> = nullifiers.iter().map(|n| n.to_vec()).collect();
+            let spent: HashSet<Vec<u8>> = sqlx::query_scalar!(
+                r#"
+                SELECT nullifier FROM nullifiers
+                WHERE nullifier = ANY($1) AND (expires_at IS NULL OR expires_at > NOW())
+                "#,
+                &values
+            )
+            .fetch_all(&self.db)
+            .await?
+            .into_iter()
+            .collect();
+
+            Ok(values.iter().map(|n| spent.contains(n)).collect())
+        }))
+    }
+}
+
+/// Bits of a Bloom filter, set by double hashing with two random keys
+#[derive(Debug)]
+struct Bloom {
+    bits: Vec<u64>,
+    hashes: u64,
+    keys: [RandomState; 2],
+}
+
+impl Bloom {
+    /// Sized for `capacity` items at `BLOOM_FALSE_POSITIVE_RATE`
+    fn with_capacity(capacity: usize) -> Self {
+        let items = capacity.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-items * BLOOM_FALSE_POSITIVE_RATE.ln() / (ln2 * ln2)).ceil() as usize;
+        Self {
+            bits: vec![0; bits.div_ceil(64)],
+            hashes: (bits as f64 / items * ln2).round().max(1.0) as u64,
+            keys: [RandomState::new(), RandomState::new()],
+        }
+    }
+
+    fn positions(&self, item: &Hash) -> impl Iterator<Item = usize> + use<> {
+        let len = self.bits.len() as u64 * 64;
+        let first = self.keys[0].hash_one(item);
+        let step = self.keys[1].hash_one(item) | 1;
+        (0..self.hashes).map(move |i| (first.wrapping_add(i.wrapping_mul(step)) % len) as usize)
+    }
+
+    fn insert(&mut self, item: &Hash) {
+        for position in self.positions(item) {
+            self.bits[position / 64] |= 1 << (position % 64);
+        }
+    }
+
+    fn contains(&self, item: &Hash) -> bool {
+        self.positions(item).all(|position| self.bits[position / 64] & (1 << (position % 64)) != 0)
+    }
+}
+
+/// Bloom filter of spent nullifiers, shared by all requests
+///
+/// Until `load` has read the nullifiers table every nullifier counts as
+/// probably spent, so checks go to the database. Spends by other instances
+/// are not in the filter; the handler's atomic `mark_spent` still rejects
+/// those, which is why the filter only ever decides to skip the early check.
+#[derive(Debug, Clone)]
+pub struct NullifierBloomFilter {
+    filter: Arc<RwLock<Bloom>>,
+    loaded: Arc<AtomicBool>,
+}
+
+impl Default for NullifierBloomFilter {
+    fn default() -> Self {
+        Self {
+            filter: Arc::new(RwLock::new(Bloom::with_capacity(BLOOM_MIN_CAPACITY))),
+            loaded: Arc::default(),
+        }
+    }
+}
+
+impl NullifierBloomFilter {
+    /// False only for a nullifier this process has certainly not seen spent
+    pub fn probably_spent(&self, nullifier: &Hash) -> bool {
+        !self.loaded.load(Ordering::Acquire) || self.filter.read().unwrap().contains(nullifier)
+    }
+
+    pub fn mark_spent(&self, nullifier: &Hash) {
+        self.filter.write().unwrap().insert(nullifier);
+    }
+
+    /// Stream the nullifiers table into a filter sized for twice its rows,
+    /// returning how many were read. Spends marked meanwhile are kept.
+    pub async fn load(&self, db: &PgPool) -> Result<usize, sqlx::Error> {
+        let rows = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM nullifiers"#)
+            .fetch_one(db)
+            .await?;
+        let capacity = (rows as usize).saturating_mul(2).max(BLOOM_MIN_CAPACITY);
+        *self.filter.write().unwrap() = Bloom::with_capacity(capacity);
+        self.loaded.store(false, Ordering::Release);
+
+        let mut loaded = 0;
+        let mut nullifiers = sqlx::query_scalar!("SELECT nullifier FROM nullifiers").fetch(db);
+        while let Some(nullifier) = nullifiers.try_next().await? {
+            if let Ok(nullifier) = Hash::try_from(nullifier.as_slice()) {
+                self.mark_spent(&nullifier);
+                loaded += 1;
+            }
+        }
+        self.loaded.store(true, Ordering::Release);
+        Ok(loaded)
+    }
+}
+
+/// Process-local nullifier set for tests and local tooling
+#[derive(Debug, Default)]
+pub struct InMemoryNullifierDB {
+    spent: Mutex<HashMap<Hash, SpentNullifier>>,
+}
+
+impl NullifierDB for InMemoryNullifierDB {
+    fn is_spent<'a>(&'a self, nullifier: &'a Hash) -> DBFuture<'a, bool> {
+        let spent = self.spent.lock().unwrap().contains_key(nullifier);
+        Box::pin(async move { Ok(spent) })
+    }
+
+    fn find_spent<'a>(&'a self, nullifier: &'a Hash) -> DBFuture<'a, Option<SpentNullifier>> {
+        let spent = self.spent.lock().unwrap().get(nullifier).cloned();
+        Box::pin(async move { Ok(spent) })
+    }
+
+    fn mark_spent<'a>(&'a self, nullifier: Hash, campaign_address: &'a str) -> DBFuture<'a, ()> {
+        let inserted = match self.spent.lock().unwrap().entry(nullifier) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(SpentNullifier {
+                    campaign_address: campaign_address.to_string(),
+                    spent_at: Utc::now(),
+                });
+                true
+            }
+        };
+        Box::pin(async move {
+            if inserted { Ok(()) } else { Err(DBError::AlreadySpent(nullifier)) }
+        })
+    }
+
+    fn batch_is_spent<'a>(&'a self, nullifiers: &'a [Hash]) -> DBFuture<'a, Vec<bool>> {
+        let spent = self.spent.lock().unwrap();
+        let flags = nullifiers.iter().map(|n| spent.contains_key(n)).collect();
+        Box::pin(async move { Ok(flags) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::test_helpers::{SeedCampaign, seed_campaign_as, test_state};
+
+    async fn exercise(db: &dyn NullifierDB) {
+        let a = [1u8; 32];
+        let b = [2u8; 32];
+
+        assert!(!db.is_spent(&a).await.unwrap());
+        assert_eq!(db.find_spent(&a).await.unwrap(), None);
+        db.mark_spent(a, "campaign").await.unwrap();
+        assert!(db.is_spent(&a).await.unwrap());
+        let spent = db.find_spent(&a).await.unwrap().unwrap();
+        assert_eq!(spent.campaign_address, "campaign");
+        assert!(spent.spent_at <= Utc::now());
+
+        assert!(matches!(
+            db.mark_spent(a, "campaign").await,
+            Err(DBError::AlreadySpent(n)) if n == a
+        ));
+        assert_eq!(db.batch_is_spent(&[b, a, b]).await.unwrap(), vec![false, true, false]);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_nullifier_db() {
+        exercise(&InMemoryNullifierDB::default()).await;
+    }
+
+    async fn insert_campaign(pool: &PgPool, address: &str, close_at: Option<chrono::DateTime<Utc>>) {
+        let campaign = SeedCampaign { address, close_at, ..SeedCampaign::default() };
+        seed_campaign_as(&test_state(pool.clone()), &[], campaign).await;
+    }
+
+    #[test]
+    fn test_bloom_has_no_false_negatives() {
+        let mut bloom = Bloom::with_capacity(1000);
+        let nullifier = |i: u32| {
+            let mut nullifier = [0u8; 32];
+            nullifier[..4].copy_from_slice(&i.to_be_bytes());
+            nullifier
+        };
+        (0..1000).for_each(|i| bloom.insert(&nullifier(i)));
+        assert!((0..1000).all(|i| bloom.contains(&nullifier(i))));
+        // About 1% at capacity; allow for chance
+        let false_positives = (1000..11_000).filter(|i| bloom.contains(&nullifier(*i))).count();
+        assert!(false_positives < 300, "{false_positives} false positives");
+    }
+
+    #[sqlx::test]
+    async fn test_bloom_filter_loads_the_nullifiers_table(pool: PgPool) {
+        insert_campaign(&pool, "campaign", None).await;
+        let db = PgNullifierDB::new(pool.clone(), 7);
+        db.mark_spent([1u8; 32], "campaign").await.unwrap();
+        let filter = NullifierBloomFilter::default();
+
+        // Everything may be spent until the table is read
+        assert!(filter.probably_spent(&[2u8; 32]));
+        assert_eq!(filter.load(&pool).await.unwrap(), 1);
+        assert!(filter.probably_spent(&[1u8; 32]));
+        assert!(!filter.probably_spent(&[2u8; 32]));
+        filter.mark_spent(&[2u8; 32]);
+        assert!(filter.probably_spent(&[2u8; 32]));
+    }
+
+    #[sqlx::test]
+    async fn test_postgres_nullifier_db(pool: PgPool) {
+        insert_campaign(&pool, "campaign", None).await;
+
+        exercise(&PgNullifierDB::new(pool, 7)).await;
+    }
+
+    #[sqlx::test]
+    async fn test_batch_insert_spans_chunks_and_skips_spent(pool: PgPool) {
+        insert_campaign(&pool, "campaign", None).await;
+        let db = PgNullifierDB::new(pool, 7);
+        let entries: Vec<(Hash, String)> = (0..2000u32)
+            .map(|i| {
+                let mut nullifier = [0u8; 32];
+                nullifier[..4].copy_from_slice(&i.to_be_bytes());
+                (nullifier, "campaign".to_string())
+            })
+            .collect();
+        db.mark_spent(entries[1500].0, "campaign").await.unwrap();
+
+        assert_eq!(db.batch_insert(&entries).await.unwrap(), 1999);
+        assert_eq!(db.batch_insert(&entries).await.unwrap(), 0);
+        assert!(db.is_spent(&entries[1999].0).await.unwrap());
+    }
+
+    #[sqlx::test]
+    async fn test_nullifiers_expire_after_close_plus_retention(pool: PgPool) {
+        let now = Utc::now();
+        insert_campaign(&pool, "closed", Some(now - chrono::Duration::days(8))).await;
+        insert_campaign(&pool, "open", Some(now - chrono::Duration::days(6))).await;
+        let db = PgNullifierDB::new(pool, 7);
+        let (expired, live) = ([1u8; 32], [2u8; 32]);
+
+        db.mark_spent(expired, "closed").await.unwrap();
+        db.mark_spent(live, "open").await.unwrap();
+
+        // Past close_at + 7 days: treated as unspent and can be spent again
+        assert_eq!(db.batch_is_spent(&[expired, live]).await.unwrap(), vec![false, true]);
+        db.mark_spent(expired, "closed").await.unwrap();
+        assert!(db.mark_spent(live, "open").await.is_err());
+
+        assert_eq!(db.purge_expired().await.unwrap(), 1);
+        assert!(!db.is_spent(&expired).await.unwrap());
+        assert!(db.is_spent(&live).await.unwrap());
+    }
+}
@@ -0,0 +1,431 @@
+//! Cache of generated claim proofs
+//!
+//! With `REDIS_URL` set, proofs are kept in Redis under
+//! `merkle_proof:{campaign}:{wallet}` so they survive restarts. Commands are
+//! RESP2 over one short-lived TCP connection per call, like the RPC client in
+//! `funding`, so no Redis crate is needed for a handful of commands. Without
+//! Redis, or while it is unreachable, a process-local map is used instead.
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Write},
+    net::TcpStream,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use url::Url;
+
+/// Connect, read and write timeout of Redis commands
+const REDIS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Keys requested per `SCAN` round trip while invalidating
+const SCAN_COUNT: &str = "500";
+
+/// Hit and miss counters of the proof cache
+#[derive(Debug, Serialize)]
+pub struct CacheStats {
+    /// `redis` when `REDIS_URL` is configured, `memory` otherwise
+    pub backend: &'static str,
+    pub hits: u64,
+    pub misses: u64,
+    /// Share of lookups that were hits, 0 before the first lookup
+    pub hit_rate: f64,
+    /// Redis commands that failed and fell back to the in-memory map
+    pub redis_errors: u64,
+    pub memory_entries: usize,
+}
+
+/// Proof cache backed by Redis with an in-memory fallback
+#[derive(Debug, Default)]
+pub struct ProofCache {
+    redis: Option<Url>,
+    memory: Mutex<HashMap<String, (String, Instant)>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    redis_errors: AtomicU64,
+}
+
+fn cache_key(campaign: &str, wallet: &str) -> String {
+    format!("merkle_proof:{}:{}", campaign, wallet)
+}
+
+impl ProofCache {
+    /// Cache using Redis at `redis_url`; in-memory only when None or not a `redis://` URL
+    pub fn new(redis_url: Option<&str>) -> Self {
+        let redis = redis_url.and_then(|url| match Url::parse(url) {
+            Ok(url) if url.scheme() == "redis" => Some(url),
+            _ => {
+                tracing::warn!("Ignoring REDIS_URL: expected redis://[:password@]host[:port][/db]");
+                None
+            }
+        });
+        Self {
+            redis,
+            ..Self::default()
+        }
+    }
+
+    /// Cached proof JSON of a recipient
+    pub async fn get(&self, campaign: &str, wallet: &str) -> Option<String> {
+        let key = cache_key(campaign, wallet);
+        let cached = match self.redis_command(vec![b"GET".to_vec(), key.clone().into_bytes()]).await {
+            Some(Reply::Bulk(value)) => value.and_then(|v| String::from_utf8(v).ok()),
+            _ => {
+                let mut memory = self.memory.lock().unwrap();
+                match memory.get(&key) {
+                    Some((_, expires_at)) if *expires_at <= Instant::now() => {
+                        memory.remove(&key);
+                        None
+                    }
+                    entry => entry.map(|(proof, _)| proof.clone()),
+                }
+            }
+        };
+
+        let counter = if cached.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        cached
+    }
+
+    /// Cache proof JSON of a recipient for `ttl`
+    pub async fn set(&self, campaign: &str, wallet: &str, proof: &str, ttl: Duration) {
+        let key = cache_key(campaign, wallet);
+        let seconds = ttl.as_secs().max(1).to_string();
+        let command = vec![
+            b"SET".to_vec(),
+            key.clone().into_bytes(),
+            proof.as_bytes().to_vec(),
+            b"EX".to_vec(),
+            seconds.into_bytes(),
+        ];
+        if !matches!(self.redis_command(command).await, Some(Reply::Simple(_))) {
+            let expires_at = Instant::now() + ttl;
+            self.memory.lock().unwrap().insert(key, (proof.to_string(), expires_at));
+        }
+    }
+
+    /// Drop every cached proof of a campaign, e.g. after its tree was rebuilt
+    pub async fn invalidate(&self, campaign: &str) {
+        let prefix = cache_key(campaign, "");
+        self.memory.lock().unwrap().retain(|key, _| !key.starts_with(&prefix));
+
+        let Some(url) = self.redis.clone() else { return };
+        let pattern = format!("{}*", prefix);
+        let result = tokio::task::spawn_blocking(move || delete_matching(&url, &pattern))
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|r| r);
+        match result {
+            Ok(deleted) => tracing::debug!(campaign, deleted, "Invalidated cached proofs"),
+            Err(e) => {
+                self.redis_errors.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!(campaign, "Failed to invalidate cached proofs in Redis: {}", e);
+            }
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+        CacheStats {
+            backend: if self.redis.is_some() { "redis" } else { "memory" },
+            hits,
+            misses,
+            hit_rate: if lookups == 0 { 0.0 } else { hits as f64 / lookups as f64 },
+            redis_errors: self.redis_errors.load(Ordering::Relaxed),
+            memory_entries: self.memory.lock().unwrap().len(),
+        }
+    }
+
+    /// Run one command against Redis; None without Redis or on failure
+    async fn redis_command(&self, command: Vec<Vec<u8>>) -> Option<Reply> {
+        let url = self.redis.clone()?;
+        let result = tokio::task::spawn_blocking(move || {
+            let mut connection = Connection::open(&url)?;
+            connection.command(&command)
+        })
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|r| r);
+
+        match result {
+            Ok(Reply::Error(e)) | Err(e) => {
+                self.redis_errors.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!("Redis command failed, using the in-memory cache: {}", e);
+                None
+            }
+            Ok(reply) => Some(reply),
+        }
+    }
+}
+
+/// `SCAN` for keys matching `pattern` and `DEL` them, returning how many were deleted
+fn delete_matching(url: &Url, pattern: &str) -> Result<i64, String> {
+    let mut connection = Connection::open(url)?;
+    let mut cursor = b"0".to_vec();
+    let mut deleted = 0;
+    loop {
+        let reply = connection.command(&[
+            b"SCAN".to_vec(),
+            cursor,
+            b"MATCH".to_vec(),
+            pattern.as_bytes().to_vec(),
+            b"COUNT".to_vec(),
+            SCAN_COUNT.as_bytes().to_vec(),
+        ])?;
+        let Reply::Array(Some(mut parts)) = reply else {
+            return Err("unexpected SCAN reply".to_string());
+        };
+        let (Some(Reply::Array(Some(keys))), Some(Reply::Bulk(Some(next)))) = (parts.pop(), parts.pop())
+        else {
+            return Err("unexpected SCAN reply".to_string());
+        };
+
+        let keys: Vec<Vec<u8>> = keys
+            .into_iter()
+            .filter_map(|key| match key {
+                Reply::Bulk(Some(key)) => Some(key),
+                _ => None,
+            })
+            .collect();
+        if !keys.is_empty() {
+            let mut command = vec![b"DEL".to_vec()];
+            command.extend(keys);
+            if let Reply::Integer(count) = connection.command(&command)? {
+                deleted += count;
+            }
+        }
+
+        if next == b"0" {
+            return Ok(deleted);
+        }
+        cursor = next;
+    }
+}
+
+/// A RESP2 reply
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Reply {
+    Simple(String),
+    Error(String),
+    Integer(i64),
+    Bulk(Option<Vec<u8>>),
+    Array(Option<Vec<Reply>>),
+}
+
+/// Blocking connection authenticated and switched to the URL's database
+struct Connection {
+    reader: BufReader<TcpStream>,
+}
+
+impl Connection {
+    fn open(url: &Url) -> Result<Self, String> {
+        let host = url.host_str().ok_or("Redis URL has no host")?;
+        let port = url.port().unwrap_or(6379);
+        let address = std::net::ToSocketAddrs::to_socket_addrs(&(host, port))
+            .map_err(|e| e.to_string())?
+            .next()
+            .ok_or("Redis host did not resolve")?;
+        let tcp = TcpStream::connect_timeout(&address, REDIS_TIMEOUT).map_err(|e| e.to_string())?;
+        tcp.set_read_timeout(Some(REDIS_TIMEOUT)).map_err(|e| e.to_string())?;
+        tcp.set_write_timeout(Some(REDIS_TIMEOUT)).map_err(|e| e.to_string())?;
+        let mut connection = Self {
+            reader: BufReader::new(tcp),
+        };
+
+        if let Some(password) = url.password() {
+            let mut auth = vec![b"AUTH".to_vec()];
+            if !url.username().is_empty() {
+                auth.push(url.username().as_bytes().to_vec());
+            }
+            auth.push(password.as_bytes().to_vec());
+            connection.expect_ok(&auth)?;
+        }
+        let database = url.path().trim_start_matches('/');
+        if !database.is_empty() {
+            connection.expect_ok(&[b"SELECT".to_vec(), database.as_bytes().to_vec()])?;
+        }
+        Ok(connection)
+    }
+
+    fn command(&mut self, args: &[Vec<u8>]) -> Result<Reply, String> {
+        self.reader
+            .get_mut()
+            .write_all(&encode_command(args))
+            .map_err(|e| e.to_string())?;
+        read_reply(&mut self.reader)
+    }
+
+    fn expect_ok(&mut self, args: &[Vec<u8>]) -> Result<(), String> {
+        match self.command(args)? {
+            Reply::Simple(_) => Ok(()),
+            Reply::Error(e) => Err(e),
+            other => Err(format!("unexpected reply {:?}", other)),
+        }
+    }
+}
+
+/// A command as a RESP array of bulk strings
+fn encode_command(args: &[Vec<u8>]) -> Vec<u8> {
+    let mut encoded = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        encoded.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+        encoded.extend_from_slice(arg);
+        encoded.extend_from_slice(b"\r\n");
+    }
+    encoded
+}
+
+fn read_line(reader: &mut impl BufRead) -> Result<String, String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+        return Err("connection closed".to_string());
+    }
+    line.strip_suffix("\r\n")
+        .map(str::to_string)
+        .ok_or_else(|| "malformed RESP line".to_string())
+}
+
+fn read_reply(reader: &mut impl BufRead) -> Result<Reply, String> {
+    let line = read_line(reader)?;
+    let (kind, rest) = line.split_at_checked(1).ok_or("empty RESP line")?;
+    let length = || rest.parse::<i64>().map_err(|e| e.to_string());
+
+    Ok(match kind {
+        "+" => Reply::Simple(rest.to_string()),
+        "-" => Reply::Error(rest.to_string()),
+        ":" => Reply::Integer(length()?),
+        "$" if length()? < 0 => Reply::Bulk(None),
+        "$" => {
+            let mut value = vec![0u8; length()? as usize + 2];
+            reader.read_exact(&mut value).map_err(|e| e.to_string())?;
+            value.truncate(value.len() - 2);
+            Reply::Bulk(Some(value))
+        }
+        "*" if length()? < 0 => Reply::Array(None),
+        "*" => Reply::Array(Some(
+            (0..length()?).map(|_| read_reply(reader)).collect::<Result<_, _>>()?,
+        )),
+        other => return Err(format!("unknown RESP type '{}'", other)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_resp_round_trip() {
+        let encoded = encode_command(&[b"GET".to_vec(), b"key".to_vec()]);
+        assert_eq!(encoded, b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n");
+        assert_eq!(
+            read_reply(&mut &encoded[..]).unwrap(),
+            Reply::Array(Some(vec![
+                Reply::Bulk(Some(b"GET".to_vec())),
+                Reply::Bulk(Some(b"key".to_vec())),
+            ]))
+        );
+        assert_eq!(read_reply(&mut &b"$-1\r\n"[..]).unwrap(), Reply::Bulk(None));
+        assert_eq!(read_reply(&mut &b":3\r\n"[..]).unwrap(), Reply::Integer(3));
+    }
+
+    #[tokio::test]
+    async fn test_memory_cache_expires_and_invalidates() {
+        let cache = ProofCache::new(None);
+        cache.set("campaign", "a", "proof-a", Duration::from_secs(60)).await;
+        cache.set("campaign", "b", "proof-b", Duration::ZERO).await;
+        cache.set("other", "a", "proof-other", Duration::from_secs(60)).await;
+
+        assert_eq!(cache.get("campaign", "a").await.as_deref(), Some("proof-a"));
+        assert_eq!(cache.get("campaign", "b").await, None);
+
+        cache.invalidate("campaign").await;
+        assert_eq!(cache.get("campaign", "a").await, None);
+        assert_eq!(cache.get("other", "a").await.as_deref(), Some("proof-other"));
+
+        let stats = cache.stats();
+        assert_eq!((stats.backend, stats.hits, stats.misses), ("memory", 2, 2));
+        assert_eq!(stats.hit_rate, 0.5);
+    }
+
+    /// Redis stand-in answering GET, SET, SCAN and DEL from a map, one command per connection
+    fn serve_redis() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut store: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+            for stream in listener.incoming() {
+                let mut reader = BufReader::new(stream.unwrap());
+                while let Ok(Reply::Array(Some(args))) = read_reply(&mut reader) {
+                    let args: Vec<Vec<u8>> = args
+                        .into_iter()
+                        .map(|a| match a {
+                            Reply::Bulk(Some(a)) => a,
+                            _ => Vec::new(),
+                        })
+                        .collect();
+                    let reply = match args[0].as_slice() {
+                        b"GET" => match store.get(&args[1]) {
+                            Some(v) => format!("${}\r\n{}\r\n", v.len(), String::from_utf8_lossy(v)),
+                            None => "$-1\r\n".to_string(),
+                        },
+                        b"SET" => {
+                            assert_eq!(args[3], b"EX");
+                            store.insert(args[1].clone(), args[2].clone());
+                            "+OK\r\n".to_string()
+                        }
+                        b"SCAN" => {
+                            let prefix = args[3].strip_suffix(b"*").unwrap();
+                            let keys: Vec<_> = store.keys().filter(|k| k.starts_with(prefix)).collect();
+                            let mut reply = format!("*2\r\n$1\r\n0\r\n*{}\r\n", keys.len());
+                            for key in keys {
+                                reply += &format!("${}\r\n{}\r\n", key.len(), String::from_utf8_lossy(key));
+                            }
+                            reply
+                        }
+                        b"DEL" => {
+                            let deleted = args[1..].iter().filter(|k| store.remove(*k).is_some()).count();
+                            format!(":{}\r\n", deleted)
+                        }
+                        _ => "-ERR unknown command\r\n".to_string(),
+                    };
+                    reader.get_mut().write_all(reply.as_bytes()).unwrap();
+                }
+            }
+        });
+        format!("redis://{}", address)
+    }
+
+    #[tokio::test]
+    async fn test_redis_cache_sets_gets_and_deletes_by_pattern() {
+        let url = serve_redis();
+        let cache = ProofCache::new(Some(&url));
+        cache.set("campaign", "a", "proof-a", Duration::from_secs(60)).await;
+        cache.set("campaign", "b", "proof-b", Duration::from_secs(60)).await;
+
+        assert_eq!(cache.get("campaign", "a").await.as_deref(), Some("proof-a"));
+        cache.invalidate("campaign").await;
+        assert_eq!(cache.get("campaign", "b").await, None);
+
+        let stats = cache.stats();
+        assert_eq!((stats.backend, stats.redis_errors, stats.memory_entries), ("redis", 0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_redis_falls_back_to_memory() {
+        // Bound and dropped: nothing listens on the port any more
+        let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let cache = ProofCache::new(Some(&format!("redis://{}", address)));
+
+        cache.set("campaign", "a", "proof-a", Duration::from_secs(60)).await;
+        assert_eq!(cache.get("campaign", "a").await.as_deref(), Some("proof-a"));
+        assert_eq!(cache.stats().redis_errors, 2);
+    }
+}
@@ -0,0 +1,193 @@
+//! Sign-In With Solana routes
+//!
+//! The client fetches a nonce, has the wallet sign a SIWS message carrying it,
+//! and trades the signed message for a session token. Each nonce signs in once.
+
+use axum::{
+    Json, Router,
+    extract::State,
+    routing::{get, post},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{
+        error::ShadowDropError,
+        jwt::{self, Claims},
+        response::{ApiResponse, ApiSuccessResponse},
+        signature::verify_wallet_signature,
+        siws::SiwsMessage,
+    },
+    state::AppState,
+};
+
+/// Lifetime of a session token
+const SESSION_TTL_SECS: i64 = 60 * 60;
+
+/// A nonce to put in the next sign-in message
+#[derive(Debug, Serialize)]
+pub struct NonceResponse {
+    /// 16 random bytes, hex
+    pub nonce: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Request body for signing in
+#[derive(Debug, Deserialize)]
+pub struct SiwsRequest {
+    /// SIWS message text, exactly as signed
+    pub message: String,
+    /// Base58 Ed25519 signature of `message`
+    pub signature: String,
+    /// Wallet address (base58) that signed
+    pub pubkey: String,
+}
+
+/// A session for a signed-in wallet
+#[derive(Debug, Serialize)]
+pub struct SessionResponse {
+    /// HS256 JWT whose `sub` is the wallet
+    pub token: String,
+    pub wallet: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Build auth routes
+pub fn auth_routes() -> Router<AppState> {
+    Router::new()
+        .route("/nonce", get(get_nonce))
+        .route("/siws", post(sign_in))
+}
+
+/// GET /api/v1/auth/nonce - Nonce for a sign-in message, valid for 10 minutes
+async fn get_nonce(State(state): State<AppState>) -> ApiResponse<NonceResponse> {
+    let mut bytes = [0u8; 16];
+    openssl::rand::rand_bytes(&mut bytes)
+        .map_err(|e| ShadowDropError::Internal(format!("Failed to generate nonce: {}", e)))?;
+    let nonce = hex::encode(bytes);
+    let expires_at = state.siws_nonces.issue(&nonce).await?;
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(NonceResponse { nonce, expires_at })
+        .with_message("Nonce issued"))
+}
+
+/// POST /api/v1/auth/siws - Trade a signed sign-in message for a session token
+///
+/// The message must be for the configured `SIWS_DOMAIN` and carry an unused
+/// nonce from `GET /auth/nonce`. The token expires after an hour, or when the
+/// message does if that is sooner.
+async fn sign_in(
+    State(state): State<AppState>,
+    Json(body): Json<SiwsRequest>,
+) -> ApiResponse<SessionResponse> {
+    let Some(secret) = state.config.auth.jwt_secret.as_deref() else {
+        return Err(ShadowDropError::Forbidden("Sign-in is disabled".to_string()));
+    };
+
+    let message = SiwsMessage::parse(&body.message)?;
+    if message.address != body.pubkey {
+        return Err(ShadowDropError::BadRequest(
+            "Sign-in message is for another wallet".to_string(),
+        ));
+    }
+    let now = Utc::now();
+    message.validate(&state.config.auth.siws_domain, now)?;
+    verify_wallet_signature(&body.pubkey, body.message.as_bytes(), &body.signature)?;
+
+    // Only now, so a forged request cannot burn someone else's nonce
+    if !state.siws_nonces.consume(&message.nonce).await? {
+        return Err(ShadowDropError::Forbidden(
+            "Nonce is unknown, expired or already used".to_string(),
+        ));
+    }
+
+    let session_end = now + chrono::Duration::seconds(SESSION_TTL_SECS);
+    let expires_at = message
+        .expiration_time
+        .map_or(session_end, |expires| expires.min(session_end));
+    let claims = Claims {
+        sub: body.pubkey.clone(),
+        iat: now.timestamp(),
+        exp: expires_at.timestamp(),
+    };
+    tracing::info!(wallet = %body.pubkey, "🔑 Wallet signed in");
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(SessionResponse {
+            token: jwt::issue(secret.as_bytes(), &claims),
+            wallet: body.pubkey,
+            expires_at,
+        })
+        .with_message("Signed in"))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode, header},
+    };
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{
+        common::signature::tests::{sign, test_wallet},
+        routes::{
+            app_routes,
+            test_helpers::{TEST_JWT_SECRET, TEST_SIWS_DOMAIN, send, test_state},
+        },
+    };
+
+    fn siws_message(domain: &str, wallet: &str, nonce: &str) -> String {
+        let now = Utc::now();
+        format!(
+            "{domain} wants you to sign in with your Solana account:\n{wallet}\n\n\
+             Sign in to Shadow Drop\n\n\
+             URI: https://{domain}\nVersion: 1\nNonce: {nonce}\n\
+             Issued At: {}\nExpiration Time: {}",
+            now.to_rfc3339(),
+            (now + chrono::Duration::minutes(5)).to_rfc3339()
+        )
+    }
+
+    #[sqlx::test]
+    async fn test_sign_in_once_per_nonce(pool: PgPool) {
+        let app = app_routes(test_state(pool));
+        let (key, wallet) = test_wallet();
+        let sign_in = |message: &str| {
+            let body = serde_json::json!({
+                "message": message,
+                "signature": sign(&key, message.as_bytes()),
+                "pubkey": wallet,
+            });
+            Request::post("/api/v1/auth/siws")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let (status, body) = send(&app, Request::get("/api/v1/auth/nonce").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        let nonce = body["data"]["nonce"].as_str().unwrap().to_string();
+        assert_eq!(nonce.len(), 32);
+
+        let (status, _) = send(&app, sign_in(&siws_message("evil.example", &wallet, &nonce))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = send(&app, sign_in(&siws_message(TEST_SIWS_DOMAIN, &wallet, "never-issued"))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let message = siws_message(TEST_SIWS_DOMAIN, &wallet, &nonce);
+        let (status, body) = send(&app, sign_in(&message)).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let token = body["data"]["token"].as_str().unwrap();
+        let claims = jwt::verify(TEST_JWT_SECRET.as_bytes(), token, Utc::now().timestamp()).unwrap();
+        assert_eq!(claims.sub, wallet);
+        assert!(claims.exp <= (Utc::now() + chrono::Duration::minutes(5)).timestamp());
+
+        // The nonce is used up
+        let (status, _) = send(&app, sign_in(&message)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+}
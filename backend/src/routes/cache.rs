@@ -0,0 +1,32 @@
+//! Proof and tree cache inspection routes
+
+use axum::{Router, extract::State, routing::get};
+
+use crate::{
+    common::response::{ApiResponse, ApiSuccessResponse},
+    middleware::admin_auth::AdminAuth,
+    proof_cache::CacheStats,
+    state::AppState,
+    tree_cache::TreeCacheStats,
+};
+
+/// Build cache routes
+pub fn cache_routes() -> Router<AppState> {
+    Router::new()
+        .route("/stats", get(get_cache_stats))
+        .route("/tree-stats", get(get_tree_cache_stats))
+}
+
+/// GET /api/v1/cache/stats - Proof cache hit and miss counters (admin only)
+async fn get_cache_stats(_admin: AdminAuth, State(state): State<AppState>) -> ApiResponse<CacheStats> {
+    Ok(ApiSuccessResponse::default()
+        .with_data(state.proof_cache.stats())
+        .with_message("Cache stats retrieved"))
+}
+
+/// GET /api/v1/cache/tree-stats - Merkle tree cache fill and hit rate (admin only)
+async fn get_tree_cache_stats(_admin: AdminAuth, State(state): State<AppState>) -> ApiResponse<TreeCacheStats> {
+    Ok(ApiSuccessResponse::default()
+        .with_data(state.tree_cache.stats().await)
+        .with_message("Tree cache stats retrieved"))
+}
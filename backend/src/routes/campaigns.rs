@@ -1,21 +1,68 @@
+use std::{sync::Arc, time::Duration};
+
 use axum::{
     Json, Router,
-    extract::{Path, State},
-    http::StatusCode,
-    routing::{get, post},
+    body::{Body, to_bytes},
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderName, StatusCode, header},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post},
 };
-use chrono::Utc;
-use serde::Deserialize;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tracing::Instrument;
+use uuid::Uuid;
 
 use crate::{
-    common::response::{ApiErrorResponse, ApiResponse, ApiSuccessResponse},
-    models::{Campaign, CampaignInfo, EligibilityResponse, EligibleCampaign, Recipient},
+    common::{
+        error::ShadowDropError,
+        fees::{BPS_DENOMINATOR, Lamports, fee_breakdown},
+        merkle::{
+            Hash, MAX_LEAVES, MerkleError, compute_nullifier_v2, installment_nullifier, parse_hash,
+            round_nullifier,
+        },
+        response::{ApiResponse, ApiSuccessResponse},
+        schema::CLAIM_REQUEST_SCHEMA,
+        signature::{verify_claim_signature, verify_wallet_signature},
+        solana_tx::{submit_jito_bundle, transaction_signature},
+        stale_cache::{CACHE_WARNING_HEADER, is_unavailable},
+    },
+    metrics,
+    middleware::{
+        admin_auth::AdminAuth,
+        check_nullifier::check_nullifier,
+        conditional_get::conditional_root,
+        json_schema::validate_json_body,
+        request_id::request_id,
+        timeout::{read_timeout, request_timeout},
+    },
+    models::{
+        AmountChange, AppendOutcome, Availability, CAMPAIGN_STATUS_ACTIVE, CAMPAIGN_STATUS_DRAFT,
+        CAMPAIGN_STATUS_PAUSED, CLAIM_TX_CONFIRMED, CLAIM_TX_FAILED, Campaign, CampaignFilter,
+        CampaignInfo, CampaignSort, ClaimOutcome, EligibilityResponse, EligibleCampaign,
+        IdempotencyState, InstallmentOutcome, NewRound, Recipient, RecipientPage, ReferralStats,
+        RemoveOutcome, RoundInfo, UpdateOutcome, campaign_id_from_root,
+    },
+    funding::{Commitment, poll_transaction, validate_campaign_funds},
+    nullifiers::DBError,
     state::AppState,
+    webhooks,
+};
+
+use super::proofs::{
+    ProofResponse, SubmitMethod, build_claim_tx, campaign_tree, download_proofs,
+    invalidate_campaign_proofs, jito_url, new_round_tree, round_proof, spawn_proof_pregeneration,
 };
 
 /// Request body for creating a campaign
 #[derive(Debug, Deserialize)]
 pub struct CreateCampaignRequest {
+    /// Campaign id; defaults to one derived from `merkle_root`
+    #[serde(default)]
+    pub id: Option<String>,
     pub address: String,
     pub name: String,
     pub merkle_root: String,
@@ -40,6 +87,15 @@ pub struct CreateCampaignRequest {
     pub token_symbol: Option<String>,
     #[serde(default)]
     pub token_decimals: Option<u8>,
+    /// End of the claim window (RFC 3339), None = open-ended
+    #[serde(default)]
+    pub close_at: Option<DateTime<Utc>>,
+    /// Share of a referred claim credited to the referrer, in basis points
+    #[serde(default)]
+    pub referral_bonus_bps: u16,
+    /// Claims accepted first come, first served; None = every recipient can claim
+    #[serde(default)]
+    pub max_claims: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -48,6 +104,47 @@ pub struct RecipientInput {
     pub amount: String,
 }
 
+/// Request body for adding recipients to a draft campaign
+#[derive(Debug, Deserialize)]
+pub struct AddRecipientsRequest {
+    pub recipients: Vec<RecipientInput>,
+}
+
+/// Why one entry of an `AddRecipientsRequest` was not added
+#[derive(Debug, Serialize)]
+pub struct RecipientInputError {
+    pub wallet: String,
+    pub error: String,
+}
+
+/// Response of adding recipients: entries that failed validation are listed
+/// in `errors`, the rest were appended
+#[derive(Debug, Serialize)]
+pub struct AddRecipientsResponse {
+    pub added: usize,
+    pub errors: Vec<RecipientInputError>,
+}
+
+/// Query parameters of a recipient removal
+#[derive(Debug, Deserialize)]
+pub struct RemoveRecipientQuery {
+    /// Why the recipient is removed, e.g. a sanctioned address; kept in the audit log
+    pub reason: String,
+}
+
+/// Recipient removed from a draft campaign
+#[derive(Debug, Serialize)]
+pub struct RemovedRecipientResponse {
+    pub wallet: String,
+    pub amount: String,
+}
+
+/// Request body for correcting one recipient's allocation
+#[derive(Debug, Deserialize)]
+pub struct UpdateRecipientRequest {
+    pub amount: String,
+}
+
 /// Request body for checking eligibility
 #[derive(Debug, Deserialize)]
 pub struct CheckEligibilityRequest {
@@ -58,27 +155,316 @@ pub struct CheckEligibilityRequest {
 #[derive(Debug, Deserialize)]
 pub struct MarkClaimedRequest {
     pub wallet: String,
+    /// Nullifier the claim spends (32 bytes hex): a proof's `claim_nullifier`,
+    /// not its on-chain `nullifier_hash`. Optional, and when sent it must
+    /// match the one derived from `secret` and `leaf_index`
+    #[serde(default)]
+    pub nullifier_hash: Option<String>,
+    /// Claim secret (32 bytes hex), required. With `leaf_index` it gives the
+    /// claim's nullifier, `compute_nullifier_v2(secret, leaf_index, wallet)`.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Leaf index of the wallet in the campaign tree, required
+    #[serde(default)]
+    pub leaf_index: Option<usize>,
+    /// Round of a multi-round campaign the claim is for; None = the campaign's own recipients
+    #[serde(default)]
+    pub round_id: Option<String>,
+    /// Lamports to claim as an installment; None = the whole remaining allocation
+    #[serde(default)]
+    pub claim_amount: Option<Lamports>,
+    /// Registered delegate submitting the claim for `wallet`
+    #[serde(default)]
+    pub delegate_wallet: Option<String>,
+    /// Base58 signature of the claim: the delegate's of `delegate_claim_message`,
+    /// or else the recipient's of `claim_signing_message`
+    #[serde(default)]
+    pub signature: Option<String>,
+    /// Wallet-signed claim transaction (base64) from `build-claim-tx`, for
+    /// `submit_method=jito`
+    #[serde(default)]
+    pub signed_transaction: Option<String>,
+}
+
+/// Request body for registering a recipient's delegate
+#[derive(Debug, Deserialize)]
+pub struct SetDelegateRequest {
+    /// Wallet allowed to claim for the recipient; None removes the delegate
+    #[serde(default)]
+    pub delegate_wallet: Option<String>,
+    /// Unix seconds the message was signed at
+    pub issued_at: i64,
+    /// Recipient's base58 signature of `set_delegate_message`
+    pub signature: String,
+}
+
+/// Delegate registered for a recipient
+#[derive(Debug, Serialize)]
+pub struct DelegateResponse {
+    pub wallet: String,
+    pub delegate_wallet: Option<String>,
+}
+
+/// Request body for adding a round to a campaign
+///
+/// The round's tree and root are built server-side, with a fresh secret per
+/// recipient; a `merkle_root` sent anyway is refused.
+#[derive(Debug, Deserialize)]
+pub struct CreateRoundRequest {
+    /// Not accepted; the response carries the derived root
+    #[serde(default)]
+    pub merkle_root: Option<String>,
+    /// Start of the claim window, defaults to now
+    #[serde(default)]
+    pub open_at: Option<DateTime<Utc>>,
+    /// End of the claim window, None = open-ended
+    #[serde(default)]
+    pub close_at: Option<DateTime<Utc>>,
+    pub recipients: Vec<RecipientInput>,
+}
+
+/// Query parameters of campaign creation
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateCampaignQuery {
+    /// Create a new campaign even if one with the same recipient list exists
+    #[serde(default)]
+    pub force_new: bool,
+}
+
+/// Query parameters of a claim submission
+#[derive(Debug, Default, Deserialize)]
+pub struct ClaimQuery {
+    /// Wallet that referred the claimant; must be a recipient of the campaign
+    pub referrer: Option<String>,
+    /// Send `signed_transaction` on-chain this way before recording the claim
+    pub submit_method: Option<SubmitMethod>,
+}
+
+/// Direction of a listing's sort
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Query parameters of the campaign listing
+#[derive(Debug, Default, Deserialize)]
+pub struct ListCampaignsQuery {
+    /// `draft`, `active` or `paused`
+    pub status: Option<String>,
+    /// Creator wallet (base58)
+    pub creator: Option<String>,
+    /// Opened (created) on or after this day, UTC
+    pub open_after: Option<NaiveDate>,
+    /// Opened (created) before this day, UTC
+    pub open_before: Option<NaiveDate>,
+    #[serde(default)]
+    pub sort_by: CampaignSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    /// 1-based page number
+    pub page: Option<i64>,
+    /// Page size, `DEFAULT_CAMPAIGNS_PER_PAGE` when absent
+    pub per_page: Option<i64>,
+}
+
+/// Query parameters of the recipient listing
+#[derive(Debug, Default, Deserialize)]
+pub struct RecipientsQuery {
+    /// Leaf index of the last recipient already seen
+    pub cursor: Option<usize>,
+    /// Page size, `DEFAULT_RECIPIENTS_LIMIT` when absent
+    pub limit: Option<usize>,
+}
+
+/// Response of a successful claim: what the recipient receives after fees.
+/// Amounts are lamports as decimal strings, like recipient amounts.
+#[derive(Debug, Serialize)]
+pub struct ClaimResponse {
+    pub amount: String,
+    pub fee: String,
+    pub net_amount: String,
+    /// Allocation left for later installments, before fees
+    pub remaining: String,
+    /// Jito bundle the claim transaction was sent in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bundle_id: Option<String>,
+    /// Signature (base58) of the claim transaction sent on-chain
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_signature: Option<String>,
+}
+
+/// Claimed and remaining allocation of a recipient, in lamports
+#[derive(Debug, Serialize)]
+pub struct BalanceResponse {
+    pub allocation: String,
+    pub claimed: String,
+    pub remaining: String,
+}
+
+/// Request body for pausing a campaign
+#[derive(Debug, Deserialize)]
+pub struct PauseCampaignRequest {
+    pub reason: String,
+}
+
+/// Header carrying the client-generated idempotency key for claim submissions
+pub const IDEMPOTENCY_KEY_HEADER: &str = "x-idempotency-key";
+
+/// Recipients per page when `limit` is absent
+pub const DEFAULT_RECIPIENTS_LIMIT: usize = 20;
+
+/// Largest accepted `limit` of the recipient listing
+pub const MAX_RECIPIENTS_LIMIT: usize = 100;
+
+/// Campaigns per page when `per_page` is absent
+pub const DEFAULT_CAMPAIGNS_PER_PAGE: i64 = 20;
+
+/// Largest accepted `per_page` of the campaign listing
+pub const MAX_CAMPAIGNS_PER_PAGE: i64 = 100;
+
+/// Campaigns matching a listing's filters, across all pages
+pub const TOTAL_COUNT_HEADER: HeaderName = HeaderName::from_static("x-total-count");
+
+/// How old a signed `set_delegate_message` may be, in seconds
+pub const DELEGATE_SIGNATURE_MAX_AGE_SECS: i64 = 300;
+
+/// Message a recipient signs to register `delegate` (None removes it)
+pub fn set_delegate_message(address: &str, wallet: &str, delegate: Option<&str>, issued_at: i64) -> String {
+    format!(
+        "shadow-drop set-delegate\ncampaign: {}\nrecipient: {}\ndelegate: {}\nissued_at: {}",
+        address,
+        wallet,
+        delegate.unwrap_or("none"),
+        issued_at
+    )
+}
+
+/// Message a delegate signs to claim for `wallet`; the nullifier makes
+/// every signature single-use
+pub fn delegate_claim_message(address: &str, wallet: &str, nullifier: &Hash) -> String {
+    format!(
+        "shadow-drop claim\ncampaign: {}\nrecipient: {}\nnullifier: {}",
+        address,
+        wallet,
+        hex::encode(nullifier)
+    )
 }
 
 /// Build campaign routes
-pub fn campaign_routes() -> Router<AppState> {
+///
+/// GET routes get the read timeout and claims the claim timeout; the proof
+/// download is added last, under the proof timeout instead.
+pub fn campaign_routes(state: &AppState) -> Router<AppState> {
+    let timeouts = &state.config.timeouts;
     Router::new()
-        .route("/", post(create_campaign))
+        .route("/", get(list_campaigns).post(create_campaign))
         .route("/eligible/{wallet}", get(get_eligible_campaigns))
         .route("/{address}", get(get_campaign))
-        .route("/{address}/check", post(check_eligibility))
-        .route("/{address}/claim", post(mark_claimed))
+        .route(
+            "/{address}/check",
+            post(check_eligibility).layer(middleware::from_fn_with_state(
+                &*CLAIM_REQUEST_SCHEMA,
+                validate_json_body,
+            )),
+        )
+        .route(
+            "/{address}/claim",
+            post(mark_claimed)
+                .layer(middleware::from_fn_with_state(
+                    (state.nullifiers.clone(), state.nullifier_bloom.clone(), IDEMPOTENCY_KEY_HEADER),
+                    check_nullifier,
+                ))
+                .layer(middleware::from_fn_with_state(
+                    &*CLAIM_REQUEST_SCHEMA,
+                    validate_json_body,
+                ))
+                .layer(middleware::from_fn_with_state(
+                    Duration::from_secs(timeouts.claim_secs),
+                    request_timeout,
+                )),
+        )
+        .route(
+            "/{address}/root",
+            get(get_root).layer(middleware::from_fn_with_state(state.clone(), conditional_root)),
+        )
+        .route("/{address}/availability", get(get_availability))
+        .route("/{address}/balance/{wallet}", get(get_balance))
+        .route(
+            "/{address}/recipients",
+            get(get_recipients).put(add_recipients).patch(update_recipients),
+        )
+        .route(
+            "/{address}/recipients/{wallet}",
+            delete(remove_recipient).patch(update_recipient),
+        )
+        .route("/{address}/recipients/{wallet}/set-delegate", post(set_delegate))
+        .route("/{address}/rounds", post(create_round))
+        .route("/{address}/rounds/{round_id}/proof/{wallet}", get(get_round_proof))
+        .route("/{address}/referrals/{wallet}", get(get_referral_stats))
+        .route("/{address}/build-claim-tx", post(build_claim_tx))
+        .route("/{address}/clone", post(clone_campaign))
+        .route("/{address}/activate", patch(activate_campaign))
+        .route("/{address}/pause", patch(pause_campaign))
+        .route("/{address}/resume", patch(resume_campaign))
         .route("/wallet/{wallet}", get(get_campaigns_by_wallet))
+        .route("/id/{id}", get(get_campaign_by_id))
+        .layer(middleware::from_fn_with_state(
+            Duration::from_secs(timeouts.read_secs),
+            read_timeout,
+        ))
+        .route(
+            "/{address}/proofs/download",
+            get(download_proofs).layer(middleware::from_fn_with_state(
+                Duration::from_secs(timeouts.proof_secs),
+                request_timeout,
+            )),
+        )
 }
 
 /// POST /api/v1/campaigns - Create a new campaign
+///
+/// Without an explicit id the campaign is content-addressed by its Merkle
+/// root, and creating the same campaign again returns the existing one.
+/// `?force_new=true` skips that and always creates a campaign with a fresh id.
 async fn create_campaign(
     State(state): State<AppState>,
+    Query(query): Query<CreateCampaignQuery>,
     Json(body): Json<CreateCampaignRequest>,
 ) -> ApiResponse<CampaignInfo> {
+    record_campaign_id(&body.address);
+    let id = match (&body.id, parse_hash(&body.merkle_root)) {
+        _ if query.force_new => Uuid::new_v4().to_string(),
+        (Some(id), _) => id.clone(),
+        (None, Some(root)) => campaign_id_from_root(&root).to_string(),
+        (None, None) => Uuid::new_v4().to_string(),
+    };
+    if !query.force_new
+        && let Some(existing) = state.campaign_store.get_by_id(&id).await
+    {
+        return Ok(ApiSuccessResponse::default()
+            .with_data(CampaignInfo::from(&existing))
+            .with_message("Campaign already exists"));
+    }
+    if u64::from(body.referral_bonus_bps) > BPS_DENOMINATOR {
+        return Err(ShadowDropError::BadRequest(
+            "referral_bonus_bps must be at most 10000".to_string(),
+        ));
+    }
+    let max_claims = body
+        .max_claims
+        .map(|m| i32::try_from(m).ok().filter(|m| *m > 0))
+        .map(|m| {
+            m.ok_or_else(|| {
+                ShadowDropError::BadRequest("max_claims must be a positive 32-bit integer".to_string())
+            })
+        })
+        .transpose()?;
     let now = Utc::now().timestamp();
     let campaign = Campaign {
-        id: uuid_simple(),
+        id,
         address: body.address,
         name: body.name,
         merkle_root: body.merkle_root,
@@ -96,6 +482,7 @@ async fn create_campaign(
                 claimed: false,
                 claimed_at: None,
                 id: None,
+                leaf_index: None,
             })
             .collect(),
         // Vesting fields with defaults
@@ -107,6 +494,11 @@ async fn create_campaign(
         token_mint: body.token_mint,
         token_symbol: body.token_symbol,
         token_decimals: body.token_decimals.map(|d| d as i16),
+        status: CAMPAIGN_STATUS_ACTIVE.to_string(),
+        pause_reason: None,
+        close_at: body.close_at,
+        referral_bonus_bps: body.referral_bonus_bps as i16,
+        max_claims,
     };
 
     let created = state.campaign_store.create(campaign).await;
@@ -119,83 +511,3042 @@ async fn create_campaign(
 }
 
 /// GET /api/v1/campaigns/:address - Get campaign info
+///
+/// While the database is unreachable, the info last read for the campaign
+/// (if under a minute old) is returned with `X-Cache-Warning: stale`.
 async fn get_campaign(
     State(state): State<AppState>,
     Path(address): Path<String>,
-) -> ApiResponse<CampaignInfo> {
-    match state.campaign_store.get(&address).await {
-        Some(campaign) => Ok(ApiSuccessResponse::default()
-            .with_data(CampaignInfo::from(&campaign))
-            .with_message("Campaign found")),
-        None => Err(ApiErrorResponse::default()
-            .with_code(StatusCode::NOT_FOUND)
-            .with_message("Campaign not found")),
+) -> Result<Response, ShadowDropError> {
+    record_campaign_id(&address);
+    match campaign_info(&state, &address).await {
+        Err(ShadowDropError::Database(e)) if is_unavailable(&e) => {
+            let Some(info) = state.campaign_info_cache.get(&address) else {
+                return Err(e.into());
+            };
+            tracing::warn!(campaign = %address, "Database unreachable, serving stale campaign info: {}", e);
+            let response = ApiSuccessResponse::default()
+                .with_data(info)
+                .with_message("Campaign found");
+            Ok(([(CACHE_WARNING_HEADER, "stale")], response).into_response())
+        }
+        result => result.map(IntoResponse::into_response),
     }
 }
 
+/// Current info of a campaign, remembered for `get_campaign` to fall back on
+async fn campaign_info(state: &AppState, address: &str) -> ApiResponse<CampaignInfo> {
+    let campaign = state
+        .campaign_store
+        .try_get(address)
+        .await?
+        .ok_or_else(|| ShadowDropError::NotFound("Campaign not found".to_string()))?;
+    let info = CampaignInfo::from(&campaign);
+    state.campaign_info_cache.store(address, info.clone());
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(info)
+        .with_message("Campaign found"))
+}
+
+/// GET /api/v1/campaigns/:address/root - Current Merkle root, with a weak `ETag`
+///
+/// An active campaign's committed root may be cached for a year. Drafts are
+/// never cached since recipient edits change the root. A round replaces the
+/// root too, so once a campaign has one, while it is paused, or while its
+/// root is still the all-zero placeholder, clients revalidate every time
+/// (and get 304 via `conditional_root`).
+async fn get_root(State(state): State<AppState>, Path(address): Path<String>) -> Result<Response, ShadowDropError> {
+    record_campaign_id(&address);
+    let root = state
+        .campaign_store
+        .root(&address)
+        .await?
+        .ok_or_else(|| ShadowDropError::NotFound("Campaign not found".to_string()))?;
+    let cache_control = match root.status.as_str() {
+        CAMPAIGN_STATUS_DRAFT => "no-store",
+        CAMPAIGN_STATUS_ACTIVE if root.committed && !root.has_rounds => "max-age=31536000, immutable",
+        _ => "no-cache",
+    };
+    let etag = format!("W/\"{}\"", root.merkle_root);
+
+    let response = ApiSuccessResponse::default()
+        .with_data(root)
+        .with_message("Merkle root retrieved");
+    Ok((
+        [(header::ETAG, etag.as_str()), (header::CACHE_CONTROL, cache_control)],
+        response,
+    )
+        .into_response())
+}
+
 /// POST /api/v1/campaigns/:address/check - Check eligibility
 async fn check_eligibility(
     State(state): State<AppState>,
     Path(address): Path<String>,
     Json(body): Json<CheckEligibilityRequest>,
 ) -> ApiResponse<EligibilityResponse> {
+    record_campaign_id(&address);
     match state.campaign_store.check_eligibility(&address, &body.wallet).await {
         Some(eligibility) => Ok(ApiSuccessResponse::default()
             .with_data(eligibility)
             .with_message("Eligibility checked")),
-        None => Err(ApiErrorResponse::default()
-            .with_code(StatusCode::NOT_FOUND)
-            .with_message("Campaign not found")),
+        None => Err(ShadowDropError::NotFound("Campaign not found".to_string())),
     }
 }
 
 /// POST /api/v1/campaigns/:address/claim - Mark as claimed
+///
+/// When an `X-Idempotency-Key` header is present, the first response for that
+/// key is stored and replayed for retries within the TTL window.
+///
+/// The claim runs in a task of its own: when the claim timeout fires, the
+/// client gets its 504 but the claim still finishes, so a sent bundle is
+/// recorded and the idempotency key completes instead of staying "processing".
 async fn mark_claimed(
     State(state): State<AppState>,
     Path(address): Path<String>,
+    Query(query): Query<ClaimQuery>,
+    headers: HeaderMap,
     Json(body): Json<MarkClaimedRequest>,
-) -> ApiResponse<()> {
-    if state.campaign_store.mark_claimed(&address, &body.wallet).await {
-        Ok(ApiSuccessResponse::default()
-            .with_message("Claimed successfully"))
-    } else {
-        Err(ApiErrorResponse::default()
-            .with_code(StatusCode::BAD_REQUEST)
-            .with_message("Claim failed - already claimed or not eligible"))
+) -> Response {
+    record_campaign_id(&address);
+    let task = tokio::spawn(
+        idempotent_claim(state, address, query, headers, body).in_current_span(),
+    );
+    match task.await {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::error!("Claim task failed: {}", e);
+            ShadowDropError::Internal("Claim failed".to_string()).into_response()
+        }
     }
 }
 
-/// GET /api/v1/campaigns/eligible/:wallet - Get campaigns where wallet is eligible
-async fn get_eligible_campaigns(
+/// Run a claim, storing and replaying its response under the idempotency key
+async fn idempotent_claim(
+    state: AppState,
+    address: String,
+    query: ClaimQuery,
+    headers: HeaderMap,
+    body: MarkClaimedRequest,
+) -> Response {
+    let Some(raw_key) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return claim(&state, &address, &body, &query, request_id(&headers))
+            .await
+            .into_response();
+    };
+
+    let key = match raw_key
+        .to_str()
+        .ok()
+        .and_then(|k| uuid::Uuid::parse_str(k).ok())
+    {
+        Some(k) => k.to_string(),
+        None => {
+            return ShadowDropError::BadRequest(
+                "X-Idempotency-Key must be a valid UUID".to_string(),
+            )
+            .into_response();
+        }
+    };
+
+    match state.idempotency_store.begin(&key, &address).await {
+        Ok(IdempotencyState::Acquired) => {}
+        Ok(IdempotencyState::Processing) => {
+            return ShadowDropError::Conflict {
+                message: "A request with this idempotency key is still being processed"
+                    .to_string(),
+                details: Some("processing".to_string()),
+            }
+            .into_response();
+        }
+        Ok(IdempotencyState::Completed {
+            status_code,
+            response_body,
+        }) => {
+            let status = StatusCode::from_u16(status_code).unwrap_or(StatusCode::OK);
+            return (status, Json(response_body)).into_response();
+        }
+        Err(e) => return ShadowDropError::from(e).into_response(),
+    }
+
+    let (parts, response_body) = claim(&state, &address, &body, &query, request_id(&headers))
+        .await
+        .into_response()
+        .into_parts();
+    let bytes = match to_bytes(response_body, usize::MAX).await {
+        Ok(b) => b,
+        Err(e) => {
+            tracing::error!("Failed to buffer claim response: {}", e);
+            return ShadowDropError::Internal("Failed to buffer claim response".to_string())
+                .into_response();
+        }
+    };
+
+    let stored = serde_json::from_slice::<serde_json::Value>(&bytes).unwrap_or_default();
+    if let Err(e) = state
+        .idempotency_store
+        .complete(&key, &address, parts.status.as_u16(), &stored)
+        .await
+    {
+        tracing::error!("Failed to store idempotent response: {}", e);
+    }
+
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// Handle a claim and count its outcome in `shadow_drop_claims_total`
+async fn claim(
+    state: &AppState,
+    address: &str,
+    body: &MarkClaimedRequest,
+    query: &ClaimQuery,
+    request_id: Option<&str>,
+) -> ApiResponse<ClaimResponse> {
+    let result = process_claim(state, address, body, query, request_id).await;
+    let status = match &result {
+        Ok(_) => "success",
+        Err(e) if e.status_code().is_server_error() => "error",
+        Err(_) => "rejected",
+    };
+    metrics::CLAIMS_TOTAL.inc(&[address, status]);
+    result
+}
+
+async fn process_claim(
+    state: &AppState,
+    address: &str,
+    body: &MarkClaimedRequest,
+    query: &ClaimQuery,
+    request_id: Option<&str>,
+) -> ApiResponse<ClaimResponse> {
+    match state.campaign_store.get_status(address).await {
+        None => return Err(ShadowDropError::NotFound("Campaign not found".to_string())),
+        Some((status, reason)) if status == CAMPAIGN_STATUS_PAUSED => {
+            return Err(ShadowDropError::CampaignPaused { reason });
+        }
+        // Drafts are still being edited and have no committed root yet
+        Some((status, _)) if status != CAMPAIGN_STATUS_ACTIVE => {
+            return Err(ShadowDropError::Conflict {
+                message: format!("Campaign is {}, expected {}", status, CAMPAIGN_STATUS_ACTIVE),
+                details: None,
+            });
+        }
+        Some(_) => {}
+    }
+
+    // Validated in full before a bundle is sent, so a claim that would be
+    // refused never reaches Jito
+    let nullifier = claim_nullifier(body)?;
+    let valid = validate_claim(state, address, body, query, &nullifier).await?;
+
+    // Submitting before recording means a bundle that fails leaves the claim unrecorded
+    let Some(SubmitMethod::Jito) = query.submit_method else {
+        let response = record_claim(state, address, body, query, &nullifier, valid, request_id).await?;
+        notify_claim_webhook(state, address, body, &response);
+        return Ok(response);
+    };
+    let (bundle_id, signature) = submit_claim_bundle(state, address, body).await?;
+    let mut response = match record_claim(state, address, body, query, &nullifier, valid, request_id).await {
+        Ok(response) => response,
+        Err(e) => {
+            // Only a claim racing this one gets here; the bundle is already out
+            tracing::error!(campaign = %address, wallet = %body.wallet, bundle_id = %bundle_id, signature = %signature, "Claim sent as a bundle but not recorded: {}", e);
+            return Err(ShadowDropError::Conflict {
+                message: format!("Claim transaction was sent but the claim was not recorded: {}", e),
+                details: Some(format!("bundle_id: {}, transaction_signature: {}", bundle_id, signature)),
+            });
+        }
+    };
+    if let Some(data) = response.data.as_mut() {
+        data.bundle_id = Some(bundle_id);
+        data.transaction_signature = Some(signature);
+    }
+    notify_claim_webhook(state, address, body, &response);
+    Ok(response)
+}
+
+/// POST a recorded claim to the claim webhook, if one is configured
+fn notify_claim_webhook(
+    state: &AppState,
+    address: &str,
+    body: &MarkClaimedRequest,
+    response: &ApiSuccessResponse<ClaimResponse>,
+) {
+    let Some(data) = &response.data else {
+        return;
+    };
+    let payload = serde_json::json!({
+        "event": "claim",
+        "campaign": address,
+        "wallet": body.wallet,
+        "round_id": body.round_id,
+        "amount": data.amount,
+        "fee": data.fee,
+        "net_amount": data.net_amount,
+        "remaining": data.remaining,
+        "transaction_signature": data.transaction_signature,
+        "claimed_at": Utc::now(),
+    });
+    webhooks::notify_claim(state, address, payload);
+}
+
+/// Send the wallet-signed claim transaction to Jito as a bundle of its own,
+/// and start waiting for it to confirm. Returns the bundle ID and signature.
+async fn submit_claim_bundle(
+    state: &AppState,
+    address: &str,
+    body: &MarkClaimedRequest,
+) -> Result<(String, String), ShadowDropError> {
+    let jito_url = jito_url(state)?;
+    let transaction = body
+        .signed_transaction
+        .as_deref()
+        .and_then(|tx| STANDARD.decode(tx).ok())
+        .ok_or_else(|| {
+            ShadowDropError::BadRequest(
+                "submit_method=jito requires a base64 signed_transaction".to_string(),
+            )
+        })?;
+    let signature = transaction_signature(&transaction).ok_or_else(|| {
+        ShadowDropError::BadRequest("signed_transaction is not signed".to_string())
+    })?;
+    let bundle_id = submit_jito_bundle(jito_url, &[transaction]).await?;
+    tracing::info!(campaign = %address, wallet = %body.wallet, bundle_id = %bundle_id, "📦 Claim sent as a Jito bundle");
+
+    state
+        .claim_transactions
+        .record(&signature, address, &body.wallet, Some(&bundle_id))
+        .await?;
+    spawn_claim_confirmation(state.clone(), signature.clone());
+    Ok((bundle_id, signature))
+}
+
+/// Wait for a sent claim transaction to confirm and record the outcome: a
+/// transaction that fails on-chain or times out is marked failed
+fn spawn_claim_confirmation(state: AppState, signature: String) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let rpc_url = &state.config.campaign.solana_rpc_url;
+        let breaker = state.rpc_circuits.get(rpc_url);
+        let timeout = Duration::from_secs(state.config.campaign.tx_confirm_timeout_secs);
+        let (status, slot) =
+            match poll_transaction(rpc_url, &signature, Commitment::Confirmed, timeout, &breaker).await {
+                Ok(landed) if landed.err.is_none() => {
+                    tracing::info!(signature = %signature, slot = landed.slot, "✅ Claim transaction confirmed");
+                    (CLAIM_TX_CONFIRMED, Some(landed.slot))
+                }
+                Ok(landed) => {
+                    tracing::warn!(signature = %signature, slot = landed.slot, err = ?landed.err, "Claim transaction failed");
+                    (CLAIM_TX_FAILED, Some(landed.slot))
+                }
+                Err(e) => {
+                    tracing::warn!(signature = %signature, "Claim transaction not confirmed: {}", e);
+                    (CLAIM_TX_FAILED, None)
+                }
+            };
+        if let Err(e) = state.claim_transactions.set_status(&signature, status, slot).await {
+            tracing::error!("Failed to record claim transaction status: {}", e);
+        }
+    })
+}
+
+/// A claim that passed `validate_claim`, with what recording it needs
+enum ValidClaim {
+    /// Against the recipients of a round
+    Round(Uuid),
+    /// Installment number `sequence` of `claim_amount`, spending `nullifier`
+    Installment { claim_amount: Lamports, sequence: i32, nullifier: Hash },
+    /// The whole remaining allocation
+    Allocation,
+}
+
+/// Check everything about a claim that can be checked before anything is
+/// spent or sent: its parameters, the recipient's or delegate's signature,
+/// the referrer, the round window, the claim slot and the installment amount
+///
+/// `nullifier` is the claim's wallet-bound nullifier. Claims racing this
+/// one can still take the recipient's allocation or the last slot; the
+/// store re-checks both when the claim is recorded.
+async fn validate_claim(
+    state: &AppState,
+    address: &str,
+    body: &MarkClaimedRequest,
+    query: &ClaimQuery,
+    nullifier: &Hash,
+) -> Result<ValidClaim, ShadowDropError> {
+    let not_claimable =
+        || ShadowDropError::BadRequest("Claim failed - already claimed or not eligible".to_string());
+
+    if let Some(round_id) = &body.round_id {
+        if query.referrer.is_some() || body.claim_amount.is_some() || body.delegate_wallet.is_some() {
+            return Err(ShadowDropError::BadRequest(
+                "Referrals, partial and delegate claims are not supported for round claims".to_string(),
+            ));
+        }
+        verify_recipient_signature(state, address, body, nullifier)?;
+        check_nullifier_hash(body, nullifier)?;
+        let round_id = parse_round_id(round_id)?;
+        let round = state
+            .campaign_store
+            .get_round(address, round_id)
+            .await
+            .ok_or_else(|| ShadowDropError::NotFound("Round not found".to_string()))?;
+        if !round.is_open(Utc::now()) {
+            return Err(ShadowDropError::Conflict {
+                message: format!("Round {} is not open for claims", round.round_number),
+                details: None,
+            });
+        }
+        if !round.recipients.iter().any(|r| r.wallet == body.wallet && !r.claimed) {
+            return Err(not_claimable());
+        }
+        return Ok(ValidClaim::Round(round_id));
+    }
+
+    match &body.delegate_wallet {
+        Some(delegate) => verify_delegate(state, address, body, delegate, nullifier).await?,
+        None => verify_recipient_signature(state, address, body, nullifier)?,
+    }
+    if body.claim_amount.is_some() && query.referrer.is_some() {
+        return Err(ShadowDropError::BadRequest(
+            "Referrals are not supported for partial claims".to_string(),
+        ));
+    }
+    if let Some(referrer) = &query.referrer {
+        validate_referrer(state, address, &body.wallet, referrer).await?;
+    }
+
+    let balance = state
+        .campaign_store
+        .recipient_balance(address, &body.wallet)
+        .await
+        .ok_or_else(not_claimable)?;
+    // Rejecting a full campaign up front keeps its nullifier unspent. A
+    // recipient part way through its installments already holds a slot.
+    if balance.claim_count == 0
+        && let Some(availability) = state.campaign_store.availability(address).await
+        && availability.max_claims.is_some()
+        && availability.remaining_slots == 0
+    {
+        return Err(ShadowDropError::CampaignFull {
+            claims_accepted: availability.claims_accepted,
+        });
+    }
+
+    let Some(claim_amount) = body.claim_amount else {
+        check_nullifier_hash(body, nullifier)?;
+        return Ok(ValidClaim::Allocation);
+    };
+    if claim_amount == 0 || claim_amount > balance.remaining() {
+        return Err(ShadowDropError::BadRequest(format!(
+            "claim_amount must be between 1 and the remaining allocation of {}",
+            balance.remaining()
+        )));
+    }
+    // Installment `n` (0-based) spends `installment_nullifier(nullifier, n)`
+    let spent = installment_nullifier(nullifier, balance.claim_count as u32);
+    check_nullifier_hash(body, &spent)?;
+    Ok(ValidClaim::Installment {
+        claim_amount,
+        sequence: balance.claim_count,
+        nullifier: spent,
+    })
+}
+
+/// Record a claim `validate_claim` passed, against a round or the
+/// campaign's own recipients
+///
+/// A round claim spends `nullifier` scoped to the round, an installment its
+/// installment nullifier and a full claim `nullifier` itself.
+async fn record_claim(
+    state: &AppState,
+    address: &str,
+    body: &MarkClaimedRequest,
+    query: &ClaimQuery,
+    nullifier: &Hash,
+    claim: ValidClaim,
+    request_id: Option<&str>,
+) -> ApiResponse<ClaimResponse> {
+    let response = match claim {
+        ValidClaim::Round(round_id) => return claim_round(state, address, round_id, body, nullifier).await,
+        ValidClaim::Installment { claim_amount, sequence, nullifier } => {
+            claim_installment(state, address, body, claim_amount, sequence, &nullifier).await?
+        }
+        ValidClaim::Allocation => claim_allocation(state, address, body, query, nullifier).await?,
+    };
+
+    if let Some(delegate) = &body.delegate_wallet {
+        tracing::info!(campaign = %address, wallet = %body.wallet, delegate = %delegate, "🤝 Delegate claim");
+        if let Err(e) = state
+            .campaign_store
+            .record_delegate_claim(address, &body.wallet, delegate, request_id)
+            .await
+        {
+            tracing::error!("Failed to audit delegate claim: {}", e);
+        }
+    }
+    Ok(response)
+}
+
+/// Claim the remaining allocation from the campaign's own recipient list
+async fn claim_allocation(
+    state: &AppState,
+    address: &str,
+    body: &MarkClaimedRequest,
+    query: &ClaimQuery,
+    nullifier: &Hash,
+) -> ApiResponse<ClaimResponse> {
+    let outcome = metrics::time_db(
+        "campaign_mark_claimed",
+        state
+            .campaign_store
+            .mark_claimed(address, &body.wallet, query.referrer.as_deref(), nullifier),
+    )
+    .await;
+    match outcome {
+        ClaimOutcome::Claimed { amount, last_slot } => {
+            if last_slot {
+                tracing::info!(campaign = %address, "🈵 Last claim slot filled, campaign full");
+            }
+            Ok(claim_response(state, amount, 0))
+        }
+        ClaimOutcome::CampaignFull { claims_accepted } => {
+            Err(ShadowDropError::CampaignFull { claims_accepted })
+        }
+        ClaimOutcome::NotClaimable => Err(ShadowDropError::BadRequest(
+            "Claim failed - already claimed or not eligible".to_string(),
+        )),
+        ClaimOutcome::NullifierSpent => Err(DBError::AlreadySpent(*nullifier).into()),
+    }
+}
+
+/// A recipient's own claim must be signed by the wallet, over the claim's
+/// nullifier, when the config requires it; a signature sent anyway is
+/// checked all the same
+fn verify_recipient_signature(
+    state: &AppState,
+    address: &str,
+    body: &MarkClaimedRequest,
+    nullifier: &Hash,
+) -> Result<(), ShadowDropError> {
+    let Some(signature) = body.signature.as_deref() else {
+        if state.config.campaign.require_claim_signature {
+            return Err(ShadowDropError::BadRequest(
+                "Claims require the wallet's signature".to_string(),
+            ));
+        }
+        return Ok(());
+    };
+    verify_claim_signature(&body.wallet, address, nullifier, signature, Utc::now().timestamp())?;
+    Ok(())
+}
+
+/// A delegate claim must come from the recipient's registered delegate and
+/// carry the delegate's signature over `delegate_claim_message`. The
+/// nullifier is still the recipient's, so a delegate cannot mint extra ones.
+async fn verify_delegate(
+    state: &AppState,
+    address: &str,
+    body: &MarkClaimedRequest,
+    delegate: &str,
+    nullifier: &Hash,
+) -> Result<(), ShadowDropError> {
+    let registered = state
+        .campaign_store
+        .recipient_delegate(address, &body.wallet)
+        .await
+        .flatten();
+    if registered.as_deref() != Some(delegate) {
+        return Err(ShadowDropError::Forbidden(
+            "delegate_wallet is not the recipient's delegate".to_string(),
+        ));
+    }
+
+    let signature = body.signature.as_deref().ok_or_else(|| {
+        ShadowDropError::BadRequest("Delegate claims require a signature".to_string())
+    })?;
+    let message = delegate_claim_message(address, &body.wallet, nullifier);
+    verify_wallet_signature(delegate, message.as_bytes(), signature)?;
+    Ok(())
+}
+
+/// POST /api/v1/campaigns/:address/recipients/:wallet/set-delegate - Let another wallet claim
+///
+/// Signed by the recipient over `set_delegate_message`, at most
+/// `DELEGATE_SIGNATURE_MAX_AGE_SECS` old.
+async fn set_delegate(
     State(state): State<AppState>,
-    Path(wallet): Path<String>,
-) -> ApiResponse<Vec<EligibleCampaign>> {
-    let campaigns = state.campaign_store.get_eligible_for_wallet(&wallet).await;
+    Path((address, wallet)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(body): Json<SetDelegateRequest>,
+) -> ApiResponse<DelegateResponse> {
+    record_campaign_id(&address);
+    if (Utc::now().timestamp() - body.issued_at).abs() > DELEGATE_SIGNATURE_MAX_AGE_SECS {
+        return Err(ShadowDropError::BadRequest(
+            "issued_at is too far from the current time".to_string(),
+        ));
+    }
+    if let Some(delegate) = &body.delegate_wallet
+        && (!is_wallet_address(delegate) || *delegate == wallet)
+    {
+        return Err(ShadowDropError::BadRequest(
+            "delegate_wallet must be a Base58 wallet address other than the recipient".to_string(),
+        ));
+    }
+    let delegate = body.delegate_wallet.as_deref();
+    let message = set_delegate_message(&address, &wallet, delegate, body.issued_at);
+    verify_wallet_signature(&wallet, message.as_bytes(), &body.signature)?;
+
+    if !state
+        .campaign_store
+        .set_delegate(&address, &wallet, delegate, request_id(&headers))
+        .await?
+    {
+        return Err(ShadowDropError::NotFound("Recipient not found".to_string()));
+    }
+    tracing::info!(campaign = %address, wallet = %wallet, delegate = ?delegate, "🤝 Delegate set");
 
     Ok(ApiSuccessResponse::default()
-        .with_data(campaigns)
-        .with_message("Eligible campaigns retrieved"))
+        .with_data(DelegateResponse {
+            wallet,
+            delegate_wallet: body.delegate_wallet,
+        })
+        .with_message("Delegate updated"))
 }
 
-/// GET /api/v1/campaigns/wallet/:wallet - Get campaigns by creator wallet
-async fn get_campaigns_by_wallet(
+/// Claim against the recipients of one round; its nullifier is spent per round
+async fn claim_round(
+    state: &AppState,
+    address: &str,
+    round_id: Uuid,
+    body: &MarkClaimedRequest,
+    nullifier: &Hash,
+) -> ApiResponse<ClaimResponse> {
+    let scoped = round_nullifier(nullifier, round_id.as_bytes());
+    let amount = state
+        .campaign_store
+        .mark_round_claimed(address, round_id, &body.wallet, &scoped)
+        .await?
+        .ok_or_else(|| {
+            ShadowDropError::BadRequest("Claim failed - already claimed or not eligible".to_string())
+        })?;
+    Ok(claim_response(state, amount.parse().unwrap_or(0), 0))
+}
+
+/// Claim `claim_amount` of the recipient's allocation as installment `sequence`
+///
+/// Installment `n` (0-based) spends `installment_nullifier(nullifier, n)`,
+/// so every installment has a fresh nullifier while the proof, whose leaf
+/// commits to the full allocation, stays the same. Replaying an installment
+/// resubmits a spent nullifier and is rejected.
+async fn claim_installment(
+    state: &AppState,
+    address: &str,
+    body: &MarkClaimedRequest,
+    claim_amount: Lamports,
+    sequence: i32,
+    spent: &Hash,
+) -> ApiResponse<ClaimResponse> {
+    match state
+        .campaign_store
+        .claim_installment(address, &body.wallet, claim_amount, sequence, spent)
+        .await?
+    {
+        InstallmentOutcome::Paid(balance) => Ok(claim_response(state, claim_amount, balance.remaining())),
+        InstallmentOutcome::CampaignFull { claims_accepted } => {
+            Err(ShadowDropError::CampaignFull { claims_accepted })
+        }
+        InstallmentOutcome::NotClaimable => Err(ShadowDropError::BadRequest(
+            "Claim failed - already claimed or not eligible".to_string(),
+        )),
+    }
+}
+
+/// Success response of a claim paying out `amount`, leaving `remaining` to claim
+fn claim_response(
+    state: &AppState,
+    amount: Lamports,
+    remaining: Lamports,
+) -> ApiSuccessResponse<ClaimResponse> {
+    let fees = fee_breakdown(amount, &state.config.fees);
+    ApiSuccessResponse::default()
+        .with_data(ClaimResponse {
+            amount: fees.amount.to_string(),
+            fee: fees.fee.to_string(),
+            net_amount: fees.net_amount.to_string(),
+            remaining: remaining.to_string(),
+            bundle_id: None,
+            transaction_signature: None,
+        })
+        .with_message("Claimed successfully")
+}
+
+fn parse_round_id(round_id: &str) -> Result<Uuid, ShadowDropError> {
+    Uuid::parse_str(round_id)
+        .map_err(|_| ShadowDropError::BadRequest("round_id must be a UUID".to_string()))
+}
+
+/// POST /api/v1/campaigns/:address/rounds - Add a claim round (admin only)
+///
+/// The round gets the next round number. Its tree is built over the
+/// recipients in list order and stored with it, and its root becomes the
+/// campaign's `merkle_root`.
+async fn create_round(
+    _admin: AdminAuth,
     State(state): State<AppState>,
-    Path(wallet): Path<String>,
-) -> ApiResponse<Vec<CampaignInfo>> {
-    let campaigns = state.campaign_store.get_by_wallet(&wallet).await;
-    let infos: Vec<CampaignInfo> = campaigns.iter().map(CampaignInfo::from).collect();
+    Path(address): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<CreateRoundRequest>,
+) -> ApiResponse<RoundInfo> {
+    record_campaign_id(&address);
+    if body.merkle_root.is_some() {
+        return Err(ShadowDropError::BadRequest(
+            "merkle_root is derived from the round's recipients and cannot be set".to_string(),
+        ));
+    }
+    if body.recipients.is_empty() {
+        return Err(ShadowDropError::BadRequest("A round needs at least one recipient".to_string()));
+    }
+    let mut wallets = std::collections::HashSet::new();
+    if let Some(duplicate) = body.recipients.iter().find(|r| !wallets.insert(&r.wallet)) {
+        return Err(ShadowDropError::BadRequest(format!(
+            "wallet {} is listed more than once",
+            duplicate.wallet
+        )));
+    }
+    let open_at = body.open_at.unwrap_or_else(Utc::now);
+    if body.close_at.is_some_and(|close_at| close_at <= open_at) {
+        return Err(ShadowDropError::BadRequest("close_at must be after open_at".to_string()));
+    }
+
+    let recipients: Vec<(String, String)> = body
+        .recipients
+        .into_iter()
+        .map(|r| (r.wallet, r.amount))
+        .collect();
+    let (leaves, tree) = new_round_tree(&recipients, &state.config.fees).await?;
+    let round = NewRound {
+        merkle_root: tree.root(),
+        leaves: &leaves,
+        open_at,
+        close_at: body.close_at,
+        recipients: &recipients,
+    };
+    let round = state
+        .campaign_store
+        .create_round(&address, round, request_id(&headers))
+        .await?
+        .ok_or_else(|| ShadowDropError::NotFound("Campaign not found".to_string()))?;
+    state.tree_cache.insert(round.id, Arc::new(tree)).await;
+    state.root_etags.remove(&address);
+    tracing::info!(campaign = %address, round = round.round_number, "🔁 Round created");
 
     Ok(ApiSuccessResponse::default()
-        .with_data(infos)
-        .with_message("Campaigns retrieved"))
+        .with_code(StatusCode::CREATED)
+        .with_data(RoundInfo::from(&round))
+        .with_message("Round created successfully"))
+}
+
+/// GET /api/v1/campaigns/:address/rounds/:round_id/proof/:wallet - Proof against a round's tree
+async fn get_round_proof(
+    State(state): State<AppState>,
+    Path((address, round_id, wallet)): Path<(String, String, String)>,
+) -> ApiResponse<ProofResponse> {
+    record_campaign_id(&address);
+    let round = state
+        .campaign_store
+        .get_round(&address, parse_round_id(&round_id)?)
+        .await
+        .ok_or_else(|| ShadowDropError::NotFound("Round not found".to_string()))?;
+
+    let proof = round_proof(&state, &round, &wallet).await?;
+    Ok(ApiSuccessResponse::default()
+        .with_data(proof)
+        .with_message("Proof generated successfully"))
+}
+
+/// Whether `wallet` is a Base58 encoded 32-byte public key
+fn is_wallet_address(wallet: &str) -> bool {
+    bs58::decode(wallet).into_vec().is_ok_and(|bytes| bytes.len() == 32)
+}
+
+/// A referrer must be a Solana pubkey listed in the campaign, other than the claimant
+async fn validate_referrer(
+    state: &AppState,
+    address: &str,
+    wallet: &str,
+    referrer: &str,
+) -> Result<(), ShadowDropError> {
+    if !is_wallet_address(referrer) {
+        return Err(ShadowDropError::BadRequest(
+            "referrer must be a Base58 wallet address".to_string(),
+        ));
+    }
+    if referrer == wallet {
+        return Err(ShadowDropError::BadRequest("A wallet cannot refer itself".to_string()));
+    }
+
+    let is_recipient = state
+        .campaign_store
+        .check_eligibility(address, referrer)
+        .await
+        .is_some_and(|e| e.amount.is_some());
+    if !is_recipient {
+        return Err(ShadowDropError::BadRequest(
+            "referrer must be a recipient of the campaign".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// GET /api/v1/campaigns/:address/balance/:wallet - Claimed and remaining allocation
+async fn get_balance(
+    State(state): State<AppState>,
+    Path((address, wallet)): Path<(String, String)>,
+) -> ApiResponse<BalanceResponse> {
+    record_campaign_id(&address);
+    let balance = state
+        .campaign_store
+        .recipient_balance(&address, &wallet)
+        .await
+        .ok_or_else(|| ShadowDropError::NotFound("Recipient not found".to_string()))?;
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(BalanceResponse {
+            allocation: balance.allocation.to_string(),
+            claimed: balance.claimed.to_string(),
+            remaining: balance.remaining().to_string(),
+        })
+        .with_message("Balance retrieved"))
+}
+
+/// GET /api/v1/campaigns/:address/recipients - Recipients in leaf order, paginated by cursor
+async fn get_recipients(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(query): Query<RecipientsQuery>,
+) -> ApiResponse<RecipientPage> {
+    record_campaign_id(&address);
+    let limit = query.limit.unwrap_or(DEFAULT_RECIPIENTS_LIMIT);
+    if !(1..=MAX_RECIPIENTS_LIMIT).contains(&limit) {
+        return Err(ShadowDropError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_RECIPIENTS_LIMIT
+        )));
+    }
+
+    match state.campaign_store.recipients_page(&address, query.cursor, limit).await {
+        Some(page) => Ok(ApiSuccessResponse::default()
+            .with_data(page)
+            .with_message("Recipients retrieved")),
+        None => Err(ShadowDropError::NotFound("Campaign not found".to_string())),
+    }
+}
+
+/// PUT /api/v1/campaigns/:address/recipients - Append recipients to a draft (admin only)
+///
+/// Invalid entries are reported per wallet and skipped; the others are
+/// appended. The whole request is rejected if the campaign is not a draft or
+/// would outgrow the Merkle tree.
+async fn add_recipients(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<AddRecipientsRequest>,
+) -> ApiResponse<AddRecipientsResponse> {
+    record_campaign_id(&address);
+    if body.recipients.is_empty() {
+        return Err(ShadowDropError::BadRequest("recipients must not be empty".to_string()));
+    }
+
+    let mut errors = Vec::new();
+    let mut valid: Vec<(String, Lamports)> = Vec::new();
+    for recipient in body.recipients {
+        let amount = recipient.amount.parse::<Lamports>().ok().filter(|a| *a > 0);
+        let error = if !is_wallet_address(&recipient.wallet) {
+            "wallet must be a Base58 wallet address"
+        } else if valid.iter().any(|(wallet, _)| *wallet == recipient.wallet) {
+            "wallet is listed more than once"
+        } else if amount.is_none() {
+            "amount must be a positive integer of lamports"
+        } else {
+            valid.push((recipient.wallet, amount.unwrap_or_default()));
+            continue;
+        };
+        errors.push(RecipientInputError {
+            wallet: recipient.wallet,
+            error: error.to_string(),
+        });
+    }
+
+    let outcome = state
+        .campaign_store
+        .append_recipients(&address, &valid, request_id(&headers))
+        .await?
+        .ok_or_else(|| ShadowDropError::NotFound("Campaign not found".to_string()))?;
+    let (added, already_listed) = match outcome {
+        AppendOutcome::Appended { added, already_listed } => (added, already_listed),
+        AppendOutcome::NotDraft(status) => return Err(not_draft(&status)),
+        AppendOutcome::TooManyLeaves { count } => {
+            return Err(MerkleError::TooManyLeaves { count, max: MAX_LEAVES }.into());
+        }
+    };
+    errors.extend(already_listed.into_iter().map(|wallet| RecipientInputError {
+        wallet,
+        error: "wallet is already a recipient".to_string(),
+    }));
+    invalidate_campaign_proofs(&state, &address).await?;
+    tracing::info!(campaign = %address, added, rejected = errors.len(), "👥 Recipients added");
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(AddRecipientsResponse { added, errors })
+        .with_message("Recipients added"))
+}
+
+/// DELETE /api/v1/campaigns/:address/recipients/:wallet - Remove a recipient from a draft (admin only)
+///
+/// Other recipients keep their leaf indices. Once a campaign is active its
+/// root is committed and recipients can no longer be removed.
+async fn remove_recipient(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path((address, wallet)): Path<(String, String)>,
+    Query(query): Query<RemoveRecipientQuery>,
+    headers: HeaderMap,
+) -> ApiResponse<RemovedRecipientResponse> {
+    record_campaign_id(&address);
+    let reason = query.reason.trim();
+    if reason.is_empty() {
+        return Err(ShadowDropError::BadRequest("reason must not be empty".to_string()));
+    }
+
+    let outcome = state
+        .campaign_store
+        .remove_recipient(&address, &wallet, reason, request_id(&headers))
+        .await?
+        .ok_or_else(|| ShadowDropError::NotFound("Campaign not found".to_string()))?;
+    let amount = match outcome {
+        RemoveOutcome::Removed { amount } => amount,
+        RemoveOutcome::NotDraft(status) => return Err(not_draft(&status)),
+        RemoveOutcome::NotARecipient => {
+            return Err(ShadowDropError::NotFound("Recipient not found".to_string()));
+        }
+    };
+    invalidate_campaign_proofs(&state, &address).await?;
+    tracing::info!(campaign = %address, wallet = %wallet, reason, "🗑️ Recipient removed");
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(RemovedRecipientResponse { wallet, amount })
+        .with_message("Recipient removed"))
+}
+
+/// PATCH /api/v1/campaigns/:address/recipients/:wallet - Correct an allocation in a draft (admin only)
+async fn update_recipient(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path((address, wallet)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(body): Json<UpdateRecipientRequest>,
+) -> ApiResponse<AmountChange> {
+    record_campaign_id(&address);
+    let entry = RecipientInput { wallet, amount: body.amount };
+    let change = update_amounts(&state, &address, vec![entry], request_id(&headers))
+        .await?
+        .pop()
+        .ok_or_else(|| ShadowDropError::Internal("Update returned no change".to_string()))?;
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(change)
+        .with_message("Recipient updated"))
+}
+
+/// PATCH /api/v1/campaigns/:address/recipients - Correct several allocations in a draft (admin only)
+///
+/// The body is an array of `{ wallet, amount }`. Updates are atomic: if one
+/// entry is invalid or not a recipient, nothing changes.
+async fn update_recipients(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<Vec<RecipientInput>>,
+) -> ApiResponse<Vec<AmountChange>> {
+    record_campaign_id(&address);
+    if body.is_empty() {
+        return Err(ShadowDropError::BadRequest("updates must not be empty".to_string()));
+    }
+    let changes = update_amounts(&state, &address, body, request_id(&headers)).await?;
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(changes)
+        .with_message("Recipients updated"))
+}
+
+/// Validate and apply allocation updates in one transaction.
+///
+/// Leaf hashes commit to the amount, so the tree built at activation (and
+/// any proof derived from it) picks up the new allocations.
+async fn update_amounts(
+    state: &AppState,
+    address: &str,
+    entries: Vec<RecipientInput>,
+    request_id: Option<&str>,
+) -> Result<Vec<AmountChange>, ShadowDropError> {
+    let mut updates: Vec<(String, Lamports)> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let Some(amount) = entry.amount.parse::<Lamports>().ok().filter(|a| *a > 0) else {
+            return Err(ShadowDropError::BadRequest(format!(
+                "amount of {} must be a positive integer of lamports",
+                entry.wallet
+            )));
+        };
+        if updates.iter().any(|(wallet, _)| *wallet == entry.wallet) {
+            return Err(ShadowDropError::BadRequest(format!(
+                "wallet {} is listed more than once",
+                entry.wallet
+            )));
+        }
+        updates.push((entry.wallet, amount));
+    }
+
+    let outcome = state
+        .campaign_store
+        .update_amounts(address, &updates, request_id)
+        .await?
+        .ok_or_else(|| ShadowDropError::NotFound("Campaign not found".to_string()))?;
+    match outcome {
+        UpdateOutcome::Updated(changes) => {
+            invalidate_campaign_proofs(state, address).await?;
+            tracing::info!(campaign = %address, updated = changes.len(), "✏️ Recipient amounts updated");
+            Ok(changes)
+        }
+        UpdateOutcome::NotDraft(status) => Err(not_draft(&status)),
+        UpdateOutcome::NotARecipient(wallet) => {
+            Err(ShadowDropError::NotFound(format!("Wallet {} is not a recipient", wallet)))
+        }
+    }
+}
+
+/// Conflict for a recipient change on a campaign that is no longer a draft
+fn not_draft(status: &str) -> ShadowDropError {
+    ShadowDropError::Conflict {
+        message: format!("Campaign is {}, expected {}", status, CAMPAIGN_STATUS_DRAFT),
+        details: None,
+    }
 }
 
-/// Simple UUID generator (without external dependency)
-fn uuid_simple() -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_nanos();
-    format!("{:x}", now)
+/// GET /api/v1/campaigns/:address/availability - Claim slots left
+async fn get_availability(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> ApiResponse<Availability> {
+    record_campaign_id(&address);
+    match state.campaign_store.availability(&address).await {
+        Some(availability) => Ok(ApiSuccessResponse::default()
+            .with_data(availability)
+            .with_message("Availability retrieved")),
+        None => Err(ShadowDropError::NotFound("Campaign not found".to_string())),
+    }
+}
+
+/// GET /api/v1/campaigns/:address/referrals/:wallet - Claims referred by a wallet
+async fn get_referral_stats(
+    State(state): State<AppState>,
+    Path((address, wallet)): Path<(String, String)>,
+) -> ApiResponse<ReferralStats> {
+    record_campaign_id(&address);
+    match state.campaign_store.referral_stats(&address, &wallet).await {
+        Some(stats) => Ok(ApiSuccessResponse::default()
+            .with_data(stats)
+            .with_message("Referral stats retrieved")),
+        None => Err(ShadowDropError::NotFound("Campaign not found".to_string())),
+    }
+}
+
+/// POST /api/v1/campaigns/:address/clone - Copy a campaign into a new draft (admin only)
+async fn clone_campaign(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    headers: HeaderMap,
+) -> ApiResponse<CampaignInfo> {
+    record_campaign_id(&address);
+    let new_address = state
+        .campaign_store
+        .clone_campaign(&address, request_id(&headers))
+        .await?
+        .ok_or_else(|| ShadowDropError::NotFound("Campaign not found".to_string()))?;
+    tracing::info!(campaign = %address, clone = %new_address, "📋 Campaign cloned");
+
+    let clone = state
+        .campaign_store
+        .get(&new_address)
+        .await
+        .ok_or_else(|| ShadowDropError::Internal("Cloned campaign not found".to_string()))?;
+    Ok(ApiSuccessResponse::default()
+        .with_code(StatusCode::CREATED)
+        .with_data(CampaignInfo::from(&clone))
+        .with_message("Campaign cloned successfully"))
+}
+
+/// PATCH /api/v1/campaigns/:address/activate - Open a draft for claims (admin only)
+///
+/// SOL campaigns are only activated once the creator wallet holds the total
+/// amount plus fees, unless `SKIP_FUND_CHECK` is set. Token campaigns are not
+/// checked: their balance lives in a token account, not the creator wallet.
+/// The campaign's tree is built from its recipients and its root and
+/// commitment stored with the status change. Once active, recipient proofs
+/// are pre-generated in the background.
+async fn activate_campaign(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    headers: HeaderMap,
+) -> ApiResponse<CampaignInfo> {
+    record_campaign_id(&address);
+    let campaign = state
+        .campaign_store
+        .get(&address)
+        .await
+        .ok_or_else(|| ShadowDropError::NotFound("Campaign not found".to_string()))?;
+
+    let campaign_config = &state.config.campaign;
+    if !campaign_config.skip_fund_check && campaign.token_mint.is_none() {
+        let total: Lamports = campaign.total_amount.parse().map_err(|_| {
+            ShadowDropError::Internal("Campaign total amount is not an integer".to_string())
+        })?;
+        let rpc_url = &campaign_config.solana_rpc_url;
+        let breaker = state.rpc_circuits.get(rpc_url);
+        validate_campaign_funds(total, &campaign.creator_wallet, rpc_url, &breaker).await?;
+    }
+
+    // Drafts carry their source's root, or none; commit to the recipients' tree
+    let tree = campaign_tree(&state, &campaign).await?;
+    let activated = state
+        .campaign_store
+        .activate(&address, &tree.root(), campaign.recipients.len(), request_id(&headers))
+        .await?;
+    check_transitioned(&state, &address, CAMPAIGN_STATUS_DRAFT, activated).await?;
+    tracing::info!(campaign = %address, "🚀 Campaign activated");
+    spawn_proof_pregeneration(state.clone(), address.clone());
+
+    campaign_info(&state, &address).await
+}
+
+/// PATCH /api/v1/campaigns/:address/pause - Halt claims (admin only)
+async fn pause_campaign(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<PauseCampaignRequest>,
+) -> ApiResponse<CampaignInfo> {
+    record_campaign_id(&address);
+    if body.reason.trim().is_empty() {
+        return Err(ShadowDropError::BadRequest(
+            "A pause reason is required".to_string(),
+        ));
+    }
+
+    transition(
+        &state,
+        &address,
+        CAMPAIGN_STATUS_ACTIVE,
+        CAMPAIGN_STATUS_PAUSED,
+        Some(&body.reason),
+        request_id(&headers),
+    )
+    .await?;
+    tracing::warn!(campaign = %address, reason = %body.reason, "⏸️ Campaign paused");
+
+    campaign_info(&state, &address).await
+}
+
+/// PATCH /api/v1/campaigns/:address/resume - Re-enable claims (admin only)
+async fn resume_campaign(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    headers: HeaderMap,
+) -> ApiResponse<CampaignInfo> {
+    record_campaign_id(&address);
+    transition(
+        &state,
+        &address,
+        CAMPAIGN_STATUS_PAUSED,
+        CAMPAIGN_STATUS_ACTIVE,
+        None,
+        request_id(&headers),
+    )
+    .await?;
+    tracing::info!(campaign = %address, "▶️ Campaign resumed");
+
+    campaign_info(&state, &address).await
+}
+
+async fn transition(
+    state: &AppState,
+    address: &str,
+    from_status: &str,
+    to_status: &str,
+    reason: Option<&str>,
+    request_id: Option<&str>,
+) -> Result<(), ShadowDropError> {
+    let moved = state
+        .campaign_store
+        .transition_status(address, from_status, to_status, reason, request_id)
+        .await?;
+    check_transitioned(state, address, from_status, moved).await
+}
+
+/// Explain a status change that did not happen: the campaign is missing or
+/// not in `from_status`
+async fn check_transitioned(
+    state: &AppState,
+    address: &str,
+    from_status: &str,
+    moved: bool,
+) -> Result<(), ShadowDropError> {
+    if moved {
+        return Ok(());
+    }
+
+    match state.campaign_store.get_status(address).await {
+        Some((current, _)) => Err(ShadowDropError::Conflict {
+            message: format!("Campaign is {}, expected {}", current, from_status),
+            details: None,
+        }),
+        None => Err(ShadowDropError::NotFound("Campaign not found".to_string())),
+    }
+}
+
+/// GET /api/v1/campaigns/eligible/:wallet - Get campaigns where wallet is eligible
+async fn get_eligible_campaigns(
+    State(state): State<AppState>,
+    Path(wallet): Path<String>,
+) -> ApiResponse<Vec<EligibleCampaign>> {
+    let campaigns = state.campaign_store.get_eligible_for_wallet(&wallet).await;
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(campaigns)
+        .with_message("Eligible campaigns retrieved"))
+}
+
+/// GET /api/v1/campaigns - List campaigns, filtered, sorted and paged
+///
+/// Campaigns open when they are created, so `open_after` and `open_before`
+/// bound `created_at`. The number of matches across all pages is sent in
+/// `X-Total-Count`.
+async fn list_campaigns(
+    State(state): State<AppState>,
+    Query(query): Query<ListCampaignsQuery>,
+) -> Result<Response, ShadowDropError> {
+    let per_page = query.per_page.unwrap_or(DEFAULT_CAMPAIGNS_PER_PAGE);
+    if !(1..=MAX_CAMPAIGNS_PER_PAGE).contains(&per_page) {
+        return Err(ShadowDropError::BadRequest(format!(
+            "per_page must be between 1 and {}",
+            MAX_CAMPAIGNS_PER_PAGE
+        )));
+    }
+    let page = query.page.unwrap_or(1);
+    if page < 1 {
+        return Err(ShadowDropError::BadRequest("page must be at least 1".to_string()));
+    }
+    if let (Some(open_after), Some(open_before)) = (query.open_after, query.open_before)
+        && open_after >= open_before
+    {
+        return Err(ShadowDropError::BadRequest("open_after must be before open_before".to_string()));
+    }
+    if let Some(status) = &query.status
+        && ![CAMPAIGN_STATUS_DRAFT, CAMPAIGN_STATUS_ACTIVE, CAMPAIGN_STATUS_PAUSED].contains(&status.as_str())
+    {
+        return Err(ShadowDropError::BadRequest(
+            "status must be draft, active or paused".to_string(),
+        ));
+    }
+
+    let start_of = |day: NaiveDate| day.and_time(NaiveTime::MIN).and_utc();
+    let filter = CampaignFilter {
+        status: query.status,
+        creator_wallet: query.creator,
+        created_after: query.open_after.map(start_of),
+        created_before: query.open_before.map(start_of),
+        sort: query.sort_by,
+        descending: query.order == SortOrder::Desc,
+    };
+    let offset = (page - 1).saturating_mul(per_page);
+    let (campaigns, total) = state.campaign_store.list(&filter, per_page, offset).await?;
+
+    let response = ApiSuccessResponse::default()
+        .with_data(campaigns)
+        .with_message("Campaigns retrieved");
+    Ok(([(TOTAL_COUNT_HEADER, total.to_string())], response).into_response())
+}
+
+/// GET /api/v1/campaigns/wallet/:wallet - Get campaigns by creator wallet
+async fn get_campaigns_by_wallet(
+    State(state): State<AppState>,
+    Path(wallet): Path<String>,
+) -> ApiResponse<Vec<CampaignInfo>> {
+    let campaigns = state.campaign_store.get_by_wallet(&wallet).await;
+    let infos: Vec<CampaignInfo> = campaigns.iter().map(CampaignInfo::from).collect();
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(infos)
+        .with_message("Campaigns retrieved"))
+}
+
+/// GET /api/v1/campaigns/id/:id - Get campaign info by campaign id
+async fn get_campaign_by_id(State(state): State<AppState>, Path(id): Path<String>) -> ApiResponse<CampaignInfo> {
+    let campaign = state
+        .campaign_store
+        .get_by_id(&id)
+        .await
+        .ok_or_else(|| ShadowDropError::NotFound("Campaign not found".to_string()))?;
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(CampaignInfo::from(&campaign))
+        .with_message("Campaign found"))
+}
+
+/// The wallet-bound (v2) nullifier of a claim, from its `secret` and `leaf_index`
+///
+/// Always derived from the claiming wallet, so a leaked secret or nullifier
+/// cannot be spent from another wallet.
+fn claim_nullifier(body: &MarkClaimedRequest) -> Result<Hash, ShadowDropError> {
+    let (Some(secret), Some(leaf_index)) = (&body.secret, body.leaf_index) else {
+        return Err(ShadowDropError::BadRequest(
+            "Claims require secret and leaf_index".to_string(),
+        ));
+    };
+    let secret = parse_hash(secret).ok_or_else(|| {
+        ShadowDropError::BadRequest("secret must be 32 hex-encoded bytes".to_string())
+    })?;
+    Ok(compute_nullifier_v2(&secret, leaf_index, &body.wallet))
+}
+
+/// A `nullifier_hash` sent with a claim must be the nullifier it spends:
+/// the v2 nullifier, or `installment_nullifier(v2, n)` for installment `n`
+fn check_nullifier_hash(body: &MarkClaimedRequest, spent: &Hash) -> Result<(), ShadowDropError> {
+    let Some(claimed) = body.nullifier_hash.as_deref() else {
+        return Ok(());
+    };
+    let claimed = parse_hash(claimed).ok_or_else(|| {
+        ShadowDropError::BadRequest("nullifier_hash must be 32 hex-encoded bytes".to_string())
+    })?;
+    if claimed != *spent {
+        return Err(ShadowDropError::BadRequest(
+            "nullifier_hash does not match the claiming wallet".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Tag every log line of the current request with the campaign it targets
+fn record_campaign_id(address: &str) {
+    tracing::Span::current().record("campaign_id", address);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::{
+        app_routes,
+        test_helpers::{TEST_ADMIN_KEY, TEST_CAMPAIGN_ADDRESS, seed_campaign_with, send, test_state},
+    };
+    use crate::nullifiers::InMemoryNullifierDB;
+    use axum::http::{Request, header};
+    use sqlx::PgPool;
+
+    const ADDRESS: &str = TEST_CAMPAIGN_ADDRESS;
+    const WALLET: &str = "wallet1";
+
+    async fn seed_campaign(state: &AppState) {
+        seed_campaign_with(state, &[WALLET]).await;
+    }
+
+    /// Secret of every test claim; the nullifier still differs per wallet
+    const CLAIM_SECRET: [u8; 32] = [5; 32];
+
+    /// Body of a claim by `wallet` at leaf 0
+    fn claim_body(wallet: &str) -> serde_json::Value {
+        serde_json::json!({ "wallet": wallet, "secret": hex::encode(CLAIM_SECRET), "leaf_index": 0 })
+    }
+
+    fn claim_request(key: &str) -> Request<Body> {
+        Request::post(format!("/api/v1/campaigns/{ADDRESS}/claim"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(IDEMPOTENCY_KEY_HEADER, key)
+            .body(Body::from(claim_body(WALLET).to_string()))
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_concurrent_claims_with_same_idempotency_key(pool: PgPool) {
+        // ARRANGE
+        let state = test_state(pool);
+        seed_campaign(&state).await;
+        let app = app_routes(state.clone());
+        let key = uuid::Uuid::new_v4().to_string();
+
+        // ACTION
+        let (first, second) = tokio::join!(
+            send(&app, claim_request(&key)),
+            send(&app, claim_request(&key))
+        );
+
+        // ASSERT
+        // Exactly one request performs the claim; the other either sees it in
+        // flight or replays the stored response. Neither may be a "claim failed".
+        let statuses = [first.0, second.0];
+        assert!(statuses.contains(&StatusCode::OK));
+        assert!(statuses.iter().all(|s| *s == StatusCode::OK || *s == StatusCode::CONFLICT));
+
+        let eligibility = state
+            .campaign_store
+            .check_eligibility(ADDRESS, WALLET)
+            .await
+            .unwrap();
+        assert!(eligibility.already_claimed);
+
+        // A later retry replays the stored response byte-for-byte
+        let original = if first.0 == StatusCode::OK { first.1 } else { second.1 };
+        let (status, replay) = send(&app, claim_request(&key)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(replay, original);
+    }
+
+    fn plain_claim_request(wallet: &str) -> Request<Body> {
+        Request::post(format!("/api/v1/campaigns/{ADDRESS}/claim"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(claim_body(wallet).to_string()))
+            .unwrap()
+    }
+
+    fn admin_request(action: &str, body: &str) -> Request<Body> {
+        Request::patch(format!("/api/v1/campaigns/{ADDRESS}/{action}"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::AUTHORIZATION, format!("Bearer {TEST_ADMIN_KEY}"))
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_claims_rejected_only_while_paused(pool: PgPool) {
+        // ARRANGE
+        let state = test_state(pool);
+        seed_campaign_with(&state, &["before", "during", "after"]).await;
+        let app = app_routes(state);
+
+        // ACTION & ASSERT
+        let (status, body) = send(&app, plain_claim_request("before")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["amount"], "100");
+        assert_eq!(body["data"]["net_amount"], "100");
+
+        let (status, body) = send(&app, admin_request("pause", r#"{"reason":"bug in vault"}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["status"], "paused");
+
+        let (status, body) = send(&app, plain_claim_request("during")).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["errors"]["message"], "campaign_paused");
+        assert_eq!(body["errors"]["details"], "bug in vault");
+
+        let (status, _) = send(&app, admin_request("resume", "")).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, _) = send(&app, plain_claim_request("during")).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&app, plain_claim_request("after")).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[sqlx::test]
+    async fn test_drafts_do_not_accept_claims(pool: PgPool) {
+        let state = test_state(pool);
+        seed_campaign_with(&state, &["first"]).await;
+        let app = app_routes(state.clone());
+        let request = Request::post(format!("/api/v1/campaigns/{ADDRESS}/clone"))
+            .header(header::AUTHORIZATION, format!("Bearer {TEST_ADMIN_KEY}"))
+            .body(Body::empty())
+            .unwrap();
+        let (_, body) = send(&app, request).await;
+        let draft = body["data"]["address"].as_str().unwrap().to_string();
+
+        let claim = Request::post(format!("/api/v1/campaigns/{draft}/claim"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(claim_body("first").to_string()))
+            .unwrap();
+        let (status, body) = send(&app, claim).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["errors"]["message"], "Campaign is draft, expected active");
+
+        // The store refuses too, for callers that skip the handler's check
+        assert_eq!(
+            state.campaign_store.mark_claimed(&draft, "first", None, &[1; 32]).await,
+            ClaimOutcome::NotClaimable
+        );
+        let installment = state.campaign_store.claim_installment(&draft, "first", 10, 0, &[2; 32]).await;
+        assert!(matches!(installment, Ok(InstallmentOutcome::NotClaimable)));
+    }
+
+    #[sqlx::test]
+    async fn test_status_changes_are_audited_with_request_id(pool: PgPool) {
+        let state = test_state(pool.clone());
+        seed_campaign(&state).await;
+        let app = app_routes(state);
+
+        let mut request = admin_request("pause", r#"{"reason":"maintenance"}"#);
+        request
+            .headers_mut()
+            .insert("x-request-id", "req-123".parse().unwrap());
+        let (status, _) = send(&app, request).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let entry = sqlx::query!(
+            "SELECT action, details, payload FROM audit_log WHERE campaign_address = $1",
+            ADDRESS
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(entry.action, "campaign_paused");
+        assert_eq!(entry.details.as_deref(), Some("maintenance"));
+        assert_eq!(entry.payload["request_id"], "req-123");
+    }
+
+    #[sqlx::test]
+    async fn test_reused_nullifier_is_rejected(pool: PgPool) {
+        let state = test_state(pool);
+        seed_campaign_with(&state, &["first", "second"]).await;
+        let app = app_routes(state);
+        let nullifier = hex::encode(compute_nullifier_v2(&CLAIM_SECRET, 0, "first"));
+
+        let claim_with = |wallet: &str, nullifier: &str| {
+            let mut body = claim_body(wallet);
+            body["nullifier_hash"] = nullifier.into();
+            Request::post(format!("/api/v1/campaigns/{ADDRESS}/claim"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        // Another wallet cannot present it: its own nullifier differs
+        let (status, _) = send(&app, claim_with("second", &nullifier)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = send(&app, claim_with("second", "abcd")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = send(&app, claim_with("first", &nullifier)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = send(&app, claim_with("first", &nullifier)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["errors"]["message"], "Nullifier already spent");
+    }
+
+    #[sqlx::test]
+    async fn test_rejected_claim_leaves_its_nullifier_unspent(pool: PgPool) {
+        let state = test_state(pool);
+        seed_campaign_with(&state, &["first"]).await;
+        let app = app_routes(state.clone());
+
+        // Not a recipient: the claim fails after the nullifier was spent, and rolls back
+        let (status, _) = send(&app, plain_claim_request("stranger")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let stranger = compute_nullifier_v2(&CLAIM_SECRET, 0, "stranger");
+        assert!(!state.nullifiers.is_spent(&stranger).await.unwrap());
+
+        // Claims without the secret spend nothing and are refused
+        let request = Request::post(format!("/api/v1/campaigns/{ADDRESS}/claim"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"wallet":"first"}"#))
+            .unwrap();
+        let (status, body) = send(&app, request).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["errors"]["message"], "Claims require secret and leaf_index");
+
+        let (status, _) = send(&app, plain_claim_request("first")).await;
+        assert_eq!(status, StatusCode::OK);
+        let first = compute_nullifier_v2(&CLAIM_SECRET, 0, "first");
+        assert!(state.nullifiers.is_spent(&first).await.unwrap());
+    }
+
+    #[sqlx::test]
+    async fn test_claim_flow_from_creation_to_double_spend(pool: PgPool) {
+        let app = app_routes(test_state(pool));
+        let creator_wallet = bs58::encode([1u8; 32]).into_string();
+        let wallet = bs58::encode([2u8; 32]).into_string();
+        let json_request = |method: &str, uri: String, body: serde_json::Value| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::AUTHORIZATION, format!("Bearer {TEST_ADMIN_KEY}"))
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        // Campaigns are created active; recipients are added to a draft copy
+        let create = serde_json::json!({
+            "address": ADDRESS,
+            "name": "Flow",
+            "merkle_root": "00".repeat(32),
+            "total_amount": "100",
+            "creator_wallet": creator_wallet,
+            "recipients": [{ "wallet": creator_wallet, "amount": "100" }],
+        });
+        let (status, _) = send(&app, json_request("POST", "/api/v1/campaigns".to_string(), create)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let uri = format!("/api/v1/campaigns/{ADDRESS}/clone");
+        let (status, body) = send(&app, json_request("POST", uri, serde_json::json!({}))).await;
+        assert_eq!(status, StatusCode::CREATED, "{body}");
+        let draft = body["data"]["address"].as_str().unwrap().to_string();
+
+        let recipients = serde_json::json!({ "recipients": [{ "wallet": wallet, "amount": "250" }] });
+        let uri = format!("/api/v1/campaigns/{draft}/recipients");
+        let (status, body) = send(&app, json_request("PUT", uri, recipients)).await;
+        assert_eq!((status, body["data"]["added"].clone()), (StatusCode::OK, serde_json::json!(1)));
+        let uri = format!("/api/v1/campaigns/{draft}/activate");
+        let (status, _) = send(&app, json_request("PATCH", uri, serde_json::json!({}))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let uri = format!("/api/v1/proofs/{draft}/generate");
+        let (status, proof) = send(&app, json_request("POST", uri, serde_json::json!({ "wallet": wallet }))).await;
+        assert_eq!(status, StatusCode::OK);
+        let proof = &proof["data"];
+        assert_eq!(proof["leaf_index"], 1);
+
+        let claim = serde_json::json!({
+            "wallet": wallet,
+            "secret": proof["secret"],
+            "leaf_index": proof["leaf_index"],
+        });
+        let uri = format!("/api/v1/campaigns/{draft}/claim");
+        let (status, body) = send(&app, json_request("POST", uri.clone(), claim.clone())).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+
+        // The backend spends the wallet-bound nullifier, not the on-chain one
+        let nullifier = proof["claim_nullifier"].as_str().unwrap();
+        let lookup = Request::get(format!("/api/v1/nullifier/{nullifier}")).body(Body::empty()).unwrap();
+        let (_, status_body) = send(&app, lookup).await;
+        assert_eq!(status_body["data"]["spent"], true);
+        assert_eq!(status_body["data"]["campaign_address"], draft.as_str());
+
+        let (status, _) = send(&app, json_request("POST", uri, claim)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+
+    #[sqlx::test]
+    async fn test_proofs_download_as_a_zip_of_one_file_per_wallet(pool: PgPool) {
+        use tower::ServiceExt;
+
+        let state = test_state(pool);
+        seed_campaign_with(&state, &["first", "second"]).await;
+        let campaign_id = state.campaign_store.get(ADDRESS).await.unwrap().id;
+        let app = app_routes(state);
+        let download = |authorization: &str| {
+            Request::get(format!("/api/v1/campaigns/{ADDRESS}/proofs/download"))
+                .header(header::AUTHORIZATION, authorization)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let (status, _) = send(&app, download("Bearer wrong-key")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let response = app.clone().oneshot(download(&format!("Bearer {TEST_ADMIN_KEY}"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/zip");
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            format!("attachment; filename=\"proofs-{campaign_id}.zip\"")
+        );
+        let archive = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&archive[..4], b"PK\x03\x04");
+        let end = &archive[archive.len() - 22..];
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 2);
+        let first_name = &archive[30..30 + "first.json".len()];
+        assert_eq!(first_name, b"first.json");
+    }
+
+    #[sqlx::test]
+    async fn test_build_claim_tx_carries_a_verifiable_proof(pool: PgPool) {
+        use base64::{Engine, engine::general_purpose::STANDARD};
+
+        use crate::common::{
+            merkle::{MerkleProof, compute_nullifier, verify_proof},
+            solana_tx::{Pubkey, nullifier_address},
+        };
+
+        let state = test_state(pool);
+        let wallet = bs58::encode([2u8; 32]).into_string();
+        seed_campaign_with(&state, &[&wallet, "other"]).await;
+        // The campaign address is the program's campaign account
+        let mut campaign = state.campaign_store.get(ADDRESS).await.unwrap();
+        let address = bs58::encode([1u8; 32]).into_string();
+        campaign.id = Uuid::new_v4().to_string();
+        campaign.address = address.clone();
+        state.campaign_store.create(campaign).await;
+        let app = app_routes(state.clone());
+        let build = |address: &str, blockhash: &str| {
+            Request::post(format!("/api/v1/campaigns/{address}/build-claim-tx"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    serde_json::json!({ "wallet": wallet, "recent_blockhash": blockhash }).to_string(),
+                ))
+                .unwrap()
+        };
+        let blockhash = bs58::encode([9u8; 32]).into_string();
+
+        let (status, body) = send(&app, build(&address, &blockhash)).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let data = &body["data"];
+        let transaction = STANDARD.decode(data["transaction"].as_str().unwrap()).unwrap();
+        assert_eq!(transaction[0], 1, "one signer");
+        assert_eq!(&transaction[1 + 64 + 4..1 + 64 + 4 + 32], [2u8; 32], "wallet pays");
+
+        let depth = data["proof"]["merkle_path"].as_array().unwrap().len();
+        let data_len = 8 + 8 + 32 + 4 + 32 * depth + 4 + depth;
+        let instruction_data = &transaction[transaction.len() - data_len..];
+        let (_, proof) = MerkleProof::from_solana_instruction_data(instruction_data).unwrap();
+        let root = parse_hash(data["proof"]["merkle_root"].as_str().unwrap()).unwrap();
+        assert!(verify_proof(&root, &proof));
+
+        // The instruction carries the v1 nullifier the circuit checks
+        let nullifier = parse_hash(data["proof"]["nullifier_hash"].as_str().unwrap()).unwrap();
+        let secret = parse_hash(data["proof"]["secret"].as_str().unwrap()).unwrap();
+        assert_eq!(nullifier, compute_nullifier(&secret, proof.leaf_index));
+        let (nullifier_account, _) = nullifier_address(&nullifier, &Pubkey([7; 32]));
+        assert_eq!(data["nullifier_account"], nullifier_account.to_string());
+        assert_eq!(data["estimated_fee_lamports"], 5_000);
+        assert!(data["token_account"].is_null());
+
+        // Built for Jito, the transaction ends with the tip and the fee covers it
+        let mut config = (*state.config).clone();
+        config.jito.rpc_url = Some("http://127.0.0.1:1".to_string());
+        let jito_app = app_routes(AppState { config: Arc::new(config), ..state });
+        let mut request = build(&address, &blockhash);
+        *request.uri_mut() = format!("/api/v1/campaigns/{address}/build-claim-tx?submit_method=jito")
+            .parse()
+            .unwrap();
+        let (status, body) = send(&jito_app, request).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["data"]["estimated_fee_lamports"], 5_000 + 10_000);
+        let tipped = STANDARD.decode(body["data"]["transaction"].as_str().unwrap()).unwrap();
+        assert_eq!(tipped[tipped.len() - 12..], [2, 0, 0, 0, 0x10, 0x27, 0, 0, 0, 0, 0, 0]);
+        let mut request = build(&address, &blockhash);
+        *request.uri_mut() = format!("/api/v1/campaigns/{address}/build-claim-tx?submit_method=jito")
+            .parse()
+            .unwrap();
+        let (status, _) = send(&app, request).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "Jito is not enabled");
+
+        let (status, _) = send(&app, build(&address, "too-short")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = send(&app, build(ADDRESS, &blockhash)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[sqlx::test]
+    async fn test_spent_nullifier_is_rejected_before_the_handler(pool: PgPool) {
+        let mut state = test_state(pool.clone());
+        state.nullifiers = Arc::new(InMemoryNullifierDB::default());
+        let nullifier = [0xab; 32];
+        state.nullifiers.mark_spent(nullifier, ADDRESS).await.unwrap();
+        // No campaign exists: the handler would answer 404, the middleware answers first
+        let app = app_routes(state.clone());
+
+        let request = || {
+            Request::post(format!("/api/v1/campaigns/{ADDRESS}/claim"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(format!(
+                    r#"{{"wallet":"{WALLET}","nullifier_hash":"{}"}}"#,
+                    hex::encode(nullifier)
+                )))
+                .unwrap()
+        };
+        let (status, body) = send(&app, request()).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["errors"]["message"], "Nullifier already spent");
+
+        let (status, _) = send(&app, plain_claim_request(WALLET)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // Once loaded from the (empty) table, the filter skips the lookup
+        state.nullifier_bloom.load(&pool).await.unwrap();
+        let (status, _) = send(&app, request()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_claim_nullifier_is_bound_to_wallet() {
+        let secret = [3u8; 32];
+        let request = |wallet: &str, nullifier: Option<Hash>| MarkClaimedRequest {
+            wallet: wallet.to_string(),
+            nullifier_hash: nullifier.map(hex::encode),
+            secret: Some(hex::encode(secret)),
+            leaf_index: Some(1),
+            round_id: None,
+            claim_amount: None,
+            delegate_wallet: None,
+            signature: None,
+            signed_transaction: None,
+        };
+        let nullifier = compute_nullifier_v2(&secret, 1, "first");
+
+        assert_eq!(claim_nullifier(&request("first", None)).unwrap(), nullifier);
+        assert!(check_nullifier_hash(&request("first", Some(nullifier)), &nullifier).is_ok());
+        // The same secret presented by another wallet does not reproduce the nullifier
+        let second = request("second", Some(nullifier));
+        assert!(matches!(
+            check_nullifier_hash(&second, &claim_nullifier(&second).unwrap()),
+            Err(ShadowDropError::BadRequest(_))
+        ));
+        // Without the secret there is nothing to derive it from
+        let bare = MarkClaimedRequest { secret: None, ..request("first", Some(nullifier)) };
+        assert!(matches!(claim_nullifier(&bare), Err(ShadowDropError::BadRequest(_))));
+    }
+
+    #[sqlx::test]
+    async fn test_referred_claims_credit_the_referrer(pool: PgPool) {
+        let state = test_state(pool);
+        let referrer = bs58::encode([1u8; 32]).into_string();
+        let claimant = bs58::encode([2u8; 32]).into_string();
+        seed_campaign_with(&state, &[&referrer, &claimant]).await;
+        let app = app_routes(state);
+
+        let referred_claim = |referrer: &str| {
+            Request::post(format!("/api/v1/campaigns/{ADDRESS}/claim?referrer={referrer}"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(claim_body(&claimant).to_string()))
+                .unwrap()
+        };
+
+        for invalid in ["not-a-pubkey", &claimant, &bs58::encode([3u8; 32]).into_string()] {
+            let (status, _) = send(&app, referred_claim(invalid)).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "referrer {invalid}");
+        }
+
+        let (status, _) = send(&app, referred_claim(&referrer)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let request = Request::get(format!("/api/v1/campaigns/{ADDRESS}/referrals/{referrer}"))
+            .body(Body::empty())
+            .unwrap();
+        let (status, body) = send(&app, request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["referred_count"], 1);
+        assert_eq!(body["data"]["referred_volume_sol"], 100.0 / 1e9);
+        // 5% of the 100 claimed
+        assert_eq!(body["data"]["pending_reward"], "5");
+    }
+
+    #[sqlx::test]
+    async fn test_clone_copies_recipients_into_a_draft(pool: PgPool) {
+        let state = test_state(pool.clone());
+        seed_campaign_with(&state, &["first", "second"]).await;
+        let app = app_routes(state.clone());
+        let (status, _) = send(&app, plain_claim_request("first")).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let request = Request::post(format!("/api/v1/campaigns/{ADDRESS}/clone"))
+            .header(header::AUTHORIZATION, format!("Bearer {TEST_ADMIN_KEY}"))
+            .body(Body::empty())
+            .unwrap();
+        let (status, body) = send(&app, request).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["data"]["status"], CAMPAIGN_STATUS_DRAFT);
+        assert_eq!(body["data"]["total_recipients"], 2);
+        assert_eq!(body["data"]["claimed_count"], 0);
+        assert_eq!(body["data"]["referral_bonus_bps"], 500);
+
+        let clone_address = body["data"]["address"].as_str().unwrap();
+        let clone = state.campaign_store.get(clone_address).await.unwrap();
+        assert_eq!(clone.name, "Test");
+        assert!(clone.merkle_root.is_empty());
+
+        let entry = sqlx::query!(
+            "SELECT action, payload FROM audit_log WHERE campaign_address = $1",
+            clone_address
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(entry.action, "campaign_cloned");
+        assert_eq!(entry.payload["source_campaign"], ADDRESS);
+    }
+
+    #[sqlx::test]
+    async fn test_claims_beyond_max_claims_are_rejected(pool: PgPool) {
+        let state = test_state(pool.clone());
+        seed_campaign_with(&state, &["first", "second"]).await;
+        sqlx::query!("UPDATE campaigns SET max_claims = 1 WHERE address = $1", ADDRESS)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let app = app_routes(state);
+        let availability = || {
+            Request::get(format!("/api/v1/campaigns/{ADDRESS}/availability"))
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let (status, body) = send(&app, availability()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["remaining_slots"], 1);
+        assert_eq!(body["data"]["is_open"], true);
+
+        let (status, _) = send(&app, plain_claim_request("first")).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = send(&app, plain_claim_request("second")).await;
+        assert_eq!(status, StatusCode::GONE);
+        assert_eq!(body["errors"]["message"], "campaign_full");
+        assert_eq!(body["errors"]["details"], "claims_accepted: 1");
+
+        let (_, body) = send(&app, availability()).await;
+        assert_eq!(body["data"]["remaining_slots"], 0);
+        assert_eq!(body["data"]["is_open"], false);
+    }
+
+    #[sqlx::test]
+    async fn test_recipients_paginate_by_leaf_index(pool: PgPool) {
+        let state = test_state(pool);
+        seed_campaign_with(&state, &["a", "b", "c"]).await;
+        let app = app_routes(state);
+        let page = |query: &str| {
+            Request::get(format!("/api/v1/campaigns/{ADDRESS}/recipients?{query}"))
+                .body(Body::empty())
+                .unwrap()
+        };
+        send(&app, plain_claim_request("b")).await;
+
+        let (status, body) = send(&app, page("limit=2")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["total"], 3);
+        assert_eq!(body["data"]["next_cursor"], 1);
+        assert_eq!(body["data"]["recipients"][0]["wallet"], "a");
+        assert_eq!(body["data"]["recipients"][1]["claim_status"], "claimed");
+        assert_eq!(body["data"]["recipients"][1]["amount_sol"], 1e-7);
+
+        let (_, body) = send(&app, page("cursor=1&limit=2")).await;
+        assert_eq!(body["data"]["recipients"][0]["leaf_index"], 2);
+        assert_eq!(body["data"]["recipients"][0]["claim_status"], "unclaimed");
+        assert!(body["data"]["next_cursor"].is_null());
+
+        // Past the last recipient: an empty page, not a 404
+        let (status, body) = send(&app, page("cursor=2")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["recipients"], serde_json::json!([]));
+
+        let (status, _) = send(&app, page("limit=0")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[sqlx::test]
+    async fn test_root_revalidates_with_etag(pool: PgPool) {
+        use tower::ServiceExt;
+
+        let state = test_state(pool.clone());
+        seed_campaign(&state).await;
+        let app = app_routes(state.clone());
+        let get_root = |etag: Option<&str>| {
+            let mut request = Request::get(format!("/api/v1/campaigns/{ADDRESS}/root"));
+            if let Some(etag) = etag {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        // The all-zero placeholder is not a committed root
+        let placeholder = format!("W/\"{}\"", "00".repeat(32));
+        let response = get_root(None).await.unwrap();
+        assert_eq!(response.headers()[header::ETAG], placeholder.as_str());
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-cache");
+        assert_eq!(get_root(Some(&placeholder)).await.unwrap().status(), StatusCode::NOT_MODIFIED);
+        assert!(state.root_etags.get(ADDRESS).is_none());
+
+        // Activation commits the recipients' root
+        sqlx::query!("UPDATE campaigns SET status = 'draft' WHERE address = $1", ADDRESS)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let (status, _) = send(&app, admin_request("activate", "")).await;
+        assert_eq!(status, StatusCode::OK);
+        let root = state.campaign_store.get(ADDRESS).await.unwrap().merkle_root;
+        let committed = format!("W/\"{root}\"");
+
+        let response = get_root(None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::ETAG], committed.as_str());
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "max-age=31536000, immutable");
+        let response = get_root(Some(&committed)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert!(to_bytes(response.into_body(), usize::MAX).await.unwrap().is_empty());
+
+        // A committed root is answered from memory, without the database
+        pool.close().await;
+        let response = get_root(Some(&committed)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        let response = get_root(Some("W/\"other\"")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[sqlx::test]
+    async fn test_round_replaces_cached_root(pool: PgPool) {
+        use tower::ServiceExt;
+
+        let state = test_state(pool);
+        seed_campaign(&state).await;
+        let app = app_routes(state);
+        let get_root = |etag: &str| {
+            let request = Request::get(format!("/api/v1/campaigns/{ADDRESS}/root"))
+                .header(header::IF_NONE_MATCH, etag)
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        let committed = format!("W/\"{}\"", "00".repeat(32));
+        assert_eq!(get_root(&committed).await.unwrap().status(), StatusCode::NOT_MODIFIED);
+
+        let request = Request::post(format!("/api/v1/campaigns/{ADDRESS}/rounds"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::AUTHORIZATION, format!("Bearer {TEST_ADMIN_KEY}"))
+            .body(Body::from(format!(
+                r#"{{"recipients":[{{"wallet":"{WALLET}","amount":"40"}}]}}"#
+            )))
+            .unwrap();
+        let (status, body) = send(&app, request).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let response = get_root(&committed).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-cache");
+        let round_root = format!("W/\"{}\"", body["data"]["merkle_root"].as_str().unwrap());
+        assert_eq!(response.headers()[header::ETAG], round_root.as_str());
+        assert_eq!(get_root(&round_root).await.unwrap().status(), StatusCode::NOT_MODIFIED);
+
+        let (status, _) = send(&app, admin_request("pause", r#"{"reason":"maintenance"}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+        let response = get_root("W/\"stale\"").await.unwrap();
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-cache");
+    }
+
+    #[sqlx::test]
+    async fn test_round_nullifiers_are_scoped_per_round(pool: PgPool) {
+        let state = test_state(pool);
+        seed_campaign_with(&state, &["first"]).await;
+        let app = app_routes(state.clone());
+        let create_round = || {
+            Request::post(format!("/api/v1/campaigns/{ADDRESS}/rounds"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::AUTHORIZATION, format!("Bearer {TEST_ADMIN_KEY}"))
+                .body(Body::from(r#"{"recipients":[{"wallet":"first","amount":"40"}]}"#))
+                .unwrap()
+        };
+
+        let (status, body) = send(&app, create_round()).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["data"]["round_number"], 1);
+        let round_1 = body["data"]["id"].as_str().unwrap().to_string();
+        let root_1 = body["data"]["merkle_root"].as_str().unwrap().to_string();
+        let (_, body) = send(&app, create_round()).await;
+        assert_eq!(body["data"]["round_number"], 2);
+        let round_2 = body["data"]["id"].as_str().unwrap().to_string();
+        let campaign = state.campaign_store.get(ADDRESS).await.unwrap();
+        assert_eq!(campaign.merkle_root, body["data"]["merkle_root"]);
+        assert_ne!(campaign.merkle_root, root_1);
+
+        // The root is derived from the recipients, never taken from the admin
+        let request = Request::post(format!("/api/v1/campaigns/{ADDRESS}/rounds"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::AUTHORIZATION, format!("Bearer {TEST_ADMIN_KEY}"))
+            .body(Body::from(format!(
+                r#"{{"merkle_root":"{}","recipients":[{{"wallet":"first","amount":"40"}}]}}"#,
+                "11".repeat(32)
+            )))
+            .unwrap();
+        let (status, _) = send(&app, request).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let request = Request::get(format!("/api/v1/campaigns/{ADDRESS}/rounds/{round_1}/proof/first"))
+            .body(Body::empty())
+            .unwrap();
+        let (status, proof) = send(&app, request).await;
+        assert_eq!(status, StatusCode::OK);
+        // Proven against the stored round tree, from a fresh cache too
+        assert_eq!(proof["data"]["merkle_root"], root_1.as_str());
+        state.tree_cache.remove(&Uuid::parse_str(&round_1).unwrap()).await;
+        let request = Request::get(format!("/api/v1/campaigns/{ADDRESS}/rounds/{round_1}/proof/first"))
+            .body(Body::empty())
+            .unwrap();
+        let (_, rebuilt) = send(&app, request).await;
+        assert_eq!(rebuilt["data"], proof["data"]);
+        let claim = |round_id: &str| {
+            Request::post(format!("/api/v1/campaigns/{ADDRESS}/claim"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "wallet": "first",
+                        "nullifier_hash": proof["data"]["claim_nullifier"],
+                        "secret": proof["data"]["secret"],
+                        "leaf_index": proof["data"]["leaf_index"],
+                        "round_id": round_id,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+
+        let (status, body) = send(&app, claim(&round_1)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["amount"], "40");
+        let (status, _) = send(&app, claim(&round_1)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        // The same nullifier is unspent in the next round
+        let (status, _) = send(&app, claim(&round_2)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[sqlx::test]
+    async fn test_installments_spend_one_nullifier_each(pool: PgPool) {
+        let state = test_state(pool);
+        seed_campaign_with(&state, &["first"]).await;
+        let app = app_routes(state);
+        let secret = [3u8; 32];
+        let base = compute_nullifier_v2(&secret, 0, "first");
+        let installment = |sequence: u32, amount: u64| {
+            Request::post(format!("/api/v1/campaigns/{ADDRESS}/claim"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "wallet": "first",
+                        "nullifier_hash": hex::encode(installment_nullifier(&base, sequence)),
+                        "secret": hex::encode(secret),
+                        "leaf_index": 0,
+                        "claim_amount": amount,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+
+        let (status, body) = send(&app, installment(0, 30)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["amount"], "30");
+        assert_eq!(body["data"]["remaining"], "70");
+
+        let (status, _) = send(&app, installment(0, 30)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (status, _) = send(&app, installment(1, 71)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, body) = send(&app, installment(1, 70)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["remaining"], "0");
+
+        let request = Request::get(format!("/api/v1/campaigns/{ADDRESS}/balance/first"))
+            .body(Body::empty())
+            .unwrap();
+        let (status, body) = send(&app, request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["allocation"], "100");
+        assert_eq!(body["data"]["claimed"], "100");
+        assert_eq!(body["data"]["remaining"], "0");
+    }
+
+    #[sqlx::test]
+    async fn test_an_installment_takes_a_claim_slot(pool: PgPool) {
+        let state = test_state(pool.clone());
+        seed_campaign_with(&state, &["first", "second"]).await;
+        sqlx::query!("UPDATE campaigns SET max_claims = 1 WHERE address = $1", ADDRESS)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let app = app_routes(state.clone());
+        let installment = |wallet: &str, sequence: u32, amount: u64| {
+            let base = compute_nullifier_v2(&CLAIM_SECRET, 0, wallet);
+            Request::post(format!("/api/v1/campaigns/{ADDRESS}/claim"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "wallet": wallet,
+                        "nullifier_hash": hex::encode(installment_nullifier(&base, sequence)),
+                        "secret": hex::encode(CLAIM_SECRET),
+                        "leaf_index": 0,
+                        "claim_amount": amount,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+
+        let (status, _) = send(&app, installment("first", 0, 30)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = send(&app, installment("second", 0, 30)).await;
+        assert_eq!(status, StatusCode::GONE);
+        assert_eq!(body["errors"]["details"], "claims_accepted: 1");
+        let (status, _) = send(&app, plain_claim_request("second")).await;
+        assert_eq!(status, StatusCode::GONE);
+
+        // The store refuses too, without spending the nullifier
+        let outcome = state.campaign_store.claim_installment(ADDRESS, "second", 30, 0, &[9; 32]).await;
+        assert!(matches!(outcome, Ok(InstallmentOutcome::CampaignFull { claims_accepted: 1 })));
+        assert!(!state.nullifiers.is_spent(&[9; 32]).await.unwrap());
+
+        // The slot holder finishes its allocation
+        let (status, body) = send(&app, installment("first", 1, 70)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["remaining"], "0");
+    }
+
+    #[sqlx::test]
+    async fn test_registered_delegate_claims_for_recipient(pool: PgPool) {
+        use crate::common::signature::tests::{sign, test_wallet};
+
+        let (recipient_key, recipient) = test_wallet();
+        let (delegate_key, delegate) = test_wallet();
+        let state = test_state(pool.clone());
+        seed_campaign_with(&state, &[&recipient]).await;
+        let app = app_routes(state);
+
+        let issued_at = Utc::now().timestamp();
+        let message = set_delegate_message(ADDRESS, &recipient, Some(&delegate), issued_at);
+        let request = Request::post(format!(
+            "/api/v1/campaigns/{ADDRESS}/recipients/{recipient}/set-delegate"
+        ))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::json!({
+                "delegate_wallet": delegate,
+                "issued_at": issued_at,
+                "signature": sign(&recipient_key, message.as_bytes()),
+            })
+            .to_string(),
+        ))
+        .unwrap();
+        let (status, body) = send(&app, request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["delegate_wallet"], delegate);
+
+        let nullifier = compute_nullifier_v2(&CLAIM_SECRET, 0, &recipient);
+        let claim = |signature: String| {
+            let mut body = claim_body(&recipient);
+            body["delegate_wallet"] = delegate.clone().into();
+            body["signature"] = signature.into();
+            Request::post(format!("/api/v1/campaigns/{ADDRESS}/claim"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let message = delegate_claim_message(ADDRESS, &recipient, &nullifier);
+
+        // Signed by the recipient rather than the delegate
+        let (status, _) = send(&app, claim(sign(&recipient_key, message.as_bytes()))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = send(&app, claim(sign(&delegate_key, message.as_bytes()))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let entry = sqlx::query!(
+            "SELECT payload FROM audit_log WHERE campaign_address = $1 AND action = 'delegate_claim'",
+            ADDRESS
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(entry.payload["wallet"], recipient.as_str());
+        assert_eq!(entry.payload["delegate_wallet"], delegate.as_str());
+    }
+
+    #[sqlx::test]
+    async fn test_required_claim_signature(pool: PgPool) {
+        use crate::common::signature::{
+            claim_bucket, claim_signing_message,
+            tests::{sign, test_wallet},
+        };
+
+        let (key, recipient) = test_wallet();
+        let state = test_state(pool);
+        seed_campaign_with(&state, &[&recipient]).await;
+        let mut config = (*state.config).clone();
+        config.campaign.require_claim_signature = true;
+        let app = app_routes(AppState { config: Arc::new(config), ..state });
+
+        let nullifier = compute_nullifier_v2(&CLAIM_SECRET, 0, &recipient);
+        let claim = |signature: Option<String>| {
+            let mut body = claim_body(&recipient);
+            body["signature"] = signature.into();
+            Request::post(format!("/api/v1/campaigns/{ADDRESS}/claim"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let bucket = claim_bucket(Utc::now().timestamp());
+
+        let (status, _) = send(&app, claim(None)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        // A signature from two buckets ago has expired
+        let stale = claim_signing_message(ADDRESS, &nullifier, bucket - 2);
+        let (status, _) = send(&app, claim(Some(sign(&key, &stale)))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let message = claim_signing_message(ADDRESS, &nullifier, bucket);
+        let (status, body) = send(&app, claim(Some(sign(&key, &message)))).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+    }
+
+    #[sqlx::test]
+    async fn test_round_claims_require_the_signature(pool: PgPool) {
+        use crate::common::signature::{
+            claim_bucket, claim_signing_message,
+            tests::{sign, test_wallet},
+        };
+
+        let (key, recipient) = test_wallet();
+        let state = test_state(pool);
+        seed_campaign_with(&state, &[&recipient]).await;
+        let mut config = (*state.config).clone();
+        config.campaign.require_claim_signature = true;
+        let app = app_routes(AppState { config: Arc::new(config), ..state });
+
+        let request = Request::post(format!("/api/v1/campaigns/{ADDRESS}/rounds"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::AUTHORIZATION, format!("Bearer {TEST_ADMIN_KEY}"))
+            .body(Body::from(format!(
+                r#"{{"recipients":[{{"wallet":"{recipient}","amount":"40"}}]}}"#
+            )))
+            .unwrap();
+        let (status, body) = send(&app, request).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let round_id = body["data"]["id"].as_str().unwrap().to_string();
+
+        let nullifier = compute_nullifier_v2(&CLAIM_SECRET, 0, &recipient);
+        let claim = |signature: Option<String>| {
+            let mut body = claim_body(&recipient);
+            body["round_id"] = round_id.clone().into();
+            if let Some(signature) = signature {
+                body["signature"] = signature.into();
+            }
+            Request::post(format!("/api/v1/campaigns/{ADDRESS}/claim"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let (status, body) = send(&app, claim(None)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["errors"]["message"], "Claims require the wallet's signature");
+        let message = claim_signing_message(ADDRESS, &nullifier, claim_bucket(Utc::now().timestamp()));
+        let (status, body) = send(&app, claim(Some(sign(&key, &message)))).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["data"]["amount"], "40");
+    }
+
+    #[sqlx::test]
+    async fn test_claim_submitted_as_a_jito_bundle(pool: PgPool) {
+        use crate::{
+            common::solana_tx::tests::serve_block_engine, funding::tests::serve_rpc, models::CLAIM_TX_SUBMITTED,
+        };
+
+        let state = test_state(pool);
+        seed_campaign_with(&state, &[WALLET]).await;
+        // One signature slot, signed, then a message the mock block engine ignores
+        let mut signed = vec![1u8];
+        signed.extend([3u8; 64]);
+        signed.extend([0u8; 8]);
+        let signed = STANDARD.encode(signed);
+        let signature = bs58::encode([3u8; 64]).into_string();
+        let claim = |signed_transaction: Option<&str>| {
+            Request::post(format!("/api/v1/campaigns/{ADDRESS}/claim?submit_method=jito"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from({
+                    let mut body = claim_body(WALLET);
+                    body["signed_transaction"] = signed_transaction.into();
+                    body.to_string()
+                }))
+                .unwrap()
+        };
+
+        // Refused while no block engine is configured
+        let (status, _) = send(&app_routes(state.clone()), claim(Some(&signed))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (jito_url, server) =
+            serve_block_engine(vec![(200, r#"{"jsonrpc":"2.0","result":"bundle-1","id":1}"#)]).await;
+        let statuses = r#"[{"slot":42,"confirmations":0,"err":null,"confirmationStatus":"confirmed"}]"#;
+        let mut config = (*state.config).clone();
+        config.jito.rpc_url = Some(jito_url);
+        config.campaign.solana_rpc_url = serve_rpc("getSignatureStatuses", statuses.to_string()).await;
+        let claim_transactions = state.claim_transactions.clone();
+        let app = app_routes(AppState { config: Arc::new(config), ..state });
+
+        let (status, _) = send(&app, claim(None)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        // An unsigned transaction has nothing to track
+        let (status, _) = send(&app, claim(Some("AQID"))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        // A claim the backend would refuse is never sent: the block engine
+        // answers once, and that answer is left for the valid claim below
+        let over_allocation = Request::post(format!("/api/v1/campaigns/{ADDRESS}/claim?submit_method=jito"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from({
+                let mut body = claim_body(WALLET);
+                body["signed_transaction"] = signed.as_str().into();
+                body["claim_amount"] = 1000.into();
+                body.to_string()
+            }))
+            .unwrap();
+        let (status, body) = send(&app, over_allocation).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["errors"]["message"], "claim_amount must be between 1 and the remaining allocation of 100");
+        assert_eq!(claim_transactions.status(&signature).await.unwrap(), None);
+        let (status, body) = send(&app, claim(Some(&signed))).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["data"]["bundle_id"], "bundle-1");
+        assert_eq!(body["data"]["transaction_signature"], signature.as_str());
+        assert!(server.await.unwrap()[0].contains(&format!(r#"[["{signed}"],{{"encoding":"base64"}}]"#)));
+
+        // Confirmed in the background
+        let mut recorded = claim_transactions.status(&signature).await.unwrap();
+        for _ in 0..50 {
+            if recorded.as_deref() != Some(CLAIM_TX_SUBMITTED) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            recorded = claim_transactions.status(&signature).await.unwrap();
+        }
+        assert_eq!(recorded.as_deref(), Some(CLAIM_TX_CONFIRMED));
+    }
+
+    #[sqlx::test]
+    async fn test_claim_finishes_after_the_claim_timeout(pool: PgPool) {
+        use crate::funding::tests::serve_rpc;
+        use tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::TcpListener,
+        };
+
+        // A block engine that answers after the claim timeout has fired
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let jito_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0u8; 8192];
+            let _ = socket.read(&mut request).await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+            let body = r#"{"jsonrpc":"2.0","result":"bundle-1","id":1}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        let state = test_state(pool.clone());
+        seed_campaign_with(&state, &[WALLET]).await;
+        let mut config = (*state.config).clone();
+        config.timeouts.claim_secs = 1;
+        config.jito.rpc_url = Some(jito_url);
+        let statuses = r#"[{"slot":42,"confirmations":0,"err":null,"confirmationStatus":"confirmed"}]"#;
+        config.campaign.solana_rpc_url = serve_rpc("getSignatureStatuses", statuses.to_string()).await;
+        let app = app_routes(AppState { config: Arc::new(config), ..state });
+
+        let mut signed = vec![1u8];
+        signed.extend([3u8; 64]);
+        signed.extend([0u8; 8]);
+        let mut body = claim_body(WALLET);
+        body["signed_transaction"] = STANDARD.encode(signed).into();
+        let key = Uuid::new_v4().to_string();
+        let claim = || {
+            Request::post(format!("/api/v1/campaigns/{ADDRESS}/claim?submit_method=jito"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(IDEMPOTENCY_KEY_HEADER, &key)
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let (status, _) = send(&app, claim()).await;
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+        let (status, body) = send(&app, claim()).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["errors"]["details"], "processing");
+
+        // The claim kept running: the retry replays its response once it lands
+        let mut replay = send(&app, claim()).await;
+        for _ in 0..50 {
+            if replay.0 != StatusCode::CONFLICT {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            replay = send(&app, claim()).await;
+        }
+        assert_eq!(replay.0, StatusCode::OK, "{}", replay.1);
+        assert_eq!(replay.1["data"]["bundle_id"], "bundle-1");
+
+        let claimed = sqlx::query_scalar!(
+            "SELECT claimed FROM recipients WHERE campaign_address = $1 AND wallet = $2",
+            ADDRESS,
+            WALLET
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(claimed);
+    }
+
+    #[sqlx::test]
+    async fn test_stale_campaign_info_is_served_while_db_is_down(pool: PgPool) {
+        use crate::models::CampaignStore;
+        use sqlx::postgres::PgPoolOptions;
+        use tower::ServiceExt;
+
+        let state = test_state(pool);
+        seed_campaign(&state).await;
+        let get = |address: &str| {
+            Request::get(format!("/api/v1/campaigns/{address}"))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app_routes(state.clone()).oneshot(get(ADDRESS)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key(CACHE_WARNING_HEADER));
+
+        // Same state, but every query now fails to connect
+        let unreachable = PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(200))
+            .connect_lazy("postgres://127.0.0.1:1/offline")
+            .unwrap();
+        let mut offline = state;
+        offline.campaign_store = CampaignStore::new(unreachable, 0);
+        let app = app_routes(offline);
+
+        let response = app.clone().oneshot(get(ADDRESS)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CACHE_WARNING_HEADER], "stale");
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["data"]["address"], ADDRESS);
+
+        // Nothing cached to fall back on
+        let (status, _) = send(&app, get("never-read")).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[sqlx::test]
+    async fn test_activate_only_moves_drafts(pool: PgPool) {
+        let state = test_state(pool);
+        seed_campaign_with(&state, &["first"]).await;
+        let app = app_routes(state.clone());
+
+        let (status, _) = send(&app, admin_request("activate", "")).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let request = Request::post(format!("/api/v1/campaigns/{ADDRESS}/clone"))
+            .header(header::AUTHORIZATION, format!("Bearer {TEST_ADMIN_KEY}"))
+            .body(Body::empty())
+            .unwrap();
+        let (_, body) = send(&app, request).await;
+        let clone_address = body["data"]["address"].as_str().unwrap();
+
+        let request = Request::patch(format!("/api/v1/campaigns/{clone_address}/activate"))
+            .header(header::AUTHORIZATION, format!("Bearer {TEST_ADMIN_KEY}"))
+            .body(Body::empty())
+            .unwrap();
+        let (status, body) = send(&app, request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["status"], CAMPAIGN_STATUS_ACTIVE);
+
+        // Activation committed the clone to its recipients' tree, the one proofs use
+        let committed = state.campaign_store.get(clone_address).await.unwrap();
+        assert_ne!(committed.merkle_root, "00".repeat(32));
+        let commitment = state.campaign_store.get_commitment(clone_address).await.unwrap().unwrap();
+        assert_eq!(hex::encode(commitment.root), committed.merkle_root);
+        assert_eq!(commitment.leaf_count, 1);
+
+        let request = Request::post(format!("/api/v1/proofs/{clone_address}/generate"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"wallet":"first"}"#))
+            .unwrap();
+        let (status, proof) = send(&app, request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(proof["data"]["merkle_root"], committed.merkle_root.as_str());
+    }
+
+    #[sqlx::test]
+    async fn test_recipients_are_appended_to_drafts_only(pool: PgPool) {
+        let existing = bs58::encode([1u8; 32]).into_string();
+        let added = bs58::encode([2u8; 32]).into_string();
+        let state = test_state(pool.clone());
+        seed_campaign_with(&state, &[&existing]).await;
+        let app = app_routes(state);
+        let put = || {
+            Request::put(format!("/api/v1/campaigns/{ADDRESS}/recipients"))
+                .header(header::AUTHORIZATION, format!("Bearer {TEST_ADMIN_KEY}"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "recipients": [
+                            { "wallet": added, "amount": "250" },
+                            { "wallet": existing, "amount": "100" },
+                            { "wallet": "not-a-wallet", "amount": "100" },
+                            { "wallet": added, "amount": "250" },
+                        ],
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+
+        let (status, _) = send(&app, put()).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        sqlx::query!("UPDATE campaigns SET status = 'draft' WHERE address = $1", ADDRESS)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let (status, body) = send(&app, put()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["added"], 1);
+        let errors: Vec<&str> = body["data"]["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["error"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "wallet must be a Base58 wallet address",
+                "wallet is listed more than once",
+                "wallet is already a recipient",
+            ]
+        );
+
+        let campaign = sqlx::query!(
+            "SELECT total_amount, merkle_root FROM campaigns WHERE address = $1",
+            ADDRESS
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(campaign.total_amount, "350");
+        assert_eq!(campaign.merkle_root, "00".repeat(32));
+        let leaf_index = sqlx::query_scalar!(
+            "SELECT leaf_index FROM recipients WHERE campaign_address = $1 AND wallet = $2",
+            ADDRESS,
+            added
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(leaf_index, 1);
+
+        // Two recipients, but the last leaf index is taken: nothing fits after it
+        sqlx::query!(
+            "UPDATE recipients SET leaf_index = $2 WHERE campaign_address = $1 AND wallet = $3",
+            ADDRESS,
+            MAX_LEAVES as i32 - 1,
+            added
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let request = Request::put(format!("/api/v1/campaigns/{ADDRESS}/recipients"))
+            .header(header::AUTHORIZATION, format!("Bearer {TEST_ADMIN_KEY}"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                serde_json::json!({ "recipients": [{ "wallet": bs58::encode([3u8; 32]).into_string(), "amount": "1" }] })
+                    .to_string(),
+            ))
+            .unwrap();
+        let (status, _) = send(&app, request).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[sqlx::test]
+    async fn test_removed_recipient_leaves_the_next_tree(pool: PgPool) {
+        use crate::common::merkle::verify_proof;
+
+        let state = test_state(pool.clone());
+        seed_campaign_with(&state, &["first", "second", "third"]).await;
+        let app = app_routes(state.clone());
+        let remove = |wallet: &str| {
+            Request::delete(format!(
+                "/api/v1/campaigns/{ADDRESS}/recipients/{wallet}?reason=sanctioned"
+            ))
+            .header(header::AUTHORIZATION, format!("Bearer {TEST_ADMIN_KEY}"))
+            .body(Body::empty())
+            .unwrap()
+        };
+
+        // The root of an active campaign is committed
+        let (status, _) = send(&app, remove("second")).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        sqlx::query!("UPDATE campaigns SET status = 'draft' WHERE address = $1", ADDRESS)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let (status, body) = send(&app, remove("second")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["amount"], "100");
+        let (status, _) = send(&app, remove("second")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let campaign = state.campaign_store.get(ADDRESS).await.unwrap();
+        assert_eq!(campaign.total_amount, "0");
+        let tree = campaign_tree(&state, &campaign).await.unwrap();
+        assert!(tree.get_leaf_index("second").is_none());
+        // The gap stays empty: the next recipient keeps its index
+        let proof = tree.get_proof("third").unwrap();
+        assert_eq!(proof.leaf_index, 2);
+        assert!(verify_proof(&tree.root(), &proof));
+
+        // Leaf indices are not shifted to close the gap
+        let leaf_indices = sqlx::query_scalar!(
+            "SELECT leaf_index FROM recipients WHERE campaign_address = $1 ORDER BY leaf_index",
+            ADDRESS
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(leaf_indices, [0, 2]);
+
+        let entry = sqlx::query!(
+            "SELECT details, payload FROM audit_log WHERE action = 'recipient_removed'"
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(entry.details.as_deref(), Some("sanctioned"));
+        assert_eq!(entry.payload["wallet"], "second");
+    }
+
+    #[sqlx::test]
+    async fn test_amount_updates_are_atomic(pool: PgPool) {
+        let state = test_state(pool.clone());
+        seed_campaign_with(&state, &["first", "second"]).await;
+        sqlx::query!("UPDATE campaigns SET status = 'draft' WHERE address = $1", ADDRESS)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let app = app_routes(state.clone());
+        let patch = |path: &str, body: serde_json::Value| {
+            Request::patch(format!("/api/v1/campaigns/{ADDRESS}/recipients{path}"))
+                .header(header::AUTHORIZATION, format!("Bearer {TEST_ADMIN_KEY}"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let (status, body) = send(&app, patch("/first", serde_json::json!({ "amount": "250" }))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["old_amount"], "100");
+        assert_eq!(body["data"]["new_amount"], "250");
+
+        // One unknown wallet rolls back the whole batch
+        let batch = serde_json::json!([
+            { "wallet": "second", "amount": "300" },
+            { "wallet": "stranger", "amount": "300" },
+        ]);
+        let (status, _) = send(&app, patch("", batch)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let balance = state.campaign_store.recipient_balance(ADDRESS, "second").await.unwrap();
+        assert_eq!(balance.allocation, 100);
+
+        let batch = serde_json::json!([
+            { "wallet": "first", "amount": "50" },
+            { "wallet": "second", "amount": "300" },
+        ]);
+        let (status, body) = send(&app, patch("", batch)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"][0]["old_amount"], "250");
+        assert_eq!(body["data"][1]["new_amount"], "300");
+        let campaign = state.campaign_store.get(ADDRESS).await.unwrap();
+        assert_eq!(campaign.total_amount, "250");
+
+        let (status, _) = send(&app, patch("/first", serde_json::json!({ "amount": "0" }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = send(&app, admin_request("activate", "")).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&app, patch("/first", serde_json::json!({ "amount": "10" }))).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+
+    #[sqlx::test]
+    async fn test_computed_proofs_share_one_cached_tree(pool: PgPool) {
+        use crate::routes::proofs::invalidate_campaign_proofs;
+
+        let state = test_state(pool);
+        seed_campaign_with(&state, &["first", "second"]).await;
+        let app = app_routes(state.clone());
+        let generate = |wallet: &str| {
+            Request::post(format!("/api/v1/proofs/{ADDRESS}/generate"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::json!({ "wallet": wallet }).to_string()))
+                .unwrap()
+        };
+        let tree_stats = || {
+            Request::get("/api/v1/cache/tree-stats")
+                .header(header::AUTHORIZATION, format!("Bearer {TEST_ADMIN_KEY}"))
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let (_, first) = send(&app, generate("first")).await;
+        let (_, second) = send(&app, generate("second")).await;
+        assert_eq!(first["data"]["merkle_root"], second["data"]["merkle_root"]);
+        assert_ne!(first["data"]["secret"], second["data"]["secret"]);
+        let (status, stats) = send(&app, tree_stats()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(stats["data"], serde_json::json!({ "size": 1, "capacity": 10, "hit_rate": 0.5 }));
+
+        // The snapshot outlives the cached tree, so proofs keep their root
+        let mut restarted = state.clone();
+        restarted.tree_cache = crate::tree_cache::MerkleTreeCache::new(10);
+        restarted.proof_cache = Default::default();
+        let (_, again) = send(&app_routes(restarted), generate("first")).await;
+        assert_eq!(again["data"], first["data"]);
+
+        // Changed recipients get a fresh tree
+        invalidate_campaign_proofs(&state, ADDRESS).await.unwrap();
+        let (_, stats) = send(&app, tree_stats()).await;
+        assert_eq!(stats["data"]["size"], 0);
+        let (_, rebuilt) = send(&app, generate("first")).await;
+        assert_ne!(rebuilt["data"]["merkle_root"], first["data"]["merkle_root"]);
+    }
+
+    #[sqlx::test]
+    async fn test_pregenerated_proofs_are_served_from_cache(pool: PgPool) {
+        use crate::routes::proofs::{invalidate_campaign_proofs, pregenerate_proofs};
+
+        let state = test_state(pool.clone());
+        seed_campaign_with(&state, &["first", "second"]).await;
+        let app = app_routes(state.clone());
+        let generate = || {
+            Request::post(format!("/api/v1/proofs/{ADDRESS}/generate"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::json!({ "wallet": "second" }).to_string()))
+                .unwrap()
+        };
+        let cached_count = || {
+            sqlx::query_scalar!(
+                r#"SELECT COUNT(*) AS "count!" FROM merkle_proofs WHERE campaign_address = $1"#,
+                ADDRESS
+            )
+            .fetch_one(&pool)
+        };
+
+        // A proof computed on request is cached for the next one
+        let (_, computed) = send(&app, generate()).await;
+        let (_, cached) = send(&app, generate()).await;
+        assert_eq!(computed["data"], cached["data"]);
+        assert_eq!(state.proof_cache.stats().hits, 1);
+
+        // Pre-generated proofs come from the campaign's tree, like computed ones
+        send(&app, plain_claim_request("first")).await;
+        assert_eq!(pregenerate_proofs(&state, ADDRESS).await.unwrap(), 1);
+        let (status, first) = send(&app, generate()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(first["data"], computed["data"]);
+        assert_eq!(first["data"]["leaf_index"], 1);
+
+        // Regenerating after the tree changed replaces the previous proofs and the cache
+        invalidate_campaign_proofs(&state, ADDRESS).await.unwrap();
+        pregenerate_proofs(&state, ADDRESS).await.unwrap();
+        assert_eq!(cached_count().await.unwrap(), 1);
+        let (_, second) = send(&app, generate()).await;
+        assert_ne!(first["data"]["secret"], second["data"]["secret"]);
+    }
+
+    #[sqlx::test]
+    async fn test_creation_is_content_addressed_by_root(pool: PgPool) {
+        let state = test_state(pool);
+        let store = state.campaign_store.clone();
+        let app = app_routes(state);
+        let root = "ab".repeat(32);
+        let create = |address: &str, query: &str| {
+            Request::post(format!("/api/v1/campaigns{query}"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    serde_json::json!({
+                        "address": address,
+                        "name": "Test",
+                        "merkle_root": root,
+                        "total_amount": "100",
+                        "creator_wallet": "creator",
+                        "recipients": [{ "wallet": WALLET, "amount": "100" }],
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+        let expected_id = campaign_id_from_root(&[0xab; 32]).to_string();
+        assert_eq!(&expected_id[14..15], "5");
+
+        let (status, first) = send(&app, create("first", "")).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(first["data"]["id"], expected_id);
+        let by_id = Request::get(format!("/api/v1/campaigns/id/{expected_id}")).body(Body::empty()).unwrap();
+        let (status, found) = send(&app, by_id).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(found["data"]["address"], "first");
+        let commitment = store.get_commitment("first").await.unwrap().unwrap();
+        assert_eq!((commitment.root, commitment.depth, commitment.leaf_count), ([0xab; 32], 8, 1));
+
+        // Same recipient list: the existing campaign comes back
+        let (status, again) = send(&app, create("second", "")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(again["data"]["address"], "first");
+
+        let (status, forced) = send(&app, create("second", "?force_new=true")).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_ne!(forced["data"]["id"], expected_id);
+    }
+
+    #[sqlx::test]
+    async fn test_pause_requires_admin_key(pool: PgPool) {
+        let state = test_state(pool);
+        seed_campaign(&state).await;
+        let app = app_routes(state);
+
+        let request = Request::patch(format!("/api/v1/campaigns/{ADDRESS}/pause"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::AUTHORIZATION, "Bearer wrong-key")
+            .body(Body::from(r#"{"reason":"test"}"#))
+            .unwrap();
+        let (status, _) = send(&app, request).await;
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[sqlx::test]
+    async fn test_claim_body_is_schema_validated(pool: PgPool) {
+        let app = app_routes(test_state(pool));
+
+        let request = Request::post(format!("/api/v1/campaigns/{ADDRESS}/claim"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"wallet":42}"#))
+            .unwrap();
+        let (status, body) = send(&app, request).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["errors"]["validation"][0]["path"], "/wallet");
+        assert_eq!(
+            body["errors"]["validation"][0]["message"],
+            "expected string, found number"
+        );
+    }
+
+    #[sqlx::test]
+    async fn test_claim_rejects_malformed_idempotency_key(pool: PgPool) {
+        let state = test_state(pool);
+        seed_campaign(&state).await;
+        let app = app_routes(state);
+
+        let (status, body) = send(&app, claim_request("not-a-uuid")).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["success"], false);
+    }
+
+    #[sqlx::test]
+    async fn test_list_campaigns_filters_and_pages(pool: PgPool) {
+        use tower::ServiceExt;
+
+        let state = test_state(pool);
+        for (i, (creator, status, day)) in [
+            ("alice", CAMPAIGN_STATUS_ACTIVE, "2025-01-05"),
+            ("alice", CAMPAIGN_STATUS_ACTIVE, "2025-01-20"),
+            ("alice", CAMPAIGN_STATUS_PAUSED, "2025-01-25"),
+            ("bob", CAMPAIGN_STATUS_ACTIVE, "2025-02-10"),
+            ("bob", CAMPAIGN_STATUS_DRAFT, "2024-12-31"),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            state
+                .campaign_store
+                .create(Campaign {
+                    id: Uuid::new_v4().to_string(),
+                    address: format!("campaign-{i}"),
+                    name: format!("Campaign {i}"),
+                    merkle_root: format!("{i:02}").repeat(32),
+                    total_amount: "100".to_string(),
+                    creator_wallet: creator.to_string(),
+                    tx_signature: None,
+                    vault_address: None,
+                    created_at: format!("{day}T12:00:00Z").parse().unwrap(),
+                    recipients: vec![Recipient {
+                        id: None,
+                        wallet: WALLET.to_string(),
+                        amount: "100".to_string(),
+                        claimed: false,
+                        claimed_at: None,
+                        leaf_index: None,
+                    }],
+                    airdrop_type: "instant".to_string(),
+                    vesting_start: 0,
+                    vesting_cliff_seconds: 0,
+                    vesting_duration_seconds: 0,
+                    token_mint: None,
+                    token_symbol: None,
+                    token_decimals: None,
+                    status: status.to_string(),
+                    pause_reason: None,
+                    close_at: None,
+                    referral_bonus_bps: 500,
+                    max_claims: None,
+                })
+                .await;
+        }
+        let app = app_routes(state);
+        let list = |query: &str| {
+            let app = app.clone();
+            let request = Request::get(format!("/api/v1/campaigns?{query}")).body(Body::empty()).unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let total = response.headers().get(TOTAL_COUNT_HEADER).map(|v| v.to_str().unwrap().to_string());
+                let body: serde_json::Value =
+                    serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+                let addresses: Vec<String> = body["data"]
+                    .as_array()
+                    .map(|campaigns| campaigns.iter().map(|c| c["address"].as_str().unwrap().to_string()).collect())
+                    .unwrap_or_default();
+                (status, total, addresses)
+            }
+        };
+
+        // Newest first by default
+        let (status, total, addresses) = list("").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(total.as_deref(), Some("5"));
+        assert_eq!(addresses, ["campaign-3", "campaign-2", "campaign-1", "campaign-0", "campaign-4"]);
+
+        let (_, total, addresses) =
+            list("status=active&creator=alice&open_after=2025-01-01&open_before=2025-02-01&order=asc").await;
+        assert_eq!(total.as_deref(), Some("2"));
+        assert_eq!(addresses, ["campaign-0", "campaign-1"]);
+
+        // The total counts every page
+        let (_, total, addresses) = list("sort_by=name&order=asc&page=2&per_page=2").await;
+        assert_eq!(total.as_deref(), Some("5"));
+        assert_eq!(addresses, ["campaign-2", "campaign-3"]);
+        let (_, _, addresses) = list("page=9").await;
+        assert!(addresses.is_empty());
+
+        for invalid in [
+            "per_page=101",
+            "per_page=0",
+            "page=0",
+            "open_after=2025-02-01&open_before=2025-01-01",
+            "status=closed",
+        ] {
+            let (status, total, _) = list(invalid).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{invalid}");
+            assert_eq!(total, None);
+        }
+    }
 }
@@ -0,0 +1,66 @@
+//! API documentation routes
+
+use axum::{
+    Router,
+    http::header,
+    response::{Html, IntoResponse},
+    routing::get,
+};
+
+use crate::state::AppState;
+
+/// OpenAPI 3.0 description of every route under `/api/v1`
+pub const OPENAPI_SPEC: &str = include_str!("../../docs/openapi.json");
+
+const SWAGGER_UI: &str = include_str!("../../docs/swagger.html");
+
+/// Build documentation routes
+pub fn docs_routes() -> Router<AppState> {
+    Router::new()
+        .route("/openapi.json", get(openapi_spec))
+        .route("/docs", get(swagger_ui))
+}
+
+/// GET /openapi.json - OpenAPI spec
+async fn openapi_spec() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "application/json")], OPENAPI_SPEC)
+}
+
+/// GET /docs - Swagger UI
+async fn swagger_ui() -> Html<&'static str> {
+    Html(SWAGGER_UI)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_spec_is_valid_openapi_3() {
+        let spec: Value = serde_json::from_str(OPENAPI_SPEC).unwrap();
+
+        assert!(spec["openapi"].as_str().unwrap().starts_with("3.0"));
+        assert!(spec["info"]["title"].is_string());
+
+        // Every $ref must resolve to a declared component
+        fn check_refs(node: &Value, spec: &Value) {
+            match node {
+                Value::Object(map) => {
+                    if let Some(Value::String(target)) = map.get("$ref") {
+                        let pointer = target.trim_start_matches('#');
+                        assert!(spec.pointer(pointer).is_some(), "dangling $ref {}", target);
+                    }
+                    map.values().for_each(|v| check_refs(v, spec));
+                }
+                Value::Array(items) => items.iter().for_each(|v| check_refs(v, spec)),
+                _ => {}
+            }
+        }
+        check_refs(&spec, &spec);
+
+        for path in ["/api/v1/campaigns/{address}/claim", "/api/v1/hash/poseidon"] {
+            assert!(spec["paths"][path].is_object(), "missing {}", path);
+        }
+    }
+}
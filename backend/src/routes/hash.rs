@@ -1,41 +1,106 @@
-use axum::{routing::post, Json, Router};
+use axum::{extract::{Query, State}, middleware, routing::{get, post}, Json, Router};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use crate::state::AppState;
-use crate::common::response::ApiErrorResponse;
-use axum::http::StatusCode;
-use ark_bn254::Fr;
-use ark_ff::{PrimeField, BigInteger};
-use taceo_poseidon2::bn254::t4 as poseidon2;
+use crate::common::poseidon::{
+    poseidon2_bn254_t3_hash_fields, poseidon2_bn254_t4_hash_fields, poseidon2_bn254_hash4,
+    poseidon_hash_n,
+};
+use crate::common::error::{CryptoError, ShadowDropError};
+use crate::common::field::{Fr, fr_from_be_bytes_checked, fr_to_be_bytes};
+use crate::common::poseidon_trace::poseidon2_t4_permutation_trace;
+use crate::common::schema::HASH_REQUEST_SCHEMA;
+use crate::metrics;
+use crate::middleware::{content_type::require_json, json_schema::validate_json_body};
 
 #[derive(Deserialize)]
 pub struct HashRequest {
-    pub inputs: Vec<String>,
+    /// Hex strings (0x-prefixed or not) or unsigned 64-bit integers
+    pub inputs: Vec<Value>,
 }
 
 #[derive(Serialize)]
 pub struct HashResponse {
+    /// Always hex, whatever encoding was requested
     pub hash: String,
+    /// The hash's 32 big-endian bytes as unpadded base64url, with `?encoding=base64url`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash_base64: Option<String>,
+    /// The hash as a decimal integer, with `?encoding=decimal`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash_decimal: Option<String>,
+}
+
+impl HashResponse {
+    /// Response carrying only the hex hash
+    fn hex(f: Fr) -> Self {
+        Self::encoded(f, HashEncoding::Hex)
+    }
+
+    /// Hex hash plus the alternate `encoding`, if any
+    fn encoded(f: Fr, encoding: HashEncoding) -> Self {
+        let bytes = fr_to_be_bytes(f);
+        Self {
+            hash: hex::encode(bytes),
+            hash_base64: (encoding == HashEncoding::Base64url).then(|| URL_SAFE_NO_PAD.encode(bytes)),
+            hash_decimal: (encoding == HashEncoding::Decimal).then(|| f.to_string()),
+        }
+    }
+}
+
+/// Output encodings of `POST /hash/poseidon`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashEncoding {
+    #[default]
+    Hex,
+    Base64url,
+    Decimal,
+}
+
+/// Query parameters of `POST /hash/poseidon`
+#[derive(Debug, Default, Deserialize)]
+pub struct HashQuery {
+    #[serde(default)]
+    pub encoding: HashEncoding,
 }
 
 /// Convert hex string (0x-prefixed or not) to Fr
-fn hex_to_fr(hex: &str) -> Result<Fr, String> {
+fn hex_to_fr(hex: &str) -> Result<Fr, CryptoError> {
     let clean = hex.strip_prefix("0x").unwrap_or(hex);
-    let bytes = hex::decode(clean).map_err(|e| format!("Invalid hex: {}", e))?;
-    // ark-bn254 Fr from Big Endian bytes (modulo order)
-    Ok(Fr::from_be_bytes_mod_order(&bytes))
+    let bytes = hex::decode(clean).map_err(|e| CryptoError::InvalidHex(e.to_string()))?;
+    // Big Endian, like Prover.toml; values at or above the modulus are rejected
+    Ok(fr_from_be_bytes_checked(&bytes)?)
 }
 
-fn field_to_hex(f: Fr) -> String {
-    let bytes = f.into_bigint().to_bytes_be();
-    let hex_str = hex::encode(bytes);
-    format!("{:0>64}", hex_str)
+/// Convert a hash input to Fr: a hex string as in `hex_to_fr`, or a JSON
+/// integer in `u64` range, e.g. an amount from JavaScript
+fn value_to_fr(v: &Value) -> Result<Fr, String> {
+    match v {
+        Value::String(hex) => hex_to_fr(hex).map_err(|e| e.to_string()),
+        Value::Number(n) => match n.as_u64() {
+            Some(n) => Ok(Fr::from(n)),
+            None if n.is_f64() => Err(format!("number {} is not an integer", n)),
+            None => Err(format!("number {} is not an unsigned 64-bit integer", n)),
+        },
+        Value::Bool(_) => Err("expected a hex string or number, found a boolean".to_string()),
+        Value::Null => Err("expected a hex string or number, found null".to_string()),
+        Value::Array(_) | Value::Object(_) => {
+            Err("expected a hex string or number, found a nested value".to_string())
+        }
+    }
 }
 
 /// Handler for Poseidon2 hashing (Noir compatible Sponge)
+///
+/// `?encoding=base64url|decimal` adds the hash in that encoding next to the hex one.
 pub async fn hash_poseidon(
+    Query(query): Query<HashQuery>,
     Json(payload): Json<HashRequest>,
-) -> Result<Json<HashResponse>, ApiErrorResponse> {
+) -> Result<Json<HashResponse>, ShadowDropError> {
     let inputs = payload.inputs;
+    check_inputs(&inputs, 3)?;
     
     // Validate input count for t4 sponge (state size 4)
     // t4 can handle: 2 inputs (leaves 1 capacity + iv) or 3 inputs (leaves iv).
@@ -43,38 +108,315 @@ pub async fn hash_poseidon(
     // 2 inputs: [a, b, 0, iv] where iv = 2 * 2^64
     // 3 inputs: [a, b, c, iv] where iv = 3 * 2^64
     if inputs.len() != 2 && inputs.len() != 3 {
-         return Err(ApiErrorResponse::default()
-            .with_code(StatusCode::BAD_REQUEST)
-            .with_message("Only 2 or 3 inputs supported for Noir Poseidon compatibility"));
-    }
-
-    let a = hex_to_fr(&inputs[0]).map_err(|e| ApiErrorResponse::default().with_message(&e))?;
-    let b = hex_to_fr(&inputs[1]).map_err(|e| ApiErrorResponse::default().with_message(&e))?;
-
-    // Noir compatible IV calculation
-    let two_pow_64 = Fr::from(18446744073709551616u128);
-    let iv_val = inputs.len() as u64; 
-    let iv = Fr::from(iv_val) * two_pow_64;
-
-    let result_fr = if inputs.len() == 2 {
-        // State: [a, b, 0, iv]
-        let mut state = [a, b, Fr::from(0u64), iv];
-        poseidon2::permutation_in_place(&mut state);
-        state[0]
-    } else {
-        // len == 3
-        let c = hex_to_fr(&inputs[2]).map_err(|e| ApiErrorResponse::default().with_message(&e))?;
-        // State: [a, b, c, iv]
-        let mut state = [a, b, c, iv];
-        poseidon2::permutation_in_place(&mut state);
-        state[0]
-    };
+         return Err(ShadowDropError::BadRequest(
+            "Only 2 or 3 inputs supported for Noir Poseidon compatibility".to_string(),
+        ));
+    }
+
+    let fields = parse_inputs(&inputs)?;
+    metrics::HASH_REQUESTS_TOTAL.inc(&[&fields.len().to_string()]);
+    let result_fr = poseidon2_bn254_t4_hash_fields(&fields).map_err(CryptoError::from)?;
+
+    Ok(Json(HashResponse::encoded(result_fr, query.encoding)))
+}
+
+/// Handler for Poseidon2 t3 hashing (state size 3, 1 or 2 inputs)
+pub async fn hash_poseidon_t3(
+    Json(payload): Json<HashRequest>,
+) -> Result<Json<HashResponse>, ShadowDropError> {
+    check_inputs(&payload.inputs, 2)?;
+    let fields = parse_inputs(&payload.inputs)?;
+    metrics::HASH_REQUESTS_TOTAL.inc(&[&fields.len().to_string()]);
+    let result_fr = poseidon2_bn254_t3_hash_fields(&fields).map_err(CryptoError::from)?;
+
+    Ok(Json(HashResponse::hex(result_fr)))
+}
+
+/// Handler for 4-input Poseidon2 hashing (state size 8, a single permutation)
+pub async fn hash_poseidon_t8(
+    Json(payload): Json<HashRequest>,
+) -> Result<Json<HashResponse>, ShadowDropError> {
+    check_inputs(&payload.inputs, 4)?;
+    let fields = parse_inputs(&payload.inputs)?;
+    metrics::HASH_REQUESTS_TOTAL.inc(&[&fields.len().to_string()]);
+    let result_fr = poseidon2_bn254_hash4(&fields).map_err(CryptoError::from)?;
 
-    Ok(Json(HashResponse { 
-        hash: field_to_hex(result_fr) 
+    Ok(Json(HashResponse::hex(result_fr)))
+}
+
+/// Handler for Poseidon2 sponge hashing of any number of inputs
+pub async fn hash_poseidon_sponge(
+    State(state): State<AppState>,
+    Json(payload): Json<HashRequest>,
+) -> Result<Json<HashResponse>, ShadowDropError> {
+    let max_inputs = state.config.hash.max_sponge_inputs;
+    check_inputs(&payload.inputs, max_inputs)?;
+    if payload.inputs.len() > max_inputs {
+        return Err(ShadowDropError::BadRequest(format!(
+            "At most {} inputs supported",
+            max_inputs
+        )));
+    }
+
+    let fields = parse_inputs(&payload.inputs)?;
+    metrics::HASH_REQUESTS_TOTAL.inc(&[&fields.len().to_string()]);
+    let result_fr = poseidon_hash_n(&fields).map_err(CryptoError::from)?;
+
+    Ok(Json(HashResponse::hex(result_fr)))
+}
+
+/// Query parameters of `GET /debug/hash-trace`
+#[derive(Debug, Deserialize)]
+pub struct HashTraceQuery {
+    /// The four state elements as comma-separated hex
+    pub state: String,
+}
+
+#[derive(Serialize)]
+pub struct HashTraceResponse {
+    /// Hex state after the initial linear layer and after each round, as
+    /// `poseidon2_t4_permutation_trace` records them
+    pub states_per_round: Vec<[String; 4]>,
+}
+
+/// Handler for the Poseidon2 t4 permutation trace of `?state=a,b,c,d`, to
+/// find the round at which a circuit's permutation diverges
+pub async fn hash_trace(Query(query): Query<HashTraceQuery>) -> Result<Json<HashTraceResponse>, ShadowDropError> {
+    let elements = query
+        .state
+        .split(',')
+        .enumerate()
+        .map(|(i, element)| {
+            hex_to_fr(element.trim())
+                .map_err(|e| ShadowDropError::BadRequest(format!("state element {}: {}", i, e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let state: [Fr; 4] = elements.try_into().map_err(|elements: Vec<Fr>| {
+        ShadowDropError::BadRequest(format!("state must have 4 elements, got {}", elements.len()))
+    })?;
+
+    let trace = poseidon2_t4_permutation_trace(&state);
+    Ok(Json(HashTraceResponse {
+        states_per_round: trace
+            .states_per_round
+            .iter()
+            .map(|state| state.map(|f| hex::encode(fr_to_be_bytes(f))))
+            .collect(),
     }))
 }
 
+/// Reject an empty input list, reporting the route's accepted length range,
+/// and empty strings, which would otherwise fail as malformed hex
+fn check_inputs(inputs: &[Value], max_inputs: usize) -> Result<(), ShadowDropError> {
+    if inputs.is_empty() {
+        return Err(ShadowDropError::InvalidRequest {
+            message: "inputs array must not be empty".to_string(),
+            details: Some(format!("min_length: 1, max_length: {}", max_inputs)),
+        });
+    }
+    if let Some(index) = inputs.iter().position(|input| input.as_str() == Some("")) {
+        return Err(ShadowDropError::InvalidRequest {
+            message: format!("input at index {} is an empty string", index),
+            details: None,
+        });
+    }
+    Ok(())
+}
+
+fn parse_inputs(inputs: &[Value]) -> Result<Vec<Fr>, ShadowDropError> {
+    inputs
+        .iter()
+        .enumerate()
+        .map(|(i, input)| {
+            value_to_fr(input)
+                .map_err(|e| ShadowDropError::BadRequest(format!("input at index {}: {}", i, e)))
+        })
+        .collect()
+}
+
 pub fn hash_routes() -> Router<AppState> {
-    Router::new().route("/poseidon", post(hash_poseidon))
+    Router::new()
+        .route("/poseidon", post(hash_poseidon))
+        .route("/poseidon/t3", post(hash_poseidon_t3))
+        .route("/poseidon/t8", post(hash_poseidon_t8))
+        .route("/poseidon/sponge", post(hash_poseidon_sponge))
+        .route_layer(middleware::from_fn_with_state(
+            &*HASH_REQUEST_SCHEMA,
+            validate_json_body,
+        ))
+        .route_layer(middleware::from_fn(require_json))
+}
+
+/// Development-only routes exposing hashing internals
+pub fn debug_routes() -> Router<AppState> {
+    Router::new().route("/hash-trace", get(hash_trace))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::{
+        app_routes,
+        test_helpers::{send, test_state},
+    };
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    #[test]
+    fn test_value_to_fr_accepts_hex_strings_and_integers() {
+        assert_eq!(value_to_fr(&json!("0x2a")).unwrap(), Fr::from(42u64));
+        assert_eq!(value_to_fr(&json!("2a")).unwrap(), Fr::from(42u64));
+        assert_eq!(value_to_fr(&json!(42)).unwrap(), Fr::from(42u64));
+        assert_eq!(value_to_fr(&json!(u64::MAX)).unwrap(), Fr::from(u64::MAX));
+    }
+
+    #[test]
+    fn test_value_to_fr_rejects_other_values() {
+        assert!(value_to_fr(&json!("zz")).unwrap_err().starts_with("Invalid hex"));
+        assert_eq!(value_to_fr(&json!(1.5)).unwrap_err(), "number 1.5 is not an integer");
+        assert_eq!(
+            value_to_fr(&json!(-1)).unwrap_err(),
+            "number -1 is not an unsigned 64-bit integer"
+        );
+        assert!(value_to_fr(&json!(true)).unwrap_err().contains("boolean"));
+        assert!(value_to_fr(&json!(null)).unwrap_err().contains("null"));
+        assert!(value_to_fr(&json!([1])).unwrap_err().contains("nested"));
+    }
+
+    fn hash(inputs: Value) -> Request<Body> {
+        Request::post("/api/v1/hash/poseidon")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({ "inputs": inputs }).to_string()))
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_numeric_inputs_hash_like_their_hex_form(pool: PgPool) {
+        let app = app_routes(test_state(pool));
+
+        let (status, numeric) = send(&app, hash(json!([1_000_000_000u64, "0x07"]))).await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, hex) = send(&app, hash(json!(["0x3b9aca00", "0x07"]))).await;
+        assert_eq!(numeric["hash"], hex["hash"]);
+
+        let (status, body) = send(&app, hash(json!([1.5, "0x07"]))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["errors"]["validation"][0]["path"], "/inputs/0");
+    }
+
+    #[sqlx::test]
+    async fn test_empty_inputs_are_rejected_with_limits(pool: PgPool) {
+        let app = app_routes(test_state(pool));
+
+        let (status, body) = send(&app, hash(json!([]))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["errors"]["message"], "inputs array must not be empty");
+        assert_eq!(body["errors"]["details"], "min_length: 1, max_length: 3");
+
+        let (status, body) = send(&app, hash(json!(["0x01", ""]))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["errors"]["message"], "input at index 1 is an empty string");
+    }
+
+    #[sqlx::test]
+    async fn test_non_json_bodies_are_unsupported(pool: PgPool) {
+        let app = app_routes(test_state(pool));
+        let body = json!({ "inputs": ["0x01", "0x02"] }).to_string();
+
+        let plain = Request::post("/api/v1/hash/poseidon")
+            .header("content-type", "text/plain")
+            .body(Body::from(body.clone()))
+            .unwrap();
+        assert_eq!(send(&app, plain).await.0, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let missing = Request::post("/api/v1/hash/poseidon").body(Body::from(body.clone())).unwrap();
+        assert_eq!(send(&app, missing).await.0, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let charset = Request::post("/api/v1/hash/poseidon")
+            .header("content-type", "application/json; charset=utf-8")
+            .body(Body::from(body))
+            .unwrap();
+        assert_eq!(send(&app, charset).await.0, StatusCode::OK);
+    }
+
+    fn hash_encoded(encoding: &str) -> Request<Body> {
+        Request::post(format!("/api/v1/hash/poseidon?encoding={encoding}"))
+            .header("content-type", "application/json")
+            .body(Body::from(json!({ "inputs": ["0x01", "0x02"] }).to_string()))
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_alternate_encodings_round_trip_to_the_hex_hash(pool: PgPool) {
+        use ark_ff::PrimeField;
+        use std::str::FromStr;
+
+        let app = app_routes(test_state(pool));
+        let (_, hex) = send(&app, hash_encoded("hex")).await;
+        let bytes = hex::decode(hex["hash"].as_str().unwrap()).unwrap();
+        assert!(hex.get("hash_base64").is_none() && hex.get("hash_decimal").is_none());
+
+        let (_, base64) = send(&app, hash_encoded("base64url")).await;
+        assert_eq!(base64["hash"], hex["hash"]);
+        let decoded = URL_SAFE_NO_PAD.decode(base64["hash_base64"].as_str().unwrap()).unwrap();
+        assert_eq!(decoded, bytes);
+
+        let (_, decimal) = send(&app, hash_encoded("decimal")).await;
+        assert_eq!(decimal["hash"], hex["hash"]);
+        let value = Fr::from_str(decimal["hash_decimal"].as_str().unwrap()).unwrap();
+        assert_eq!(value, Fr::from_be_bytes_mod_order(&bytes));
+
+        let (status, _) = send(&app, hash_encoded("base58")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+    #[sqlx::test]
+    async fn test_t8_hashes_exactly_four_inputs(pool: PgPool) {
+        let app = app_routes(test_state(pool));
+        let t8 = |inputs: Value| {
+            Request::post("/api/v1/hash/poseidon/t8")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "inputs": inputs }).to_string()))
+                .unwrap()
+        };
+
+        let (status, body) = send(&app, t8(json!([1, 2, 3, 4]))).await;
+        assert_eq!(status, StatusCode::OK);
+        let expected = poseidon2_bn254_hash4(&[1u64, 2, 3, 4].map(Fr::from)).unwrap();
+        assert_eq!(body["hash"], hex::encode(fr_to_be_bytes(expected)));
+
+        assert_eq!(send(&app, t8(json!([1, 2, 3]))).await.0, StatusCode::BAD_REQUEST);
+        let old = Request::post("/api/v1/hash/poseidon/t5")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({ "inputs": [1, 2, 3, 4] }).to_string()))
+            .unwrap();
+        assert_eq!(send(&app, old).await.0, StatusCode::NOT_FOUND);
+    }
+
+    #[sqlx::test]
+    async fn test_hash_trace_is_served_outside_production_only(pool: PgPool) {
+        let state = test_state(pool);
+        let app = app_routes(state.clone());
+        let trace = |query: &str| Request::get(format!("/api/v1/debug/hash-trace?state={query}")).body(Body::empty()).unwrap();
+
+        let (status, body) = send(&app, trace("0x00,0x01,0x02,0x03")).await;
+        assert_eq!(status, StatusCode::OK);
+        let states = body["states_per_round"].as_array().unwrap();
+        assert_eq!(states.len(), 65);
+        let expected = poseidon2_t4_permutation_trace(&[0u64, 1, 2, 3].map(Fr::from));
+        let output = expected.states_per_round[64].map(|f| hex::encode(fr_to_be_bytes(f)));
+        assert_eq!(states[64], json!(output));
+
+        let (status, _) = send(&app, trace("0x00,0x01,0x02")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let mut config = (*state.config).clone();
+        config.is_production = true;
+        let production = app_routes(AppState { config: std::sync::Arc::new(config), ..state });
+        let (status, _) = send(&production, trace("0x00,0x01,0x02,0x03")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
 }
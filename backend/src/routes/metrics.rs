@@ -0,0 +1,51 @@
+//! Prometheus scrape endpoint
+
+use axum::{Router, http::header, response::IntoResponse, routing::get};
+
+use crate::{metrics, state::AppState};
+
+/// Build the metrics route, outside `/api/v1` and without admin auth so
+/// scrapers need no credentials
+pub fn metrics_routes() -> Router<AppState> {
+    Router::new().route("/metrics", get(get_metrics))
+}
+
+/// GET /metrics - Every metric in the Prometheus text format
+async fn get_metrics() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, metrics::CONTENT_TYPE)], metrics::render())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::routes::{app_routes, test_helpers::test_state};
+    use axum::{
+        body::{Body, to_bytes},
+        http::{Request, StatusCode, header},
+    };
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    #[sqlx::test]
+    async fn test_metrics_are_exposed_without_auth(pool: PgPool) {
+        let app = app_routes(test_state(pool));
+        let hash = Request::post("/api/v1/hash/poseidon")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"inputs":["01","02"]}"#))
+            .unwrap();
+        assert_eq!(app.clone().oneshot(hash).await.unwrap().status(), StatusCode::OK);
+
+        let response = app
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/plain; version=0.0.4"
+        );
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("# TYPE shadow_drop_claims_total counter"));
+        assert!(body.contains("shadow_drop_hash_requests_total{inputs=\"2\"}"));
+    }
+}
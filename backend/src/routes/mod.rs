@@ -1,22 +1,64 @@
-use axum::Router;
+use std::time::Duration;
 
-use crate::state::AppState;
+use axum::{Router, middleware};
 
+use crate::{
+    middleware::{
+        compression::compress_response,
+        in_flight::track_in_flight,
+        timeout::{request_timeout, response_time},
+    },
+    state::AppState,
+};
+
+mod auth;
+mod cache;
 mod campaigns;
+mod docs;
+mod metrics;
+mod nullifiers;
+mod proof_jobs;
 mod proofs;
+mod recipients;
+mod webhooks;
 mod zk_proofs;
 mod hash;
 
+pub use campaigns::TOTAL_COUNT_HEADER;
+pub use proof_jobs::spawn_proof_workers;
+
 pub fn app_routes(state: AppState) -> Router {
-    let api_routes = Router::new()
-        .nest("/campaigns", campaigns::campaign_routes())
-        .nest("/proofs", proofs::proof_routes())
-        .nest("/zk-proofs", zk_proofs::zk_proof_routes())
-        .nest("/hash", hash::hash_routes());
+    let timeouts = &state.config.timeouts;
+    let timeout = |secs| middleware::from_fn_with_state(Duration::from_secs(secs), request_timeout);
+    let (hash, proof, read) = (timeouts.hash_secs, timeouts.proof_secs, timeouts.read_secs);
+    let claim = timeouts.claim_secs;
+
+    // Campaign and proof job routes set their own timeouts per route
+    let mut api_routes = Router::new()
+        .nest("/campaigns", campaigns::campaign_routes(&state))
+        .nest("/proofs", proofs::proof_routes().layer(timeout(proof)))
+        .nest("/proof", proof_jobs::proof_job_routes(&state))
+        .nest("/recipient", recipients::recipient_routes().layer(timeout(read)))
+        .nest("/zk-proofs", zk_proofs::zk_proof_routes().layer(timeout(proof)))
+        .nest("/hash", hash::hash_routes().layer(timeout(hash)))
+        .nest("/cache", cache::cache_routes().layer(timeout(read)))
+        .nest("/nullifier", nullifiers::nullifier_routes().layer(timeout(read)))
+        .nest("/auth", auth::auth_routes().layer(timeout(read)))
+        // A replay waits on the webhook target, up to as long as a claim
+        .nest("/admin", webhooks::admin_routes().layer(timeout(claim)));
+    if !state.config.is_production {
+        api_routes = api_routes.nest("/debug", hash::debug_routes().layer(timeout(hash)));
+    }
 
     Router::new()
         .nest("/api/v1", api_routes)
+        .merge(docs::docs_routes())
+        // Prometheus scrapes /metrics as plain text
+        .layer(middleware::from_fn(compress_response))
+        .merge(metrics::metrics_routes())
         .fallback(common::handle_404)
+        .layer(middleware::from_fn_with_state(state.clone(), track_in_flight))
+        .layer(middleware::from_fn(response_time))
         .with_state(state)
 }
 
@@ -31,3 +73,170 @@ mod common {
             .with_message("The requested endpoint does not exist.")
     }
 }
+
+#[cfg(test)]
+pub(crate) mod test_helpers {
+    use std::sync::Arc;
+
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+    };
+    use chrono::{DateTime, Utc};
+    use serde_json::Value;
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    use crate::{
+        common::{fees::FeeConfig, solana_tx::Pubkey},
+        config::{
+            AuthConfig, CacheConfig, CampaignConfig, Config, HashConfig, JitoConfig, LogFormat, LoggingConfig,
+            NullifierConfig, ProofConfig, ServerConfig, TimeoutConfig, WebhookConfig,
+        },
+        models::{CAMPAIGN_STATUS_ACTIVE, Campaign, Recipient},
+        state::AppState,
+    };
+
+    /// Admin key configured for test application state
+    pub const TEST_ADMIN_KEY: &str = "test-admin-key";
+    /// Session token key and sign-in domain of test application state
+    pub const TEST_JWT_SECRET: &str = "test-jwt-secret";
+    pub const TEST_SIWS_DOMAIN: &str = "shadow-drop.test";
+    /// Address of the campaign `seed_campaign_with` creates
+    pub const TEST_CAMPAIGN_ADDRESS: &str = "campaign-address";
+
+    /// Campaign created by `seed_campaign_as`
+    pub struct SeedCampaign<'a> {
+        /// Campaign id; a fresh UUID when unset
+        pub id: Option<&'a str>,
+        pub address: &'a str,
+        pub status: &'a str,
+        pub close_at: Option<DateTime<Utc>>,
+        /// Raw amount allocated to each recipient
+        pub amount: &'a str,
+    }
+
+    impl Default for SeedCampaign<'_> {
+        fn default() -> Self {
+            Self {
+                id: None,
+                address: TEST_CAMPAIGN_ADDRESS,
+                status: CAMPAIGN_STATUS_ACTIVE,
+                close_at: None,
+                amount: "100",
+            }
+        }
+    }
+
+    /// Build application state backed by a test database
+    pub fn test_state(db: PgPool) -> AppState {
+        let config = Config {
+            rust_env: "test".to_string(),
+            is_production: false,
+            server: ServerConfig {
+                port: 0,
+                cors_allowed_origins: vec![],
+            },
+            logging: LoggingConfig {
+                level: "info".to_string(),
+                format: LogFormat::Pretty,
+            },
+            auth: AuthConfig {
+                admin_api_key: Some(TEST_ADMIN_KEY.to_string()),
+                jwt_secret: Some(TEST_JWT_SECRET.to_string()),
+                siws_domain: TEST_SIWS_DOMAIN.to_string(),
+            },
+            hash: HashConfig {
+                max_sponge_inputs: 16,
+            },
+            nullifier: NullifierConfig { retention_days: 7 },
+            fees: FeeConfig::default(),
+            campaign: CampaignConfig {
+                skip_fund_check: true,
+                solana_rpc_url: String::new(),
+                rpc_failure_threshold: 5,
+                rpc_circuit_open_secs: 30,
+                program_id: Pubkey([7; 32]),
+                require_claim_signature: false,
+                tx_confirm_timeout_secs: 5,
+            },
+            jito: JitoConfig {
+                rpc_url: None,
+                tip_lamports: 10_000,
+                tip_account: Pubkey([5; 32]),
+            },
+            webhook: WebhookConfig { claim_url: None },
+            cache: CacheConfig {
+                redis_url: None,
+                default_ttl_secs: 60,
+                tree_cache_size: 10,
+            },
+            proof: ProofConfig { workers: 1 },
+            timeouts: TimeoutConfig {
+                hash_secs: 5,
+                proof_secs: 30,
+                claim_secs: 10,
+                read_secs: 5,
+            },
+        };
+        AppState::new(Arc::new(config), db)
+    }
+
+    /// Create an active campaign at `TEST_CAMPAIGN_ADDRESS` with `wallets`
+    /// as its recipients
+    pub async fn seed_campaign_with(state: &AppState, wallets: &[&str]) {
+        seed_campaign_as(state, wallets, SeedCampaign::default()).await;
+    }
+
+    /// Create `campaign` with `wallets` as its unclaimed recipients
+    pub async fn seed_campaign_as(state: &AppState, wallets: &[&str], campaign: SeedCampaign<'_>) {
+        state
+            .campaign_store
+            .create(Campaign {
+                id: campaign.id.map_or_else(|| Uuid::new_v4().to_string(), str::to_string),
+                address: campaign.address.to_string(),
+                name: "Test".to_string(),
+                merkle_root: "00".repeat(32),
+                total_amount: "100".to_string(),
+                creator_wallet: "creator".to_string(),
+                tx_signature: None,
+                vault_address: None,
+                created_at: Utc::now(),
+                recipients: wallets
+                    .iter()
+                    .map(|w| Recipient {
+                        id: None,
+                        wallet: w.to_string(),
+                        amount: campaign.amount.to_string(),
+                        claimed: false,
+                        claimed_at: None,
+                        leaf_index: None,
+                    })
+                    .collect(),
+                airdrop_type: "instant".to_string(),
+                vesting_start: 0,
+                vesting_cliff_seconds: 0,
+                vesting_duration_seconds: 0,
+                token_mint: None,
+                token_symbol: None,
+                token_decimals: None,
+                status: campaign.status.to_string(),
+                pause_reason: None,
+                close_at: campaign.close_at,
+                referral_bonus_bps: 500,
+                max_claims: None,
+            })
+            .await;
+    }
+
+    /// Send a request through the router and decode the JSON response
+    pub async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+        (status, body)
+    }
+}
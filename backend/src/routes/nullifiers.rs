@@ -0,0 +1,118 @@
+//! Nullifier lookup routes
+
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    routing::get,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{
+        error::ShadowDropError,
+        merkle::parse_hash,
+        response::{ApiResponse, ApiSuccessResponse},
+    },
+    state::AppState,
+};
+
+/// Query parameters of a nullifier lookup
+#[derive(Debug, Default, Deserialize)]
+pub struct NullifierQuery {
+    /// Campaign the caller expects the nullifier to belong to
+    #[serde(default)]
+    pub campaign_id: Option<String>,
+}
+
+/// Whether a nullifier is spent, and where and when if it is
+#[derive(Debug, Serialize)]
+pub struct NullifierStatus {
+    pub nullifier: String,
+    pub spent: bool,
+    pub campaign_address: Option<String>,
+    pub spent_at: Option<DateTime<Utc>>,
+}
+
+/// Build nullifier routes
+pub fn nullifier_routes() -> Router<AppState> {
+    Router::new().route("/{nullifier}", get(get_nullifier))
+}
+
+/// GET /api/v1/nullifier/:nullifier - Whether a nullifier is spent
+///
+/// Nullifiers are unique across campaigns, so one spent in any campaign is
+/// reported as spent. `?campaign_id=` only checks the campaign exists.
+async fn get_nullifier(
+    State(state): State<AppState>,
+    Path(nullifier): Path<String>,
+    Query(query): Query<NullifierQuery>,
+) -> ApiResponse<NullifierStatus> {
+    let hash = parse_hash(&nullifier).ok_or_else(|| {
+        ShadowDropError::BadRequest("nullifier must be 32 hex-encoded bytes".to_string())
+    })?;
+    if let Some(id) = &query.campaign_id
+        && state.campaign_store.get_by_id(id).await.is_none()
+    {
+        return Err(ShadowDropError::NotFound("Campaign not found".to_string()));
+    }
+
+    let spent = state.nullifiers.find_spent(&hash).await?;
+    Ok(ApiSuccessResponse::default()
+        .with_data(NullifierStatus {
+            nullifier: hex::encode(hash),
+            spent: spent.is_some(),
+            campaign_address: spent.as_ref().map(|spent| spent.campaign_address.clone()),
+            spent_at: spent.map(|spent| spent.spent_at),
+        })
+        .with_message("Nullifier status retrieved"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use sqlx::PgPool;
+
+    use crate::{
+        nullifiers::{InMemoryNullifierDB, NullifierDB},
+        routes::{
+            app_routes,
+            test_helpers::{send, test_state},
+        },
+    };
+
+    fn get(uri: &str) -> Request<Body> {
+        Request::get(uri).body(Body::empty()).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_get_nullifier_reports_spends(pool: PgPool) {
+        let mut state = test_state(pool);
+        let nullifiers = Arc::new(InMemoryNullifierDB::default());
+        nullifiers.mark_spent([7u8; 32], "campaign").await.unwrap();
+        state.nullifiers = nullifiers;
+        let app = app_routes(state);
+
+        let (status, body) = send(&app, get(&format!("/api/v1/nullifier/0x{}", "07".repeat(32)))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["spent"], true);
+        assert_eq!(body["data"]["campaign_address"], "campaign");
+        assert!(body["data"]["spent_at"].is_string());
+
+        let (status, body) = send(&app, get(&format!("/api/v1/nullifier/{}", "08".repeat(32)))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["spent"], false);
+        assert!(body["data"]["spent_at"].is_null());
+
+        let (status, _) = send(&app, get("/api/v1/nullifier/0x07")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let uri = format!("/api/v1/nullifier/{}?campaign_id=missing", "07".repeat(32));
+        let (status, _) = send(&app, get(&uri)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
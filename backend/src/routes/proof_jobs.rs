@@ -0,0 +1,484 @@
+//! Background ZK proof jobs
+//!
+//! Proving can take seconds, so `POST /proof/generate` only queues a job and
+//! returns its id; the result is polled with `GET /proof/jobs/:id`.
+//!
+//! Each new job is announced on the `proof_jobs` notification channel. Worker
+//! tasks listen on it and take pending jobs with `FOR UPDATE SKIP LOCKED`, so
+//! a job starts as soon as one is free and is never proved twice, even with
+//! several API instances sharing the database.
+//!
+//! Instead of polling, clients can hold `GET /proof/jobs/:id/subscribe` open
+//! and receive the outcome as a single server-sent event.
+
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    middleware,
+    response::sse::{Event, KeepAlive, Sse},
+    routing::{get, post},
+    Json, Router,
+};
+use futures_util::{Stream, stream};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::{
+    common::{
+        error::ShadowDropError,
+        response::{ApiResponse, ApiSuccessResponse},
+        schema::PROOF_JOB_REQUEST_SCHEMA,
+    },
+    middleware::{json_schema::validate_json_body, timeout::request_timeout},
+    models::{CancelJobOutcome, PROOF_JOB_PENDING, ProofJob, ProofJobStore},
+    state::AppState,
+};
+
+use super::zk_proofs::{claimable_recipient, prepare_zk_proof};
+
+/// How often idle workers check the queue without a notification, catching
+/// jobs announced while their listener was reconnecting
+const QUEUE_SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long a subscriber waits for its job; proofs take well under this
+const SUBSCRIBE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Request to generate a ZK proof in the background
+#[derive(Debug, Deserialize)]
+pub struct ProofJobRequest {
+    pub address: String,
+    pub wallet: String,
+}
+
+/// Response to a newly queued job
+#[derive(Debug, Serialize)]
+pub struct ProofJobCreated {
+    pub job_id: Uuid,
+    pub status: &'static str,
+}
+
+/// Build proof job routes
+///
+/// The event stream stays open until its job finishes, so only the other
+/// routes have timeouts.
+pub fn proof_job_routes(state: &AppState) -> Router<AppState> {
+    let timeouts = &state.config.timeouts;
+    Router::new()
+        .route("/generate", post(create_job))
+        .route_layer(middleware::from_fn_with_state(
+            &*PROOF_JOB_REQUEST_SCHEMA,
+            validate_json_body,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            Duration::from_secs(timeouts.proof_secs),
+            request_timeout,
+        ))
+        .route(
+            "/jobs/{id}",
+            get(get_job).delete(cancel_job).layer(middleware::from_fn_with_state(
+                Duration::from_secs(timeouts.read_secs),
+                request_timeout,
+            )),
+        )
+        .route("/jobs/{id}/subscribe", get(subscribe_job))
+}
+
+/// POST /api/v1/proof/generate - Queue a ZK proof for a claim
+///
+/// The campaign and recipient are checked before the job is created, so an
+/// unknown wallet is rejected here rather than as a failed job.
+async fn create_job(
+    State(state): State<AppState>,
+    Json(body): Json<ProofJobRequest>,
+) -> ApiResponse<ProofJobCreated> {
+    claimable_recipient(&state, &body.address, &body.wallet).await?;
+    let job_id = state.proof_jobs.create(&body.address, &body.wallet).await?;
+
+    Ok(ApiSuccessResponse::default()
+        .with_code(StatusCode::ACCEPTED)
+        .with_data(ProofJobCreated {
+            job_id,
+            status: PROOF_JOB_PENDING,
+        })
+        .with_message("Proof job queued"))
+}
+
+/// Start `workers` tasks computing queued proof jobs until the runtime shuts down
+pub fn spawn_proof_workers(state: AppState, workers: usize) -> Vec<JoinHandle<()>> {
+    (0..workers.max(1))
+        .map(|worker| tokio::spawn(run_worker(state.clone(), worker)))
+        .collect()
+}
+
+async fn run_worker(state: AppState, worker: usize) {
+    let mut listener = None;
+    loop {
+        // Drain the queue, then sleep until a job is announced
+        loop {
+            match state.proof_jobs.claim_next().await {
+                Ok(Some(job)) => run_job(&state, worker, job).await,
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::error!(worker, "Failed to take a proof job: {}", e);
+                    break;
+                }
+            }
+        }
+        wait_for_job(&state.proof_jobs, &mut listener, worker).await;
+    }
+}
+
+/// Wait for a notification on the job channel, at most `QUEUE_SWEEP_INTERVAL`
+async fn wait_for_job(jobs: &ProofJobStore, listener: &mut Option<PgListener>, worker: usize) {
+    if listener.is_none() {
+        match jobs.listen().await {
+            Ok(connected) => *listener = Some(connected),
+            Err(e) => tracing::warn!(worker, "Failed to listen for proof jobs: {}", e),
+        }
+    }
+
+    let Some(connected) = listener else {
+        tokio::time::sleep(QUEUE_SWEEP_INTERVAL).await;
+        return;
+    };
+    if let Ok(Err(e)) = tokio::time::timeout(QUEUE_SWEEP_INTERVAL, connected.recv()).await {
+        tracing::warn!(worker, "Proof job listener failed: {}", e);
+        *listener = None;
+        tokio::time::sleep(QUEUE_SWEEP_INTERVAL).await;
+    }
+}
+
+/// Prove a running job on a blocking thread and store the outcome
+async fn run_job(state: &AppState, worker: usize, job: ProofJob) {
+    let started = Instant::now();
+    let queued_ms = job
+        .started_at
+        .map_or(0, |started_at| (started_at - job.created_at).num_milliseconds());
+    tracing::info!(worker, job_id = %job.id, queued_ms, "Proof job started");
+
+    let outcome = match prepare_zk_proof(state, &job.campaign_address, &job.wallet).await {
+        Ok(prepared) => {
+            // The prover shells out and blocks until it exits
+            let runtime = tokio::runtime::Handle::current();
+            tokio::task::spawn_blocking(move || runtime.block_on(prepared.prove()))
+                .await
+                .unwrap_or_else(|e| Err(ShadowDropError::Internal(format!("Proof task failed: {}", e))))
+        }
+        Err(e) => Err(e),
+    }
+    .and_then(|(proof, _)| {
+        serde_json::to_value(proof).map_err(|e| ShadowDropError::Internal(e.to_string()))
+    });
+
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    let finished = match outcome {
+        Ok(result) => {
+            tracing::info!(worker, job_id = %job.id, elapsed_ms, "Proof job completed");
+            state.proof_jobs.complete(job.id, &result).await
+        }
+        Err(e) => {
+            tracing::warn!(worker, job_id = %job.id, elapsed_ms, error = %e, "Proof job failed");
+            state.proof_jobs.fail(job.id, &e.to_string()).await
+        }
+    };
+    match finished {
+        Ok(Some(finished)) => state.proof_job_subscribers.notify(&job.id, finished),
+        Ok(None) => {}
+        Err(e) => tracing::error!(worker, job_id = %job.id, "Failed to store proof job result: {}", e),
+    }
+}
+
+/// GET /api/v1/proof/jobs/:id - Status of a job, with its proof once completed
+async fn get_job(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResponse<ProofJob> {
+    let job = state
+        .proof_jobs
+        .get(parse_job_id(&id)?)
+        .await?
+        .ok_or_else(|| ShadowDropError::NotFound("Proof job not found".to_string()))?;
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(job)
+        .with_message("Proof job retrieved"))
+}
+
+/// DELETE /api/v1/proof/jobs/:id - Cancel a pending job
+async fn cancel_job(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResponse<ProofJob> {
+    match state.proof_jobs.cancel(parse_job_id(&id)?).await? {
+        CancelJobOutcome::Cancelled(job) => {
+            state.proof_job_subscribers.notify(&job.id, job.clone());
+            Ok(ApiSuccessResponse::default()
+                .with_data(job)
+                .with_message("Proof job cancelled"))
+        }
+        CancelJobOutcome::NotFound => Err(ShadowDropError::NotFound("Proof job not found".to_string())),
+        CancelJobOutcome::NotPending(status) => Err(ShadowDropError::Conflict {
+            message: format!("Proof job is {}, only pending jobs can be cancelled", status),
+            details: None,
+        }),
+    }
+}
+
+/// GET /api/v1/proof/jobs/:id/subscribe - The job's outcome as a server-sent event
+///
+/// Sends one event named after the final status (`completed`, `failed` or
+/// `cancelled`), at once if the job has already finished. A job still
+/// unfinished after `SUBSCRIBE_TIMEOUT` gets a `timeout` event instead.
+async fn subscribe_job(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, ShadowDropError> {
+    let id = parse_job_id(&id)?;
+    // Subscribe before reading, so a job finishing in between is not missed
+    let finished = state.proof_job_subscribers.subscribe(id);
+    let job = state
+        .proof_jobs
+        .get(id)
+        .await?
+        .ok_or_else(|| ShadowDropError::NotFound("Proof job not found".to_string()))?;
+
+    let event = async move {
+        if job.is_finished() {
+            return job_event(&job);
+        }
+        match tokio::time::timeout(SUBSCRIBE_TIMEOUT, finished).await {
+            Ok(Ok(job)) => job_event(&job),
+            // Finished by another instance's worker, or still running
+            _ => match state.proof_jobs.get(id).await {
+                Ok(Some(job)) if job.is_finished() => job_event(&job),
+                Ok(Some(job)) => Event::default().event("timeout").json_data(job_outcome(&job)),
+                Ok(None) => Event::default().event("timeout").json_data(serde_json::json!({})),
+                Err(e) => Err(axum::Error::new(e)),
+            },
+        }
+    };
+
+    Ok(Sse::new(stream::once(event)).keep_alive(KeepAlive::default()))
+}
+
+fn job_event(job: &ProofJob) -> Result<Event, axum::Error> {
+    Event::default().event(&job.status).json_data(job_outcome(job))
+}
+
+/// `data` of a job event
+fn job_outcome(job: &ProofJob) -> serde_json::Value {
+    serde_json::json!({
+        "status": job.status,
+        "result": job.result,
+        "error": job.error,
+    })
+}
+
+fn parse_job_id(id: &str) -> Result<Uuid, ShadowDropError> {
+    Uuid::parse_str(id).map_err(|_| ShadowDropError::BadRequest("job id must be a UUID".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{PROOF_JOB_CANCELLED, PROOF_JOB_COMPLETED, PROOF_JOB_RUNNING},
+        routes::{
+            app_routes,
+            test_helpers::{SeedCampaign, seed_campaign_as, send, test_state},
+        },
+    };
+    use axum::{
+        body::{Body, to_bytes},
+        http::{Request, header},
+        response::Response,
+    };
+    use serde_json::Value;
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    async fn seed_recipient(state: &AppState) {
+        let campaign = SeedCampaign { address: "campaign", ..SeedCampaign::default() };
+        seed_campaign_as(state, &["wallet1"], campaign).await;
+    }
+
+    fn generate_request(wallet: &str) -> Request<Body> {
+        Request::post("/api/v1/proof/generate")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(format!(r#"{{"address":"campaign","wallet":"{wallet}"}}"#)))
+            .unwrap()
+    }
+
+    fn job_request(method: &str, id: &str) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(format!("/api/v1/proof/jobs/{id}"))
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_proof_job_completes_in_background(pool: PgPool) {
+        let state = test_state(pool);
+        seed_recipient(&state).await;
+        let app = app_routes(state.clone());
+        let workers = spawn_proof_workers(state, 2);
+
+        let (status, body) = send(&app, generate_request("wallet1")).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(body["data"]["status"], PROOF_JOB_PENDING);
+        let job_id = body["data"]["job_id"].as_str().unwrap().to_string();
+
+        // Without Sunspot installed the job stores a mock proof
+        let mut job = Value::Null;
+        for _ in 0..50 {
+            let (status, body) = send(&app, job_request("GET", &job_id)).await;
+            assert_eq!(status, StatusCode::OK);
+            job = body["data"].clone();
+            if job["status"] != PROOF_JOB_PENDING && job["status"] != PROOF_JOB_RUNNING {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(job["status"], PROOF_JOB_COMPLETED);
+        assert_eq!(job["result"]["leaf_index"], 0);
+        assert!(job["started_at"].is_string());
+        assert!(job["completed_at"].is_string());
+
+        // Finished jobs can no longer be cancelled
+        let (status, _) = send(&app, job_request("DELETE", &job_id)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        workers.iter().for_each(JoinHandle::abort);
+    }
+
+    #[sqlx::test]
+    async fn test_unknown_recipient_is_rejected_before_queueing(pool: PgPool) {
+        let state = test_state(pool);
+        seed_recipient(&state).await;
+        let app = app_routes(state);
+
+        let (status, _) = send(&app, generate_request("stranger")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = send(&app, job_request("GET", &Uuid::new_v4().to_string())).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(&app, job_request("GET", "not-a-uuid")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[sqlx::test]
+    async fn test_cancelled_job_is_not_picked_up(pool: PgPool) {
+        let state = test_state(pool);
+        let app = app_routes(state.clone());
+        let job_id = state.proof_jobs.create("campaign", "wallet1").await.unwrap();
+
+        let (status, body) = send(&app, job_request("DELETE", &job_id.to_string())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["status"], PROOF_JOB_CANCELLED);
+
+        assert!(state.proof_jobs.claim_next().await.unwrap().is_none());
+        assert!(state.proof_jobs.complete(job_id, &Value::Null).await.unwrap().is_none());
+        let job = state.proof_jobs.get(job_id).await.unwrap().unwrap();
+        assert_eq!(job.status, PROOF_JOB_CANCELLED);
+        assert!(job.result.is_none());
+    }
+
+    async fn subscribe(app: &Router, id: Uuid) -> Response {
+        app.clone().oneshot(job_request("GET", &format!("{id}/subscribe"))).await.unwrap()
+    }
+
+    async fn read_events(response: Response) -> String {
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_subscriber_is_sent_completion_event(pool: PgPool) {
+        let state = test_state(pool);
+        let app = app_routes(state.clone());
+        let job_id = state.proof_jobs.create("campaign", "wallet1").await.unwrap();
+
+        // The stream is open once the response starts; the event follows later
+        let response = subscribe(&app, job_id).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Stand in for a worker finishing the job
+        state.proof_jobs.claim_next().await.unwrap();
+        let finished = state
+            .proof_jobs
+            .complete(job_id, &serde_json::json!({ "leaf_index": 0 }))
+            .await
+            .unwrap()
+            .unwrap();
+        state.proof_job_subscribers.notify(&job_id, finished);
+
+        let body = read_events(response).await;
+        assert!(body.starts_with("event: completed\n"), "{body}");
+        assert!(body.contains(r#"data: {"error":null,"result":{"leaf_index":0},"status":"completed"}"#), "{body}");
+    }
+
+    #[sqlx::test]
+    async fn test_subscriber_to_finished_job_gets_stored_outcome(pool: PgPool) {
+        let state = test_state(pool);
+        let app = app_routes(state.clone());
+        let job_id = state.proof_jobs.create("campaign", "wallet1").await.unwrap();
+        state.proof_jobs.cancel(job_id).await.unwrap();
+
+        let body = read_events(subscribe(&app, job_id).await).await;
+        assert!(body.starts_with("event: cancelled\n"), "{body}");
+
+        let response = subscribe(&app, Uuid::new_v4()).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[sqlx::test]
+    async fn test_concurrent_workers_take_distinct_jobs(pool: PgPool) {
+        let jobs = ProofJobStore::new(pool);
+        let first = jobs.create("campaign", "wallet1").await.unwrap();
+        let second = jobs.create("campaign", "wallet2").await.unwrap();
+
+        let (a, b, c) = tokio::join!(jobs.claim_next(), jobs.claim_next(), jobs.claim_next());
+        let mut taken: Vec<Uuid> = [a, b, c]
+            .into_iter()
+            .filter_map(|job| job.unwrap().map(|job| job.id))
+            .collect();
+        taken.sort();
+        let mut expected = vec![first, second];
+        expected.sort();
+        assert_eq!(taken, expected);
+
+        // Running jobs can no longer be cancelled
+        assert!(matches!(jobs.cancel(first).await.unwrap(), CancelJobOutcome::NotPending(_)));
+    }
+
+    #[sqlx::test]
+    async fn test_finished_jobs_are_purged_after_ttl(pool: PgPool) {
+        let state = test_state(pool.clone());
+        let (old, recent, pending) = (
+            state.proof_jobs.create("campaign", "wallet1").await.unwrap(),
+            state.proof_jobs.create("campaign", "wallet2").await.unwrap(),
+            state.proof_jobs.create("campaign", "wallet3").await.unwrap(),
+        );
+        // Failing needs the jobs running; the oldest two are taken
+        state.proof_jobs.claim_next().await.unwrap();
+        state.proof_jobs.claim_next().await.unwrap();
+        state.proof_jobs.fail(old, "boom").await.unwrap();
+        state.proof_jobs.fail(recent, "boom").await.unwrap();
+        sqlx::query!(
+            "UPDATE proof_jobs SET completed_at = NOW() - INTERVAL '2 hours' WHERE id = $1",
+            old
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        assert_eq!(state.proof_jobs.purge_expired().await.unwrap(), 1);
+        assert!(state.proof_jobs.get(old).await.unwrap().is_none());
+        assert!(state.proof_jobs.get(recent).await.unwrap().is_some());
+        assert!(state.proof_jobs.get(pending).await.unwrap().is_some());
+    }
+}
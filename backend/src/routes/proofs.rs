@@ -2,17 +2,45 @@
 
 use axum::{
     Json, Router,
-    extract::{Path, State},
-    http::StatusCode,
+    body::Body,
+    extract::{Path, Query, State},
+    http::header,
+    middleware,
+    response::{IntoResponse, Response},
     routing::post,
 };
+use std::{
+    convert::Infallible,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
 
 use crate::{
     common::{
-        merkle::{compute_nullifier, generate_secret, MerkleTree},
-        response::{ApiErrorResponse, ApiResponse, ApiSuccessResponse},
+        merkle::{
+            Hash, MerkleProof, MerkleTree, compute_commitment, compute_leaf_hash,
+            compute_nullifier, compute_nullifier_v2, generate_secret, parse_hash,
+        },
+        error::ShadowDropError,
+        fees::{FeeConfig, Lamports, net_amount},
+        response::{ApiResponse, ApiSuccessResponse},
+        schema::CLAIM_REQUEST_SCHEMA,
+        solana_tx::{
+            ParsePubkeyError, Pubkey, TokenPayout, build_claim_transaction, derive_ata,
+            estimated_claim_cost, nullifier_address, system_transfer,
+        },
+        zip::ZipWriter,
     },
+    funding::account_exists,
+    metrics,
+    middleware::{admin_auth::AdminAuth, json_schema::validate_json_body},
+    models::{Campaign, CampaignRound, Recipient},
     state::AppState,
 };
 
@@ -22,27 +50,76 @@ pub struct GenerateProofRequest {
     pub wallet: String,
 }
 
-/// Response containing proof data for ZK claim
+/// Request body for building a claim transaction
+#[derive(Debug, Deserialize)]
+pub struct BuildClaimTxRequest {
+    /// Recipient wallet, which signs and pays for the transaction
+    pub wallet: String,
+    /// Blockhash (base58) the transaction is valid until
+    pub recent_blockhash: String,
+}
+
+/// How a signed claim transaction is sent on-chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubmitMethod {
+    /// As a Jito bundle, tipping the block engine
+    Jito,
+}
+
+/// Query parameters of building a claim transaction
+#[derive(Debug, Default, Deserialize)]
+pub struct BuildClaimTxQuery {
+    /// How the transaction will be sent; `jito` adds the tip to it
+    pub submit_method: Option<SubmitMethod>,
+}
+
+/// An unsigned claim transaction and the proof it carries
 #[derive(Debug, Serialize)]
+pub struct BuildClaimTxResponse {
+    /// Serialized legacy transaction, base64, with a zeroed signature
+    pub transaction: String,
+    /// Account the program creates to record the nullifier as spent
+    pub nullifier_account: String,
+    /// Signature fees, plus the rent of the token account if it is created
+    /// and the Jito tip if there is one
+    pub estimated_fee_lamports: Lamports,
+    /// ATA the tokens are paid to, for SPL token campaigns
+    pub token_account: Option<String>,
+    pub proof: ProofResponse,
+}
+
+/// Response containing proof data for ZK claim
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ProofResponse {
     /// Merkle root (32 bytes hex)
     pub merkle_root: String,
-    /// Nullifier hash (32 bytes hex)
+    /// On-chain nullifier the circuit and the claim instruction check,
+    /// `compute_nullifier(secret, leaf_index)` (32 bytes hex)
     pub nullifier_hash: String,
+    /// Commitment hash(merkle_root, nullifier_hash) (32 bytes hex)
+    pub commitment: String,
     /// Leaf index in tree
     pub leaf_index: usize,
     /// Merkle proof path (array of 32-byte hashes)
     pub merkle_path: Vec<String>,
-    /// Claim amount in SOL
+    /// Claim amount after the protocol fee
     pub amount: String,
     /// Secret for this claim (should be stored securely by user)
     pub secret: String,
+    /// Wallet-bound nullifier a backend claim spends, which a claim
+    /// signature covers (32 bytes hex)
+    pub claim_nullifier: String,
 }
 
 /// Build proof routes
 pub fn proof_routes() -> Router<AppState> {
     Router::new()
         .route("/{address}/generate", post(generate_proof))
+        .route_layer(middleware::from_fn_with_state(
+            &*CLAIM_REQUEST_SCHEMA,
+            validate_json_body,
+        ))
 }
 
 /// POST /api/v1/proofs/:address/generate - Generate ZK proof for claim
@@ -52,86 +129,468 @@ async fn generate_proof(
     Json(body): Json<GenerateProofRequest>,
 ) -> ApiResponse<ProofResponse> {
     // Get campaign
-    let campaign = match state.campaign_store.get(&address).await {
-        Some(c) => c,
-        None => {
-            return Err(ApiErrorResponse::default()
-                .with_code(StatusCode::NOT_FOUND)
-                .with_message("Campaign not found"))
+    let campaign = state
+        .campaign_store
+        .get(&address)
+        .await
+        .ok_or_else(|| ShadowDropError::NotFound("Campaign not found".to_string()))?;
+
+    let proof = recipient_proof(&state, &campaign, &body.wallet).await?;
+    Ok(ApiSuccessResponse::default()
+        .with_data(proof)
+        .with_message("Proof generated successfully"))
+}
+
+/// The proof of an unclaimed recipient: cached, pre-generated or computed
+/// from the campaign's tree, in that order, and cached for next time
+async fn recipient_proof(
+    state: &AppState,
+    campaign: &Campaign,
+    wallet: &str,
+) -> Result<ProofResponse, ShadowDropError> {
+    unclaimed_recipient(&campaign.recipients, wallet)?;
+    let address = &campaign.address;
+
+    let start = Instant::now();
+    let cache = &state.proof_cache;
+    if let Some(proof) = cache.get(address, wallet).await
+        && let Ok(proof) = serde_json::from_str::<ProofResponse>(&proof)
+    {
+        metrics::observe_proof_gen(proof.merkle_path.len(), true, start);
+        return Ok(proof);
+    }
+
+    // Campaigns pre-generate proofs on activation; fall back to computing one
+    let pregenerated = state
+        .campaign_store
+        .cached_proof(address, wallet)
+        .await
+        .and_then(|(proof, _)| serde_json::from_value::<ProofResponse>(proof).ok());
+    let proof = match pregenerated {
+        Some(proof) => {
+            metrics::observe_proof_gen(proof.merkle_path.len(), true, start);
+            proof
         }
+        None => tree_proof(state, campaign, wallet).await?,
     };
 
-    // Find recipient
-    let recipient = match campaign.recipients.iter().find(|r| r.wallet == body.wallet) {
-        Some(r) => r,
-        None => {
-            return Err(ApiErrorResponse::default()
-                .with_code(StatusCode::NOT_FOUND)
-                .with_message("Wallet not found in campaign recipients"))
-        }
+    // Cached proofs live until the claim window closes
+    let ttl = match campaign.close_at {
+        Some(close_at) => (close_at - Utc::now()).to_std().ok(),
+        None => Some(Duration::from_secs(state.config.cache.default_ttl_secs)),
     };
+    if let Some(ttl) = ttl
+        && let Ok(json) = serde_json::to_string(&proof)
+    {
+        cache.set(address, wallet, &json, ttl).await;
+    }
+    Ok(proof)
+}
 
-    // Check if already claimed
-    if recipient.claimed {
-        return Err(ApiErrorResponse::default()
-            .with_code(StatusCode::BAD_REQUEST)
-            .with_message("Already claimed"));
+/// POST /api/v1/campaigns/:address/build-claim-tx - Unsigned transaction
+/// claiming the wallet's allocation on-chain
+///
+/// The wallet is the fee payer and only signer; the response carries the
+/// proof too, since its secret is needed to claim again should the
+/// transaction expire. With `?submit_method=jito` the last instruction tips
+/// `JITO_TIP_LAMPORTS` to the block engine.
+pub(super) async fn build_claim_tx(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(query): Query<BuildClaimTxQuery>,
+    Json(body): Json<BuildClaimTxRequest>,
+) -> ApiResponse<BuildClaimTxResponse> {
+    if query.submit_method == Some(SubmitMethod::Jito) {
+        jito_url(&state)?;
     }
+    let payer: Pubkey = body
+        .wallet
+        .parse()
+        .map_err(|e: ParsePubkeyError| ShadowDropError::BadRequest(e.to_string()))?;
+    let recent_blockhash: Hash = bs58::decode(&body.recent_blockhash)
+        .into_vec()
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| {
+            ShadowDropError::BadRequest("recent_blockhash must be 32 base58-encoded bytes".to_string())
+        })?;
 
-    // Generate secret for this claim (in real app, this would be stored per-user)
-    let secret = generate_secret();
+    let campaign = state
+        .campaign_store
+        .get(&address)
+        .await
+        .ok_or_else(|| ShadowDropError::NotFound("Campaign not found".to_string()))?;
+    let campaign_pda: Pubkey = address.parse().map_err(|_| {
+        ShadowDropError::BadRequest("Campaign address is not a Solana account".to_string())
+    })?;
 
-    // Build merkle tree with secrets
-    let recipients_with_secrets: Vec<(String, u64, [u8; 32])> = campaign
-        .recipients
+    let proof = recipient_proof(&state, &campaign, &body.wallet).await?;
+    let (merkle_proof, nullifier) = decode_proof(&proof, &body.wallet)?;
+    let program_id = &state.config.campaign.program_id;
+    let token = match &campaign.token_mint {
+        Some(mint) => Some(token_payout(&state, &payer, mint).await?),
+        None => None,
+    };
+    let mut transaction = build_claim_transaction(
+        program_id,
+        &payer,
+        &campaign_pda,
+        &merkle_proof,
+        &nullifier,
+        token.as_ref(),
+        recent_blockhash,
+    );
+    let mut estimated_fee_lamports = estimated_claim_cost(&transaction, token.as_ref());
+    if query.submit_method == Some(SubmitMethod::Jito) {
+        let jito = &state.config.jito;
+        transaction = transaction.with_instruction(system_transfer(&payer, &jito.tip_account, jito.tip_lamports));
+        estimated_fee_lamports += jito.tip_lamports;
+    }
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(BuildClaimTxResponse {
+            transaction: STANDARD.encode(transaction.serialize()),
+            nullifier_account: nullifier_address(&nullifier, program_id).0.to_string(),
+            estimated_fee_lamports,
+            token_account: token.map(|token| derive_ata(&payer, &token.mint).to_string()),
+            proof,
+        })
+        .with_message("Claim transaction built"))
+}
+
+/// The configured Jito block engine, which `submit_method=jito` requires
+pub(super) fn jito_url(state: &AppState) -> Result<&str, ShadowDropError> {
+    state
+        .config
+        .jito
+        .rpc_url
+        .as_deref()
+        .ok_or_else(|| ShadowDropError::BadRequest("Jito submission is not enabled".to_string()))
+}
+
+/// Where a claim of a `mint` campaign pays `payer`, and whether the ATA
+/// has to be created, which is looked up over RPC
+async fn token_payout(state: &AppState, payer: &Pubkey, mint: &str) -> Result<TokenPayout, ShadowDropError> {
+    let mint: Pubkey = mint
+        .parse()
+        .map_err(|_| ShadowDropError::Internal("Campaign token mint is not a public key".to_string()))?;
+    let rpc_url = &state.config.campaign.solana_rpc_url;
+    let breaker = state.rpc_circuits.get(rpc_url);
+    let ata = derive_ata(payer, &mint).to_string();
+    let exists = account_exists(rpc_url, &ata, &breaker).await?;
+    Ok(TokenPayout {
+        mint,
+        create_account: !exists,
+    })
+}
+
+/// The Merkle proof and nullifier of `proof`, as the claim instruction takes them
+fn decode_proof(proof: &ProofResponse, wallet: &str) -> Result<(MerkleProof, Hash), ShadowDropError> {
+    let malformed = || ShadowDropError::Internal("Stored proof is malformed".to_string());
+    let siblings = proof
+        .merkle_path
         .iter()
-        .map(|r| {
-            // For demo: use deterministic secret based on wallet
-            // In production: secrets would be stored per-recipient
-            let mut recipient_secret = [0u8; 32];
-            if r.wallet == body.wallet {
-                recipient_secret = secret;
+        .map(|sibling| parse_hash(sibling))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(malformed)?;
+    let secret = parse_hash(&proof.secret).ok_or_else(malformed)?;
+    let amount = proof.amount.parse::<u64>().map_err(|_| malformed())?;
+    let nullifier = parse_hash(&proof.nullifier_hash).ok_or_else(malformed)?;
+
+    let merkle_proof = MerkleProof {
+        leaf_index: proof.leaf_index,
+        siblings,
+        leaf: compute_leaf_hash(wallet, amount, &secret),
+    };
+    Ok((merkle_proof, nullifier))
+}
+
+/// Proof of an unclaimed recipient from the campaign's tree
+async fn tree_proof(
+    state: &AppState,
+    campaign: &Campaign,
+    wallet: &str,
+) -> Result<ProofResponse, ShadowDropError> {
+    let recipient = unclaimed_recipient(&campaign.recipients, wallet)?;
+    let tree = campaign_tree(state, campaign).await?;
+
+    let start = Instant::now();
+    let (proof, secret) = tree
+        .get_proof(wallet)
+        .zip(tree.secret(wallet))
+        .ok_or_else(|| ShadowDropError::Internal("Recipient is missing from the campaign tree".to_string()))?;
+    metrics::observe_merkle_proofs(tree.depth(), 1, start);
+    let amount = leaf_amount(recipient, &state.config.fees);
+    Ok(proof_response(&tree.root(), &proof, wallet, amount, secret))
+}
+
+/// The campaign's tree: from the tree cache, else rebuilt from its
+/// `merkle_snapshots` row, else built with a fresh secret per recipient and
+/// snapshotted, so every computed proof of the campaign shares one root
+pub(super) async fn campaign_tree(state: &AppState, campaign: &Campaign) -> Result<Arc<MerkleTree>, ShadowDropError> {
+    let key = Uuid::parse_str(&campaign.id).ok();
+    if let Some(key) = &key
+        && let Some(tree) = state.tree_cache.get(key).await
+    {
+        return Ok(tree);
+    }
+
+    let store = &state.campaign_store;
+    let leaves = match store.merkle_snapshot(&campaign.id).await? {
+        Some(leaves) => leaves,
+        None => {
+            // Removed recipients leave gaps, so each leaf stays at its stored index
+            let leaves: Vec<(usize, String, u64, Hash)> = campaign
+                .recipients
+                .iter()
+                .enumerate()
+                .map(|(i, r)| {
+                    let leaf_index = r.leaf_index.map_or(i, |leaf_index| leaf_index as usize);
+                    (leaf_index, r.wallet.clone(), leaf_amount(r, &state.config.fees), generate_secret())
+                })
+                .collect();
+            // Another request may have snapshotted the campaign first
+            if store.save_merkle_snapshot(&campaign.id, &leaves).await? {
+                leaves
             } else {
-                // Use wallet hash as placeholder secret for other recipients
-                let wallet_bytes = r.wallet.as_bytes();
-                for (i, b) in wallet_bytes.iter().enumerate() {
-                    recipient_secret[i % 32] ^= *b;
-                }
+                store.merkle_snapshot(&campaign.id).await?.ok_or_else(|| {
+                    ShadowDropError::Internal("Merkle snapshot is malformed".to_string())
+                })?
             }
-            let amount_u64 = r.amount.parse::<u64>().unwrap_or(0);
-            (r.wallet.clone(), amount_u64, recipient_secret)
+        }
+    };
+
+    let tree = Arc::new(build_tree(leaves).await?);
+    if let Some(key) = key {
+        state.tree_cache.insert(key, tree.clone()).await;
+    }
+    Ok(tree)
+}
+
+/// Tree of leaves `(leaf_index, wallet, amount, secret)`, built off the async runtime
+async fn build_tree(leaves: Vec<(usize, String, u64, Hash)>) -> Result<MerkleTree, ShadowDropError> {
+    let count = leaves.len();
+    let tree = tokio::task::spawn_blocking(move || MerkleTree::from_indexed_recipients(&leaves))
+        .await
+        .map_err(|e| ShadowDropError::Internal(format!("Tree build panicked: {}", e)))??;
+    metrics::observe_leaf_hashes(count);
+    Ok(tree)
+}
+
+/// A new round's tree over `recipients` `(wallet, amount)`, in list order
+/// with a fresh secret each. Returns the leaves to store with the round
+/// alongside the tree.
+pub(super) async fn new_round_tree(
+    recipients: &[(String, String)],
+    fees: &FeeConfig,
+) -> Result<(Vec<(usize, String, u64, Hash)>, MerkleTree), ShadowDropError> {
+    let leaves: Vec<(usize, String, u64, Hash)> = recipients
+        .iter()
+        .enumerate()
+        .map(|(i, (wallet, amount))| {
+            (i, wallet.clone(), net_amount(amount.parse::<u64>().unwrap_or(0), fees), generate_secret())
         })
         .collect();
+    let tree = build_tree(leaves.clone()).await?;
+    Ok((leaves, tree))
+}
 
-    let tree = MerkleTree::from_recipients(&recipients_with_secrets);
+/// A round's tree: from the tree cache, else rebuilt from the leaves stored
+/// with the round, so its proofs match the round's `merkle_root`
+pub(super) async fn round_tree(state: &AppState, round: &CampaignRound) -> Result<Arc<MerkleTree>, ShadowDropError> {
+    if let Some(tree) = state.tree_cache.get(&round.id).await {
+        return Ok(tree);
+    }
 
-    // Get proof for wallet
-    let proof = match tree.get_proof(&body.wallet) {
-        Some(p) => p,
-        None => {
-            return Err(ApiErrorResponse::default()
-                .with_code(StatusCode::INTERNAL_SERVER_ERROR)
-                .with_message("Failed to generate proof"))
-        }
-    };
+    let leaves = state.campaign_store.round_snapshot(round.id).await?.ok_or_else(|| ShadowDropError::Conflict {
+        message: format!("Round {} has no stored tree to prove against", round.round_number),
+        details: None,
+    })?;
+    let tree = Arc::new(build_tree(leaves).await?);
+    state.tree_cache.insert(round.id, tree.clone()).await;
+    Ok(tree)
+}
 
-    // Compute nullifier
-    let nullifier = compute_nullifier(&secret, proof.leaf_index);
+/// Proof of an unclaimed recipient of a round, from the round's tree
+pub(super) async fn round_proof(
+    state: &AppState,
+    round: &CampaignRound,
+    wallet: &str,
+) -> Result<ProofResponse, ShadowDropError> {
+    let recipient = unclaimed_recipient(&round.recipients, wallet)?;
+    let tree = round_tree(state, round).await?;
 
-    // Convert to hex strings
-    let merkle_root_hex = hex::encode(tree.root());
-    let nullifier_hex = hex::encode(nullifier);
-    let secret_hex = hex::encode(secret);
-    let merkle_path: Vec<String> = proof.siblings.iter().map(|s| hex::encode(s)).collect();
+    let start = Instant::now();
+    let (proof, secret) = tree
+        .get_proof(wallet)
+        .zip(tree.secret(wallet))
+        .ok_or_else(|| ShadowDropError::Internal("Recipient is missing from the round tree".to_string()))?;
+    metrics::observe_merkle_proofs(tree.depth(), 1, start);
+    let amount = leaf_amount(recipient, &state.config.fees);
+    Ok(proof_response(&tree.root(), &proof, wallet, amount, secret))
+}
 
-    Ok(ApiSuccessResponse::default()
-        .with_data(ProofResponse {
-            merkle_root: merkle_root_hex,
-            nullifier_hash: nullifier_hex,
-            leaf_index: proof.leaf_index,
-            merkle_path,
-            amount: recipient.amount.clone(),
-            secret: secret_hex,
+/// Drop everything proofs of a campaign were computed from, after its
+/// recipients change: cached proofs, the tree snapshot and the cached tree
+pub(super) async fn invalidate_campaign_proofs(state: &AppState, address: &str) -> Result<(), ShadowDropError> {
+    state.proof_cache.invalidate(address).await;
+    if let Some(campaign_id) = state.campaign_store.delete_merkle_snapshot(address).await?
+        && let Ok(key) = Uuid::parse_str(&campaign_id)
+    {
+        state.tree_cache.remove(&key).await;
+    }
+    Ok(())
+}
+
+/// Proofs of every unclaimed recipient as `(wallet, proof)`, from the
+/// campaign's `tree`
+fn campaign_proofs(tree: &MerkleTree, recipients: &[Recipient], fees: &FeeConfig) -> Vec<(String, ProofResponse)> {
+    let root = tree.root();
+    let start = Instant::now();
+    let proofs = tree.batch_proofs();
+    metrics::observe_merkle_proofs(tree.depth(), proofs.len(), start);
+    proofs
+        .into_iter()
+        .filter_map(|(wallet, proof)| {
+            let recipient = recipients.iter().find(|r| r.wallet == wallet && !r.claimed)?;
+            let secret = tree.secret(&wallet)?;
+            let response = proof_response(&root, &proof, &wallet, leaf_amount(recipient, fees), secret);
+            Some((wallet, response))
         })
-        .with_message("Proof generated successfully"))
+        .collect()
+}
+
+/// GET /api/v1/campaigns/:address/proofs/download - Proofs of every unclaimed
+/// recipient as a ZIP of `<wallet>.json` files (admin only)
+///
+/// The proofs come from the campaign's tree, as on activation; the archive
+/// is then streamed an entry at a time.
+pub(super) async fn download_proofs(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Response, ShadowDropError> {
+    let campaign = state
+        .campaign_store
+        .get(&address)
+        .await
+        .ok_or_else(|| ShadowDropError::NotFound("Campaign not found".to_string()))?;
+    let disposition = format!("attachment; filename=\"proofs-{}.zip\"", campaign.id);
+    let tree = campaign_tree(&state, &campaign).await?;
+    let fees = state.config.fees.clone();
+    let proofs = tokio::task::spawn_blocking(move || campaign_proofs(&tree, &campaign.recipients, &fees))
+        .await
+        .map_err(|e| ShadowDropError::Internal(format!("Proof generation panicked: {}", e)))?;
+    // Wallet labels become file names, so they must not reach outside the archive root
+    if let Some((wallet, _)) = proofs.iter().find(|(wallet, _)| {
+        wallet.starts_with('.')
+            || !wallet.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+    }) {
+        return Err(ShadowDropError::Internal(format!(
+            "wallet {:?} cannot be used as a file name",
+            wallet
+        )));
+    }
+
+    let mut files = proofs.into_iter();
+    let mut zip = Some(ZipWriter::new());
+    let chunks = std::iter::from_fn(move || {
+        let writer = zip.as_mut()?;
+        match files.next() {
+            Some((wallet, proof)) => {
+                let contents = serde_json::to_vec_pretty(&proof).unwrap_or_default();
+                Some(writer.entry(&format!("{}.json", wallet), &contents))
+            }
+            None => zip.take().map(ZipWriter::finish),
+        }
+    });
+    let body = Body::from_stream(futures_util::stream::iter(chunks.map(Ok::<_, Infallible>)));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Pre-generate and cache the proofs of every unclaimed recipient of a
+/// campaign, replacing those of an earlier tree. Returns how many were stored.
+pub(super) async fn pregenerate_proofs(state: &AppState, address: &str) -> Result<usize, ShadowDropError> {
+    let campaign = state
+        .campaign_store
+        .get(address)
+        .await
+        .ok_or_else(|| ShadowDropError::NotFound("Campaign not found".to_string()))?;
+    let tree = campaign_tree(state, &campaign).await?;
+    let fees = state.config.fees.clone();
+    let proofs = tokio::task::spawn_blocking(move || campaign_proofs(&tree, &campaign.recipients, &fees))
+        .await
+        .map_err(|e| ShadowDropError::Internal(format!("Proof generation panicked: {}", e)))?;
+
+    let proofs: Vec<(String, serde_json::Value)> = proofs
+        .into_iter()
+        .map(|(wallet, proof)| Ok((wallet, serde_json::to_value(proof)?)))
+        .collect::<Result<_, serde_json::Error>>()
+        .map_err(|e| ShadowDropError::Internal(format!("Failed to encode proof: {}", e)))?;
+    state.campaign_store.replace_proofs(address, &proofs).await?;
+    state.proof_cache.invalidate(address).await;
+    Ok(proofs.len())
+}
+
+/// Run `pregenerate_proofs` in the background, e.g. after a campaign activates
+pub(super) fn spawn_proof_pregeneration(state: AppState, address: String) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        match pregenerate_proofs(&state, &address).await {
+            Ok(count) => tracing::info!(campaign = %address, count, "🧾 Proofs pre-generated"),
+            Err(e) => tracing::error!(campaign = %address, "Failed to pre-generate proofs: {}", e),
+        }
+    })
+}
+
+/// The recipient `wallet` among `recipients`, if it has not claimed yet
+fn unclaimed_recipient<'a>(
+    recipients: &'a [Recipient],
+    wallet: &str,
+) -> Result<&'a Recipient, ShadowDropError> {
+    let recipient = recipients
+        .iter()
+        .find(|r| r.wallet == wallet)
+        .ok_or_else(|| {
+            ShadowDropError::NotFound("Wallet not found in campaign recipients".to_string())
+        })?;
+
+    if recipient.claimed {
+        return Err(ShadowDropError::BadRequest("Already claimed".to_string()));
+    }
+    Ok(recipient)
+}
+
+/// Leaves commit to what the recipient is paid after fees
+fn leaf_amount(recipient: &Recipient, fees: &FeeConfig) -> u64 {
+    net_amount(recipient.amount.parse::<u64>().unwrap_or(0), fees)
+}
+
+/// Hex-encoded proof data of one leaf of the tree with `root`
+fn proof_response(
+    root: &Hash,
+    proof: &MerkleProof,
+    wallet: &str,
+    amount: u64,
+    secret: &[u8; 32],
+) -> ProofResponse {
+    let nullifier = compute_nullifier(secret, proof.leaf_index);
+
+    ProofResponse {
+        merkle_root: hex::encode(root),
+        nullifier_hash: hex::encode(nullifier),
+        commitment: hex::encode(compute_commitment(root, &nullifier)),
+        leaf_index: proof.leaf_index,
+        merkle_path: proof.siblings.iter().map(hex::encode).collect(),
+        amount: amount.to_string(),
+        secret: hex::encode(secret),
+        claim_nullifier: hex::encode(compute_nullifier_v2(secret, proof.leaf_index, wallet)),
+    }
 }
@@ -0,0 +1,228 @@
+//! Recipient-facing routes across campaigns
+
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    middleware,
+    routing::{get, post},
+};
+use serde::Deserialize;
+
+use crate::{
+    common::{
+        error::ShadowDropError,
+        response::{ApiResponse, ApiSuccessResponse},
+    },
+    middleware::rate_limit::{RateLimiter, rate_limit},
+    models::{EligibilityCheck, RecipientCampaign},
+    state::AppState,
+};
+
+/// Requests per client IP and minute to the campaign listing, a scan over
+/// every campaign of the wallet
+pub const CAMPAIGNS_RATE_LIMIT_PER_MINUTE: u32 = 30;
+
+/// Campaigns one eligibility check may cover
+pub const MAX_ELIGIBILITY_CAMPAIGNS: usize = 20;
+
+/// Query parameters of a wallet's campaign listing
+#[derive(Debug, Default, Deserialize)]
+pub struct RecipientCampaignsQuery {
+    /// Only campaigns in this status, e.g. `active`
+    pub status: Option<String>,
+    /// Only campaigns the wallet has (true) or has not (false) claimed from
+    pub claimed: Option<bool>,
+}
+
+/// Request body of a bulk eligibility check
+#[derive(Debug, Deserialize)]
+pub struct VerifyEligibilityRequest {
+    pub wallet: String,
+    /// Campaign ids to check, at most `MAX_ELIGIBILITY_CAMPAIGNS`
+    pub campaign_ids: Vec<String>,
+}
+
+/// Build recipient routes
+pub fn recipient_routes() -> Router<AppState> {
+    let limiter = Arc::new(RateLimiter::new(
+        CAMPAIGNS_RATE_LIMIT_PER_MINUTE,
+        Duration::from_secs(60),
+    ));
+    Router::new()
+        .route("/verify-eligibility", post(verify_eligibility))
+        .route(
+            "/{wallet}/campaigns",
+            get(get_recipient_campaigns).layer(middleware::from_fn_with_state(limiter, rate_limit)),
+        )
+}
+
+/// GET /api/v1/recipient/:wallet/campaigns - Campaigns listing the wallet as recipient
+///
+/// Wallets in no campaign get an empty list, not 404.
+async fn get_recipient_campaigns(
+    State(state): State<AppState>,
+    Path(wallet): Path<String>,
+    Query(query): Query<RecipientCampaignsQuery>,
+) -> ApiResponse<Vec<RecipientCampaign>> {
+    let campaigns = state
+        .campaign_store
+        .campaigns_for_recipient(&wallet, query.status.as_deref(), query.claimed)
+        .await;
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(campaigns)
+        .with_message("Campaigns retrieved"))
+}
+
+/// POST /api/v1/recipient/verify-eligibility - Check a wallet against several campaigns
+///
+/// Results follow the order of `campaign_ids`; unknown ids are not eligible.
+async fn verify_eligibility(
+    State(state): State<AppState>,
+    Json(payload): Json<VerifyEligibilityRequest>,
+) -> ApiResponse<Vec<EligibilityCheck>> {
+    if payload.campaign_ids.is_empty() {
+        return Err(ShadowDropError::BadRequest(
+            "campaign_ids must not be empty".to_string(),
+        ));
+    }
+    if payload.campaign_ids.len() > MAX_ELIGIBILITY_CAMPAIGNS {
+        return Err(ShadowDropError::BadRequest(format!(
+            "At most {} campaign_ids can be checked at once",
+            MAX_ELIGIBILITY_CAMPAIGNS
+        )));
+    }
+
+    let checks = state
+        .campaign_store
+        .check_eligibility_many(&payload.wallet, &payload.campaign_ids)
+        .await?;
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(checks)
+        .with_message("Eligibility checked"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::ClaimOutcome,
+        routes::{
+            app_routes,
+            test_helpers::{SeedCampaign, seed_campaign_as, send, test_state},
+        },
+        state::AppState,
+    };
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use sqlx::PgPool;
+
+    async fn insert_recipient(state: &AppState, address: &str, status: &str, wallet: &str, claimed: bool) {
+        let campaign = SeedCampaign {
+            id: Some(address),
+            address,
+            status,
+            amount: "2500000000",
+            ..SeedCampaign::default()
+        };
+        seed_campaign_as(state, &[wallet], campaign).await;
+        if claimed {
+            let mut nullifier = [0u8; 32];
+            nullifier[..address.len()].copy_from_slice(address.as_bytes());
+            let outcome = state.campaign_store.mark_claimed(address, wallet, None, &nullifier).await;
+            assert!(matches!(outcome, ClaimOutcome::Claimed { .. }));
+        }
+    }
+
+    fn list(query: &str) -> Request<Body> {
+        Request::get(format!("/api/v1/recipient/wallet/campaigns{query}"))
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_wallet_campaigns_filter_by_status_and_claim(pool: PgPool) {
+        let state = test_state(pool);
+        insert_recipient(&state, "live", "active", "wallet", false).await;
+        insert_recipient(&state, "done", "active", "wallet", true).await;
+        insert_recipient(&state, "later", "draft", "wallet", false).await;
+        insert_recipient(&state, "other", "active", "someone", false).await;
+        let app = app_routes(state);
+
+        let (status, body) = send(&app, list("")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"].as_array().unwrap().len(), 3);
+
+        let (_, body) = send(&app, list("?status=active&claimed=false")).await;
+        assert_eq!(body["data"].as_array().unwrap().len(), 1);
+        assert_eq!(body["data"][0]["campaign_id"], "live");
+        assert_eq!(body["data"][0]["amount_sol"], 2.5);
+        assert_eq!(body["data"][0]["claim_status"], "unclaimed");
+
+        let (_, body) = send(&app, list("?claimed=true")).await;
+        assert_eq!(body["data"][0]["claim_status"], "claimed");
+
+        let request = Request::get("/api/v1/recipient/nobody/campaigns").body(Body::empty()).unwrap();
+        let (status, body) = send(&app, request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"], serde_json::json!([]));
+    }
+
+    #[sqlx::test]
+    async fn test_wallet_campaigns_are_rate_limited(pool: PgPool) {
+        let app = app_routes(test_state(pool));
+        for _ in 0..CAMPAIGNS_RATE_LIMIT_PER_MINUTE {
+            assert_eq!(send(&app, list("")).await.0, StatusCode::OK);
+        }
+        assert_eq!(send(&app, list("")).await.0, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    fn verify(body: serde_json::Value) -> Request<Body> {
+        Request::post("/api/v1/recipient/verify-eligibility")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_verify_eligibility_in_input_order(pool: PgPool) {
+        let state = test_state(pool);
+        insert_recipient(&state, "live", "active", "wallet", false).await;
+        insert_recipient(&state, "done", "active", "wallet", true).await;
+        insert_recipient(&state, "other", "active", "someone", false).await;
+        let app = app_routes(state);
+
+        let (status, body) = send(
+            &app,
+            verify(serde_json::json!({
+                "wallet": "wallet",
+                "campaign_ids": ["done", "other", "missing", "live"]
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body["data"],
+            serde_json::json!([
+                {"campaign_id": "done", "eligible": true, "amount_sol": 2.5, "claim_status": "claimed"},
+                {"campaign_id": "other", "eligible": false, "amount_sol": null, "claim_status": null},
+                {"campaign_id": "missing", "eligible": false, "amount_sol": null, "claim_status": null},
+                {"campaign_id": "live", "eligible": true, "amount_sol": 2.5, "claim_status": "unclaimed"},
+            ])
+        );
+
+        let too_many: Vec<String> =
+            (0..=MAX_ELIGIBILITY_CAMPAIGNS).map(|i| i.to_string()).collect();
+        let (status, _) = send(
+            &app,
+            verify(serde_json::json!({"wallet": "wallet", "campaign_ids": too_many})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
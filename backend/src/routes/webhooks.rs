@@ -0,0 +1,170 @@
+//! Admin routes over dead-lettered claim webhooks
+
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    common::{
+        error::ShadowDropError,
+        response::{ApiResponse, ApiSuccessResponse},
+    },
+    middleware::admin_auth::AdminAuth,
+    models::DeadLetter,
+    state::AppState,
+    webhooks,
+};
+
+/// Dead letters listed when no `limit` is given
+const DEFAULT_DLQ_LIMIT: i64 = 100;
+
+/// Query parameters of the dead letter listing
+#[derive(Debug, Default, Deserialize)]
+pub struct DeadLetterQuery {
+    /// Only events with this status, `pending` or `failed`
+    pub status: Option<String>,
+    /// At most this many events, newest first
+    pub limit: Option<i64>,
+}
+
+/// Outcome of replaying a dead letter
+#[derive(Debug, Serialize)]
+pub struct ReplayResponse {
+    pub id: Uuid,
+    pub delivered: bool,
+    /// The event as left in the queue when the replay failed
+    pub dead_letter: Option<DeadLetter>,
+}
+
+/// Build admin routes
+pub fn admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/webhook-dlq", get(list_dead_letters))
+        .route("/webhook-dlq/{id}/replay", post(replay_dead_letter))
+}
+
+/// GET /api/v1/admin/webhook-dlq - Claim webhooks whose delivery failed (admin only)
+async fn list_dead_letters(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Query(query): Query<DeadLetterQuery>,
+) -> ApiResponse<Vec<DeadLetter>> {
+    let limit = query.limit.unwrap_or(DEFAULT_DLQ_LIMIT).clamp(1, 1000);
+    let events = state.webhook_dlq.list(query.status.as_deref(), limit).await?;
+    Ok(ApiSuccessResponse::default().with_data(events))
+}
+
+/// POST /api/v1/admin/webhook-dlq/:id/replay - Retry a dead letter now (admin only)
+///
+/// Works on failed events too. The replay counts as a retry: a failed one
+/// is recorded, and a delivered event leaves the queue.
+async fn replay_dead_letter(
+    _admin: AdminAuth,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResponse<ReplayResponse> {
+    let url = state.config.webhook.claim_url.as_deref().ok_or_else(|| {
+        ShadowDropError::BadRequest("No CLAIM_WEBHOOK_URL is configured".to_string())
+    })?;
+    let event = state
+        .webhook_dlq
+        .get(id)
+        .await?
+        .ok_or_else(|| ShadowDropError::NotFound("Dead letter not found".to_string()))?;
+
+    let dead_letter = webhooks::retry(&state.webhook_dlq, url, &event).await?;
+    let delivered = dead_letter.is_none();
+    tracing::info!(id = %id, delivered, "🔁 Dead-lettered webhook replayed");
+    Ok(ApiSuccessResponse::default()
+        .with_data(ReplayResponse { id, delivered, dead_letter })
+        .with_message(if delivered { "Webhook delivered" } else { "Webhook delivery failed" }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode, header},
+    };
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{
+        common::{merkle::compute_nullifier_v2, solana_tx::tests::serve_block_engine},
+        routes::{
+            app_routes,
+            test_helpers::{TEST_ADMIN_KEY, TEST_CAMPAIGN_ADDRESS, seed_campaign_with, send, test_state},
+        },
+    };
+
+    fn admin(request: axum::http::request::Builder) -> Request<Body> {
+        request
+            .header(header::AUTHORIZATION, format!("Bearer {TEST_ADMIN_KEY}"))
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_failed_claim_webhook_is_dead_lettered_and_replayed(pool: PgPool) {
+        let state = test_state(pool);
+        seed_campaign_with(&state, &["first"]).await;
+        let (url, server) = serve_block_engine(vec![(503, "{}"), (200, "{}")]).await;
+        let mut config = (*state.config).clone();
+        config.webhook.claim_url = Some(url);
+        let state = AppState { config: Arc::new(config), ..state };
+        let app = app_routes(state.clone());
+
+        let secret = [3u8; 32];
+        let claim = Request::post(format!("/api/v1/campaigns/{TEST_CAMPAIGN_ADDRESS}/claim"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "wallet": "first",
+                    "nullifier_hash": hex::encode(compute_nullifier_v2(&secret, 0, "first")),
+                    "secret": hex::encode(secret),
+                    "leaf_index": 0,
+                })
+                .to_string(),
+            ))
+            .unwrap();
+        let (status, _) = send(&app, claim).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Delivered in the background, so wait for the dead letter
+        let mut events = Vec::new();
+        for _ in 0..50 {
+            events = state.webhook_dlq.list(None, 10).await.unwrap();
+            if !events.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].payload["wallet"], "first");
+        assert_eq!(events[0].payload["amount"], "100");
+        assert_eq!(events[0].error.as_deref(), Some("webhook target returned HTTP 503"));
+
+        let (status, body) = send(&app, admin(Request::get("/api/v1/admin/webhook-dlq?status=pending"))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"][0]["id"], events[0].id.to_string());
+        let (_, body) = send(&app, admin(Request::get("/api/v1/admin/webhook-dlq?status=failed"))).await;
+        assert_eq!(body["data"], serde_json::json!([]));
+        let (status, _) = send(&app, Request::get("/api/v1/admin/webhook-dlq").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let replay = |id: Uuid| admin(Request::post(format!("/api/v1/admin/webhook-dlq/{id}/replay")));
+        let (status, body) = send(&app, replay(events[0].id)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["delivered"], true);
+        assert!(state.webhook_dlq.get(events[0].id).await.unwrap().is_none());
+        let (status, _) = send(&app, replay(events[0].id)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(server.await.unwrap()[1].contains(r#""event":"claim""#));
+    }
+}
@@ -2,18 +2,24 @@
 
 use axum::{
     extract::{Path, State},
-    http::StatusCode,
+    middleware,
     routing::post,
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Instant};
 
 use crate::{
     common::{
-        merkle::{compute_nullifier, generate_secret, MerkleTree},
-        response::{ApiErrorResponse, ApiResponse, ApiSuccessResponse},
+        merkle::{compute_commitment, compute_nullifier, compute_nullifier_v2, generate_secret, MerkleTree},
+        error::ShadowDropError,
+        fees::net_amount,
+        response::{ApiResponse, ApiSuccessResponse},
+        schema::CLAIM_REQUEST_SCHEMA,
     },
+    metrics,
+    middleware::json_schema::validate_json_body,
+    models::{Campaign, Recipient},
     state::AppState,
     zk::{SunspotProver, ZkProofInput},
 };
@@ -35,7 +41,9 @@ pub struct ZkProofResponse {
     pub nullifier_hash: String,
     /// Nullifier bytes for contract (32 bytes, hex)
     pub nullifier: String,
-    /// Claim amount in lamports
+    /// Commitment hash(merkle_root, nullifier) for programs verifying a single value (hex)
+    pub commitment: String,
+    /// Claim amount in lamports, after the protocol fee
     pub amount: u64,
     /// Secret used (for reference)
     pub secret: String,
@@ -43,11 +51,19 @@ pub struct ZkProofResponse {
     pub merkle_root: String,
     /// Leaf index
     pub leaf_index: usize,
+    /// Wallet-bound nullifier a backend claim spends, which a claim
+    /// signature covers (hex)
+    pub claim_nullifier: String,
 }
 
 /// Build ZK proof routes
 pub fn zk_proof_routes() -> Router<AppState> {
-    Router::new().route("/{address}/generate", post(generate_zk_proof))
+    Router::new()
+        .route("/{address}/generate", post(generate_zk_proof))
+        .route_layer(middleware::from_fn_with_state(
+            &*CLAIM_REQUEST_SCHEMA,
+            validate_json_body,
+        ))
 }
 
 /// POST /api/v1/zk-proofs/:address/generate - Generate Sunspot ZK proof for claim
@@ -56,43 +72,73 @@ async fn generate_zk_proof(
     Path(address): Path<String>,
     Json(body): Json<GenerateZkProofRequest>,
 ) -> ApiResponse<ZkProofResponse> {
+    let (proof, message) = prepare_zk_proof(&state, &address, &body.wallet).await?.prove().await?;
+
+    Ok(ApiSuccessResponse::default()
+        .with_data(proof)
+        .with_message(message))
+}
+
+/// Prover input of one claim, with the response fields known before proving
+pub(crate) struct PreparedZkProof {
+    input: ZkProofInput,
+    /// Response with a mock (all-zero) Groth16 proof
+    response: ZkProofResponse,
+}
+
+/// Campaign of `address` and the entry of `wallet`, if it has not claimed yet
+pub(crate) async fn claimable_recipient(
+    state: &AppState,
+    address: &str,
+    wallet: &str,
+) -> Result<(Campaign, Recipient), ShadowDropError> {
     // Get campaign
-    let campaign = match state.campaign_store.get(&address).await {
-        Some(c) => c,
-        None => {
-            return Err(ApiErrorResponse::default()
-                .with_code(StatusCode::NOT_FOUND)
-                .with_message("Campaign not found"))
-        }
-    };
+    let campaign = state
+        .campaign_store
+        .get(address)
+        .await
+        .ok_or_else(|| ShadowDropError::NotFound("Campaign not found".to_string()))?;
 
     // Find recipient
-    let recipient = match campaign.recipients.iter().find(|r| r.wallet == body.wallet) {
-        Some(r) => r,
-        None => {
-            return Err(ApiErrorResponse::default()
-                .with_code(StatusCode::NOT_FOUND)
-                .with_message("Wallet not found in campaign recipients"))
-        }
-    };
+    let recipient = campaign
+        .recipients
+        .iter()
+        .find(|r| r.wallet == wallet)
+        .cloned()
+        .ok_or_else(|| {
+            ShadowDropError::NotFound("Wallet not found in campaign recipients".to_string())
+        })?;
 
     // Check if already claimed
     if recipient.claimed {
-        return Err(ApiErrorResponse::default()
-            .with_code(StatusCode::BAD_REQUEST)
-            .with_message("Already claimed"));
+        return Err(ShadowDropError::BadRequest("Already claimed".to_string()));
     }
 
+    Ok((campaign, recipient))
+}
+
+/// Look up the claim of `wallet` in a campaign and build its prover input
+pub(crate) async fn prepare_zk_proof(
+    state: &AppState,
+    address: &str,
+    wallet: &str,
+) -> Result<PreparedZkProof, ShadowDropError> {
+    let (campaign, recipient) = claimable_recipient(state, address, wallet).await?;
+
+    // Amount paid out after the protocol fee; leaves and the circuit use it
+    let claim_amount = net_amount(recipient.amount.parse::<u64>().unwrap_or(0), &state.config.fees);
+
     // Generate secret for this claim
     let secret = generate_secret();
 
     // Build merkle tree with secrets
-    let recipients_with_secrets: Vec<(String, u64, [u8; 32])> = campaign
+    let recipients_with_secrets: Vec<(usize, String, u64, [u8; 32])> = campaign
         .recipients
         .iter()
-        .map(|r| {
+        .enumerate()
+        .map(|(i, r)| {
             let mut recipient_secret = [0u8; 32];
-            if r.wallet == body.wallet {
+            if r.wallet == wallet {
                 recipient_secret = secret;
             } else {
                 // Use wallet hash as placeholder secret for other recipients
@@ -101,35 +147,39 @@ async fn generate_zk_proof(
                     recipient_secret[i % 32] ^= *b;
                 }
             }
-            let amount_u64 = r.amount.parse::<u64>().unwrap_or(0);
-            (r.wallet.clone(), amount_u64, recipient_secret)
+            // Leaves commit to what the recipient is paid after fees
+            let amount_u64 = net_amount(r.amount.parse::<u64>().unwrap_or(0), &state.config.fees);
+            // Removed recipients leave gaps; each leaf stays at its stored index
+            let leaf_index = r.leaf_index.map_or(i, |leaf_index| leaf_index as usize);
+            (leaf_index, r.wallet.clone(), amount_u64, recipient_secret)
         })
         .collect();
 
-    let tree = MerkleTree::from_recipients(&recipients_with_secrets);
+    let tree = MerkleTree::from_indexed_recipients(&recipients_with_secrets)?;
+    metrics::observe_leaf_hashes(recipients_with_secrets.len());
 
     // Get proof for wallet
-    let proof = match tree.get_proof(&body.wallet) {
-        Some(p) => p,
-        None => {
-            return Err(ApiErrorResponse::default()
-                .with_code(StatusCode::INTERNAL_SERVER_ERROR)
-                .with_message("Failed to generate merkle proof"))
-        }
-    };
+    let start = Instant::now();
+    let proof = tree
+        .get_proof(wallet)
+        .ok_or_else(|| ShadowDropError::Internal("Failed to generate merkle proof".to_string()))?;
+    metrics::observe_merkle_proofs(tree.depth(), 1, start);
 
-    // Compute nullifier
+    // The circuit checks the v1 nullifier; backend claims derive the
+    // wallet-bound v2 nullifier from the secret themselves
     let nullifier = compute_nullifier(&secret, proof.leaf_index);
 
+    let commitment = compute_commitment(&tree.root(), &nullifier);
+
     // Convert wallet to field element (use first 31 bytes of pubkey)
-    let wallet_field = wallet_to_field(&body.wallet);
+    let wallet_field = wallet_to_field(wallet);
 
     // Prepare ZK proof input
-    let zk_input = ZkProofInput {
+    let input = ZkProofInput {
         merkle_root: format!("0x{}", hex::encode(tree.root())),
         nullifier_hash: format!("0x{}", hex::encode(nullifier)),
         recipient: format!("0x{}", wallet_field),
-        amount: recipient.amount.clone(), // Use raw string directly
+        amount: claim_amount.to_string(),
         secret: format!("0x{}", hex::encode(secret)),
         leaf_index: proof.leaf_index as u64,
         merkle_path: proof
@@ -139,58 +189,62 @@ async fn generate_zk_proof(
             .collect(),
     };
 
-    // Initialize Sunspot prover
-    let circuits_dir = PathBuf::from(
-        std::env::var("CIRCUITS_DIR")
-            .unwrap_or_else(|_| "../circuits".to_string()),
-    );
-    let prover = SunspotProver::new(circuits_dir);
-
-    // Check prover health
-    if let Err(e) = prover.health_check().await {
-        tracing::warn!("Sunspot prover not configured: {}", e);
-
-        // Return mock proof for development (when Sunspot not available)
-        return Ok(ApiSuccessResponse::default()
-            .with_data(ZkProofResponse {
-                groth16_proof: "0x".to_string() + &"00".repeat(256),
-                public_inputs: format!(
-                    "{}{}{}",
-                    hex::encode(tree.root()),
-                    hex::encode(nullifier),
-                    wallet_field
-                ),
-                nullifier_hash: hex::encode(nullifier),
-                nullifier: hex::encode(nullifier),
-                amount: recipient.amount.parse::<u64>().unwrap_or(0),
-                secret: hex::encode(secret),
-                merkle_root: hex::encode(tree.root()),
-                leaf_index: proof.leaf_index,
-            })
-            .with_message("Mock proof generated (Sunspot not configured)"));
-    }
+    let response = ZkProofResponse {
+        groth16_proof: "0x".to_string() + &"00".repeat(256),
+        public_inputs: format!(
+            "{}{}{}",
+            hex::encode(tree.root()),
+            hex::encode(nullifier),
+            wallet_field
+        ),
+        nullifier_hash: hex::encode(nullifier),
+        nullifier: hex::encode(nullifier),
+        commitment: hex::encode(commitment),
+        amount: claim_amount,
+        secret: hex::encode(secret),
+        merkle_root: hex::encode(tree.root()),
+        leaf_index: proof.leaf_index,
+        claim_nullifier: hex::encode(compute_nullifier_v2(&secret, proof.leaf_index, wallet)),
+    };
+
+    Ok(PreparedZkProof { input, response })
+}
+
+impl PreparedZkProof {
+    /// Run the Sunspot prover, returning the proof and a message describing it.
+    ///
+    /// The prover shells out and blocks; call it from a blocking task when
+    /// it must not hold up the runtime.
+    pub(crate) async fn prove(self) -> Result<(ZkProofResponse, &'static str), ShadowDropError> {
+        // Initialize Sunspot prover
+        let circuits_dir = PathBuf::from(
+            std::env::var("CIRCUITS_DIR")
+                .unwrap_or_else(|_| "../circuits".to_string()),
+        );
+        let prover = SunspotProver::new(circuits_dir);
 
-    // Generate real proof
-    match prover.generate_proof(zk_input).await {
-        Ok(zk_output) => {
-            Ok(ApiSuccessResponse::default()
-                .with_data(ZkProofResponse {
+        // Check prover health
+        if let Err(e) = prover.health_check().await {
+            tracing::warn!("Sunspot prover not configured: {}", e);
+
+            // Return mock proof for development (when Sunspot not available)
+            return Ok((self.response, "Mock proof generated (Sunspot not configured)"));
+        }
+
+        // Generate real proof
+        match prover.generate_proof(self.input).await {
+            Ok(zk_output) => Ok((
+                ZkProofResponse {
                     groth16_proof: zk_output.proof,
                     public_inputs: zk_output.public_inputs,
-                    nullifier_hash: hex::encode(nullifier),
-                    nullifier: hex::encode(nullifier),
-                    amount: recipient.amount.parse::<u64>().unwrap_or(0),
-                    secret: hex::encode(secret),
-                    merkle_root: hex::encode(tree.root()),
-                    leaf_index: proof.leaf_index,
-                })
-                .with_message("ZK proof generated successfully"))
-        }
-        Err(e) => {
-            tracing::error!("Failed to generate ZK proof: {}", e);
-            Err(ApiErrorResponse::default()
-                .with_code(StatusCode::INTERNAL_SERVER_ERROR)
-                .with_message(&format!("Failed to generate ZK proof: {}", e)))
+                    ..self.response
+                },
+                "ZK proof generated successfully",
+            )),
+            Err(e) => {
+                tracing::error!("Failed to generate ZK proof: {}", e);
+                Err(ShadowDropError::Internal(format!("Failed to generate ZK proof: {}", e)))
+            }
         }
     }
 }
@@ -1,23 +1,87 @@
-use std::{sync::Arc, time::Instant};
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize},
+    },
+    time::{Duration, Instant},
+};
 
+use crate::common::circuit_breaker::CircuitBreakers;
+use crate::common::stale_cache::{STALE_MAX_AGE, StaleCache};
+use crate::common::subscribers::Subscribers;
 use crate::config::Config;
-use crate::models::CampaignStore;
+use crate::middleware::conditional_get::EtagCache;
+use crate::models::{
+    CampaignInfo, CampaignStore, ClaimTransactionStore, IdempotencyStore, ProofJob, ProofJobStore,
+    SiwsNonceStore, WebhookDlqStore,
+};
+use crate::nullifiers::{NullifierBloomFilter, NullifierDB, PgNullifierDB};
+use crate::proof_cache::ProofCache;
+use crate::tree_cache::MerkleTreeCache;
 
 use sqlx::PgPool;
+use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct AppState {
     pub start_time: Instant,
     pub config: Arc<Config>,
     pub campaign_store: CampaignStore,
+    pub idempotency_store: IdempotencyStore,
+    pub proof_jobs: ProofJobStore,
+    pub siws_nonces: SiwsNonceStore,
+    pub claim_transactions: ClaimTransactionStore,
+    /// Claim webhooks whose delivery failed
+    pub webhook_dlq: WebhookDlqStore,
+    /// SSE clients waiting for a proof job to finish
+    pub proof_job_subscribers: Arc<Subscribers<Uuid, ProofJob>>,
+    pub nullifiers: Arc<dyn NullifierDB>,
+    /// Spent nullifiers seen by this process, loaded at startup
+    pub nullifier_bloom: NullifierBloomFilter,
+    pub proof_cache: Arc<ProofCache>,
+    /// Campaign trees computed proofs come from, by campaign id
+    pub tree_cache: MerkleTreeCache,
+    /// Campaign info last read per address, served if the database is unreachable
+    pub campaign_info_cache: Arc<StaleCache<CampaignInfo>>,
+    /// Circuit breaker per Solana RPC endpoint
+    pub rpc_circuits: Arc<CircuitBreakers>,
+    /// Validators of committed campaign roots, answered without a query
+    pub root_etags: EtagCache,
+    /// Requests being handled, drained on shutdown
+    pub active_requests: Arc<AtomicUsize>,
+    /// Set on SIGTERM; new requests are refused from then on
+    pub shutting_down: Arc<AtomicBool>,
 }
 
 impl AppState {
     pub fn new(config: Arc<Config>, db: PgPool) -> Self {
+        let retention_days = config.nullifier.retention_days;
+        let nullifiers = PgNullifierDB::new(db.clone(), retention_days);
+        let proof_cache = ProofCache::new(config.cache.redis_url.as_deref());
+        let tree_cache = MerkleTreeCache::new(config.cache.tree_cache_size);
+        let rpc_circuits = CircuitBreakers::new(
+            config.campaign.rpc_failure_threshold,
+            Duration::from_secs(config.campaign.rpc_circuit_open_secs),
+        );
         AppState {
             start_time: Instant::now(),
             config,
-            campaign_store: CampaignStore::new(db),
+            campaign_store: CampaignStore::new(db.clone(), retention_days),
+            idempotency_store: IdempotencyStore::new(db.clone()),
+            proof_jobs: ProofJobStore::new(db.clone()),
+            siws_nonces: SiwsNonceStore::new(db.clone()),
+            claim_transactions: ClaimTransactionStore::new(db.clone()),
+            webhook_dlq: WebhookDlqStore::new(db),
+            proof_job_subscribers: Arc::new(Subscribers::new()),
+            nullifiers: Arc::new(nullifiers),
+            nullifier_bloom: NullifierBloomFilter::default(),
+            proof_cache: Arc::new(proof_cache),
+            tree_cache,
+            campaign_info_cache: Arc::new(StaleCache::new(STALE_MAX_AGE)),
+            rpc_circuits: Arc::new(rpc_circuits),
+            root_etags: EtagCache::default(),
+            active_requests: Arc::default(),
+            shutting_down: Arc::default(),
         }
     }
 }
@@ -0,0 +1,107 @@
+//! Cache of campaign Merkle trees
+//!
+//! Building a campaign's tree hashes every leaf, so computed proofs would
+//! rebuild it per request. The most recently used trees are kept here by
+//! campaign id, up to `TREE_CACHE_SIZE`. Entries are `Arc`s: a lookup clones
+//! one and releases the lock, so readers never hold it while building proofs.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use hashlink::LruCache;
+use serde::Serialize;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::common::merkle::MerkleTree;
+
+/// Fill and hit rate of the tree cache
+#[derive(Debug, Serialize)]
+pub struct TreeCacheStats {
+    pub size: usize,
+    pub capacity: usize,
+    /// Share of lookups that were hits, 0 before the first lookup
+    pub hit_rate: f64,
+}
+
+/// Least recently used campaign trees
+#[derive(Debug, Clone)]
+pub struct MerkleTreeCache {
+    inner: Arc<Mutex<LruCache<Uuid, Arc<MerkleTree>>>>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+impl MerkleTreeCache {
+    /// A cache of up to `capacity` trees, at least one
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(LruCache::new(capacity.max(1)))),
+            hits: Arc::default(),
+            misses: Arc::default(),
+        }
+    }
+
+    /// The campaign's tree, marked most recently used
+    pub async fn get(&self, campaign_id: &Uuid) -> Option<Arc<MerkleTree>> {
+        let tree = self.inner.lock().await.get(campaign_id).cloned();
+        let counter = if tree.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        tree
+    }
+
+    /// Cache a tree, evicting the least recently used one when full
+    pub async fn insert(&self, campaign_id: Uuid, tree: Arc<MerkleTree>) {
+        self.inner.lock().await.insert(campaign_id, tree);
+    }
+
+    /// Forget a campaign's tree, e.g. after its recipients change
+    pub async fn remove(&self, campaign_id: &Uuid) {
+        self.inner.lock().await.remove(campaign_id);
+    }
+
+    pub async fn stats(&self) -> TreeCacheStats {
+        let (size, capacity) = {
+            let inner = self.inner.lock().await;
+            (inner.len(), inner.capacity())
+        };
+        let hits = self.hits.load(Ordering::Relaxed);
+        let lookups = hits + self.misses.load(Ordering::Relaxed);
+        TreeCacheStats {
+            size,
+            capacity,
+            hit_rate: if lookups == 0 { 0.0 } else { hits as f64 / lookups as f64 },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(wallet: &str) -> Arc<MerkleTree> {
+        Arc::new(MerkleTree::from_recipients(&[(wallet.to_string(), 1, [1; 32])]).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_least_recently_used_tree_is_evicted() {
+        let cache = MerkleTreeCache::new(2);
+        let (first, second, third) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        cache.insert(first, tree("a")).await;
+        cache.insert(second, tree("b")).await;
+        // Using the first makes the second the least recently used
+        assert!(cache.get(&first).await.unwrap().contains("a"));
+        cache.insert(third, tree("c")).await;
+
+        assert!(cache.get(&second).await.is_none());
+        assert!(cache.get(&third).await.is_some());
+        cache.remove(&first).await;
+        assert!(cache.get(&first).await.is_none());
+
+        let stats = cache.stats().await;
+        assert_eq!((stats.size, stats.capacity), (1, 2));
+        assert_eq!(stats.hit_rate, 0.5);
+    }
+}
@@ -0,0 +1,156 @@
+//! Claim webhooks
+//!
+//! With `CLAIM_WEBHOOK_URL` set, every recorded claim is POSTed there as
+//! JSON. A delivery that fails (network error, timeout or a non-2xx answer)
+//! is not lost: it goes to the `webhook_dlq` table, from which
+//! `spawn_retry_task` retries it with exponential backoff until
+//! `WEBHOOK_MAX_RETRIES` retries have failed and it is marked failed. Admins
+//! can list dead letters and replay one by hand.
+
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::task::JoinHandle;
+use url::Url;
+
+use crate::{
+    common::http,
+    models::{DeadLetter, WEBHOOK_MAX_RETRIES, WebhookDlqStore},
+    state::AppState,
+};
+
+/// How long a webhook target may take to answer
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Dead letters retried per poll of `spawn_retry_task`
+const RETRY_BATCH: i64 = 100;
+
+/// POST `payload` to `url`, with why the delivery failed if it did
+pub async fn deliver(url: &str, payload: &Value) -> Result<(), String> {
+    let url = Url::parse(url).map_err(|e| format!("invalid webhook URL: {}", e))?;
+    let body = payload.to_string();
+    let response = tokio::task::spawn_blocking(move || {
+        http::send("POST", &url, &[("Content-Type", "application/json")], Some(&body), WEBHOOK_TIMEOUT)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    if !(200..300).contains(&response.status) {
+        return Err(format!("webhook target returned HTTP {}", response.status));
+    }
+    Ok(())
+}
+
+/// Send `payload` for a claim on the campaign at `address` in the
+/// background, dead-lettering it if the delivery fails. None when no
+/// webhook URL is configured.
+pub fn notify_claim(state: &AppState, address: &str, payload: Value) -> Option<JoinHandle<()>> {
+    let url = state.config.webhook.claim_url.clone()?;
+    let (store, address) = (state.webhook_dlq.clone(), address.to_string());
+    Some(tokio::spawn(async move {
+        let Err(error) = deliver(&url, &payload).await else {
+            return;
+        };
+        tracing::warn!(campaign = %address, "Claim webhook failed, dead-lettered: {}", error);
+        if let Err(e) = store.insert(&address, &payload, &error).await {
+            tracing::error!(campaign = %address, payload = %payload, "Failed to dead-letter claim webhook: {}", e);
+        }
+    }))
+}
+
+/// Retry a dead letter now. A delivered event leaves the queue and None is
+/// returned; otherwise the failure is recorded and the updated event returned.
+pub async fn retry(store: &WebhookDlqStore, url: &str, event: &DeadLetter) -> Result<Option<DeadLetter>, sqlx::Error> {
+    match deliver(url, &event.payload).await {
+        Ok(()) => {
+            store.delete(event.id).await?;
+            Ok(None)
+        }
+        Err(error) => store.record_failure(event.id, &error).await,
+    }
+}
+
+/// Every `period`, retry the dead letters to `url` that are due
+pub fn spawn_retry_task(store: WebhookDlqStore, url: String, period: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(period);
+        loop {
+            ticker.tick().await;
+            let due = match store.due(RETRY_BATCH).await {
+                Ok(due) => due,
+                Err(e) => {
+                    tracing::error!("Failed to load due webhooks: {}", e);
+                    continue;
+                }
+            };
+            for event in due {
+                match retry(&store, &url, &event).await {
+                    Ok(None) => tracing::info!(id = %event.id, "📬 Dead-lettered webhook delivered"),
+                    Ok(Some(event)) if event.attempt_count >= WEBHOOK_MAX_RETRIES => {
+                        tracing::error!(id = %event.id, error = ?event.error, "Webhook retries exhausted, marked failed");
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::error!(id = %event.id, "Failed to record webhook retry: {}", e),
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{
+        common::solana_tx::tests::serve_block_engine,
+        models::{WEBHOOK_FAILED, WEBHOOK_PENDING},
+        routes::test_helpers::{TEST_CAMPAIGN_ADDRESS, seed_campaign_with, test_state},
+    };
+
+    #[sqlx::test]
+    async fn test_retries_back_off_until_marked_failed(pool: PgPool) {
+        let state = test_state(pool);
+        seed_campaign_with(&state, &["first"]).await;
+        let store = &state.webhook_dlq;
+        let payload = serde_json::json!({ "event": "claim", "wallet": "first" });
+        let id = store.insert(TEST_CAMPAIGN_ADDRESS, &payload, "timed out").await.unwrap().unwrap();
+        assert_eq!(store.insert("no-such-campaign", &payload, "timed out").await.unwrap(), None);
+
+        let event = store.get(id).await.unwrap().unwrap();
+        assert_eq!((event.status.as_str(), event.attempt_count), (WEBHOOK_PENDING, 0));
+        assert_eq!((event.next_attempt_at - event.last_attempt_at).num_seconds(), 1);
+
+        for retry in 1..=WEBHOOK_MAX_RETRIES {
+            let event = store.record_failure(id, "HTTP 503").await.unwrap().unwrap();
+            assert_eq!(event.attempt_count, retry);
+            if retry < WEBHOOK_MAX_RETRIES {
+                assert_eq!(event.status, WEBHOOK_PENDING);
+                assert_eq!((event.next_attempt_at - event.last_attempt_at).num_seconds(), 1 << retry);
+            }
+        }
+        let event = store.get(id).await.unwrap().unwrap();
+        assert_eq!(event.status, WEBHOOK_FAILED);
+        assert_eq!(event.error.as_deref(), Some("HTTP 503"));
+        // Failed events are left for a manual replay
+        assert!(store.due(10).await.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn test_retry_delivers_or_records_the_failure(pool: PgPool) {
+        let state = test_state(pool);
+        seed_campaign_with(&state, &["first"]).await;
+        let store = &state.webhook_dlq;
+        let payload = serde_json::json!({ "event": "claim", "wallet": "first" });
+        let id = store.insert(TEST_CAMPAIGN_ADDRESS, &payload, "timed out").await.unwrap().unwrap();
+        let event = store.get(id).await.unwrap().unwrap();
+
+        let (url, server) = serve_block_engine(vec![(500, "{}"), (200, "{}")]).await;
+        let event = retry(store, &url, &event).await.unwrap().unwrap();
+        assert_eq!(event.attempt_count, 1);
+        assert_eq!(event.error.as_deref(), Some("webhook target returned HTTP 500"));
+        assert_eq!(retry(store, &url, &event).await.unwrap().map(|e| e.id), None);
+        assert!(store.get(id).await.unwrap().is_none());
+        let requests = server.await.unwrap();
+        assert!(requests[1].ends_with(&payload.to_string()));
+    }
+}
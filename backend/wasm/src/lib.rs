@@ -0,0 +1,24 @@
+//! `shadow_drop_core` for the browser: wasm-bindgen exports of
+//! `shadow_drop_core::wasm`, so frontends hash leaves and check proofs
+//! without a server round-trip
+//!
+//! Errors are thrown as JS strings. Build with `wasm-pack build --target web`;
+//! the crate is left out of the backend workspace so the server builds
+//! without the wasm toolchain.
+
+use shadow_drop_core::wasm;
+use wasm_bindgen::prelude::*;
+
+/// Leaf hash of a recipient, 32 bytes; throws unless `secret` is 32 bytes.
+/// `amount_lamports` is a `BigInt` on the JS side.
+#[wasm_bindgen]
+pub fn wasm_compute_leaf_hash(wallet: &str, amount_lamports: u64, secret: &[u8]) -> Result<Vec<u8>, JsValue> {
+    wasm::wasm_compute_leaf_hash(wallet, amount_lamports, secret).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Whether a proof, as JSON with hex `leaf` and `merkle_path`, verifies
+/// against a hex root; throws if either does not parse
+#[wasm_bindgen]
+pub fn wasm_verify_proof(proof_json: &str, root_hex: &str) -> Result<bool, JsValue> {
+    wasm::wasm_verify_proof(proof_json, root_hex).map_err(|e| JsValue::from_str(&e))
+}
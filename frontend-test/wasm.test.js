@@ -0,0 +1,67 @@
+// Calls the wasm-bindgen exports of backend/wasm from JavaScript. Build the
+// package first: `wasm-pack build --target web` in backend/wasm.
+import { readFileSync } from "node:fs";
+
+import {
+  initSync,
+  wasm_compute_leaf_hash,
+  wasm_verify_proof,
+} from "../backend/wasm/pkg/shadow_drop_wasm.js";
+
+initSync({
+  module: readFileSync(new URL("../backend/wasm/pkg/shadow_drop_wasm_bg.wasm", import.meta.url)),
+});
+
+// wallet2's leaf in the tree of wallet1 (100 lamports, secret [1; 32]) and
+// wallet2 (200 lamports, secret [2; 32]), as pinned in core's wasm tests
+const ROOT = "1cf3f347f6076525efdcae54414b5e5a36393f6d866ac4a80a0ae662e1973c1f";
+const LEAF = "199f3d75e27519b284e1933e0502e49b86edc4ca29758678e752bb154cf34319";
+const PROOF = {
+  leaf_index: 1,
+  leaf: LEAF,
+  merkle_path: [
+    "15a621d36f66e4e802ffc9e20412fe3691c5287979a562f3d99afb16236a0179",
+    "27a11f05a83f175e241b204b029061e853e326461fbee4d77262268c6f43449f",
+    "0cb4d837198181f1c8e5f3bc4aa43a30444b7ca85ef1d173d6c4d0265f9f6ef1",
+    "21783f55bf4de5cb25a27147a2a00f5976c00f469d10ea5dbc25864bc0f5b2a6",
+    "29b12404c1d865945e8814e78e881bb4403864b05a1ff5b59b380bc44c3617f3",
+    "1bb1eabb6471f8d27c91d489b41a485284ade30fd81cdc9bc3f0908609026bd2",
+    "184c8b767dbd09a5b90cf3864887771b038f0aeba6d54236e2ef108011dbab3a",
+    "165b769bbbf60d8ed4e4bd15c3eae1d6a593c7c1dff519df5098e19988324239",
+  ],
+};
+
+const toHex = (bytes) => Buffer.from(bytes).toString("hex");
+
+describe("wasm_compute_leaf_hash", () => {
+  test("matches the leaf the backend builds", () => {
+    const leaf = wasm_compute_leaf_hash("wallet2", 200n, new Uint8Array(32).fill(2));
+    expect(toHex(leaf)).toBe(LEAF);
+  });
+
+  test("throws unless the secret is 32 bytes", () => {
+    expect(() => wasm_compute_leaf_hash("wallet2", 200n, new Uint8Array(31))).toThrow(
+      "secret must be 32 bytes, got 31",
+    );
+  });
+});
+
+describe("wasm_verify_proof", () => {
+  const proofJson = JSON.stringify(PROOF);
+
+  test("accepts the proof against its root", () => {
+    expect(wasm_verify_proof(proofJson, ROOT)).toBe(true);
+    expect(wasm_verify_proof(proofJson, `0x${ROOT}`)).toBe(true);
+  });
+
+  test("rejects it against another root", () => {
+    expect(wasm_verify_proof(proofJson, "00".repeat(32))).toBe(false);
+    const tampered = { ...PROOF, merkle_path: [...PROOF.merkle_path].reverse() };
+    expect(wasm_verify_proof(JSON.stringify(tampered), ROOT)).toBe(false);
+  });
+
+  test("throws on input that does not parse", () => {
+    expect(() => wasm_verify_proof(proofJson, "0x00")).toThrow("root must be 32 hex-encoded bytes");
+    expect(() => wasm_verify_proof("{}", ROOT)).toThrow("invalid proof");
+  });
+});
@@ -1,7 +1,7 @@
 import { useState, useEffect, useMemo } from "react";
 import { WalletMultiButton } from "@solana/wallet-adapter-react-ui";
 import { useShadowDrop } from "../hooks/useShadowDrop";
-import { BN } from "@coral-xyz/anchor";
+import { BN, utils } from "@coral-xyz/anchor";
 import { PublicKey, SystemProgram, LAMPORTS_PER_SOL, ComputeBudgetProgram } from "@solana/web3.js";
 import { TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID, getAssociatedTokenAddress, createInitializeMintInstruction, createAssociatedTokenAccountInstruction, createMintToInstruction, getMinimumBalanceForRentExemptMint, MINT_SIZE } from "@solana/spl-token";
 import { createRpc } from '@lightprotocol/stateless.js';
@@ -1502,7 +1502,7 @@ function ClaimAirdrop() {
     const [success, setSuccess] = useState(false);
     const [lastTx, setLastTx] = useState("");
     const [lastCampaignName, setLastCampaignName] = useState("");
-    const { publicKey, program } = useShadowDrop();
+    const { publicKey, program, wallet } = useShadowDrop();
 
     // Fetch eligible campaigns on mount and when wallet changes
     useEffect(() => {
@@ -1537,7 +1537,7 @@ function ClaimAirdrop() {
 
             // Step 1: Generate ZK proof from backend
             console.log("🔐 Generating ZK proof for campaign:", campaign.name);
-            const { generateZkProof, markClaimed } = await import("../lib/api");
+            const { claimSigningMessage, generateZkProof, markClaimed } = await import("../lib/api");
             const { deriveNullifierRecordPDA, ZK_VERIFIER_PROGRAM_ID } = await import("../lib/pda");
 
             const proofData = await generateZkProof(campaign.address, publicKey.toBase58());
@@ -1648,8 +1648,11 @@ function ClaimAirdrop() {
             console.log("✅ ZK Claim tx:", tx);
             console.log("⚡ ZK Proof verified on-chain! Nullifier:", proofData.nullifier.slice(0, 16) + "...");
 
-            // Mark as claimed in backend
-            await markClaimed(campaign.address, publicKey.toBase58());
+            // Mark as claimed in backend, signed by the recipient wallet
+            if (!wallet.signMessage) throw new Error("Wallet cannot sign messages");
+            const message = await claimSigningMessage(campaign.address, proofData.claim_nullifier);
+            const signature = utils.bytes.bs58.encode(await wallet.signMessage(message));
+            await markClaimed(campaign.address, publicKey.toBase58(), proofData.secret, proofData.leaf_index, signature);
 
             // Remove from eligible list
             setEligibleCampaigns(prev => prev.filter(c => c.address !== campaign.address));
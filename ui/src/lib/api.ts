@@ -56,6 +56,7 @@ export interface EligibilityResponse {
 export interface ProofResponse {
     merkle_root: string;
     nullifier_hash: string;
+    claim_nullifier: string;
     leaf_index: number;
     merkle_path: string[];
     amount: number;
@@ -74,6 +75,7 @@ export interface ZkProofResponse {
     secret: string;              // 32 bytes hex
     merkle_root: string;         // 32 bytes hex
     leaf_index: number;
+    claim_nullifier: string;     // 32 bytes hex, signed by claimSigningMessage
 }
 
 export interface EligibleCampaign {
@@ -152,14 +154,43 @@ export async function checkEligibility(address: string, wallet: string): Promise
     return result.data;
 }
 
+/** Width in seconds of the time buckets a claim signature commits to */
+const CLAIM_SIGNATURE_WINDOW_SECS = 300;
+
 /**
- * Mark a claim as completed
+ * Message the recipient signs to claim:
+ * sha256("shadow-drop-claim" || address || nullifier || floor(unix time / 300) as i64 LE)
  */
-export async function markClaimed(address: string, wallet: string): Promise<void> {
+export async function claimSigningMessage(address: string, claimNullifier: string): Promise<Uint8Array> {
+    const domain = new TextEncoder().encode('shadow-drop-claim');
+    const campaign = new TextEncoder().encode(address);
+    const nullifier = Uint8Array.from(claimNullifier.match(/../g)!.map(byte => parseInt(byte, 16)));
+    const bucket = new Uint8Array(8);
+    new DataView(bucket.buffer).setBigInt64(0, BigInt(Math.floor(Date.now() / 1000 / CLAIM_SIGNATURE_WINDOW_SECS)), true);
+
+    const message = new Uint8Array(domain.length + campaign.length + nullifier.length + bucket.length);
+    message.set(domain, 0);
+    message.set(campaign, domain.length);
+    message.set(nullifier, domain.length + campaign.length);
+    message.set(bucket, domain.length + campaign.length + nullifier.length);
+    return new Uint8Array(await crypto.subtle.digest('SHA-256', message));
+}
+
+/**
+ * Mark a claim as completed; the backend derives and spends the nullifier from the claim secret.
+ * `signature` is the base58 wallet signature of `claimSigningMessage`.
+ */
+export async function markClaimed(
+    address: string,
+    wallet: string,
+    secret: string,
+    leafIndex: number,
+    signature: string,
+): Promise<void> {
     const response = await fetch(`${API_BASE}/api/v1/campaigns/${address}/claim`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
-        body: JSON.stringify({ wallet }),
+        body: JSON.stringify({ wallet, secret, leaf_index: leafIndex, signature }),
     });
     const result: ApiResponse<void> = await response.json();
     if (!result.success) {
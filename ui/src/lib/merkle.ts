@@ -83,10 +83,11 @@ export async function buildMerkleTree(recipients: Recipient[]): Promise<{
         return BigInt("0x" + Buffer.from(leafBytes).toString("hex"));
     }));
 
-    // 3. Pad tree with zeros
+    // 3. Pad tree with the canonical empty leaf (must match the backend's EMPTY_LEAF)
     const paddedSize = 1 << TREE_DEPTH;
+    const emptyLeaf = await poseidonHash([tagToBigInt("shadow-drop-empty"), tagToBigInt("leaf")]);
     while (leavesBn.length < paddedSize) {
-        leavesBn.push(0n);
+        leavesBn.push(emptyLeaf);
     }
 
     // 4. Build Tree
@@ -121,6 +122,11 @@ export async function buildMerkleTree(recipients: Recipient[]): Promise<{
     };
 }
 
+// Domain tag as a big-endian integer, e.g. "leaf" -> 0x6c656166
+function tagToBigInt(tag: string): bigint {
+    return BigInt("0x" + Buffer.from(tag, "utf8").toString("hex"));
+}
+
 // Helper
 function bigIntToBytes(bn: bigint): Uint8Array {
     let hex = bn.toString(16);